@@ -0,0 +1,16 @@
+use clap::CommandFactory;
+use std::path::PathBuf;
+
+// the same definitions main.rs parses with
+#[allow(dead_code)]
+#[path = "src/cli.rs"]
+mod cli;
+
+// man pages for the command and each subcommand, generated from the clap
+// definitions into $OUT_DIR/man
+fn main() -> std::io::Result<()> {
+    println!("cargo:rerun-if-changed=src/cli.rs");
+    let dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap()).join("man");
+    std::fs::create_dir_all(&dir)?;
+    clap_mangen::generate_to(cli::Cli::command(), &dir)
+}
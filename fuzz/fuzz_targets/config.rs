@@ -0,0 +1,18 @@
+#![no_main]
+
+// any text as a config.toml: parsing and compiling every profile's bindings
+// may fail, but must not panic
+use controller2keys::bindings::{self, Mapper};
+use controller2keys::config::Config;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let Ok(config) = toml::from_str::<Config>(text) else {
+        return;
+    };
+    for profile in config.profiles.values() {
+        let physical = profile.key_positions.physical();
+        let _ = bindings::errors(&profile.bindings, physical);
+        Mapper::new(&profile.bindings, physical);
+    }
+});
@@ -0,0 +1,63 @@
+#![no_main]
+
+// a config and a stream of presses and releases: once everything is let go,
+// every key and mouse button that went down has come back up
+use controller2keys::action::Action;
+use controller2keys::bindings::Mapper;
+use controller2keys::config::Config;
+use controller2keys::input::InputState;
+use gilrs::Button;
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+
+const BUTTONS: &[Button] = &[
+    Button::South,
+    Button::East,
+    Button::West,
+    Button::North,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger2,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+fuzz_target!(|input: (&str, Vec<(u8, bool)>)| {
+    let (text, events) = input;
+    let Ok(config) = toml::from_str::<Config>(text) else {
+        return;
+    };
+    let profile = config.get_profile(&config.profile);
+    let mut mapper = Mapper::new(&profile.bindings, profile.key_positions.physical());
+    let state = InputState::default();
+    let mut held = Vec::new();
+    let mut actions = Vec::new();
+    for (i, press) in events {
+        let button = BUTTONS[i as usize % BUTTONS.len()];
+        if press && !held.contains(&button) {
+            held.push(button);
+            actions.extend(mapper.press(button, &state));
+        } else if !press && held.contains(&button) {
+            held.retain(|b| *b != button);
+            actions.extend(mapper.release(button));
+        }
+    }
+    for button in held {
+        actions.extend(mapper.release(button));
+    }
+
+    let mut down = HashMap::new();
+    for action in actions {
+        match action {
+            Action::KeyDown(key) => *down.entry(format!("{:?}", key)).or_insert(0) += 1,
+            Action::KeyUp(key) => *down.entry(format!("{:?}", key)).or_insert(0) -= 1,
+            Action::MouseDown(button) => *down.entry(format!("{:?}", button)).or_insert(0) += 1,
+            Action::MouseUp(button) => *down.entry(format!("{:?}", button)).or_insert(0) -= 1,
+            _ => (),
+        }
+    }
+    assert!(down.values().all(|&n| n == 0), "left down: {:?}", down);
+});
@@ -0,0 +1,70 @@
+/* the controller2keys mapping engine as a c abi. build with
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * and link against target/release/libcontroller2keys.so (.dylib, .dll).
+ * an engine runs one profile: feed it controller events and poll it at
+ * your polling rate (~250Hz) for the actions to send. nothing is injected.
+ *
+ * an engine must only be used from one thread at a time. every pointer
+ * passed in must be null or valid; strings are nul-terminated utf-8. */
+
+#ifndef CONTROLLER2KEYS_H
+#define CONTROLLER2KEYS_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct C2kEngine C2kEngine;
+
+/* an engine running `profile` from the text of a config.toml, or NULL with
+ * the reason printed to stdout. a NULL or empty profile means the config's
+ * default profile */
+C2kEngine *c2k_create(const char *config, const char *profile);
+
+/* frees an engine. NULL is ignored */
+void c2k_destroy(C2kEngine *engine);
+
+/* one controller event, as json in the form `controller2keys record` writes
+ * them, e.g. {"ButtonPressed":["South",<code>]} or
+ * {"AxisChanged":["LeftStickX",0.5,<code>]}. returns false if the event
+ * couldn't be read */
+bool c2k_feed_event(C2kEngine *engine, const char *event);
+
+/* runs a tick and returns the actions since the last poll as a json array
+ * of objects, each with a "kind" and that kind's fields, e.g.
+ * [{"kind":"key_down","key":"Space"},{"kind":"move","x":4,"y":0}].
+ * free it with c2k_free_string. the kinds:
+ *
+ *   key_down, key_up, tap   "key": a character ("w") or key name ("Space",
+ *                           "LeftArrow"), or instead "raw": a platform
+ *                           keycode
+ *   mouse_down, mouse_up,   "button": "left", "right", "middle", "back"
+ *   click                   or "forward"
+ *   move                    "x", "y": pixels, y down
+ *   scroll                  "amount": wheel clicks, positive is down
+ *   smooth_scroll           "x", "y": 120ths of a notch, right and down
+ *   tilt                    "direction": "left" or "right"
+ *   paste, text             "text": pasted, or typed as unicode input
+ *   pad_stick               "stick": "left" or "right", "x", "y": -1 to 1,
+ *                           y up
+ *   pad_button              "button": a gilrs button name, "pressed": bool
+ *   obs                     "command", gesture "gesture", launch "launch",
+ *   gesture, launch,        display "op": as written in a config binding
+ *   display
+ *   plugin                  "name", "command": a binding's plugin command
+ *
+ * kinds may be added, so skip ones you don't know */
+char *c2k_poll_actions(C2kEngine *engine);
+
+/* frees a string returned by c2k_poll_actions. NULL is ignored */
+void c2k_free_string(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
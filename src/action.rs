@@ -0,0 +1,756 @@
+use crate::clipboard;
+use crate::display::{self, DisplayOp};
+use crate::error::{self, Error};
+use crate::gamescope;
+use crate::gesture::{self, Gesture};
+use crate::input::InputState;
+use crate::interpolate::Interpolator;
+use crate::keymap::parse_key;
+use crate::launch::Launch;
+use crate::obs::{Obs, ObsCommand};
+use crate::pad::VirtualPad;
+use crate::plugin::PluginCommand;
+use crate::pointer::Pointer;
+use crate::reverse::Stick;
+use crate::socd::Socd;
+use crate::uinput;
+use crate::wheel::{Tilt, Wheel};
+use enigo::{Enigo, Key, KeyboardControllable, MouseButton, MouseControllable};
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+// a single output event. anything that isn't the hard-coded mapping (scripts,
+// config bindings) produces these instead of talking to enigo directly
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    KeyDown(Key),
+    KeyUp(Key),
+    KeyClick(Key),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    MouseClick(MouseButton),
+    MouseMove(i32, i32),
+    Scroll(i32),
+    // scrolling in 120ths of a notch, right and down, for the hi-res wheel
+    SmoothScroll(i32, i32),
+    // one notch of a tilting wheel
+    Tilt(Tilt),
+    // put text on the clipboard and press the paste chord. far more reliable
+    // than typing it out key by key in games and terminals
+    Paste(String),
+    // type text through the platform's unicode input (KEYEVENTF_UNICODE on
+    // windows, CGEvent strings on macos, xdo on x11), for characters the
+    // current layout has no key for
+    Text(String),
+    // a request to obs over its websocket
+    Obs(ObsCommand),
+    // pinch or swipe, as touch input where the platform takes it
+    Gesture(Gesture),
+    // start a program
+    Launch(Launch),
+    // screen brightness or night light
+    Display(DisplayOp),
+    // a stick of the virtual gamepad, -1..1 with y up
+    PadStick(Stick, f32, f32),
+    // a virtual gamepad button pressed or let go
+    PadButton(gilrs::Button, bool),
+    // a command for a plugin, passed on by the main loop
+    Plugin(PluginCommand),
+}
+
+// everything we inject goes through here, so we know what is currently held
+// and can let go of all of it at once (pausing, profile switches...)
+pub struct Output {
+    enigo: Enigo,
+    // keys and the mouse go here instead of enigo inside gamescope
+    uinput: Option<uinput::Device>,
+    held_keys: HashSet<Key>,
+    held_buttons: HashSet<MouseButton>,
+    paused: bool,
+    // the real keyboard is in use, so keys are held back
+    typing: bool,
+    // mouse actions captured for a macro, with when they happened
+    recording: Option<(Instant, Vec<(Duration, Action)>)>,
+    // pixels moved and scroll clicks since the last take_motion, for haptics
+    moved: f32,
+    scrolled: i32,
+    // set when the config has an [obs] table
+    obs: Option<Obs>,
+    // plugin commands waiting for take_plugin_commands
+    plugin_commands: Vec<PluginCommand>,
+    // set when the config has a [pointer] table; mouse motion moves the pen
+    // or finger instead
+    pointer: Option<Pointer>,
+    // set when the config has a [socd] table
+    socd: Option<Socd>,
+    // opened the first time something scrolls smoothly
+    wheel: Option<Wheel>,
+    // created the first time something needs it. after failing once it
+    // isn't tried again, and analog sticks fall back to keys
+    pad: Option<VirtualPad>,
+    pad_failed: bool,
+    // actions waiting their turn, sent no closer together than `gap` for
+    // games and remote sessions that drop back-to-back events
+    queue: VecDeque<Action>,
+    gap: Duration,
+    last_sent: Option<Instant>,
+    batching: bool,
+    // with more than one piece, mouse movement is gathered up and spread
+    // across the next tick instead of sent at once
+    pieces: u32,
+    gathered: (i32, i32),
+    interpolator: Option<Interpolator>,
+    // actions executed since the last take_executed, for --json-events
+    executed: Option<Vec<Action>>,
+}
+
+impl Output {
+    pub fn new() -> Result<Self, Error> {
+        let uinput = if gamescope::running() {
+            match uinput::Device::open() {
+                Ok(device) => {
+                    println!("running inside gamescope, sending keys and the mouse through uinput");
+                    Some(device)
+                }
+                Err(e) => {
+                    println!("running inside gamescope but uinput is unavailable: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        // xtest needs an x display, uinput doesn't
+        if uinput.is_none() {
+            error::check_output()?;
+        }
+        Ok(Output {
+            enigo: Enigo::new(),
+            uinput,
+            held_keys: HashSet::new(),
+            held_buttons: HashSet::new(),
+            paused: false,
+            typing: false,
+            recording: None,
+            moved: 0.0,
+            scrolled: 0,
+            obs: None,
+            plugin_commands: Vec::new(),
+            pointer: None,
+            socd: None,
+            wheel: None,
+            pad: None,
+            pad_failed: false,
+            queue: VecDeque::new(),
+            gap: Duration::ZERO,
+            last_sent: None,
+            batching: false,
+            pieces: 1,
+            gathered: (0, 0),
+            interpolator: None,
+            executed: None,
+        })
+    }
+
+    // actions are dropped while paused
+    pub fn execute(&mut self, action: &Action) {
+        if self.paused {
+            return;
+        }
+        let keyboard = matches!(
+            action,
+            Action::KeyDown(_) | Action::KeyClick(_) | Action::Text(_) | Action::Paste(_)
+        );
+        if self.typing && keyboard {
+            return;
+        }
+        if let Some((start, steps)) = &mut self.recording {
+            if !matches!(
+                action,
+                Action::KeyDown(_) | Action::KeyUp(_) | Action::KeyClick(_)
+            ) {
+                steps.push((start.elapsed(), action.clone()));
+            }
+        }
+        if let Some(executed) = &mut self.executed {
+            executed.push(action.clone());
+        }
+        // opposite directions from different sources settled here
+        let actions = match self.socd.as_mut().and_then(|s| s.resolve(action)) {
+            Some(actions) => actions,
+            None => vec![action.clone()],
+        };
+        // sticks repeat their keys on every move, which would pile up
+        // behind the event gap
+        for action in actions {
+            let held = match action {
+                Action::KeyDown(key) | Action::KeyUp(key) => self.held_keys.contains(&key),
+                Action::MouseDown(button) | Action::MouseUp(button) => {
+                    self.held_buttons.contains(&button)
+                }
+                _ => false,
+            };
+            if !redundant(&action, &self.queue, held) {
+                self.queue.push_back(action);
+            }
+        }
+        if !self.batching {
+            self.flush();
+        }
+    }
+
+    // sends what's queued: releases first, then presses, then mouse motion,
+    // holding key and button events back until `gap` has passed since the
+    // last one. nothing goes ahead of an earlier event it depends on, so a
+    // key's press and release stay in order. called after every execute
+    // (unless batching) and once per tick
+    pub fn flush(&mut self) {
+        loop {
+            let waiting = self.last_sent.is_some_and(|t| t.elapsed() < self.gap);
+            let Some(action) = next(&self.queue, waiting).and_then(|i| self.queue.remove(i)) else {
+                return;
+            };
+            if paced(&action) {
+                self.last_sent = Some(Instant::now());
+            }
+            self.inject(&action);
+        }
+    }
+
+    // while batching, execute only queues, so everything one controller
+    // event or tick produces is sent together in priority order at the next
+    // flush
+    pub fn set_batching(&mut self, batching: bool) {
+        self.batching = batching;
+    }
+
+    // how many evenly spaced moves each tick's mouse movement becomes, from
+    // the profile's mouse_interpolation
+    pub fn set_interpolation(&mut self, pieces: u32) {
+        if pieces > 1 && self.interpolator.is_none() {
+            let mouse = self.uinput.as_ref().and_then(|d| d.mouse().ok());
+            self.interpolator = Some(Interpolator::start(mouse));
+        }
+        self.pieces = pieces.max(1);
+    }
+
+    // hands the movement gathered this tick to the interpolator, to be
+    // spread across the next `tick`. called once per tick
+    pub fn send_interpolated(&mut self, tick: Duration) {
+        let (dx, dy) = std::mem::take(&mut self.gathered);
+        if let Some(interpolator) = self.interpolator.as_ref() {
+            if (dx, dy) != (0, 0) {
+                interpolator.send(dx, dy, self.pieces, tick);
+            }
+        }
+    }
+
+    pub fn set_event_gap(&mut self, gap: Duration) {
+        self.gap = gap;
+    }
+
+    fn inject(&mut self, action: &Action) {
+        match *action {
+            Action::KeyDown(key) => {
+                self.held_keys.insert(key);
+                self.key(key, true);
+            }
+            Action::KeyUp(key) => {
+                self.held_keys.remove(&key);
+                self.key(key, false);
+            }
+            // characters outside ascii usually aren't on the layout
+            Action::KeyClick(Key::Layout(c)) if !c.is_ascii() => {
+                self.enigo.key_sequence(c.encode_utf8(&mut [0; 4]))
+            }
+            Action::KeyClick(key) if self.uinput.is_some() => {
+                self.key(key, true);
+                self.key(key, false);
+            }
+            Action::KeyClick(key) => self.enigo.key_click(key),
+            Action::MouseDown(button) => {
+                self.held_buttons.insert(button);
+                self.button(button, true);
+            }
+            Action::MouseUp(button) => {
+                self.held_buttons.remove(&button);
+                self.button(button, false);
+            }
+            Action::MouseClick(button) if self.uinput.is_some() => {
+                self.button(button, true);
+                self.button(button, false);
+            }
+            Action::MouseClick(button) => self.enigo.mouse_click(button),
+            Action::MouseMove(x, y) => {
+                self.moved += (x as f32).hypot(y as f32);
+                match self.pointer.as_mut() {
+                    Some(pointer) => pointer.move_by(x, y),
+                    None if self.pieces > 1 => {
+                        self.gathered.0 += x;
+                        self.gathered.1 += y;
+                    }
+                    None => match self.uinput.as_mut() {
+                        Some(device) => report(device.move_by(x, y)),
+                        None => self.enigo.mouse_move_relative(x, y),
+                    },
+                }
+            }
+            Action::Scroll(amount) => {
+                self.scrolled += amount.abs();
+                match self.uinput.as_mut() {
+                    Some(device) => report(device.scroll(amount)),
+                    None => self.enigo.mouse_scroll_y(amount),
+                }
+            }
+            // inside gamescope the keyboard's device has the wheel already
+            Action::SmoothScroll(x, y) => match self.uinput.as_mut() {
+                Some(device) => report(device.smooth_scroll(x, y)),
+                None => self
+                    .wheel
+                    .get_or_insert_with(Wheel::open)
+                    .scroll(x, y, &mut self.enigo),
+            },
+            Action::Tilt(tilt) => match self.uinput.as_mut() {
+                Some(device) => report(device.tilt(tilt.sign())),
+                None => self
+                    .wheel
+                    .get_or_insert_with(Wheel::open)
+                    .tilt(tilt, &mut self.enigo),
+            },
+            Action::Text(ref text) => self.enigo.key_sequence(text),
+            Action::Paste(ref text) => match clipboard::set(text) {
+                Ok(()) => {
+                    #[cfg(target_os = "macos")]
+                    let modifier = Key::Meta;
+                    #[cfg(not(target_os = "macos"))]
+                    let modifier = Key::Control;
+                    self.key(modifier, true);
+                    self.key(Key::Layout('v'), true);
+                    self.key(Key::Layout('v'), false);
+                    self.key(modifier, false);
+                }
+                Err(e) => println!("paste failed: {}", e),
+            },
+            Action::Gesture(ref g) => gesture::perform(g, &mut self.enigo),
+            Action::Launch(ref launch) => {
+                if let Err(e) = launch.spawn() {
+                    println!("failed to launch {}", e);
+                }
+            }
+            Action::Display(op) => display::perform(op),
+            Action::PadStick(stick, x, y) => {
+                if let Some(Err(e)) = self.pad.as_mut().map(|pad| pad.stick(stick, x, y)) {
+                    println!("virtual pad failed: {}", e);
+                }
+            }
+            Action::PadButton(button, pressed) => {
+                if !self.has_pad() {
+                    return;
+                }
+                if let Some(Err(e)) = self.pad.as_mut().map(|pad| pad.button(button, pressed)) {
+                    println!("virtual pad failed: {}", e);
+                }
+            }
+            Action::Obs(ref command) => match &self.obs {
+                Some(obs) => obs.send(command.clone()),
+                None => println!("obs action needs an [obs] table in the config"),
+            },
+            Action::Plugin(ref command) => self.plugin_commands.push(command.clone()),
+        }
+    }
+
+    fn key(&mut self, key: Key, down: bool) {
+        match (self.uinput.as_mut(), down) {
+            (Some(device), _) => report(device.key(key, down)),
+            (None, true) => self.enigo.key_down(key),
+            (None, false) => self.enigo.key_up(key),
+        }
+    }
+
+    fn button(&mut self, button: MouseButton, down: bool) {
+        match (self.uinput.as_mut(), down) {
+            (Some(device), _) => report(device.button(button, down)),
+            (None, true) => self.enigo.mouse_down(button),
+            (None, false) => self.enigo.mouse_up(button),
+        }
+    }
+
+    pub fn set_obs(&mut self, obs: Obs) {
+        self.obs = Some(obs);
+    }
+
+    pub fn set_socd(&mut self, socd: Socd) {
+        self.socd = Some(socd);
+    }
+
+    pub fn set_pointer(&mut self, pointer: Pointer) {
+        self.pointer = Some(pointer);
+    }
+
+    // whether the pen or finger owns this button's presses
+    pub fn pointer_takes(&self, button: gilrs::Button) -> bool {
+        self.pointer.as_ref().is_some_and(|p| p.takes(button))
+    }
+
+    // pen or finger contact follows the trigger, once per tick
+    pub fn tick_pointer(&mut self, input: &InputState) {
+        if self.paused {
+            return;
+        }
+        if let Some(pointer) = self.pointer.as_mut() {
+            if let Err(e) = pointer.update(input) {
+                println!("pointer injection failed: {}", e);
+            }
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        // a queued press would land after the release
+        self.queue.clear();
+        self.gathered = (0, 0);
+        if let Some(socd) = self.socd.as_mut() {
+            socd.clear();
+        }
+        if let Some(pointer) = self.pointer.as_mut() {
+            pointer.lift();
+        }
+        if let Some(pad) = self.pad.as_mut() {
+            pad.reset();
+        }
+        for key in std::mem::take(&mut self.held_keys) {
+            self.key(key, false);
+        }
+        for button in std::mem::take(&mut self.held_buttons) {
+            self.button(button, false);
+        }
+    }
+
+    // makes the uinput devices the config will want now, while root (if
+    // started that way) is still there to make them
+    pub fn open_devices(&mut self, pad: bool, wheel: bool) {
+        if pad {
+            self.has_pad();
+        }
+        // inside gamescope the wheel is on the keyboard's device
+        if wheel && self.uinput.is_none() {
+            self.wheel.get_or_insert_with(Wheel::open);
+        }
+    }
+
+    // whether the virtual gamepad is there, creating it the first time
+    pub fn has_pad(&mut self) -> bool {
+        if self.pad.is_none() && !self.pad_failed {
+            match VirtualPad::create() {
+                Ok(pad) => {
+                    println!("created the virtual gamepad");
+                    self.pad = Some(pad);
+                }
+                Err(e) => {
+                    println!("no virtual gamepad: {}", e);
+                    self.pad_failed = true;
+                }
+            }
+        }
+        self.pad.is_some()
+    }
+
+    // whether a controller that just connected is our own virtual pad
+    pub fn claim_pad(&mut self) -> bool {
+        self.pad.as_mut().is_some_and(|pad| pad.claim())
+    }
+
+    // keep what's executed until taken, for the event log
+    pub fn keep_executed(&mut self) {
+        self.executed = Some(Vec::new());
+    }
+
+    pub fn take_executed(&mut self) -> Vec<Action> {
+        self.executed
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    // plugin commands executed since the last call, for the main loop to
+    // hand to the plugins, which it owns
+    pub fn take_plugin_commands(&mut self) -> Vec<PluginCommand> {
+        std::mem::take(&mut self.plugin_commands)
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some((Instant::now(), Vec::new()));
+    }
+
+    pub fn stop_recording(&mut self) -> Vec<(Duration, Action)> {
+        self.recording
+            .take()
+            .map(|(_, steps)| steps)
+            .unwrap_or_default()
+    }
+
+    pub fn holding(&self) -> bool {
+        !self.held_keys.is_empty() || !self.held_buttons.is_empty()
+    }
+
+    // names of the keys and mouse buttons held right now
+    pub fn held(&self) -> Vec<String> {
+        let keys = self.held_keys.iter().map(|key| match key {
+            Key::Layout(c) => c.to_uppercase().to_string(),
+            key => format!("{:?}", key),
+        });
+        let buttons = self
+            .held_buttons
+            .iter()
+            .map(|button| format!("Mouse{:?}", button));
+        keys.chain(buttons).collect()
+    }
+
+    pub fn take_motion(&mut self) -> (f32, i32) {
+        let motion = (self.moved, self.scrolled);
+        self.moved = 0.0;
+        self.scrolled = 0;
+        motion
+    }
+
+    // keys only, so the mouse still works while typing. keys held when
+    // typing starts are let go
+    pub fn set_typing(&mut self, typing: bool) {
+        if typing {
+            for key in std::mem::take(&mut self.held_keys) {
+                self.key(key, false);
+            }
+            if let Some(socd) = self.socd.as_mut() {
+                socd.clear();
+            }
+        }
+        self.typing = typing;
+    }
+
+    // pausing releases whatever is held so nothing stays stuck down
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused {
+            self.release_all();
+        }
+        self.paused = paused;
+    }
+}
+
+fn report(result: Result<(), String>) {
+    if let Err(e) = result {
+        println!("uinput injection failed: {}", e);
+    }
+}
+
+// where in the queue the next event to send is, if anything can go now.
+// `waiting` is whether the event gap is still running
+fn next(queue: &VecDeque<Action>, waiting: bool) -> Option<usize> {
+    (0..queue.len())
+        .filter(|&i| !(waiting && paced(&queue[i])))
+        .filter(|&i| !queue.iter().take(i).any(|e| must_follow(&queue[i], e)))
+        .min_by_key(|&i| (priority(&queue[i]), i))
+}
+
+// whether `action` would change nothing when its turn comes: a press of a
+// key or button the queue ahead of it leaves down, or a release of one it
+// leaves up. `held` is whether it's down now
+fn redundant(action: &Action, queue: &VecDeque<Action>, held: bool) -> bool {
+    let (down, up) = match *action {
+        Action::KeyDown(key) | Action::KeyUp(key) => (Action::KeyDown(key), Action::KeyUp(key)),
+        Action::MouseDown(button) | Action::MouseUp(button) => {
+            (Action::MouseDown(button), Action::MouseUp(button))
+        }
+        _ => return false,
+    };
+    let held = queue
+        .iter()
+        .rev()
+        .find_map(|queued| match queued {
+            queued if *queued == down => Some(true),
+            queued if *queued == up => Some(false),
+            _ => None,
+        })
+        .unwrap_or(held);
+    (*action == down) == held
+}
+
+// key and button events wait out the event gap; motion doesn't
+fn paced(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::KeyDown(_)
+            | Action::KeyUp(_)
+            | Action::KeyClick(_)
+            | Action::MouseDown(_)
+            | Action::MouseUp(_)
+            | Action::MouseClick(_)
+    )
+}
+
+// which of several queued events goes first, lowest first: letting go
+// before pressing means opposite keys are never down together when a burst
+// swaps them, and motion is the least harmful to hold back
+fn priority(action: &Action) -> u8 {
+    match action {
+        Action::KeyUp(_) | Action::MouseUp(_) | Action::PadButton(_, false) => 0,
+        Action::MouseMove(..)
+        | Action::Scroll(_)
+        | Action::SmoothScroll(..)
+        | Action::Tilt(_)
+        | Action::PadStick(..) => 2,
+        _ => 1,
+    }
+}
+
+fn modifier(key: Key) -> bool {
+    matches!(key, Key::Shift | Key::Control | Key::Alt | Key::Meta)
+}
+
+// whether `later` has to wait for `earlier`, queued before it
+fn must_follow(later: &Action, earlier: &Action) -> bool {
+    use Action::*;
+    let mouse = |a: &Action| {
+        matches!(
+            a,
+            MouseDown(_)
+                | MouseUp(_)
+                | MouseClick(_)
+                | MouseMove(..)
+                | Scroll(_)
+                | SmoothScroll(..)
+                | Tilt(_)
+        )
+    };
+    match (later, earlier) {
+        // a modifier let go early would break the chord it's part of
+        (KeyUp(k), _) if modifier(*k) => true,
+        (KeyDown(a) | KeyUp(a) | KeyClick(a), KeyDown(b) | KeyUp(b) | KeyClick(b)) => a == b,
+        // moves add up in any order, and so does scrolling
+        (MouseMove(..), MouseMove(..))
+        | (Scroll(_), Scroll(_))
+        | (SmoothScroll(..), SmoothScroll(..)) => false,
+        // clicks land where the pointer was meant to be
+        (l, e) if mouse(l) && mouse(e) => true,
+        // ctrl+scroll and the like stay chords even during the gap
+        (l, KeyDown(k)) if mouse(l) && modifier(*k) => true,
+        (l, e) if mouse(l) && paced(e) || paced(l) && mouse(e) => false,
+        // text, pastes, gestures, launches and obs keep their place
+        _ => true,
+    }
+}
+
+pub fn parse_mouse_button(name: &str) -> Option<MouseButton> {
+    match name.to_lowercase().as_str() {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+// a single tap of a key, mouse button or wheel, for gesture-style bindings
+// that fire once rather than follow a held button
+pub fn parse_one_shot(
+    key: Option<&str>,
+    mouse: Option<&str>,
+    scroll: Option<i32>,
+) -> Result<Action, String> {
+    match (key, mouse, scroll) {
+        (Some(key), None, None) => parse_key(key)
+            .map(Action::KeyClick)
+            .ok_or_else(|| format!("unknown key '{}'", key)),
+        (None, Some(mouse), None) => parse_mouse_button(mouse)
+            .map(Action::MouseClick)
+            .ok_or_else(|| format!("unknown mouse button '{}'", mouse)),
+        (None, None, Some(clicks)) => Ok(Action::Scroll(clicks)),
+        _ => Err("exactly one of key, mouse or scroll must be set".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Action::{KeyDown, KeyUp, MouseMove};
+
+    // the order a queue would be sent in with no gap
+    fn sent(actions: Vec<Action>) -> Vec<Action> {
+        let mut queue: VecDeque<_> = actions.into();
+        let mut sent = Vec::new();
+        while let Some(i) = next(&queue, false) {
+            sent.extend(queue.remove(i));
+        }
+        sent
+    }
+
+    const A: Key = Key::Layout('a');
+    const D: Key = Key::Layout('d');
+
+    #[test]
+    fn releases_go_before_presses() {
+        assert_eq!(sent(vec![KeyDown(D), KeyUp(A)]), vec![KeyUp(A), KeyDown(D)]);
+    }
+
+    #[test]
+    fn motion_goes_last() {
+        assert_eq!(
+            sent(vec![MouseMove(3, 0), KeyDown(A)]),
+            vec![KeyDown(A), MouseMove(3, 0)]
+        );
+    }
+
+    #[test]
+    fn a_key_stays_in_order_with_itself() {
+        assert_eq!(sent(vec![KeyDown(A), KeyUp(A)]), vec![KeyDown(A), KeyUp(A)]);
+    }
+
+    #[test]
+    fn chords_keep_their_modifier_held() {
+        let chord = vec![
+            KeyDown(Key::Control),
+            KeyDown(Key::Layout('c')),
+            KeyUp(Key::Layout('c')),
+            KeyUp(Key::Control),
+        ];
+        assert_eq!(sent(chord.clone()), chord);
+    }
+
+    #[test]
+    fn motion_goes_ahead_during_the_gap() {
+        let queue: VecDeque<_> = vec![KeyDown(A), MouseMove(3, 0), KeyUp(D)].into();
+        assert_eq!(next(&queue, true), Some(1));
+        assert_eq!(next(&queue, false), Some(2));
+    }
+
+    #[test]
+    fn motion_waits_for_a_modifier_during_the_gap() {
+        let zoom: VecDeque<_> = vec![
+            KeyDown(Key::Control),
+            Action::Scroll(-4),
+            KeyUp(Key::Control),
+        ]
+        .into();
+        assert_eq!(next(&zoom, true), None);
+        assert_eq!(
+            sent(zoom.into()),
+            [
+                KeyDown(Key::Control),
+                Action::Scroll(-4),
+                KeyUp(Key::Control)
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_presses_are_not_queued_again() {
+        let queue: VecDeque<_> = vec![KeyDown(A), KeyUp(D)].into();
+        assert!(redundant(&KeyDown(A), &queue, false));
+        assert!(redundant(&KeyUp(D), &queue, true));
+        assert!(!redundant(&KeyUp(A), &queue, false));
+        assert!(redundant(&KeyDown(D), &VecDeque::new(), true));
+    }
+
+    #[test]
+    fn clicks_wait_for_the_pointer() {
+        let click = vec![MouseMove(5, 5), Action::MouseClick(MouseButton::Left)];
+        assert_eq!(sent(click.clone()), click);
+    }
+}
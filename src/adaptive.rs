@@ -0,0 +1,65 @@
+use crate::hid::TriggerEffect;
+use serde::Deserialize;
+
+// dualsense adaptive trigger feel, per trigger, e.g.
+// `adaptive_triggers = { right = { effect = "click", start = 0.3, end = 0.5 } }`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdaptiveTriggers {
+    pub left: Option<Effect>,
+    pub right: Option<Effect>,
+}
+
+// positions and force go from 0 to 1 (fully released to fully pulled,
+// none to strongest)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "effect", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Effect {
+    // a normal, free trigger
+    Off,
+    // pushes back from `start` all the way down
+    Resistance {
+        start: f32,
+        #[serde(default = "default_force")]
+        force: f32,
+    },
+    // pushes back between `start` and `end` and then gives way, for a crisp
+    // actuation point on triggers mapped to clicks
+    Click {
+        start: f32,
+        end: f32,
+        #[serde(default = "default_force")]
+        force: f32,
+    },
+}
+
+fn default_force() -> f32 {
+    1.0
+}
+
+impl AdaptiveTriggers {
+    // left and right, ready for the controller
+    pub fn effects(&self) -> (TriggerEffect, TriggerEffect) {
+        (encode(self.left.as_ref()), encode(self.right.as_ref()))
+    }
+}
+
+fn encode(effect: Option<&Effect>) -> TriggerEffect {
+    let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+    let mut raw = [0u8; 11];
+    match effect {
+        None | Some(Effect::Off) => raw[0] = 0x05,
+        Some(Effect::Resistance { start, force }) => {
+            raw[0] = 0x01;
+            raw[1] = byte(*start);
+            raw[2] = byte(*force);
+        }
+        Some(Effect::Click { start, end, force }) => {
+            raw[0] = 0x02;
+            raw[1] = byte(*start);
+            raw[2] = byte(end.max(*start));
+            raw[3] = byte(*force);
+        }
+    }
+    raw
+}
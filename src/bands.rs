@@ -0,0 +1,232 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::bindings::chord_click;
+use crate::devices::AxisNames;
+use crate::keymap::parse_chord;
+use crate::scancode;
+use gilrs::Axis;
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// how far past a band's edge the axis has to go to leave it, so a throttle
+// resting on a detent doesn't chatter between two keys
+const MARGIN: f32 = 0.03;
+
+// a key or mouse button held while an axis sits inside a stretch of its
+// travel, for throttles, rudders, pedals and extra hats, e.g.
+// `axis_bands = [{ axis = "throttle", from = 0.5, to = 1.0, key = "w" }]`.
+// values run from -1 to 1
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BandConfig {
+    pub axis: AxisName,
+    pub from: f32,
+    pub to: f32,
+    pub key: Option<String>,
+    pub mouse: Option<String>,
+    // tapped once on entering instead of held while inside
+    #[serde(default)]
+    pub tap: bool,
+    // pulsed instead of held: down for part of every this many
+    // milliseconds, from none of it at the edge of the band nearest the
+    // centre to all of it at the far edge, so a wheel turned a little taps
+    // A and turned hard holds it
+    pub pulse: Option<u64>,
+}
+
+// one of gilrs's axis names, or one a device rule's `named_axes` gave an
+// axis gilrs doesn't know
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum AxisName {
+    Gilrs(Axis),
+    Named(String),
+}
+
+impl AxisName {
+    pub fn matches(&self, axis: Axis, code: u32, names: &AxisNames) -> bool {
+        match self {
+            AxisName::Gilrs(name) => *name == axis,
+            AxisName::Named(name) => {
+                axis == Axis::Unknown && names.get(code) == Some(name.as_str())
+            }
+        }
+    }
+}
+
+impl fmt::Display for AxisName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AxisName::Gilrs(axis) => write!(f, "{:?}", axis),
+            AxisName::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+struct Band {
+    axis: AxisName,
+    from: f32,
+    to: f32,
+    press: Vec<Action>,
+    unpress: Vec<Action>,
+    pulse: Option<Duration>,
+    inside: bool,
+    // whether press was sent without unpress since
+    down: bool,
+    // how far in, 0 to 1, and when it was entered, for pulsing
+    depth: f32,
+    since: Instant,
+}
+
+impl Band {
+    fn new(config: &BandConfig, physical: bool) -> Result<Band, String> {
+        if config.from > config.to {
+            return Err(format!("from {} is past to {}", config.from, config.to));
+        }
+        if config.tap && config.pulse.is_some() {
+            return Err("tap and pulse can't both be set".to_string());
+        }
+        if config.pulse == Some(0) {
+            return Err("pulse has to be at least 1ms".to_string());
+        }
+        let (press, unpress) = match (&config.key, &config.mouse) {
+            (Some(name), None) => {
+                let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+                let keys: Vec<_> = if physical {
+                    keys.into_iter().map(scancode::physical).collect()
+                } else {
+                    keys
+                };
+                if config.tap {
+                    (chord_click(&keys), vec![])
+                } else {
+                    (
+                        keys.iter().map(|k| Action::KeyDown(*k)).collect(),
+                        keys.iter().rev().map(|k| Action::KeyUp(*k)).collect(),
+                    )
+                }
+            }
+            (None, Some(name)) => {
+                let button = parse_mouse_button(name)
+                    .ok_or_else(|| format!("unknown mouse button '{}'", name))?;
+                if config.tap {
+                    (vec![Action::MouseClick(button)], vec![])
+                } else {
+                    (
+                        vec![Action::MouseDown(button)],
+                        vec![Action::MouseUp(button)],
+                    )
+                }
+            }
+            _ => return Err("exactly one of key or mouse must be set".to_string()),
+        };
+        Ok(Band {
+            axis: config.axis.clone(),
+            from: config.from,
+            to: config.to,
+            press,
+            unpress,
+            pulse: config.pulse.map(Duration::from_millis),
+            inside: false,
+            down: false,
+            depth: 0.0,
+            since: Instant::now(),
+        })
+    }
+
+    // 0 at the edge nearest the centre, 1 at the other
+    fn depth(&self, value: f32) -> f32 {
+        let (near, far) = if self.from.abs() <= self.to.abs() {
+            (self.from, self.to)
+        } else {
+            (self.to, self.from)
+        };
+        if near == far {
+            return 1.0;
+        }
+        ((value - near) / (far - near)).clamp(0.0, 1.0)
+    }
+}
+
+// what's wrong with each band, for `check`
+pub fn errors(configs: &[BandConfig], physical: bool) -> Vec<String> {
+    configs
+        .iter()
+        .filter_map(|config| Band::new(config, physical).err().map(|e| (config, e)))
+        .map(|(config, e)| format!("{}: {}", config.axis, e))
+        .collect()
+}
+
+// an axis is in at most one of its bands at a time: the first listed that
+// holds its value
+pub struct AxisBands {
+    bands: Vec<Band>,
+}
+
+impl AxisBands {
+    // `physical` is the profile's key_positions, resolved
+    pub fn new(configs: &[BandConfig], physical: bool) -> AxisBands {
+        let mut bands = Vec::new();
+        for config in configs {
+            match Band::new(config, physical) {
+                Ok(band) => bands.push(band),
+                Err(e) => println!("ignoring axis band on {}: {}", config.axis, e),
+            }
+        }
+        AxisBands { bands }
+    }
+
+    // call when any axis changes
+    pub fn update(&mut self, axis: Axis, value: f32, code: u32, names: &AxisNames) -> Vec<Action> {
+        if self.bands.is_empty() {
+            return Vec::new();
+        }
+        let mut ours: Vec<&mut Band> = self
+            .bands
+            .iter_mut()
+            .filter(|b| b.axis.matches(axis, code, names))
+            .collect();
+        let mut actions = Vec::new();
+        if let Some(band) = ours.iter_mut().find(|b| b.inside) {
+            if value >= band.from - MARGIN && value <= band.to + MARGIN {
+                band.depth = band.depth(value);
+                return actions;
+            }
+            band.inside = false;
+            if band.down {
+                band.down = false;
+                actions.extend(band.unpress.iter().cloned());
+            }
+        }
+        if let Some(band) = ours.iter_mut().find(|b| value >= b.from && value <= b.to) {
+            band.inside = true;
+            band.depth = band.depth(value);
+            band.since = Instant::now();
+            // pulsing bands start on the next tick
+            if band.pulse.is_none() {
+                band.down = !band.unpress.is_empty();
+                actions.extend(band.press.iter().cloned());
+            }
+        }
+        actions
+    }
+
+    // presses and releases for pulsing bands. call it at the polling rate
+    pub fn tick(&mut self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for band in self.bands.iter_mut().filter(|b| b.inside) {
+            let Some(pulse) = band.pulse else {
+                continue;
+            };
+            let phase = band.since.elapsed().as_secs_f32() % pulse.as_secs_f32();
+            let down = phase < band.depth * pulse.as_secs_f32();
+            if down && !band.down {
+                actions.extend(band.press.iter().cloned());
+            } else if !down && band.down {
+                actions.extend(band.unpress.iter().cloned());
+            }
+            band.down = down;
+        }
+        actions
+    }
+}
@@ -0,0 +1,749 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::capture::{self, CaptureOp};
+use crate::display::DisplayOp;
+use crate::expr::{Evaluator, Setting, Value};
+use crate::flick::Direction;
+use crate::gesture::Gesture;
+use crate::input::InputState;
+use crate::keymap::parse_chord;
+use crate::launch::Launch;
+use crate::magnifier::{self, MagnifierOp};
+use crate::obs::ObsCommand;
+use crate::plugin::PluginCommand;
+use crate::scancode::{self, KeyPositions};
+use crate::vars::{VarChange, Vars};
+use crate::wheel::Tilt;
+use crate::window::{self, WindowOp};
+use enigo::{Key, MouseButton};
+use gilrs::{Axis, Button};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
+// what a binding's `when` can check, e.g. `when = { held = "LeftTrigger" }`
+// or `when = { not = { layer = "build" } }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    Held(Button),
+    Layer(String),
+    Toggle(String),
+    // a variable that isn't 0, or that equals a value, e.g.
+    // `when = { var_is = { name = "weapon", value = 2 } }`
+    Var(String),
+    VarIs { name: String, value: i64 },
+    LeftStickAbove(f32),
+    RightStickAbove(f32),
+    // pushed roughly that way, e.g. `when = { left_stick_toward = "up" }` on
+    // a LeftThumb binding for click-while-running
+    LeftStickToward(Direction),
+    RightStickToward(Direction),
+    // a rhai expression that's true or non-zero, with the same variables
+    // and functions as settings, e.g. `when = { expr = "lx > 0.5 && held(\"South\")" }`
+    Expr(String),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+// key-repeat behaviour for a held binding, e.g. `repeat = { max_rate = 40 }`.
+// the rate ramps from `rate` up to `max_rate` over `ramp_ms` of holding, like
+// an os with key-repeat acceleration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Repeat {
+    // before the first repeat
+    pub delay_ms: u64,
+    // repeats per second
+    pub rate: f32,
+    pub max_rate: f32,
+    pub ramp_ms: u64,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat {
+            delay_ms: 400,
+            rate: 5.0,
+            max_rate: 30.0,
+            ramp_ms: 2000,
+        }
+    }
+}
+
+impl Repeat {
+    // time until the next repeat after holding for `held`
+    pub fn interval(&self, held: Duration) -> Duration {
+        let ramping = held.saturating_sub(Duration::from_millis(self.delay_ms));
+        let t = if self.ramp_ms == 0 {
+            1.0
+        } else {
+            (ramping.as_secs_f32() * 1000.0 / self.ramp_ms as f32).min(1.0)
+        };
+        let rate = self.rate + (self.max_rate - self.rate).max(0.0) * t;
+        Duration::from_secs_f32(1.0 / rate.max(0.1))
+    }
+}
+
+// a binding as written in the config. at most one of key/mouse/scroll/tilt/
+// gamepad/paste/text/obs/gesture/launch/window/magnifier/capture/display/
+// layer/toggle/var/speed/speed_hold/speed_adjust may be set, and a binding
+// with none of them just swallows the button
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BindingConfig {
+    pub button: Button,
+    pub when: Option<Condition>,
+    // a key or a chord like "ctrl+shift+tab"
+    pub key: Option<String>,
+    // overrides the profile's key_positions for this binding's key
+    pub position: Option<KeyPositions>,
+    pub mouse: Option<String>,
+    // wheel clicks, positive is down
+    pub scroll: Option<i32>,
+    // a wheel tilt notch, "left" or "right"
+    pub tilt: Option<Tilt>,
+    // a button of the virtual gamepad, held with this one
+    pub gamepad: Option<Button>,
+    // text pasted through the clipboard
+    pub paste: Option<String>,
+    // text typed as unicode input
+    pub text: Option<String>,
+    // scene switches, mute and recording control in obs
+    pub obs: Option<ObsCommand>,
+    // a pinch or swipe
+    pub gesture: Option<Gesture>,
+    // a program started on press
+    pub launch: Option<Launch>,
+    // app switching, minimizing, maximizing...
+    pub window: Option<WindowOp>,
+    // the os screen magnifier
+    pub magnifier: Option<MagnifierOp>,
+    // screenshots and screen recording
+    pub capture: Option<CaptureOp>,
+    // screen brightness and night light
+    pub display: Option<DisplayOp>,
+    // a command handed to a plugin
+    pub plugin: Option<PluginCommand>,
+    // repeat the key or scroll while held instead of holding the key down
+    pub repeat: Option<Repeat>,
+    // active while the button is held
+    pub layer: Option<String>,
+    // flipped on each press
+    pub toggle: Option<String>,
+    // sets, counts or flips a variable on each press
+    pub var: Option<VarChange>,
+    // stick-mouse speed tiers from the profile's speed_tiers: switched to
+    // (and back from on a second press), or used while held
+    pub speed: Option<String>,
+    pub speed_hold: Option<String>,
+    // nudges the stick-mouse speed by this many percent
+    pub speed_adjust: Option<i32>,
+    // also switches to a layer for a while, on top of the target, e.g.
+    // typing a browser's link-hint key and then reading hint letters
+    pub activate: Option<TimedLayer>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimedLayer {
+    pub layer: String,
+    pub ms: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Nothing,
+    // modifiers first, pressed in order and released in reverse
+    Keys(Vec<Key>),
+    Mouse(MouseButton),
+    Scroll(i32),
+    Tilt(Tilt),
+    // a virtual gamepad button
+    Pad(Button),
+    Paste(String),
+    Text(String),
+    Obs(ObsCommand),
+    Gesture(Gesture),
+    Launch(Launch),
+    Window(WindowOp),
+    Magnifier(MagnifierOp),
+    Capture(CaptureOp),
+    Display(DisplayOp),
+    Plugin(PluginCommand),
+    Layer(String),
+    Toggle(String),
+    Var(VarChange),
+    Speed(String),
+    SpeedHold(String),
+    SpeedAdjust(i32),
+}
+
+struct Binding {
+    button: Button,
+    when: Option<Condition>,
+    target: Target,
+    repeat: Option<Repeat>,
+    activate: Option<TimedLayer>,
+}
+
+// a held repeating binding
+struct Repeating {
+    actions: Vec<Action>,
+    repeat: Repeat,
+    since: Instant,
+    next: Instant,
+}
+
+impl BindingConfig {
+    fn compile(&self, physical: bool) -> Result<Binding, String> {
+        let mut targets = Vec::new();
+        if let Some(name) = &self.key {
+            let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+            let physical = self.position.map_or(physical, |p| p.physical());
+            targets.push(Target::Keys(if physical {
+                keys.into_iter().map(scancode::physical).collect()
+            } else {
+                keys
+            }));
+        }
+        if let Some(name) = &self.mouse {
+            targets.push(Target::Mouse(
+                parse_mouse_button(name)
+                    .ok_or_else(|| format!("unknown mouse button '{}'", name))?,
+            ));
+        }
+        if let Some(clicks) = self.scroll {
+            targets.push(Target::Scroll(clicks));
+        }
+        if let Some(tilt) = self.tilt {
+            targets.push(Target::Tilt(tilt));
+        }
+        if let Some(button) = self.gamepad {
+            targets.push(Target::Pad(button));
+        }
+        if let Some(text) = &self.paste {
+            targets.push(Target::Paste(text.clone()));
+        }
+        if let Some(text) = &self.text {
+            targets.push(Target::Text(text.clone()));
+        }
+        if let Some(command) = &self.obs {
+            targets.push(Target::Obs(command.clone()));
+        }
+        if let Some(gesture) = &self.gesture {
+            gesture.validate()?;
+            targets.push(Target::Gesture(gesture.clone()));
+        }
+        if let Some(launch) = &self.launch {
+            targets.push(Target::Launch(launch.clone()));
+        }
+        if let Some(op) = self.window {
+            if !op.switches() && window::shortcut(op).is_none() {
+                return Err(format!("{:?} has no shortcut on this platform", op));
+            }
+            targets.push(Target::Window(op));
+        }
+        if let Some(op) = self.magnifier {
+            targets.push(Target::Magnifier(op));
+        }
+        if let Some(op) = self.capture {
+            if !capture::supported(op) {
+                return Err(format!("{:?} has no shortcut on this platform", op));
+            }
+            targets.push(Target::Capture(op));
+        }
+        if let Some(op) = self.display {
+            targets.push(Target::Display(op));
+        }
+        if let Some(command) = &self.plugin {
+            targets.push(Target::Plugin(command.clone()));
+        }
+        if let Some(layer) = &self.layer {
+            targets.push(Target::Layer(layer.clone()));
+        }
+        if let Some(toggle) = &self.toggle {
+            targets.push(Target::Toggle(toggle.clone()));
+        }
+        if let Some(change) = &self.var {
+            change.validate()?;
+            targets.push(Target::Var(change.clone()));
+        }
+        if let Some(tier) = &self.speed {
+            targets.push(Target::Speed(tier.clone()));
+        }
+        if let Some(tier) = &self.speed_hold {
+            targets.push(Target::SpeedHold(tier.clone()));
+        }
+        if let Some(percent) = self.speed_adjust {
+            targets.push(Target::SpeedAdjust(percent));
+        }
+        if targets.len() > 1 {
+            return Err(
+                "only one of key, mouse, scroll, tilt, gamepad, paste, text, obs, gesture, launch, \
+                window, magnifier, capture, display, plugin, layer, toggle, var, speed, speed_hold \
+                or speed_adjust may be set"
+                    .to_string(),
+            );
+        }
+
+        let target = targets.pop().unwrap_or(Target::Nothing);
+        let repeats = match target {
+            Target::Keys(_) | Target::Scroll(_) | Target::Tilt(_) => true,
+            Target::Window(op) => op.switches(),
+            Target::Magnifier(op) => op != MagnifierOp::Toggle,
+            Target::Display(op) => op.repeats(),
+            _ => false,
+        };
+        if self.repeat.is_some() && !repeats {
+            return Err(
+                "repeat only works with key, scroll, tilt, the window switcher, magnifier zoom \
+                or brightness"
+                    .to_string(),
+            );
+        }
+
+        Ok(Binding {
+            button: self.button,
+            when: self.when.clone(),
+            target,
+            repeat: self.repeat.clone(),
+            activate: self.activate.clone(),
+        })
+    }
+}
+
+// mapper state that conditions can refer to
+#[derive(Debug, Default)]
+pub struct MapperState {
+    // held layer buttons in press order, the last one is the active layer
+    layers: Vec<String>,
+    // layers switched on for a while, with when they end
+    timed: Vec<(String, Instant)>,
+    toggles: HashSet<String>,
+    // the selected speed tier, held ones in press order, and the speed
+    // nudged up or down in percent
+    speed: Option<String>,
+    held_speeds: Vec<String>,
+    speed_adjust: i32,
+    vars: Vars,
+}
+
+impl MapperState {
+    // a held layer wins over a timed one
+    pub fn layer(&self) -> Option<&str> {
+        let now = Instant::now();
+        self.layers
+            .last()
+            .or_else(|| {
+                self.timed
+                    .iter()
+                    .rev()
+                    .find(|(_, until)| now < *until)
+                    .map(|(layer, _)| layer)
+            })
+            .map(|l| l.as_str())
+    }
+
+    pub fn toggles(&self) -> impl Iterator<Item = &String> {
+        self.toggles.iter()
+    }
+
+    pub fn vars(&self) -> &Vars {
+        &self.vars
+    }
+
+    // a held speed tier wins over the selected one
+    pub fn speed(&self) -> Option<&str> {
+        self.held_speeds
+            .last()
+            .or(self.speed.as_ref())
+            .map(|s| s.as_str())
+    }
+
+    pub fn speed_scale(&self) -> f32 {
+        1.0 + self.speed_adjust as f32 / 100.0
+    }
+
+    // the tier and nudge for display, None while neither is in use
+    pub fn speed_label(&self) -> Option<String> {
+        match (self.speed(), self.speed_adjust) {
+            (None, 0) => None,
+            (Some(tier), 0) => Some(tier.to_string()),
+            (tier, adjust) => Some(format!("{} {:+}%", tier.unwrap_or("base"), adjust)),
+        }
+    }
+}
+
+fn stick_magnitude(input: &InputState, x: Axis, y: Axis) -> f32 {
+    input.axis(x).hypot(input.axis(y))
+}
+
+// how far a stick has to be pushed to point anywhere
+const TOWARD_REACH: f32 = 0.5;
+
+// within 45 degrees either side of the direction
+fn stick_toward(input: &InputState, x: Axis, y: Axis, direction: Direction) -> bool {
+    let (x, y) = (input.axis(x), input.axis(y));
+    if x.hypot(y) < TOWARD_REACH {
+        return false;
+    }
+    let off = (y.atan2(x) - direction.angle()).rem_euclid(2.0 * PI);
+    off.min(2.0 * PI - off) < PI / 4.0
+}
+
+// `expr` conditions, compiled once per mapper
+pub struct Exprs {
+    evaluator: Evaluator,
+    compiled: HashMap<String, Value>,
+}
+
+impl Exprs {
+    fn new<'a>(conditions: impl Iterator<Item = &'a Condition>) -> Exprs {
+        let evaluator = Evaluator::new();
+        let mut compiled = HashMap::new();
+        for condition in conditions {
+            for src in condition.exprs() {
+                // a broken one is reported here and then never holds
+                let value = evaluator.compile("when", Some(&Setting::Expr(src.to_string())), 0.0);
+                compiled.insert(src.to_string(), value);
+            }
+        }
+        Exprs {
+            evaluator,
+            compiled,
+        }
+    }
+
+    fn holds(&self, src: &str, input: &InputState, state: &MapperState) -> bool {
+        self.compiled
+            .get(src)
+            .is_some_and(|value| self.evaluator.eval(value, input, state) != 0.0)
+    }
+}
+
+impl Condition {
+    // the sources of the `expr` conditions in it
+    pub fn exprs(&self) -> Vec<&str> {
+        match self {
+            Condition::Expr(src) => vec![src.as_str()],
+            Condition::Not(inner) => inner.exprs(),
+            Condition::All(parts) | Condition::Any(parts) => {
+                parts.iter().flat_map(|c| c.exprs()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn eval(&self, input: &InputState, state: &MapperState, exprs: &Exprs) -> bool {
+        match self {
+            Condition::Held(button) => input.is_held(*button),
+            Condition::Layer(layer) => state.layer() == Some(layer.as_str()),
+            Condition::Toggle(toggle) => state.toggles.contains(toggle),
+            Condition::Var(name) => state.vars.get(name) != 0,
+            Condition::VarIs { name, value } => state.vars.get(name) == *value,
+            Condition::LeftStickAbove(threshold) => {
+                stick_magnitude(input, Axis::LeftStickX, Axis::LeftStickY) > *threshold
+            }
+            Condition::RightStickAbove(threshold) => {
+                stick_magnitude(input, Axis::RightStickX, Axis::RightStickY) > *threshold
+            }
+            Condition::LeftStickToward(direction) => {
+                stick_toward(input, Axis::LeftStickX, Axis::LeftStickY, *direction)
+            }
+            Condition::RightStickToward(direction) => {
+                stick_toward(input, Axis::RightStickX, Axis::RightStickY, *direction)
+            }
+            Condition::Expr(src) => exprs.holds(src, input, state),
+            Condition::Not(inner) => !inner.eval(input, state, exprs),
+            Condition::All(all) => all.iter().all(|c| c.eval(input, state, exprs)),
+            Condition::Any(any) => any.iter().any(|c| c.eval(input, state, exprs)),
+        }
+    }
+}
+
+// tap a chord: modifiers down, the last key clicked, modifiers up
+pub fn chord_click(keys: &[Key]) -> Vec<Action> {
+    let (last, modifiers) = match keys.split_last() {
+        Some(split) => split,
+        None => return vec![],
+    };
+    let mut actions: Vec<Action> = modifiers.iter().map(|k| Action::KeyDown(*k)).collect();
+    actions.push(Action::KeyClick(*last));
+    actions.extend(modifiers.iter().rev().map(|k| Action::KeyUp(*k)));
+    actions
+}
+
+// config-driven button bindings. bindings are tried in the order they're
+// written and the first one whose condition holds wins, so
+// "A -> Enter when L1 is held, otherwise Space" is two bindings with the
+// conditional one first
+pub struct Mapper {
+    bindings: Vec<Binding>,
+    state: MapperState,
+    exprs: Exprs,
+    // what each held button did when it was pressed, so the release undoes
+    // exactly that even if the conditions changed in between
+    active: HashMap<Button, Target>,
+    repeating: HashMap<Button, Repeating>,
+    // buttons holding the app switcher open; its modifier goes up when the
+    // last one is let go
+    switchers: usize,
+}
+
+// the bindings Mapper::new would skip and why, for `controller2keys check`
+pub fn errors(configs: &[BindingConfig], physical: bool) -> Vec<String> {
+    configs
+        .iter()
+        .filter_map(|c| Some(format!("{:?}: {}", c.button, c.compile(physical).err()?)))
+        .collect()
+}
+
+impl Mapper {
+    // `physical` is the profile's key_positions, resolved
+    pub fn new(configs: &[BindingConfig], physical: bool) -> Mapper {
+        let mut bindings = Vec::new();
+        for config in configs {
+            match config.compile(physical) {
+                Ok(binding) => bindings.push(binding),
+                Err(e) => println!("ignoring binding for {:?}: {}", config.button, e),
+            }
+        }
+        let exprs = Exprs::new(bindings.iter().filter_map(|b| b.when.as_ref()));
+        Mapper {
+            bindings,
+            state: MapperState::default(),
+            exprs,
+            active: HashMap::new(),
+            repeating: HashMap::new(),
+            switchers: 0,
+        }
+    }
+
+    pub fn state(&self) -> &MapperState {
+        &self.state
+    }
+
+    // variables outlive the profile, so a switch hands them from the old
+    // mapper to the new one
+    pub fn take_vars(&mut self) -> Vars {
+        std::mem::take(&mut self.state.vars)
+    }
+
+    pub fn set_vars(&mut self, vars: Vars) {
+        self.state.vars = vars;
+    }
+
+    // buttons with any config binding are owned by the mapper, everything
+    // else keeps the built-in mapping
+    pub fn handles(&self, button: Button) -> bool {
+        self.bindings.iter().any(|b| b.button == button)
+    }
+
+    pub fn press(&mut self, button: Button, input: &InputState) -> Vec<Action> {
+        let (target, repeat, activate) = self
+            .bindings
+            .iter()
+            .filter(|b| b.button == button)
+            .find(|b| {
+                b.when
+                    .as_ref()
+                    .is_none_or(|c| c.eval(input, &self.state, &self.exprs))
+            })
+            .map(|b| (b.target.clone(), b.repeat.clone(), b.activate.clone()))
+            .unwrap_or((Target::Nothing, None, None));
+
+        if let Some(timed) = activate {
+            let until = Instant::now() + Duration::from_millis(timed.ms);
+            println!("layer '{}' active for {}ms", timed.layer, timed.ms);
+            self.state.timed.retain(|(layer, _)| *layer != timed.layer);
+            self.state.timed.push((timed.layer, until));
+        }
+
+        if let Some(repeat) = repeat {
+            let actions = match &target {
+                Target::Keys(keys) => chord_click(keys),
+                Target::Scroll(clicks) => vec![Action::Scroll(*clicks)],
+                Target::Tilt(tilt) => vec![Action::Tilt(*tilt)],
+                Target::Window(op) => chord_click(&op.switch_keys()),
+                Target::Magnifier(op) => magnifier::actions(*op),
+                Target::Display(op) => vec![Action::Display(*op)],
+                _ => unreachable!("checked when compiling"),
+            };
+            let opening = match target {
+                Target::Window(_) => self.open_switcher(),
+                _ => vec![],
+            };
+            let now = Instant::now();
+            self.repeating.insert(
+                button,
+                Repeating {
+                    actions: actions.clone(),
+                    next: now + Duration::from_millis(repeat.delay_ms),
+                    repeat,
+                    since: now,
+                },
+            );
+            self.active.insert(button, target);
+            return opening.into_iter().chain(actions).collect();
+        }
+
+        let actions = match &target {
+            Target::Nothing => vec![],
+            Target::Keys(keys) => keys.iter().map(|k| Action::KeyDown(*k)).collect(),
+            Target::Mouse(mouse) => vec![Action::MouseDown(*mouse)],
+            Target::Pad(button) => vec![Action::PadButton(*button, true)],
+            Target::Scroll(clicks) => vec![Action::Scroll(*clicks)],
+            Target::Tilt(tilt) => vec![Action::Tilt(*tilt)],
+            Target::Paste(text) => vec![Action::Paste(text.clone())],
+            Target::Text(text) => vec![Action::Text(text.clone())],
+            Target::Obs(command) => vec![Action::Obs(command.clone())],
+            Target::Gesture(gesture) => vec![Action::Gesture(gesture.clone())],
+            Target::Launch(launch) => vec![Action::Launch(launch.clone())],
+            Target::Window(op) if op.switches() => {
+                let mut actions = self.open_switcher();
+                actions.extend(chord_click(&op.switch_keys()));
+                actions
+            }
+            Target::Magnifier(op) => magnifier::actions(*op),
+            Target::Capture(op) => capture::actions(*op).unwrap_or_default(),
+            Target::Display(op) => vec![Action::Display(*op)],
+            Target::Plugin(command) => vec![Action::Plugin(command.clone())],
+            Target::Window(op) => window::shortcut(*op)
+                .map(|keys| chord_click(&keys))
+                .unwrap_or_default(),
+            Target::Layer(layer) => {
+                self.state.layers.push(layer.clone());
+                println!("layer '{}' active", layer);
+                vec![]
+            }
+            Target::Toggle(toggle) => {
+                let on = self.state.toggles.insert(toggle.clone());
+                if !on {
+                    self.state.toggles.remove(toggle);
+                }
+                println!("toggle '{}' {}", toggle, if on { "on" } else { "off" });
+                vec![]
+            }
+            Target::Var(change) => {
+                let value = change.apply(&mut self.state.vars);
+                println!("var '{}' = {}", change.name, value);
+                vec![]
+            }
+            Target::Speed(tier) => {
+                if self.state.speed.as_ref() == Some(tier) {
+                    self.state.speed = None;
+                } else {
+                    self.state.speed = Some(tier.clone());
+                }
+                vec![]
+            }
+            Target::SpeedHold(tier) => {
+                self.state.held_speeds.push(tier.clone());
+                vec![]
+            }
+            Target::SpeedAdjust(percent) => {
+                // never down to a standstill
+                self.state.speed_adjust = (self.state.speed_adjust + percent).clamp(-90, 500);
+                vec![]
+            }
+        };
+        self.active.insert(button, target);
+        actions
+    }
+
+    pub fn release(&mut self, button: Button) -> Vec<Action> {
+        if self.repeating.remove(&button).is_some() {
+            return match self.active.remove(&button) {
+                Some(Target::Window(_)) => self.close_switcher(),
+                _ => vec![],
+            };
+        }
+        match self.active.remove(&button) {
+            Some(Target::Keys(keys)) => keys.iter().rev().map(|k| Action::KeyUp(*k)).collect(),
+            Some(Target::Mouse(mouse)) => vec![Action::MouseUp(mouse)],
+            Some(Target::Pad(button)) => vec![Action::PadButton(button, false)],
+            Some(Target::Window(op)) if op.switches() => self.close_switcher(),
+            Some(Target::Layer(layer)) => {
+                if let Some(i) = self.state.layers.iter().rposition(|l| *l == layer) {
+                    self.state.layers.remove(i);
+                }
+                vec![]
+            }
+            Some(Target::SpeedHold(tier)) => {
+                if let Some(i) = self.state.held_speeds.iter().rposition(|t| *t == tier) {
+                    self.state.held_speeds.remove(i);
+                }
+                vec![]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn open_switcher(&mut self) -> Vec<Action> {
+        self.switchers += 1;
+        if self.switchers == 1 {
+            vec![Action::KeyDown(window::SWITCHER)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn close_switcher(&mut self) -> Vec<Action> {
+        self.switchers = self.switchers.saturating_sub(1);
+        if self.switchers == 0 {
+            vec![Action::KeyUp(window::SWITCHER)]
+        } else {
+            vec![]
+        }
+    }
+
+    // repeats that are due, called once per loop
+    pub fn tick(&mut self) -> Vec<Action> {
+        let now = Instant::now();
+        self.state.timed.retain(|(_, until)| now < *until);
+        let mut actions = Vec::new();
+        for repeating in self.repeating.values_mut() {
+            if now >= repeating.next {
+                actions.extend(repeating.actions.iter().cloned());
+                repeating.next = now + repeating.repeat.interval(now - repeating.since);
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(all(test, feature = "expressions"))]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    // `when = { expr = ... }` is looked at on the press
+    #[test]
+    fn expr_conditions_pick_the_binding() {
+        let text = r#"
+[[profiles.p.bindings]]
+button = "South"
+key = "a"
+when = { expr = "lx > 0.5" }
+
+[[profiles.p.bindings]]
+button = "South"
+key = "space"
+"#;
+        let config: Config = toml::from_str(text).unwrap();
+        let mut mapper = Mapper::new(&config.get_profile("p").bindings, false);
+        let mut input = InputState::default();
+        assert_eq!(
+            mapper.press(Button::South, &input),
+            vec![Action::KeyDown(Key::Space)]
+        );
+        mapper.release(Button::South);
+        input.set_axis(Axis::LeftStickX, 1.0);
+        assert_eq!(
+            mapper.press(Button::South, &input),
+            vec![Action::KeyDown(Key::Layout('a'))]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::bindings::chord_click;
+use crate::input::InputState;
+use crate::keymap::parse_chord;
+use crate::launch::Launch;
+use crate::obs::ObsCommand;
+use crate::overlay::{self, Corner, Overlay};
+use gilrs::Button;
+use serde::Deserialize;
+
+// a grid of named actions opened with a button chord, moved through with the
+// d-pad and run with A, like a software stream deck, e.g.
+// `board = { open = ["Select", "Start"], actions = [...] }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoardConfig {
+    // held together to open (and close) the board
+    pub open: Vec<Button>,
+    pub columns: usize,
+    pub corner: Corner,
+    pub actions: Vec<BoardEntry>,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        BoardConfig {
+            open: vec![Button::Select, Button::Start],
+            columns: 3,
+            corner: Corner::TopLeft,
+            actions: Vec::new(),
+        }
+    }
+}
+
+// one cell. exactly one of the targets is set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoardEntry {
+    pub name: String,
+    // a key or chord, tapped
+    pub key: Option<String>,
+    pub mouse: Option<String>,
+    pub scroll: Option<i32>,
+    pub paste: Option<String>,
+    pub text: Option<String>,
+    pub obs: Option<ObsCommand>,
+    // a shell command, run detached
+    pub exec: Option<String>,
+    // a program with its own arguments, directory and environment
+    pub launch: Option<Launch>,
+    // one of the profile's macros, by name
+    #[serde(rename = "macro")]
+    pub play: Option<String>,
+}
+
+// what running a cell asks for
+#[derive(Debug, Clone)]
+pub enum Pick {
+    Actions(Vec<Action>),
+    Exec(String),
+    Macro(String),
+}
+
+impl BoardEntry {
+    fn compile(&self) -> Result<Pick, String> {
+        let mut picks = Vec::new();
+        if let Some(name) = &self.key {
+            let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+            picks.push(Pick::Actions(chord_click(&keys)));
+        }
+        if let Some(name) = &self.mouse {
+            let button = parse_mouse_button(name)
+                .ok_or_else(|| format!("unknown mouse button '{}'", name))?;
+            picks.push(Pick::Actions(vec![Action::MouseClick(button)]));
+        }
+        if let Some(clicks) = self.scroll {
+            picks.push(Pick::Actions(vec![Action::Scroll(clicks)]));
+        }
+        if let Some(text) = &self.paste {
+            picks.push(Pick::Actions(vec![Action::Paste(text.clone())]));
+        }
+        if let Some(text) = &self.text {
+            picks.push(Pick::Actions(vec![Action::Text(text.clone())]));
+        }
+        if let Some(command) = &self.obs {
+            picks.push(Pick::Actions(vec![Action::Obs(command.clone())]));
+        }
+        if let Some(command) = &self.exec {
+            picks.push(Pick::Exec(command.clone()));
+        }
+        if let Some(launch) = &self.launch {
+            picks.push(Pick::Actions(vec![Action::Launch(launch.clone())]));
+        }
+        if let Some(name) = &self.play {
+            picks.push(Pick::Macro(name.clone()));
+        }
+        match picks.len() {
+            1 => Ok(picks.remove(0)),
+            _ => Err(
+                "exactly one of key, mouse, scroll, paste, text, obs, exec, launch or macro \
+                must be set"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+pub struct Board {
+    open: Vec<Button>,
+    columns: usize,
+    corner: Corner,
+    entries: Vec<(String, Pick)>,
+    // created the first time the board opens
+    overlay: Option<Box<dyn Overlay>>,
+    showing: bool,
+    selected: usize,
+    picked: Option<Pick>,
+}
+
+impl Board {
+    pub fn new(config: &BoardConfig) -> Board {
+        let entries = config
+            .actions
+            .iter()
+            .filter_map(|entry| match entry.compile() {
+                Ok(pick) => Some((entry.name.clone(), pick)),
+                Err(e) => {
+                    println!("ignoring board entry '{}': {}", entry.name, e);
+                    None
+                }
+            })
+            .collect();
+        Board {
+            open: config.open.clone(),
+            columns: config.columns.max(1),
+            corner: config.corner,
+            entries,
+            overlay: None,
+            showing: false,
+            selected: 0,
+            picked: None,
+        }
+    }
+
+    // every button press while the board is open belongs to it, as does the
+    // press completing the open chord. returns whether `button` was taken.
+    // releases are left alone so whatever the chord buttons pressed before
+    // the board opened still gets let go
+    pub fn press(&mut self, button: Button, input: &InputState) -> bool {
+        let chord = self.open.contains(&button) && self.open.iter().all(|b| input.is_held(*b));
+        if chord {
+            self.set_showing(!self.showing);
+            return true;
+        }
+        if !self.showing {
+            return false;
+        }
+
+        let count = self.entries.len();
+        let columns = self.columns;
+        match button {
+            Button::DPadLeft if !self.selected.is_multiple_of(columns) => self.selected -= 1,
+            Button::DPadRight if self.selected % columns + 1 < columns => {
+                self.selected = (self.selected + 1).min(count.saturating_sub(1))
+            }
+            Button::DPadUp if self.selected >= columns => self.selected -= columns,
+            Button::DPadDown if self.selected + columns < count => self.selected += columns,
+            Button::South => {
+                if let Some((name, pick)) = self.entries.get(self.selected) {
+                    println!("board -> {}", name);
+                    self.picked = Some(pick.clone());
+                }
+                self.set_showing(false);
+                return true;
+            }
+            Button::East => {
+                self.set_showing(false);
+                return true;
+            }
+            _ => return true,
+        }
+        self.draw();
+        true
+    }
+
+    // the cell chosen by the last press, if any
+    pub fn take_pick(&mut self) -> Option<Pick> {
+        self.picked.take()
+    }
+
+    fn set_showing(&mut self, showing: bool) {
+        self.showing = showing;
+        if showing {
+            println!("board opened");
+            if self.overlay.is_none() {
+                self.overlay = overlay::text(self.corner);
+            }
+        } else {
+            println!("board closed");
+        }
+        self.draw();
+    }
+
+    fn draw(&mut self) {
+        let lines = if self.showing {
+            self.lines()
+        } else {
+            Vec::new()
+        };
+        if let Some(overlay) = self.overlay.as_mut() {
+            if let Err(e) = overlay.show(&lines) {
+                println!("board overlay stopped: {}", e);
+                self.overlay = None;
+            }
+        }
+        if let Some((name, _)) = self.entries.get(self.selected).filter(|_| self.showing) {
+            println!("board: {}", name);
+        }
+    }
+
+    // the selected cell in brackets, e.g. "[ mute ]  record    clip  "
+    fn lines(&self) -> Vec<String> {
+        let width = self
+            .entries
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec!["A run - B close".to_string()];
+        for (row, cells) in self.entries.chunks(self.columns).enumerate() {
+            let line = cells
+                .iter()
+                .enumerate()
+                .map(|(column, (name, _))| {
+                    let (open, close) = if row * self.columns + column == self.selected {
+                        ('[', ']')
+                    } else {
+                        (' ', ' ')
+                    };
+                    format!("{}{:<width$}{}", open, name, close, width = width)
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(line);
+        }
+        lines
+    }
+
+    // window system events for the overlay, once per tick
+    pub fn tick(&mut self) {
+        if let Some(overlay) = self.overlay.as_mut() {
+            if let Err(e) = overlay.tick() {
+                println!("board overlay stopped: {}", e);
+                self.overlay = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use gilrs::{Axis, Button, EventType, Gamepad, GamepadId, Gilrs};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const STICK_AXES: [Axis; 4] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::RightStickX,
+    Axis::RightStickY,
+];
+const TRIGGERS: [Button; 2] = [Button::LeftTrigger2, Button::RightTrigger2];
+// how long the sticks are watched at rest
+const SAMPLE_TIME: Duration = Duration::from_millis(1000);
+// further off center than this is a thumb on the stick, not drift
+const MAX_DRIFT: f32 = 0.3;
+// closer to center than this isn't worth correcting
+const MIN_DRIFT: f32 = 0.01;
+// a sweep covering less than this didn't really happen
+const MIN_TRAVEL: f32 = 0.3;
+
+// saved calibrations, one file per controller uuid, e.g.
+// ~/.local/share/controller2keys/calibration/<uuid>.toml on linux
+pub fn calibration_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("controller2keys")
+        .join("calibration")
+}
+
+fn path(gamepad: &Gamepad) -> PathBuf {
+    let uuid: String = gamepad
+        .uuid()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    calibration_dir().join(format!("{}.toml", uuid))
+}
+
+// where a stick axis rests and how far it really reaches each way
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AxisRange {
+    pub axis: Axis,
+    pub min: f32,
+    pub center: f32,
+    pub max: f32,
+}
+
+impl AxisRange {
+    fn full(axis: Axis) -> AxisRange {
+        AxisRange {
+            axis,
+            min: -1.0,
+            center: 0.0,
+            max: 1.0,
+        }
+    }
+
+    // center moved to 0 and each side stretched to reach 1
+    fn apply(&self, value: f32) -> f32 {
+        let range = if value >= self.center {
+            self.max - self.center
+        } else {
+            self.center - self.min
+        };
+        ((value - self.center) / range.max(f32::EPSILON)).clamp(-1.0, 1.0)
+    }
+}
+
+// a trigger's reading released and fully pulled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TriggerRange {
+    pub button: Button,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl TriggerRange {
+    fn apply(&self, value: f32) -> f32 {
+        ((value - self.min) / (self.max - self.min).max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+// what `controller2keys calibrate` saves for a controller
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Saved {
+    name: String,
+    axes: Vec<AxisRange>,
+    triggers: Vec<TriggerRange>,
+}
+
+// each controller's stick and trigger ranges, applied to their readings
+// before the deadzone sees them, so a drifting or worn pad doesn't need a
+// huge deadzone. comes from `controller2keys calibrate`, and with
+// `auto_calibrate = true` the resting points are also learned at startup
+#[derive(Default)]
+pub struct Calibration {
+    axes: HashMap<(GamepadId, Axis), AxisRange>,
+    triggers: HashMap<(GamepadId, Button), TriggerRange>,
+}
+
+impl Calibration {
+    // the saved calibrations of the controllers connected now
+    pub fn load(gilrs: &Gilrs) -> Calibration {
+        let mut calibration = Calibration::default();
+        for (id, _) in gilrs.gamepads() {
+            calibration.connect(gilrs, id);
+        }
+        calibration
+    }
+
+    // picks up a newly connected controller's saved calibration
+    pub fn connect(&mut self, gilrs: &Gilrs, id: GamepadId) {
+        let gamepad = gilrs.gamepad(id);
+        let path = path(&gamepad);
+        let Ok(text) = fs::read_to_string(&path) else {
+            return;
+        };
+        match toml::from_str::<Saved>(&text) {
+            Ok(saved) => {
+                for range in saved.axes {
+                    self.axes.insert((id, range.axis), range);
+                }
+                for range in saved.triggers {
+                    self.triggers.insert((id, range.button), range);
+                }
+                println!("using the saved calibration for {}", gamepad.name());
+            }
+            Err(e) => println!("ignoring {}: {}", path.display(), e),
+        }
+    }
+
+    // watches the connected controllers' sticks for a moment and moves
+    // their centers to wherever they rest. events that arrive meanwhile are
+    // dropped; nobody is touching the controller
+    pub fn sample(&mut self, gilrs: &mut Gilrs) {
+        println!("calibrating sticks, don't touch the controller...");
+        let rest = average(gilrs, SAMPLE_TIME);
+        for ((id, axis), offset) in rest.axes {
+            let name = gilrs.gamepad(id).name().to_string();
+            let range = self
+                .axes
+                .entry((id, axis))
+                .or_insert_with(|| AxisRange::full(axis));
+            if (offset - range.center).abs() > MAX_DRIFT {
+                println!(
+                    "{} {:?} was held at {:.2} while calibrating, leaving it alone",
+                    name, axis, offset
+                );
+            } else if (offset - range.center).abs() >= MIN_DRIFT {
+                println!("{} {:?} rests at {:.2}, compensating", name, axis, offset);
+                range.center = offset;
+            }
+        }
+    }
+
+    // the reading with the controller's calibration applied
+    pub fn apply(&self, id: GamepadId, event: EventType) -> EventType {
+        match event {
+            EventType::AxisChanged(axis, value, code) => match self.axes.get(&(id, axis)) {
+                Some(range) => EventType::AxisChanged(axis, range.apply(value), code),
+                None => event,
+            },
+            EventType::ButtonChanged(button, value, code) => {
+                match self.triggers.get(&(id, button)) {
+                    Some(range) => EventType::ButtonChanged(button, range.apply(value), code),
+                    None => event,
+                }
+            }
+            _ => event,
+        }
+    }
+}
+
+// readings averaged over a while, per controller
+#[derive(Default)]
+struct Averages {
+    axes: HashMap<(GamepadId, Axis), f32>,
+    triggers: HashMap<(GamepadId, Button), f32>,
+}
+
+fn average(gilrs: &mut Gilrs, time: Duration) -> Averages {
+    let mut axes: HashMap<(GamepadId, Axis), Vec<f32>> = HashMap::new();
+    let mut triggers: HashMap<(GamepadId, Button), Vec<f32>> = HashMap::new();
+    let started = Instant::now();
+    while started.elapsed() < time {
+        while gilrs.next_event().is_some() {}
+        for (id, gamepad) in gilrs.gamepads() {
+            for axis in STICK_AXES {
+                axes.entry((id, axis))
+                    .or_default()
+                    .push(gamepad.value(axis));
+            }
+            for button in TRIGGERS {
+                if let Some(data) = gamepad.button_data(button) {
+                    triggers.entry((id, button)).or_default().push(data.value());
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let mean = |v: Vec<f32>| v.iter().sum::<f32>() / v.len() as f32;
+    Averages {
+        axes: axes.into_iter().map(|(k, v)| (k, mean(v))).collect(),
+        triggers: triggers.into_iter().map(|(k, v)| (k, mean(v))).collect(),
+    }
+}
+
+// blocks until enter is pressed
+fn wait_for_enter() -> Result<(), String> {
+    io::stdin()
+        .lock()
+        .read_line(&mut String::new())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// `controller2keys calibrate`: measures the resting point and full reach of
+// every connected controller's sticks and triggers and saves them, to be
+// applied on every run from then on
+pub fn run() -> Result<(), String> {
+    let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+    let ids: Vec<GamepadId> = gilrs.gamepads().map(|(id, _)| id).collect();
+    if ids.is_empty() {
+        return Err("no controller connected".to_string());
+    }
+    for &id in &ids {
+        println!("calibrating {}", gilrs.gamepad(id).name());
+    }
+
+    println!("let go of the sticks and triggers, then press enter");
+    wait_for_enter()?;
+    let rest = average(&mut gilrs, SAMPLE_TIME);
+
+    println!(
+        "roll both sticks around their edges a few times and pull both triggers \
+        all the way, then press enter"
+    );
+    let (done, finished) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = done.send(wait_for_enter());
+    });
+    let mut reach: HashMap<(GamepadId, Axis), (f32, f32)> = HashMap::new();
+    let mut pulled: HashMap<(GamepadId, Button), f32> = HashMap::new();
+    let result = loop {
+        if let Ok(result) = finished.try_recv() {
+            break result;
+        }
+        while gilrs.next_event().is_some() {}
+        for (id, gamepad) in gilrs.gamepads() {
+            for axis in STICK_AXES {
+                let value = gamepad.value(axis);
+                let (low, high) = reach.entry((id, axis)).or_insert((value, value));
+                *low = low.min(value);
+                *high = high.max(value);
+            }
+            for button in TRIGGERS {
+                if let Some(data) = gamepad.button_data(button) {
+                    let most = pulled.entry((id, button)).or_insert(data.value());
+                    *most = most.max(data.value());
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(5));
+    };
+    result?;
+
+    fs::create_dir_all(calibration_dir()).map_err(|e| e.to_string())?;
+    for id in ids {
+        let gamepad = gilrs.gamepad(id);
+        let mut saved = Saved {
+            name: gamepad.name().to_string(),
+            ..Saved::default()
+        };
+        for axis in STICK_AXES {
+            let (Some(&center), Some(&(min, max))) =
+                (rest.axes.get(&(id, axis)), reach.get(&(id, axis)))
+            else {
+                continue;
+            };
+            if center.abs() > MAX_DRIFT || max - center < MIN_TRAVEL || center - min < MIN_TRAVEL {
+                println!("{:?} didn't center or sweep properly, skipping it", axis);
+                continue;
+            }
+            println!(
+                "{:?}: {:.2} to {:.2}, resting at {:.2}",
+                axis, min, max, center
+            );
+            saved.axes.push(AxisRange {
+                axis,
+                min,
+                center,
+                max,
+            });
+        }
+        for button in TRIGGERS {
+            let (Some(&min), Some(&max)) =
+                (rest.triggers.get(&(id, button)), pulled.get(&(id, button)))
+            else {
+                continue;
+            };
+            if max - min < MIN_TRAVEL {
+                println!("{:?} wasn't pulled, skipping it", button);
+                continue;
+            }
+            println!("{:?}: {:.2} to {:.2}", button, min, max);
+            saved.triggers.push(TriggerRange { button, min, max });
+        }
+
+        let path = path(&gamepad);
+        let text = toml::to_string(&saved).map_err(|e| e.to_string())?;
+        fs::write(&path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        println!("saved {}", path.display());
+    }
+    Ok(())
+}
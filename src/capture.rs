@@ -0,0 +1,96 @@
+use crate::action::Action;
+use crate::bindings::chord_click;
+use enigo::Key;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(not(target_os = "macos"))]
+use crate::keymap::parse_key;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use crate::window::{desktop, Desktop};
+
+// screenshots and screen recording through the os's own tool, e.g.
+// `capture = "region"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureOp {
+    // the whole screen, the focused window, or an area picked with the
+    // mouse
+    Screen,
+    Window,
+    Region,
+    // starts recording the screen, or stops it when running
+    Record,
+}
+
+// whether a recording we started is running, for where starting and
+// stopping are different shortcuts
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+// the keys for `op`, None where the platform has no shortcut for it
+pub fn actions(op: CaptureOp) -> Option<Vec<Action>> {
+    let recording = match op {
+        CaptureOp::Record => !RECORDING.fetch_xor(true, Ordering::Relaxed),
+        _ => RECORDING.load(Ordering::Relaxed),
+    };
+    let chords = shortcut(op, recording)?;
+    Some(chords.iter().flat_map(|keys| chord_click(keys)).collect())
+}
+
+// whether `op` can be bound here at all
+pub fn supported(op: CaptureOp) -> bool {
+    shortcut(op, false).is_some()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn print_screen() -> Key {
+    parse_key("printscreen").expect("print screen is in the key table")
+}
+
+// the screenshot goes to Pictures\Screenshots, the window one to the
+// clipboard, snipping tool does regions and game bar records
+#[cfg(target_os = "windows")]
+fn shortcut(op: CaptureOp, _recording: bool) -> Option<Vec<Vec<Key>>> {
+    Some(vec![match op {
+        CaptureOp::Screen => vec![Key::Meta, print_screen()],
+        CaptureOp::Window => vec![Key::Alt, print_screen()],
+        CaptureOp::Region => vec![Key::Meta, Key::Shift, Key::Layout('s')],
+        CaptureOp::Record => vec![Key::Meta, Key::Alt, Key::Layout('r')],
+    }])
+}
+
+// cmd+shift+4 then space picks a window with the next click. recording
+// opens the screenshot toolbar, and stopping is its own shortcut
+#[cfg(target_os = "macos")]
+fn shortcut(op: CaptureOp, recording: bool) -> Option<Vec<Vec<Key>>> {
+    Some(match op {
+        CaptureOp::Screen => vec![vec![Key::Meta, Key::Shift, Key::Layout('3')]],
+        CaptureOp::Window => vec![
+            vec![Key::Meta, Key::Shift, Key::Layout('4')],
+            vec![Key::Space],
+        ],
+        CaptureOp::Region => vec![vec![Key::Meta, Key::Shift, Key::Layout('4')]],
+        CaptureOp::Record if recording => vec![vec![Key::Control, Key::Meta, Key::Escape]],
+        CaptureOp::Record => vec![vec![Key::Meta, Key::Shift, Key::Layout('5')]],
+    })
+}
+
+// gnome's screenshot ui, spectacle on kde, and the xfce screenshooter's
+// defaults elsewhere, which have no recorder
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn shortcut(op: CaptureOp, _recording: bool) -> Option<Vec<Vec<Key>>> {
+    Some(vec![match (op, desktop()) {
+        (CaptureOp::Screen, Desktop::Gnome | Desktop::Kde) => vec![Key::Shift, print_screen()],
+        (CaptureOp::Screen, Desktop::Other) => vec![print_screen()],
+        (CaptureOp::Window, Desktop::Kde) => vec![Key::Meta, print_screen()],
+        (CaptureOp::Window, _) => vec![Key::Alt, print_screen()],
+        (CaptureOp::Region, Desktop::Gnome) => vec![print_screen()],
+        (CaptureOp::Region, Desktop::Kde) => vec![Key::Meta, Key::Shift, print_screen()],
+        (CaptureOp::Region, Desktop::Other) => vec![Key::Shift, print_screen()],
+        (CaptureOp::Record, Desktop::Gnome) => {
+            vec![Key::Control, Key::Shift, Key::Alt, Key::Layout('r')]
+        }
+        (CaptureOp::Record, Desktop::Kde) => vec![Key::Meta, Key::Alt, Key::Layout('r')],
+        (CaptureOp::Record, Desktop::Other) => return None,
+    }])
+}
@@ -0,0 +1,117 @@
+use crate::bands::{self, AxisName};
+use crate::bindings;
+use crate::config::{self, config_dir, Config};
+use crate::expr::{Evaluator, Setting};
+use crate::hat;
+use crate::preset;
+
+// `controller2keys check`: loads the config the way a run would, but reports
+// everything a run would skip with a warning. returns whether it's clean
+pub fn run() -> bool {
+    let path = config_dir().join("config.toml");
+    if !path.exists() {
+        println!(
+            "no config at {}, the built-in mapping is used",
+            path.display()
+        );
+        return true;
+    }
+    let config = match Config::load(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}", e);
+            return false;
+        }
+    };
+
+    let mut problems = Vec::new();
+    let known =
+        |name: &str| config.profiles.contains_key(name) || preset::names().any(|p| p == name);
+    if !known(&config.profile) && config.profile != "default" {
+        problems.push(format!(
+            "default profile '{}' isn't defined, so it's empty",
+            config.profile
+        ));
+    }
+
+    let evaluator = Evaluator::new();
+    for (name, profile) in &config.profiles {
+        let physical = profile.key_positions.physical();
+        for e in bindings::errors(&profile.bindings, physical) {
+            problems.push(format!("profile '{}': binding for {}", name, e));
+        }
+        for e in bands::errors(&profile.axis_bands, physical) {
+            problems.push(format!("profile '{}': axis band on {}", name, e));
+        }
+        for e in hat::errors(&profile.hats, physical) {
+            problems.push(format!("profile '{}': hat on {}", name, e));
+        }
+        let axes = profile.axis_bands.iter().map(|b| &b.axis);
+        let axes = axes.chain(profile.hats.iter().flat_map(|h| [&h.x, &h.y]));
+        for axis in axes {
+            if let AxisName::Named(axis) = axis {
+                let named = config
+                    .devices
+                    .iter()
+                    .any(|d| d.named_axes.values().any(|n| n == axis));
+                if !named {
+                    problems.push(format!(
+                        "profile '{}': axis '{}' is neither a gilrs axis nor in any device rule's named_axes",
+                        name, axis
+                    ));
+                }
+            }
+        }
+        for binding in &profile.bindings {
+            let exprs = binding.when.iter().flat_map(|c| c.exprs());
+            for src in exprs {
+                if let Err(e) = evaluator.check(Some(&Setting::Expr(src.to_string()))) {
+                    problems.push(format!(
+                        "profile '{}': binding for {:?}: when: {}",
+                        name, binding.button, e
+                    ));
+                }
+            }
+            for tier in [&binding.speed, &binding.speed_hold].into_iter().flatten() {
+                if !profile.speed_tiers.contains_key(tier) {
+                    problems.push(format!(
+                        "profile '{}': binding for {:?}: no speed tier '{}'",
+                        name, binding.button, tier
+                    ));
+                }
+            }
+        }
+        for (setting, value) in [
+            ("sensitivity", &profile.sensitivity),
+            ("deadzone", &profile.deadzone),
+        ] {
+            if let Err(e) = evaluator.check(value.as_ref()) {
+                problems.push(format!("profile '{}': {}: {}", name, setting, e));
+            }
+        }
+        for script in &profile.scripts {
+            let script = config::resolve(script);
+            if !script.exists() {
+                problems.push(format!(
+                    "profile '{}': script {} doesn't exist",
+                    name,
+                    script.display()
+                ));
+            } else if !cfg!(feature = "lua") {
+                problems.push(format!(
+                    "profile '{}': script {} needs the `lua` feature",
+                    name,
+                    script.display()
+                ));
+            }
+        }
+    }
+
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if problems.is_empty() {
+        println!("{} is ok", path.display());
+    }
+    problems.is_empty()
+}
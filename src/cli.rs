@@ -0,0 +1,150 @@
+use clap::{Args, Parser, Subcommand};
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+// the command line. clap turns the doc comments into --help text, and exits
+// with code 2 on bad usage. build.rs includes this file for the man pages,
+// so nothing here can refer to the rest of the crate
+
+/// Maps a game controller to keyboard and mouse input
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    // running is the default, so its flags work without `run` too
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Args, Clone, Default)]
+pub struct RunArgs {
+    /// Use a built-in preset as the default profile
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+    /// Don't ask for real-time scheduling
+    #[arg(long)]
+    pub no_rt: bool,
+    /// Keep the event loop and injection on this CPU core
+    #[arg(long, value_name = "CORE")]
+    pub cpu_affinity: Option<usize>,
+    /// Write every event and the actions it caused to FILE as JSON lines
+    #[arg(long, value_name = "FILE")]
+    pub json_events: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Map the controller to keyboard and mouse (the default)
+    Run(RunArgs),
+    /// List connected controllers, profiles and presets
+    List,
+    /// Check the config for errors without running
+    Check,
+    /// Warn about bindings that conflict, can't fire or can't be undone
+    Lint {
+        /// Only this profile, and it's the one checked against the controllers
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+    },
+    /// Write a starter config.toml
+    Init {
+        /// Start from a built-in preset
+        #[arg(long, value_name = "NAME")]
+        preset: Option<String>,
+        /// Replace an existing config.toml
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print controller events as they arrive, without mapping them
+    Monitor,
+    /// Measure the sticks' and triggers' range and save it for every run
+    Calibrate,
+    /// Control the running instance
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+    /// Copy the profiles from a shared config file into yours
+    Import {
+        file: PathBuf,
+        /// Replace profiles you already have with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Record controller input to a session file until interrupted
+    Record { file: PathBuf },
+    /// Run the mapping on a recorded session instead of the controller
+    Replay {
+        file: PathBuf,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Print the actions a config would produce for a recorded session
+    Simulate {
+        /// The config to try, instead of yours
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+        /// A session written by `record`
+        #[arg(long, value_name = "FILE")]
+        input: PathBuf,
+        /// The profile to use instead of the config's default
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Don't wait out the session's timing; repeats and timers won't fire
+        #[arg(long)]
+        fast: bool,
+    },
+    /// Built-in presets
+    Preset {
+        #[command(subcommand)]
+        command: PresetCommand,
+    },
+    /// Check for common reasons nothing happens
+    Doctor,
+    /// Install udev rules for controller and uinput access (Linux)
+    SetupUdev,
+    /// Drive a virtual gamepad from the keyboard and mouse
+    Reverse,
+    /// Print a shell completion script
+    Completions { shell: Shell },
+    /// Edit mappings in a window
+    #[cfg(feature = "gui")]
+    Gui,
+    /// Serve the mapping editor on localhost
+    #[cfg(feature = "web")]
+    Web {
+        #[arg(default_value_t = 8377)]
+        port: u16,
+    },
+    /// Edit mappings in the terminal
+    #[cfg(feature = "tui")]
+    Tui,
+}
+
+#[derive(Subcommand)]
+pub enum PresetCommand {
+    /// List the built-in presets
+    List,
+    /// Print a preset, to paste into config.toml
+    Export { name: String },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum CtlCommand {
+    /// Show the active profile and whether mapping is paused
+    Status,
+    /// Stop injecting until resumed
+    Pause,
+    /// Start injecting again
+    Resume,
+    /// Switch to a profile, or back to automatic selection without one
+    Profile { name: Option<String> },
+    /// Release every held key and mouse button
+    Release,
+    /// Print the profile, pause state, profiles and sensitivity as json, for
+    /// quick-settings plugins
+    State,
+    /// Set the stick-mouse speed until the profile changes
+    Sensitivity { value: f32 },
+}
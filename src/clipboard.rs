@@ -0,0 +1,44 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// the platform's clipboard tool, so we don't need a clipboard library and
+// its display-server plumbing
+fn copy_command() -> Command {
+    #[cfg(target_os = "macos")]
+    return Command::new("pbcopy");
+
+    #[cfg(target_os = "windows")]
+    return Command::new("clip");
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            Command::new("wl-copy")
+        } else {
+            let mut command = Command::new("xclip");
+            command.args(["-selection", "clipboard"]);
+            command
+        }
+    }
+}
+
+pub fn set(text: &str) -> Result<(), String> {
+    let mut command = copy_command();
+    let program = format!("{:?}", command.get_program());
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", program, e))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(text.as_bytes())
+        .map_err(|e| format!("failed to write to {}: {}", program, e))?;
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
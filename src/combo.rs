@@ -0,0 +1,102 @@
+use crate::action::{parse_one_shot, Action};
+use gilrs::Button;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// a binding fired by pressing buttons in order, e.g.
+// `sequence = ["DPadUp", "DPadUp", "DPadDown", "DPadDown", "East", "South"]`.
+// meant for rare or destructive actions that shouldn't fire by accident
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComboConfig {
+    pub sequence: Vec<Button>,
+    // the whole sequence has to fit in this
+    #[serde(default = "default_within_ms")]
+    pub within_ms: u64,
+    pub key: Option<String>,
+    pub mouse: Option<String>,
+    pub scroll: Option<i32>,
+}
+
+fn default_within_ms() -> u64 {
+    2000
+}
+
+struct Combo {
+    sequence: Vec<Button>,
+    within: Duration,
+    action: Action,
+}
+
+pub struct ComboMatcher {
+    combos: Vec<Combo>,
+    // recent presses, oldest first, only as many as the longest sequence
+    recent: VecDeque<(Button, Instant)>,
+    longest: usize,
+}
+
+impl ComboMatcher {
+    pub fn new(configs: &[ComboConfig]) -> ComboMatcher {
+        let mut combos = Vec::new();
+        for config in configs {
+            if config.sequence.is_empty() {
+                println!("ignoring combo with an empty sequence");
+                continue;
+            }
+            let action = parse_one_shot(
+                config.key.as_deref(),
+                config.mouse.as_deref(),
+                config.scroll,
+            );
+            match action {
+                Ok(action) => combos.push(Combo {
+                    sequence: config.sequence.clone(),
+                    within: Duration::from_millis(config.within_ms),
+                    action,
+                }),
+                Err(e) => println!("ignoring combo {:?}: {}", config.sequence, e),
+            }
+        }
+        let longest = combos.iter().map(|c| c.sequence.len()).max().unwrap_or(0);
+        ComboMatcher {
+            combos,
+            recent: VecDeque::with_capacity(longest),
+            longest,
+        }
+    }
+
+    // call on every button press. a completed combo fires once and starts
+    // over, so its last buttons can't double as the start of another
+    pub fn press(&mut self, button: Button) -> Vec<Action> {
+        if self.longest == 0 {
+            return vec![];
+        }
+        let now = Instant::now();
+        if self.recent.len() == self.longest {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((button, now));
+
+        let recent = &self.recent;
+        let fired = self.combos.iter().find(|combo| {
+            let n = combo.sequence.len();
+            n <= recent.len()
+                && recent
+                    .iter()
+                    .skip(recent.len() - n)
+                    .map(|(b, _)| b)
+                    .eq(combo.sequence.iter())
+                && now - recent[recent.len() - n].1 <= combo.within
+        });
+
+        match fired {
+            Some(combo) => {
+                println!("combo {:?} -> {:?}", combo.sequence, combo.action);
+                self.recent.clear();
+                vec![combo.action.clone()]
+            }
+            None => vec![],
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use crate::adaptive::AdaptiveTriggers;
+use crate::bands::BandConfig;
+use crate::bindings::BindingConfig;
+use crate::board::BoardConfig;
+use crate::combo::ComboConfig;
+use crate::devices::{AxisRemap, DeviceRule};
+use crate::dial::DialConfig;
+use crate::expr::Setting;
+use crate::flick::FlickConfig;
+use crate::focus::Focus;
+use crate::haptics::HapticsConfig;
+use crate::hat::HatConfig;
+use crate::highlight::CursorRingConfig;
+use crate::hooks::Hooks;
+use crate::hud::HudConfig;
+use crate::killswitch::KillSwitchConfig;
+use crate::lightbar::LightbarConfig;
+use crate::macros::MacroConfig;
+use crate::merge::MergeConfig;
+use crate::mode::AutoMode;
+use crate::obs::ObsConfig;
+use crate::pointer::PointerConfig;
+use crate::reverse::ReverseConfig;
+use crate::ring::RingConfig;
+use crate::scancode::KeyPositions;
+use crate::scroll::ScrollAcceleration;
+use crate::smoothing::SmoothingConfig;
+use crate::socd::SocdConfig;
+use crate::sound::Sounds;
+use crate::speech::SpeechConfig;
+use crate::split::SplitConfig;
+use crate::stick::{StickMode, StickThresholds};
+use crate::transition::Step;
+use crate::trigger::TriggerConfig;
+use crate::typing::TypingGuardConfig;
+use crate::watchdog::WatchdogConfig;
+use crate::zoom::TriggerZoomConfig;
+use gilrs::Button;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// everything lives in <config dir>/controller2keys, e.g.
+// ~/.config/controller2keys/config.toml on linux
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("controller2keys")
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // name of the profile to run with when no app-specific profile matches
+    pub profile: String,
+    pub profiles: BTreeMap<String, Profile>,
+    // stop injecting whenever the focused app has no profile of its own,
+    // e.g. when tabbing out of a game into a chat app
+    pub pause_outside_apps: bool,
+    // hide the controller from games reading it natively while mapping is
+    // active (evdev grab on linux, hidhide on windows)
+    pub grab: bool,
+    // learn where the sticks rest at startup and correct for drift
+    pub auto_calibrate: bool,
+    // give up (exit code 3) when no controller shows up within this many
+    // seconds. waits forever when unset
+    pub controller_timeout: Option<u64>,
+    // let go of every held key and mouse button when no controller event
+    // has arrived for this many seconds, e.g. the pad fell asleep mid-press
+    pub idle_release: Option<u64>,
+    // release keys still held after the controller has been at rest a while
+    pub watchdog: Option<WatchdogConfig>,
+    // a button that has to be held for anything to be injected, for sharing
+    // the controller with a game reading it natively
+    pub enable_button: Option<Button>,
+    // hold controller keys back while the physical keyboard is typed on
+    pub typing_guard: Option<TypingGuardConfig>,
+    // chord that releases everything and pauses, always available
+    pub kill_switch: KillSwitchConfig,
+    // several controllers feeding one mapping
+    pub merge: Option<MergeConfig>,
+    // per-controller inputs to ignore or rename
+    pub devices: Vec<DeviceRule>,
+    // axes fed into others or scaled, for every controller
+    pub remap: Vec<AxisRemap>,
+    // automatic desktop/game profile selection
+    pub auto_mode: Option<AutoMode>,
+    // commands to run on lifecycle events
+    pub hooks: Hooks,
+    // sounds to play on state changes
+    pub sounds: Sounds,
+    // spoken announcements
+    pub speech: Option<SpeechConfig>,
+    // on-screen overlay with the live state
+    pub hud: Option<HudConfig>,
+    // ring around the pointer while the stick moves it
+    pub cursor_ring: Option<CursorRingConfig>,
+    // the stick as a synthetic pen or finger instead of the mouse
+    pub pointer: Option<PointerConfig>,
+    // at least this long between injected key and mouse button events
+    pub min_event_gap_ms: u64,
+    // opposite movement keys held at once from different sources
+    pub socd: Option<SocdConfig>,
+    // obs-websocket connection for `obs` bindings
+    pub obs: Option<ObsConfig>,
+    // keyboard and mouse to virtual gamepad, for `controller2keys reverse`
+    pub reverse: Option<ReverseConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            profile: "default".to_string(),
+            profiles: BTreeMap::new(),
+            pause_outside_apps: false,
+            grab: false,
+            auto_calibrate: false,
+            controller_timeout: None,
+            idle_release: None,
+            watchdog: None,
+            enable_button: None,
+            typing_guard: None,
+            kill_switch: KillSwitchConfig::default(),
+            merge: None,
+            devices: Vec::new(),
+            remap: Vec::new(),
+            auto_mode: None,
+            hooks: Hooks::new(),
+            sounds: Sounds::new(),
+            speech: None,
+            hud: None,
+            cursor_ring: None,
+            pointer: None,
+            min_event_gap_ms: 0,
+            socd: None,
+            obs: None,
+            reverse: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    // apps that switch to this profile when focused (bundle identifiers on
+    // macos, executable names on windows, the window's WM_CLASS on x11 and
+    // its app_id on wayland)
+    pub apps: Vec<String>,
+    // steam appids that switch to this profile while the game runs, e.g.
+    // `steam_apps = [1245620]`. an app-specific profile for whatever has
+    // focus wins over these
+    pub steam_apps: Vec<u32>,
+    // only match while the focused window is fullscreen. with no `apps`
+    // this matches any fullscreen app
+    pub fullscreen_only: bool,
+    // stop injecting whenever anything but this app (or a window with this
+    // in its title) has focus
+    pub only_when_focused: Option<String>,
+    // lua scripts run alongside the mapping, relative to the config dir
+    pub scripts: Vec<PathBuf>,
+    // stick-mouse speed and stick deadzone, either numbers or expressions
+    pub sensitivity: Option<Setting>,
+    pub deadzone: Option<Setting>,
+    // named stick-mouse speeds for `speed` and `speed_hold` bindings, used
+    // instead of sensitivity while one is picked
+    pub speed_tiers: BTreeMap<String, f32>,
+    // filter on stick-mouse readings against jittery aim
+    pub mouse_smoothing: Option<SmoothingConfig>,
+    // each tick's stick-mouse movement split into this many smaller moves
+    // spread across the tick, for games reading raw input
+    pub mouse_interpolation: Option<u32>,
+    // button bindings, overriding the built-in mapping for the buttons they
+    // mention
+    pub bindings: Vec<BindingConfig>,
+    // analog press/release thresholds for the triggers
+    pub triggers: Option<TriggerConfig>,
+    // bumper scrolling speeds up with rapid taps
+    pub scroll_acceleration: Option<ScrollAcceleration>,
+    // right-stick rotation as a scroll/volume dial
+    pub dial: Option<DialConfig>,
+    // the triggers zooming in and out with ctrl+scroll
+    pub trigger_zoom: Option<TriggerZoomConfig>,
+    // stick flick gestures
+    pub flicks: Option<FlickConfig>,
+    // keys held while a stick is pushed all the way out
+    pub outer_ring: Vec<RingConfig>,
+    // keys held while an axis is inside part of its travel, e.g. throttle
+    // detents
+    pub axis_bands: Vec<BandConfig>,
+    // pov hats as 8-way inputs holding keys
+    pub hats: Vec<HatConfig>,
+    // button sequences, checked in order
+    pub combos: Vec<ComboConfig>,
+    // recordable mouse macros
+    pub macros: Vec<MacroConfig>,
+    // whether character keys follow the keyboard layout or us qwerty
+    // positions
+    pub key_positions: KeyPositions,
+    // what the sticks do. the right stick is the mouse unless set
+    pub left_stick: StickMode,
+    pub right_stick: Option<StickMode>,
+    // separate press and release points for sticks holding keys
+    pub left_stick_thresholds: Option<StickThresholds>,
+    pub right_stick_thresholds: Option<StickThresholds>,
+    // playstation lightbar color for this profile and its layers
+    pub lightbar: Option<LightbarConfig>,
+    // rumble that follows mouse and scroll speed
+    pub haptics: Option<HapticsConfig>,
+    // dualsense trigger resistance
+    pub adaptive_triggers: Option<AdaptiveTriggers>,
+    // grid of named actions opened with a chord
+    pub board: Option<BoardConfig>,
+    // hand part of the controller to another profile
+    pub split: Option<SplitConfig>,
+    // run when switching to and away from this profile
+    pub on_activate: Vec<Step>,
+    pub on_deactivate: Vec<Step>,
+}
+
+impl Profile {
+    pub fn matches(&self, focus: &Focus) -> bool {
+        if self.fullscreen_only && !focus.fullscreen {
+            return false;
+        }
+        if self.apps.is_empty() {
+            return self.fullscreen_only;
+        }
+        self.apps.iter().any(|a| a.eq_ignore_ascii_case(&focus.app))
+    }
+
+    // what both sticks do
+    pub fn sticks(&self) -> impl Iterator<Item = &StickMode> {
+        [Some(&self.left_stick), self.right_stick.as_ref()]
+            .into_iter()
+            .flatten()
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+    }
+
+    // a missing config file just means "use the built-in mapping"
+    pub fn load_or_default() -> Config {
+        let path = config_dir().join("config.toml");
+        if !path.exists() {
+            return Config::default();
+        }
+        match Config::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("{} - falling back to defaults", e);
+                Config::default()
+            }
+        }
+    }
+
+    pub fn get_profile(&self, name: &str) -> Profile {
+        self.profiles.get(name).cloned().unwrap_or_default()
+    }
+
+    // the app-specific profile for `focus`, if any. profiles are checked in
+    // name order and the first match wins
+    pub fn match_profile(&self, focus: &Focus) -> Option<&str> {
+        self.profiles
+            .iter()
+            .find(|(_, p)| p.matches(focus))
+            .map(|(name, _)| name.as_str())
+    }
+
+    // whether any profile drives the virtual gamepad, from a stick or a
+    // `gamepad` binding
+    pub fn uses_pad(&self) -> bool {
+        self.profiles.values().any(|p| {
+            p.sticks().any(|s| matches!(s, StickMode::Analog(_)))
+                || p.bindings.iter().any(|b| b.gamepad.is_some())
+        })
+    }
+
+    // whether any profile scrolls smoothly or tilts the wheel. macros might
+    // have recorded either
+    pub fn uses_wheel(&self) -> bool {
+        self.profiles.values().any(|p| {
+            p.sticks().any(|s| matches!(s, StickMode::Scroll(_)))
+                || p.bindings.iter().any(|b| b.tilt.is_some())
+                || !p.macros.is_empty()
+        })
+    }
+
+    // the profile keyed to a running steam game, if any
+    pub fn match_steam_app(&self, app: u32) -> Option<&str> {
+        self.profiles
+            .iter()
+            .find(|(_, p)| p.steam_apps.contains(&app))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+// resolve a path from the config against the config dir
+pub fn resolve(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        config_dir().join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steam_apps_pick_their_profile() {
+        let config: Config = toml::from_str(
+            "[profiles.elden]\nsteam_apps = [1245620]\n\n[profiles.other]\nsteam_apps = [570]",
+        )
+        .unwrap();
+        assert_eq!(config.match_steam_app(1245620), Some("elden"));
+        assert_eq!(config.match_steam_app(730), None);
+    }
+}
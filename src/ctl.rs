@@ -0,0 +1,164 @@
+use crate::cli::CtlCommand;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+// `controller2keys ctl` talks to the running instance over a local socket:
+// one command line in, one reply line back. unix sockets live in the user's
+// runtime dir; windows gets a localhost tcp port written to a file instead
+
+#[cfg(not(unix))]
+use std::net::{TcpListener as Listener, TcpStream as Stream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+
+#[cfg(unix)]
+fn address() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("controller2keys.sock"),
+        // /tmp is shared, so keep users apart
+        None => std::env::temp_dir().join(format!("controller2keys-{}.sock", unsafe {
+            libc::getuid()
+        })),
+    }
+}
+
+#[cfg(not(unix))]
+fn address() -> PathBuf {
+    std::env::temp_dir().join("controller2keys.port")
+}
+
+pub struct Server {
+    listener: Listener,
+}
+
+impl Server {
+    // failing to listen only costs `ctl`, so it's a warning
+    pub fn start() -> Option<Server> {
+        match bind() {
+            Ok(listener) => {
+                listener.set_nonblocking(true).ok()?;
+                Some(Server { listener })
+            }
+            Err(e) => {
+                println!("ctl unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    // a waiting command, if any. never blocks for long
+    pub fn poll(&mut self) -> Option<(CtlCommand, Connection)> {
+        let (stream, _) = self.listener.accept().ok()?;
+        stream.set_nonblocking(false).ok()?;
+        stream
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .ok()?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).ok()?;
+        let mut connection = Connection(stream);
+        match parse(line.trim()) {
+            Some(command) => Some((command, connection)),
+            None => {
+                connection.reply(&format!("unknown command '{}'", line.trim()));
+                None
+            }
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(address());
+    }
+}
+
+pub struct Connection(Stream);
+
+impl Connection {
+    pub fn reply(&mut self, text: &str) {
+        let _ = writeln!(self.0, "{}", text);
+    }
+}
+
+#[cfg(unix)]
+fn bind() -> std::io::Result<Listener> {
+    let path = address();
+    // a socket nobody answers on is left over from a crash
+    if path.exists() {
+        if Stream::connect(&path).is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                "another instance is running",
+            ));
+        }
+        std::fs::remove_file(&path)?;
+    }
+    Listener::bind(&path)
+}
+
+#[cfg(not(unix))]
+fn bind() -> std::io::Result<Listener> {
+    let listener = Listener::bind("127.0.0.1:0")?;
+    std::fs::write(address(), listener.local_addr()?.port().to_string())?;
+    Ok(listener)
+}
+
+#[cfg(unix)]
+fn connect() -> std::io::Result<Stream> {
+    Stream::connect(address())
+}
+
+#[cfg(not(unix))]
+fn connect() -> std::io::Result<Stream> {
+    let port = std::fs::read_to_string(address())?;
+    let port: u16 = port
+        .trim()
+        .parse()
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "bad port file"))?;
+    Stream::connect(("127.0.0.1", port))
+}
+
+// sends one command to the running instance and returns its reply
+pub fn send(command: &CtlCommand) -> Result<String, String> {
+    let mut stream = connect().map_err(|e| format!("controller2keys isn't running ({})", e))?;
+    writeln!(stream, "{}", format(command)).map_err(|e| e.to_string())?;
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| e.to_string())?;
+    Ok(reply.trim_end().to_string())
+}
+
+fn format(command: &CtlCommand) -> String {
+    match command {
+        CtlCommand::Status => "status".to_string(),
+        CtlCommand::Pause => "pause".to_string(),
+        CtlCommand::Resume => "resume".to_string(),
+        CtlCommand::Profile { name: None } => "profile".to_string(),
+        CtlCommand::Profile { name: Some(name) } => format!("profile {}", name),
+        CtlCommand::Release => "release".to_string(),
+        CtlCommand::State => "state".to_string(),
+        CtlCommand::Sensitivity { value } => format!("sensitivity {}", value),
+    }
+}
+
+fn parse(line: &str) -> Option<CtlCommand> {
+    let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    Some(match word {
+        "status" => CtlCommand::Status,
+        "pause" => CtlCommand::Pause,
+        "resume" => CtlCommand::Resume,
+        "profile" if rest.is_empty() => CtlCommand::Profile { name: None },
+        "profile" => CtlCommand::Profile {
+            name: Some(rest.to_string()),
+        },
+        "release" => CtlCommand::Release,
+        "state" => CtlCommand::State,
+        "sensitivity" => CtlCommand::Sensitivity {
+            value: rest.parse().ok()?,
+        },
+        _ => return None,
+    })
+}
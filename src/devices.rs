@@ -0,0 +1,261 @@
+use gilrs::ev::Code;
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// fixes for one kind of controller, from `[[devices]]`: inputs it reports
+// that should be left alone, e.g. a cheap pad's constantly drifting extra
+// axis, and non-standard inputs given a name the mapping knows
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceRule {
+    // part of the controller's name, case-insensitive
+    pub name: String,
+    #[serde(default)]
+    pub ignore_buttons: Vec<Button>,
+    #[serde(default)]
+    pub ignore_axes: Vec<Axis>,
+    // raw event codes as printed for unknown inputs, e.g.
+    // `buttons = { "300" = "Mode" }`
+    #[serde(default)]
+    pub ignore_codes: Vec<u32>,
+    #[serde(default)]
+    pub buttons: BTreeMap<String, Button>,
+    #[serde(default)]
+    pub axes: BTreeMap<String, Axis>,
+    // names of our own for axes past the ones gilrs has, for axis bands,
+    // e.g. `named_axes = { "6" = "throttle", "7" = "rudder" }`
+    #[serde(default)]
+    pub named_axes: BTreeMap<String, String>,
+    // named axes fed into others, scaled, e.g. a yoke's roll as LeftStickX
+    #[serde(default)]
+    pub remap: Vec<AxisRemap>,
+}
+
+// one row of a remap matrix, e.g. `{ from = "RightStickX", to =
+// "LeftStickX" }` or `{ from = "LeftStickY", scale = -1.0 }` to invert.
+// `to` defaults to the same axis
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AxisRemap {
+    pub from: Axis,
+    pub to: Option<Axis>,
+    #[serde(default = "one")]
+    pub scale: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+// a remap matrix ready to apply. every axis is looked up by the name it
+// came in with, so swapping two axes works
+#[derive(Debug, Clone, Default)]
+pub struct Remap {
+    axes: HashMap<Axis, (Axis, f32)>,
+}
+
+impl Remap {
+    // the first row for an axis wins
+    pub fn new(rows: &[AxisRemap]) -> Remap {
+        let mut axes = HashMap::new();
+        for row in rows {
+            if axes.contains_key(&row.from) {
+                println!("{:?} is remapped twice, using the first", row.from);
+                continue;
+            }
+            axes.insert(row.from, (row.to.unwrap_or(row.from), row.scale));
+        }
+        Remap { axes }
+    }
+
+    pub fn apply(&self, event: EventType) -> EventType {
+        match event {
+            EventType::AxisChanged(axis, value, code) => match self.axes.get(&axis) {
+                Some(&(to, scale)) => {
+                    EventType::AxisChanged(to, (value * scale).clamp(-1.0, 1.0), code)
+                }
+                None => event,
+            },
+            _ => event,
+        }
+    }
+}
+
+// the names rules gave unknown axes, by code. codes come from the platform
+// rather than the controller, so one table does for every device and the
+// first rule to name a code wins
+#[derive(Debug, Clone, Default)]
+pub struct AxisNames {
+    names: BTreeMap<u32, String>,
+}
+
+impl AxisNames {
+    pub fn new(configs: &[DeviceRule]) -> AxisNames {
+        let mut names = BTreeMap::new();
+        for config in configs {
+            for (code, name) in &config.named_axes {
+                match code.parse::<u32>() {
+                    Ok(code) => {
+                        names.entry(code).or_insert_with(|| name.clone());
+                    }
+                    Err(_) => println!(
+                        "ignoring axis name '{}' for '{}': '{}' isn't an event code",
+                        name, config.name, code
+                    ),
+                }
+            }
+        }
+        AxisNames { names }
+    }
+
+    // what a rule's `named_axes` calls the unknown axis with this code
+    pub fn get(&self, code: u32) -> Option<&str> {
+        self.names.get(&code).map(String::as_str)
+    }
+}
+
+// a rule ready to apply, codes parsed
+#[derive(Debug, Clone, Default)]
+struct Rule {
+    ignore_buttons: HashSet<Button>,
+    ignore_axes: HashSet<Axis>,
+    ignore_codes: HashSet<u32>,
+    buttons: HashMap<u32, Button>,
+    axes: HashMap<u32, Axis>,
+    remap: Remap,
+}
+
+impl DeviceRule {
+    fn compile(&self) -> Result<Rule, String> {
+        let code = |code: &String| {
+            code.parse::<u32>()
+                .map_err(|_| format!("'{}' isn't an event code", code))
+        };
+        Ok(Rule {
+            ignore_buttons: self.ignore_buttons.iter().copied().collect(),
+            ignore_axes: self.ignore_axes.iter().copied().collect(),
+            ignore_codes: self.ignore_codes.iter().copied().collect(),
+            buttons: self
+                .buttons
+                .iter()
+                .map(|(c, button)| Ok((code(c)?, *button)))
+                .collect::<Result<_, String>>()?,
+            axes: self
+                .axes
+                .iter()
+                .map(|(c, axis)| Ok((code(c)?, *axis)))
+                .collect::<Result<_, String>>()?,
+            remap: Remap::new(&self.remap),
+        })
+    }
+}
+
+// applies the first rule matching each controller's name to its events
+#[derive(Default)]
+pub struct Devices {
+    rules: Vec<(String, Rule)>,
+    // the rule each controller matched, None for controllers without one
+    matched: HashMap<GamepadId, Option<Rule>>,
+    // unknown inputs already pointed out
+    reported: HashSet<(GamepadId, u32)>,
+    // so named axes aren't pointed out as unknown
+    names: AxisNames,
+}
+
+impl Devices {
+    pub fn new(configs: &[DeviceRule], names: AxisNames) -> Devices {
+        let rules = configs
+            .iter()
+            .filter_map(|config| match config.compile() {
+                Ok(rule) => Some((config.name.to_lowercase(), rule)),
+                Err(e) => {
+                    println!("ignoring device rule for '{}': {}", config.name, e);
+                    None
+                }
+            })
+            .collect();
+        Devices {
+            rules,
+            names,
+            ..Devices::default()
+        }
+    }
+
+    // the event renamed, or None when it's ignored
+    pub fn filter(&mut self, gilrs: &Gilrs, id: GamepadId, event: EventType) -> Option<EventType> {
+        if let EventType::Disconnected = event {
+            self.matched.remove(&id);
+            return Some(event);
+        }
+        let rule = self.rule(gilrs, id);
+        let event = match rule {
+            Some(rule) => rule.apply(event)?,
+            None => event,
+        };
+        // gilrs couldn't name it and no rule did either
+        let unknown = match event {
+            EventType::ButtonPressed(Button::Unknown, code) => Some(("button", code)),
+            EventType::AxisChanged(Axis::Unknown, _, code)
+                if self.names.get(code.into_u32()).is_none() =>
+            {
+                Some(("axis", code))
+            }
+            _ => None,
+        };
+        if let Some((kind, code)) = unknown {
+            if self.reported.insert((id, code.into_u32())) {
+                println!(
+                    "{} has an unknown {} with code {}; name it in a [[devices]] rule to map it",
+                    gilrs.gamepad(id).name(),
+                    kind,
+                    code.into_u32()
+                );
+            }
+        }
+        Some(event)
+    }
+
+    fn rule(&mut self, gilrs: &Gilrs, id: GamepadId) -> Option<&Rule> {
+        let rules = &self.rules;
+        self.matched
+            .entry(id)
+            .or_insert_with(|| {
+                let name = gilrs.gamepad(id).name().to_lowercase();
+                rules
+                    .iter()
+                    .find(|(part, _)| name.contains(part.as_str()))
+                    .map(|(_, rule)| rule.clone())
+            })
+            .as_ref()
+    }
+}
+
+impl Rule {
+    fn apply(&self, event: EventType) -> Option<EventType> {
+        let code = |code: Code| code.into_u32();
+        // a renamed input is wanted whatever gilrs called it
+        let button = |button: Button, c: Code| match self.buttons.get(&code(c)) {
+            Some(renamed) => Some(*renamed),
+            None if self.ignore_codes.contains(&code(c)) => None,
+            None => (!self.ignore_buttons.contains(&button)).then_some(button),
+        };
+        let event = match event {
+            EventType::ButtonPressed(b, c) => EventType::ButtonPressed(button(b, c)?, c),
+            EventType::ButtonRepeated(b, c) => EventType::ButtonRepeated(button(b, c)?, c),
+            EventType::ButtonReleased(b, c) => EventType::ButtonReleased(button(b, c)?, c),
+            EventType::ButtonChanged(b, value, c) => {
+                EventType::ButtonChanged(button(b, c)?, value, c)
+            }
+            EventType::AxisChanged(axis, value, c) => match self.axes.get(&code(c)) {
+                Some(renamed) => EventType::AxisChanged(*renamed, value, c),
+                None if self.ignore_codes.contains(&code(c)) => return None,
+                None if self.ignore_axes.contains(&axis) => return None,
+                None => event,
+            },
+            _ => event,
+        };
+        // remapping goes by the names renaming gave
+        Some(self.remap.apply(event))
+    }
+}
@@ -0,0 +1,175 @@
+use crate::action::Action;
+use crate::input::InputState;
+use enigo::Key;
+use gilrs::{Axis, Button};
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DialTarget {
+    // clockwise scrolls down
+    Scroll,
+    // clockwise turns the volume up
+    Volume,
+    // clockwise presses right, for scrubbing timelines
+    Arrows,
+}
+
+// turns circling the right stick around its rim into steps, like a click
+// wheel, e.g. `dial = { target = "volume", hold = "RightThumb" }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DialConfig {
+    pub target: DialTarget,
+    // only a dial while this button is held, otherwise the stick moves the
+    // mouse as usual
+    pub hold: Option<Button>,
+    // how far out the stick has to be to count as on the rim
+    pub rim: f32,
+    // rotation per step
+    pub degrees_per_step: f32,
+}
+
+impl Default for DialConfig {
+    fn default() -> Self {
+        DialConfig {
+            target: DialTarget::Scroll,
+            hold: None,
+            rim: 0.8,
+            degrees_per_step: 30.0,
+        }
+    }
+}
+
+pub struct Dial {
+    config: DialConfig,
+    last_angle: Option<f32>,
+    // rotation not yet turned into steps, in degrees, clockwise positive
+    turned: f32,
+}
+
+impl Dial {
+    pub fn new(config: &DialConfig) -> Dial {
+        Dial {
+            config: config.clone(),
+            last_angle: None,
+            turned: 0.0,
+        }
+    }
+
+    pub fn active(&self, input: &InputState) -> bool {
+        self.config.hold.is_none_or(|b| input.is_held(b))
+    }
+
+    pub fn update(&mut self, input: &InputState) -> Vec<Action> {
+        let (x, y) = (input.axis(Axis::RightStickX), input.axis(Axis::RightStickY));
+        if x.hypot(y) < self.config.rim {
+            // leaving the rim ends the turn
+            self.last_angle = None;
+            self.turned = 0.0;
+            return vec![];
+        }
+
+        let angle = y.atan2(x);
+        if let Some(last) = self.last_angle {
+            // shortest way round, so crossing +-180 degrees isn't a full turn
+            let mut delta = last - angle;
+            if delta > PI {
+                delta -= 2.0 * PI;
+            } else if delta < -PI {
+                delta += 2.0 * PI;
+            }
+            self.turned += delta.to_degrees();
+        }
+        self.last_angle = Some(angle);
+
+        let step = self.config.degrees_per_step.max(1.0);
+        let mut actions = Vec::new();
+        while self.turned.abs() >= step {
+            let clockwise = self.turned > 0.0;
+            self.turned -= step.copysign(self.turned);
+            actions.push(match (self.config.target, clockwise) {
+                (DialTarget::Scroll, true) => Action::Scroll(1),
+                (DialTarget::Scroll, false) => Action::Scroll(-1),
+                (DialTarget::Volume, true) => Action::KeyClick(Key::VolumeUp),
+                (DialTarget::Volume, false) => Action::KeyClick(Key::VolumeDown),
+                (DialTarget::Arrows, true) => Action::KeyClick(Key::RightArrow),
+                (DialTarget::Arrows, false) => Action::KeyClick(Key::LeftArrow),
+            });
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the right stick on the rim at `degrees` clockwise from straight up
+    fn at(input: &mut InputState, degrees: f32) {
+        let angle = degrees.to_radians();
+        input.set_axis(Axis::RightStickX, angle.sin());
+        input.set_axis(Axis::RightStickY, angle.cos());
+    }
+
+    fn turn(target: DialTarget, from: f32, to: f32) -> Vec<Action> {
+        let mut dial = Dial::new(&DialConfig {
+            target,
+            ..DialConfig::default()
+        });
+        let mut input = InputState::default();
+        let mut actions = Vec::new();
+        let step = if to > from { 5.0 } else { -5.0 };
+        let mut degrees = from;
+        while (to - degrees).abs() > 0.1 {
+            at(&mut input, degrees);
+            actions.extend(dial.update(&input));
+            degrees += step;
+        }
+        at(&mut input, to);
+        actions.extend(dial.update(&input));
+        actions
+    }
+
+    #[test]
+    fn clockwise_scrolls_down() {
+        assert_eq!(
+            turn(DialTarget::Scroll, 0.0, 100.0),
+            vec![Action::Scroll(1); 3]
+        );
+    }
+
+    #[test]
+    fn counter_clockwise_scrolls_up() {
+        assert_eq!(
+            turn(DialTarget::Scroll, 0.0, -100.0),
+            vec![Action::Scroll(-1); 3]
+        );
+    }
+
+    #[test]
+    fn crossing_the_left_is_not_a_full_turn() {
+        assert_eq!(
+            turn(DialTarget::Volume, 240.0, 310.0),
+            vec![Action::KeyClick(Key::VolumeUp); 2]
+        );
+    }
+
+    #[test]
+    fn leaving_the_rim_forgets_the_turn() {
+        let mut dial = Dial::new(&DialConfig::default());
+        let mut input = InputState::default();
+        at(&mut input, 0.0);
+        dial.update(&input);
+        at(&mut input, 25.0);
+        assert!(dial.update(&input).is_empty());
+        input.set_axis(Axis::RightStickX, 0.0);
+        input.set_axis(Axis::RightStickY, 0.0);
+        assert!(dial.update(&input).is_empty());
+        at(&mut input, 0.0);
+        dial.update(&input);
+        at(&mut input, 10.0);
+        assert!(dial.update(&input).is_empty());
+    }
+}
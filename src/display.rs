@@ -0,0 +1,67 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+
+// screen brightness and night light from a binding, e.g.
+// `display = "brightness_up"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayOp {
+    BrightnessUp,
+    BrightnessDown,
+    // the warmer evening colors (night light, night shift)
+    NightLight,
+}
+
+impl DisplayOp {
+    pub fn repeats(self) -> bool {
+        self != DisplayOp::NightLight
+    }
+}
+
+// percent of the monitor's range per press
+const STEP: i32 = 10;
+
+// one change at a time: ddc/ci is slow, and monitors drop commands that
+// arrive while they're busy
+static BUSY: Mutex<()> = Mutex::new(());
+
+// done off the injection thread since a ddc round trip takes a good part
+// of a second
+pub fn perform(op: DisplayOp) {
+    thread::spawn(move || {
+        let _busy = BUSY.lock().unwrap_or_else(|e| e.into_inner());
+        let result = match op {
+            DisplayOp::BrightnessUp => brightness(STEP),
+            DisplayOp::BrightnessDown => brightness(-STEP),
+            DisplayOp::NightLight => night_light(),
+        };
+        if let Err(e) = result {
+            println!("{:?} failed: {}", op, e);
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+use linux::{brightness, night_light};
+#[cfg(target_os = "macos")]
+use macos::{brightness, night_light};
+#[cfg(target_os = "windows")]
+use windows::{brightness, night_light};
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn brightness(_percent: i32) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn night_light() -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
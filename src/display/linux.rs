@@ -0,0 +1,63 @@
+use std::process::Command;
+
+// runs a helper program, its stderr as the error when it fails
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", program, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// external monitors over ddc/ci with ddcutil (vcp feature 0x10 is
+// brightness), and a laptop's own panel with brightnessctl. either one
+// working is enough
+pub fn brightness(percent: i32) -> Result<(), String> {
+    let sign = if percent < 0 { "-" } else { "+" };
+    let amount = percent.abs().to_string();
+
+    let ddc = run("ddcutil", &["detect", "--terse"]).and_then(|detected| {
+        // "Display 1", "Display 2"... monitors without ddc/ci are listed as
+        // "Invalid display"
+        let displays: Vec<&str> = detected
+            .lines()
+            .filter_map(|line| line.strip_prefix("Display "))
+            .map(str::trim)
+            .collect();
+        if displays.is_empty() {
+            return Err("ddcutil: no monitors with ddc/ci".to_string());
+        }
+        for display in displays {
+            run(
+                "ddcutil",
+                &["--display", display, "setvcp", "10", sign, &amount],
+            )?;
+        }
+        Ok(())
+    });
+    let backlight = run("brightnessctl", &["set", &format!("{}%{}", amount, sign)]);
+
+    match (ddc, backlight) {
+        (Err(ddc), Err(backlight)) => Err(format!("{}; {}", ddc, backlight)),
+        _ => Ok(()),
+    }
+}
+
+const NIGHT_LIGHT: [&str; 2] = [
+    "org.gnome.settings-daemon.plugins.color",
+    "night-light-enabled",
+];
+
+// gnome's night light setting. other desktops keep theirs private
+pub fn night_light() -> Result<(), String> {
+    let [schema, key] = NIGHT_LIGHT;
+    let enabled = run("gsettings", &["get", schema, key])?.trim() == "true";
+    let value = if enabled { "false" } else { "true" };
+    run("gsettings", &["set", schema, key, value])?;
+    println!("night light {}", if enabled { "off" } else { "on" });
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use libloading::Library;
+use objc::runtime::{Class, Object, BOOL, NO, YES};
+use objc::{class, msg_send, sel, sel_impl, Encode, Encoding};
+use std::ffi::c_void;
+use std::sync::OnceLock;
+
+// NX_KEYTYPE_BRIGHTNESS_UP and _DOWN from ev_keymap.h
+const BRIGHTNESS_UP: i64 = 2;
+const BRIGHTNESS_DOWN: i64 = 3;
+// NSEventTypeSystemDefined, and its subtype for media and brightness keys
+const SYSTEM_DEFINED: u64 = 14;
+const AUX_CONTROL_BUTTONS: i16 = 8;
+// key down and key up, in the event's flags and in data1
+const KEY_DOWN: i64 = 0xa;
+const KEY_UP: i64 = 0xb;
+// kCGHIDEventTap
+const HID_EVENT_TAP: u32 = 0;
+// the brightness keys step a sixteenth of the range
+const PERCENT_PER_KEY: i32 = 6;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn CGEventPost(tap: u32, event: *mut c_void);
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSPoint {
+    x: f64,
+    y: f64,
+}
+
+unsafe impl Encode for NSPoint {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGPoint=dd}") }
+    }
+}
+
+// what a keyboard's brightness key sends, which the system handles for the
+// built-in display and apple displays
+unsafe fn post_key(key: i64, state: i64) {
+    let event: *mut Object = msg_send![
+        class!(NSEvent),
+        otherEventWithType: SYSTEM_DEFINED
+        location: NSPoint { x: 0.0, y: 0.0 }
+        modifierFlags: (state << 8) as u64
+        timestamp: 0.0f64
+        windowNumber: 0i64
+        context: std::ptr::null_mut::<Object>()
+        subtype: AUX_CONTROL_BUTTONS
+        data1: (key << 16) | (state << 8)
+        data2: -1i64
+    ];
+    if event.is_null() {
+        return;
+    }
+    let event: *mut c_void = msg_send![event, CGEvent];
+    CGEventPost(HID_EVENT_TAP, event);
+}
+
+// as brightness key presses, rounded to at least one
+pub fn brightness(percent: i32) -> Result<(), String> {
+    let key = if percent < 0 {
+        BRIGHTNESS_DOWN
+    } else {
+        BRIGHTNESS_UP
+    };
+    let presses = (percent.abs() / PERCENT_PER_KEY).max(1);
+    for _ in 0..presses {
+        unsafe {
+            post_key(key, KEY_DOWN);
+            post_key(key, KEY_UP);
+        }
+    }
+    Ok(())
+}
+
+// CBBlueLightStatus, which CBBlueLightClient fills in. only `enabled` is
+// read; the rest is room for the schedule fields after it
+#[repr(C)]
+struct BlueLightStatus {
+    active: BOOL,
+    enabled: BOOL,
+    rest: [u8; 64],
+}
+
+// night shift lives in the private CoreBrightness framework, loaded once
+// and kept for the process's lifetime
+fn blue_light_client() -> Result<&'static Class, String> {
+    static FRAMEWORK: OnceLock<Result<Library, String>> = OnceLock::new();
+    FRAMEWORK
+        .get_or_init(|| unsafe {
+            Library::new(
+                "/System/Library/PrivateFrameworks/CoreBrightness.framework/CoreBrightness",
+            )
+            .map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(|e| format!("can't load CoreBrightness: {}", e))?;
+    Class::get("CBBlueLightClient").ok_or_else(|| "no night shift on this mac".to_string())
+}
+
+pub fn night_light() -> Result<(), String> {
+    let class = blue_light_client()?;
+    unsafe {
+        let client: *mut Object = msg_send![class, alloc];
+        let client: *mut Object = msg_send![client, init];
+        let mut status = BlueLightStatus {
+            active: NO,
+            enabled: NO,
+            rest: [0; 64],
+        };
+        let read: BOOL = msg_send![
+            client,
+            getBlueLightStatus: &mut status as *mut BlueLightStatus as *mut c_void
+        ];
+        let result = if read == NO {
+            Err("couldn't read the night shift state".to_string())
+        } else {
+            let enable = if status.enabled == NO { YES } else { NO };
+            let set: BOOL = msg_send![client, setEnabled: enable];
+            if set == NO {
+                Err("night shift didn't change".to_string())
+            } else {
+                println!("night shift {}", if enable == YES { "on" } else { "off" });
+                Ok(())
+            }
+        };
+        let _: () = msg_send![client, release];
+        result
+    }
+}
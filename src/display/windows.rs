@@ -0,0 +1,58 @@
+use windows::Win32::Devices::Display::{
+    DestroyPhysicalMonitors, GetMonitorBrightness, GetNumberOfPhysicalMonitorsFromHMONITOR,
+    GetPhysicalMonitorsFromHMONITOR, SetMonitorBrightness, PHYSICAL_MONITOR,
+};
+use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+
+unsafe extern "system" fn visit(monitor: HMONITOR, _: HDC, _: *mut RECT, lparam: LPARAM) -> BOOL {
+    let monitors = &mut *(lparam.0 as *mut Vec<HMONITOR>);
+    monitors.push(monitor);
+    true.into()
+}
+
+// every monitor's brightness over ddc/ci through dxva2. a laptop's built-in
+// panel doesn't speak ddc/ci and is left alone
+pub fn brightness(percent: i32) -> Result<(), String> {
+    let mut monitors: Vec<HMONITOR> = Vec::new();
+    let mut changed = 0;
+    unsafe {
+        EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(visit),
+            LPARAM(&mut monitors as *mut Vec<HMONITOR> as isize),
+        );
+        for monitor in monitors {
+            let mut count = 0u32;
+            if GetNumberOfPhysicalMonitorsFromHMONITOR(monitor, &mut count).is_err() || count == 0 {
+                continue;
+            }
+            let mut physical = vec![PHYSICAL_MONITOR::default(); count as usize];
+            if GetPhysicalMonitorsFromHMONITOR(monitor, &mut physical).is_err() {
+                continue;
+            }
+            for handle in physical.iter().map(|p| p.hPhysicalMonitor) {
+                let (mut min, mut current, mut max) = (0u32, 0u32, 0u32);
+                if GetMonitorBrightness(handle, &mut min, &mut current, &mut max) == 0 {
+                    continue;
+                }
+                let step = (max - min) as i32 * percent / 100;
+                let target = (current as i32 + step).clamp(min as i32, max as i32);
+                if SetMonitorBrightness(handle, target as u32) != 0 {
+                    changed += 1;
+                }
+            }
+            let _ = DestroyPhysicalMonitors(&physical);
+        }
+    }
+    if changed == 0 {
+        return Err("no monitor took a ddc/ci brightness change".to_string());
+    }
+    Ok(())
+}
+
+// night light is only reachable through the settings app
+pub fn night_light() -> Result<(), String> {
+    Err("windows has no interface for switching night light".to_string())
+}
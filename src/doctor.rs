@@ -0,0 +1,169 @@
+use crate::config::{config_dir, Config};
+
+// `controller2keys doctor`: checks the usual reasons nothing happens and
+// says how to fix each. returns whether everything passed
+pub fn run() -> bool {
+    let mut report = Report::default();
+    check_config(&mut report);
+    check_gamepads(&mut report);
+    check_platform(&mut report);
+
+    println!();
+    if report.failed == 0 {
+        println!("all checks passed");
+    } else {
+        println!("{} check(s) failed", report.failed);
+    }
+    report.failed == 0
+}
+
+#[derive(Default)]
+struct Report {
+    failed: usize,
+}
+
+impl Report {
+    fn pass(&mut self, what: &str) {
+        println!("[ ok ] {}", what);
+    }
+
+    fn warn(&mut self, what: &str, fix: &str) {
+        println!("[warn] {}", what);
+        println!("       {}", fix);
+    }
+
+    fn fail(&mut self, what: &str, fix: &str) {
+        self.failed += 1;
+        println!("[fail] {}", what);
+        println!("       {}", fix);
+    }
+}
+
+fn check_config(report: &mut Report) {
+    let path = config_dir().join("config.toml");
+    if !path.exists() {
+        report.pass(&format!(
+            "no config at {}, using the built-in mapping",
+            path.display()
+        ));
+        return;
+    }
+    match Config::load(&path) {
+        Ok(_) => report.pass(&format!("config {} parses", path.display())),
+        Err(e) => report.fail(
+            &e,
+            "fix the config; until then the built-in mapping is used",
+        ),
+    }
+}
+
+fn check_gamepads(report: &mut Report) {
+    let gilrs = match gilrs::Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(e) => {
+            report.fail(
+                &format!("gamepad input unavailable: {}", e),
+                "on linux, check that udev is running and libudev is installed",
+            );
+            return;
+        }
+    };
+    report.pass("gamepad input initialized");
+
+    let names: Vec<String> = gilrs
+        .gamepads()
+        .map(|(_, gamepad)| gamepad.name().to_string())
+        .collect();
+    if names.is_empty() {
+        report.warn(
+            "no controller connected",
+            "connect one over usb or bluetooth; wireless xbox pads need their dongle or a recent driver",
+        );
+    } else {
+        report.pass(&format!("controllers: {}", names.join(", ")));
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_platform(report: &mut Report) {
+    // controllers udev knows about but we can't read
+    match crate::error::check_access() {
+        Ok(()) => report.pass("controller device nodes are readable"),
+        Err(e) => report.fail(
+            &e.to_string(),
+            "run `controller2keys setup-udev`, or `sudo usermod -aG input $USER` and log in again",
+        ),
+    }
+
+    let uinput = std::path::Path::new("/dev/uinput");
+    if !uinput.exists() {
+        report.warn(
+            "/dev/uinput is missing (only needed for reverse mode and gamescope)",
+            "run `controller2keys setup-udev`, or `sudo modprobe uinput` and add it to /etc/modules-load.d",
+        );
+    } else if std::fs::OpenOptions::new()
+        .write(true)
+        .open(uinput)
+        .is_err()
+    {
+        report.warn(
+            "/dev/uinput isn't writable (only needed for reverse mode and gamescope)",
+            "run `controller2keys setup-udev`",
+        );
+    } else {
+        report.pass("/dev/uinput is writable");
+    }
+
+    // gamescope's xwayland has no xtest, so keys go through uinput instead
+    if crate::gamescope::running() {
+        report.pass("gamescope session: keys and the mouse go through uinput");
+        return;
+    }
+
+    let session = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    let display = std::env::var_os("DISPLAY").is_some();
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some() || session == "wayland";
+    match (display, wayland) {
+        (true, false) => report.pass("x11 session"),
+        (true, true) => report.warn(
+            "wayland session: keys only reach xwayland apps",
+            "native wayland apps need an x11 session or a compositor that accepts xtest input",
+        ),
+        (false, _) => report.fail(
+            "no X display (DISPLAY is unset)",
+            "run from inside your desktop session, or export DISPLAY=:0",
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn check_platform(report: &mut Report) {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+    if unsafe { AXIsProcessTrusted() } {
+        report.pass("accessibility access granted");
+    } else {
+        report.fail(
+            "no accessibility access, so key and mouse events are dropped",
+            "allow your terminal (or controller2keys) in System Settings > Privacy & Security > Accessibility",
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn check_platform(report: &mut Report) {
+    let hidhide = std::path::Path::new(r"\\.\HidHide");
+    if std::fs::File::open(hidhide).is_ok() {
+        report.pass("hidhide installed (for `grab`)");
+    } else {
+        report.warn(
+            "hidhide not found (only needed for `grab`)",
+            "install it from https://github.com/nefarius/HidHide",
+        );
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn check_platform(_report: &mut Report) {}
@@ -0,0 +1,197 @@
+use crate::action::parse_mouse_button;
+use crate::config::{config_dir, Profile};
+use crate::keymap::parse_chord;
+use gilrs::Button;
+use std::fs;
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+// the buttons an editor can bind, in the order they're listed
+pub const BUTTONS: &[Button] = &[
+    Button::South,
+    Button::East,
+    Button::West,
+    Button::North,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+// what a button does in a profile, as far as the editors can change it.
+// anything else in the config shows up as `Other` and is replaced on save
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Assignment {
+    // no binding, so the built-in mapping applies
+    Default,
+    Key(String),
+    Mouse(String),
+    // plays the mouse macro with this name
+    Macro(String),
+    Other,
+}
+
+impl Assignment {
+    pub fn current(profile: &Profile, button: Button) -> Assignment {
+        if let Some(m) = profile.macros.iter().find(|m| m.play == Some(button)) {
+            return Assignment::Macro(m.name.clone());
+        }
+        let binding = profile
+            .bindings
+            .iter()
+            .find(|b| b.button == button && b.when.is_none());
+        match binding {
+            None => Assignment::Default,
+            Some(b) => match (&b.key, &b.mouse) {
+                (Some(key), None) if b.repeat.is_none() => Assignment::Key(key.clone()),
+                (None, Some(mouse)) => Assignment::Mouse(mouse.clone()),
+                _ => Assignment::Other,
+            },
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Assignment::Default => "built-in".to_string(),
+            Assignment::Key(key) => key.clone(),
+            Assignment::Mouse(mouse) => format!("mouse {}", mouse),
+            Assignment::Macro(name) => format!("macro {}", name),
+            Assignment::Other => "set in config.toml".to_string(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Assignment::Key(key) if parse_chord(key).is_none() => {
+                Err(format!("unknown key '{}'", key))
+            }
+            Assignment::Mouse(mouse) if parse_mouse_button(mouse).is_none() => {
+                Err(format!("unknown mouse button '{}'", mouse))
+            }
+            Assignment::Macro(name) if name.is_empty() => Err("macro needs a name".to_string()),
+            Assignment::Other => Err("nothing to save".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// rewrites config.toml in place, so comments and everything the editors
+// don't know about survive. bindings with a `when` are left alone
+pub fn save(profile: &str, button: Button, assignment: &Assignment) -> Result<(), String> {
+    assignment.validate()?;
+    let mut doc = load()?;
+    let name = format!("{:?}", button);
+    let table = profile_table(&mut doc, profile)?;
+
+    let bindings = tables(table, "bindings")?;
+    bindings.retain(|b| !is_button(b.get("button"), &name) || b.contains_key("when"));
+    let mut binding = Table::new();
+    binding.insert("button", value(&name));
+    match assignment {
+        Assignment::Key(key) => binding.insert("key", value(key)),
+        Assignment::Mouse(mouse) => binding.insert("mouse", value(mouse)),
+        _ => None,
+    };
+    if binding.len() > 1 {
+        bindings.push(binding);
+    }
+
+    let macros = tables(table, "macros")?;
+    for m in macros.iter_mut() {
+        if is_button(m.get("play"), &name) {
+            m.remove("play");
+        }
+    }
+    if let Assignment::Macro(macro_name) = assignment {
+        let existing = macros
+            .iter_mut()
+            .find(|m| m.get("name").and_then(Item::as_str) == Some(macro_name));
+        match existing {
+            Some(m) => {
+                m.insert("play", value(&name));
+            }
+            None => {
+                let mut m = Table::new();
+                m.insert("name", value(macro_name));
+                m.insert("play", value(&name));
+                macros.push(m);
+            }
+        }
+    }
+    // don't leave empty `bindings = []` behind
+    for key in ["bindings", "macros"] {
+        if table
+            .get(key)
+            .and_then(Item::as_array_of_tables)
+            .is_some_and(|a| a.is_empty())
+        {
+            table.remove(key);
+        }
+    }
+
+    store(&doc)
+}
+
+// sets a number setting like `deadzone` on a profile, replacing an
+// expression if it had one
+pub fn save_setting(profile: &str, key: &str, number: f64) -> Result<(), String> {
+    let mut doc = load()?;
+    // keep what's written short, 0.15 rather than 0.15000000000000002
+    let number = (number * 1000.0).round() / 1000.0;
+    profile_table(&mut doc, profile)?.insert(key, value(number));
+    store(&doc)
+}
+
+fn load() -> Result<DocumentMut, String> {
+    let path = config_dir().join("config.toml");
+    fs::read_to_string(&path)
+        .unwrap_or_default()
+        .parse()
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+fn store(doc: &DocumentMut) -> Result<(), String> {
+    let path = config_dir().join("config.toml");
+    fs::create_dir_all(config_dir()).map_err(|e| e.to_string())?;
+    fs::write(&path, doc.to_string())
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn is_button(item: Option<&Item>, name: &str) -> bool {
+    item.and_then(Item::as_str) == Some(name)
+}
+
+fn profile_table<'a>(doc: &'a mut DocumentMut, profile: &str) -> Result<&'a mut Table, String> {
+    let profiles = doc.entry("profiles").or_insert_with(implicit_table);
+    profiles
+        .as_table_mut()
+        .ok_or("`profiles` isn't a table")?
+        .entry(profile)
+        .or_insert_with(implicit_table)
+        .as_table_mut()
+        .ok_or_else(|| format!("profile '{}' isn't a table", profile))
+}
+
+// a table with no header of its own, so a new profile shows up as just
+// its [[profiles.<name>.bindings]] entries
+fn implicit_table() -> Item {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    Item::Table(table)
+}
+
+fn tables<'a>(table: &'a mut Table, key: &str) -> Result<&'a mut ArrayOfTables, String> {
+    table
+        .entry(key)
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| format!("write `{}` as [[...]] tables to edit it here", key))
+}
@@ -0,0 +1,151 @@
+use crate::action::Action;
+use crate::config::Config;
+use crate::engine::Engine;
+use crate::error;
+use gilrs::Event;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Sink = Box<dyn FnMut(Action) + Send>;
+
+// the engine reading the controllers on a thread of its own, for rust
+// programs that want the mapping without the injection:
+//
+//   let mapping = Controller2Keys::builder()
+//       .profile("minecraft")
+//       .on_action(|action| println!("{:?}", action))
+//       .start()?;
+//
+// actions go to the callback, then the channel, as they're made. the thread
+// stops when this is dropped
+pub struct Controller2Keys {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Controller2Keys {
+    pub fn builder() -> Builder {
+        Builder {
+            config: None,
+            profile: None,
+            callback: None,
+            sender: None,
+            poll_rate: Duration::from_millis(4),
+        }
+    }
+
+    // the same as dropping it: waits for the thread to finish its tick
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for Controller2Keys {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub struct Builder {
+    config: Option<Config>,
+    profile: Option<String>,
+    callback: Option<Sink>,
+    sender: Option<Sender<Action>>,
+    poll_rate: Duration,
+}
+
+impl Builder {
+    // the user's config.toml unless given
+    pub fn config(mut self, config: Config) -> Builder {
+        self.config = Some(config);
+        self
+    }
+
+    // the config's default profile unless given
+    pub fn profile(mut self, name: impl Into<String>) -> Builder {
+        self.profile = Some(name.into());
+        self
+    }
+
+    pub fn on_action(mut self, callback: impl FnMut(Action) + Send + 'static) -> Builder {
+        self.callback = Some(Box::new(callback));
+        self
+    }
+
+    pub fn sender(mut self, sender: Sender<Action>) -> Builder {
+        self.sender = Some(sender);
+        self
+    }
+
+    // how often events are read and the engine ticks, 4ms (~250Hz) by
+    // default
+    pub fn poll_rate(mut self, poll_rate: Duration) -> Builder {
+        self.poll_rate = poll_rate;
+        self
+    }
+
+    // opens the controllers and loads the profile on the new thread, and
+    // returns once that has worked or failed
+    pub fn start(self) -> Result<Controller2Keys, String> {
+        let Builder {
+            config,
+            profile,
+            mut callback,
+            sender,
+            poll_rate,
+        } = self;
+        let config = config.unwrap_or_else(Config::load_or_default);
+        let profile = profile.unwrap_or_else(|| config.profile.clone());
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let (started, result) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            // the engine can't move between threads, so it's made on this one
+            let setup = error::init_gilrs()
+                .map_err(|e| e.to_string())
+                .and_then(|gilrs| Engine::new(&config, &profile).map(|engine| (gilrs, engine)));
+            let (mut gilrs, mut engine) = match setup {
+                Ok(setup) => {
+                    let _ = started.send(Ok(()));
+                    setup
+                }
+                Err(e) => {
+                    let _ = started.send(Err(e));
+                    return;
+                }
+            };
+            let mut send = |actions: Vec<Action>| {
+                for action in actions {
+                    if let Some(callback) = callback.as_mut() {
+                        callback(action.clone());
+                    }
+                    if let Some(sender) = sender.as_ref() {
+                        // a dropped receiver just means nobody's listening
+                        let _ = sender.send(action);
+                    }
+                }
+            };
+            while !stopped.load(Ordering::Relaxed) {
+                while let Some(Event { event, .. }) = gilrs.next_event() {
+                    send(engine.feed(event));
+                }
+                send(engine.tick());
+                thread::sleep(poll_rate);
+            }
+        });
+        match result.recv() {
+            Ok(Ok(())) => Ok(Controller2Keys {
+                stop,
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("the mapping thread panicked while starting".to_string()),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::action::Action;
+use crate::config::Config;
+use crate::devices::AxisNames;
+use crate::expr::Evaluator;
+use crate::input::InputState;
+use crate::profile::ActiveProfile;
+use crate::stick::{Stick, StickOutput};
+use gilrs::{Axis, EventType};
+
+// a profile's mapping on its own: controller events in, actions out, and
+// nothing injected. for front-ends that read the controller and send the
+// keys themselves. covers bindings, trigger thresholds, the sticks, axis
+// bands and hats; combos, macros, the board and everything tied to the
+// running binary (plugins, focus, obs...) stay with `controller2keys run`
+pub struct Engine {
+    evaluator: Evaluator,
+    profile: ActiveProfile,
+    input: InputState,
+    axis_names: AxisNames,
+}
+
+impl Engine {
+    pub fn new(config: &Config, profile: &str) -> Result<Engine, String> {
+        if profile != config.profile && !config.profiles.contains_key(profile) {
+            return Err(format!("no profile named '{}'", profile));
+        }
+        let evaluator = Evaluator::new();
+        let profile = ActiveProfile::load(profile, &config.get_profile(profile), &evaluator);
+        Ok(Engine {
+            evaluator,
+            profile,
+            input: InputState::default(),
+            axis_names: AxisNames::new(&config.devices),
+        })
+    }
+
+    // from the text of a config.toml. an empty profile name means the
+    // config's default profile
+    pub fn parse(config: &str, profile: &str) -> Result<Engine, String> {
+        let config: Config =
+            toml::from_str(config).map_err(|e| format!("failed to parse config: {}", e))?;
+        let profile = match profile {
+            "" => config.profile.clone(),
+            name => name.to_string(),
+        };
+        Engine::new(&config, &profile)
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile.name
+    }
+
+    // one controller event, and what the mapping makes of it
+    pub fn feed(&mut self, event: EventType) -> Vec<Action> {
+        self.input.update(&event);
+        let (deadzone, mouse_speed) = self.profile.settings(&self.evaluator, &self.input);
+        let event = match self.profile.triggers.as_mut() {
+            Some(triggers) => match triggers.filter(event) {
+                Some(event) => event,
+                None => return Vec::new(),
+            },
+            None => event,
+        };
+        let mut actions = match event {
+            EventType::AxisChanged(axis, value, code) => {
+                let code = code.into_u32();
+                let names = &self.axis_names;
+                let mut actions = self.profile.bands.update(axis, value, code, names);
+                actions.extend(self.profile.hats.update(axis, value, code, names));
+                actions
+            }
+            _ => Vec::new(),
+        };
+        let mapped = match event {
+            EventType::ButtonPressed(button, _) if self.profile.mapper.handles(button) => {
+                self.profile.mapper.press(button, &self.input)
+            }
+            EventType::ButtonReleased(button, _) if self.profile.mapper.handles(button) => {
+                self.profile.mapper.release(button)
+            }
+            EventType::ButtonPressed(button, _) => {
+                self.profile.stick_click(button, true).unwrap_or_default()
+            }
+            EventType::ButtonReleased(button, _) => {
+                self.profile.stick_click(button, false).unwrap_or_default()
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                let (stick, horizontal) = match axis {
+                    Axis::LeftStickX => (Stick::Left, true),
+                    Axis::LeftStickY => (Stick::Left, false),
+                    Axis::RightStickX => (Stick::Right, true),
+                    Axis::RightStickY => (Stick::Right, false),
+                    _ => return actions,
+                };
+                match self.profile.stick_mut(stick) {
+                    StickOutput::Held(keys) => keys.update(horizontal, value, deadzone),
+                    // there's no virtual gamepad here, so analog sticks
+                    // fall back to keys
+                    StickOutput::Analog(analog) => {
+                        analog.update(horizontal, value, deadzone, false)
+                    }
+                    StickOutput::Mouse if value.abs() > deadzone => {
+                        let movement = (value * mouse_speed) as i32;
+                        vec![if horizontal {
+                            Action::MouseMove(movement, 0)
+                        } else {
+                            Action::MouseMove(0, -movement)
+                        }]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        };
+        actions.extend(mapped);
+        actions
+    }
+
+    // repeats, timers and stick modes that act over time. call it at the
+    // polling rate, ~250 times a second
+    pub fn tick(&mut self) -> Vec<Action> {
+        let mut actions = self.profile.tick_sticks(&self.input);
+        actions.extend(self.profile.bands.tick());
+        actions.extend(self.profile.mapper.tick());
+        actions
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+use thiserror::Error;
+
+// why controller2keys couldn't start, each with its own exit code so
+// scripts and service managers can tell them apart
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("no controller connected within {0} seconds")]
+    NoController(u64),
+    #[error("permission denied: {0}")]
+    Permission(String),
+    #[error("{0}")]
+    Backend(String),
+}
+
+impl Error {
+    // 1 and 2 are bad presets and bad usage
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NoController(_) => 3,
+            Error::Permission(_) => 4,
+            Error::Backend(_) => 5,
+        }
+    }
+
+    pub fn exit(&self) -> ! {
+        println!("{}", self);
+        std::process::exit(self.exit_code());
+    }
+}
+
+// gilrs, retrying with backoff for a while since udev or the display
+// server may still be coming up when we're started at login
+pub fn init_gilrs() -> Result<gilrs::Gilrs, Error> {
+    const ATTEMPTS: u32 = 5;
+    let mut delay = Duration::from_millis(250);
+    let mut attempt = 1;
+    loop {
+        let e = match gilrs::Gilrs::new() {
+            Ok(gilrs) => return Ok(gilrs),
+            Err(gilrs::Error::NotImplemented(_)) => {
+                return Err(Error::Backend(
+                    "gamepad input isn't supported on this platform".to_string(),
+                ))
+            }
+            Err(gilrs::Error::Other(e)) if permission_denied(e.as_ref()) => {
+                return Err(Error::Permission(e.to_string()))
+            }
+            Err(e) => e,
+        };
+        if attempt == ATTEMPTS {
+            return Err(Error::Backend(format!(
+                "failed to initialize gamepad input: {}",
+                e
+            )));
+        }
+        println!(
+            "failed to initialize gamepad input ({}), retrying in {:?}",
+            e, delay
+        );
+        std::thread::sleep(delay);
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+fn permission_denied(e: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if e.downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+        {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+// gilrs skips controllers it can't open without saying so. udev names every
+// gamepad's node in /dev/input/by-id, so an unreadable one there is ours
+#[cfg(target_os = "linux")]
+pub fn check_access() -> Result<(), Error> {
+    let Ok(entries) = std::fs::read_dir("/dev/input/by-id") else {
+        return Ok(());
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        let joystick = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with("-event-joystick"));
+        if !joystick {
+            continue;
+        }
+        if let Err(e) = std::fs::File::open(&path) {
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                return Err(Error::Permission(format!(
+                    "can't read {} (add your user to the input group or install a udev rule)",
+                    path.display()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn check_access() -> Result<(), Error> {
+    Ok(())
+}
+
+// enigo's x11 backend quietly does nothing without a display to talk to
+#[cfg(target_os = "linux")]
+pub fn check_output() -> Result<(), Error> {
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err(Error::Backend(
+            "no X display to send keys to (DISPLAY is unset)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn check_output() -> Result<(), Error> {
+    Ok(())
+}
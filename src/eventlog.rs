@@ -0,0 +1,78 @@
+use crate::action::Action;
+use gilrs::{EventType, GamepadId};
+use serde_json::json;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// `--json-events <file>`: one json object per line for every controller
+// event and what the mapping made of it, e.g.
+//
+//   {"time_ms":1718000000123,"controller":0,
+//    "input":{"ButtonPressed":["South",..]},"actions":["KeyDown(Space)"],
+//    "latency_us":310}
+//
+// actions from repeats, timers and scripts that no event caused are logged
+// with a null controller and input. latency is from the backend seeing the
+// event to its actions having been sent
+pub struct EventLog {
+    file: LineWriter<File>,
+}
+
+impl EventLog {
+    pub fn create(path: &Path) -> Result<EventLog, String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(EventLog {
+            file: LineWriter::new(file),
+        })
+    }
+
+    pub fn event(
+        &mut self,
+        id: GamepadId,
+        event: &EventType,
+        time: SystemTime,
+        actions: &[Action],
+    ) {
+        let latency = SystemTime::now().duration_since(time).unwrap_or_default();
+        self.write(json!({
+            "time_ms": millis(time),
+            "controller": usize::from(id),
+            "input": event,
+            "actions": names(actions),
+            "latency_us": latency.as_micros() as u64,
+        }));
+    }
+
+    pub fn tick(&mut self, actions: &[Action]) {
+        if actions.is_empty() {
+            return;
+        }
+        self.write(json!({
+            "time_ms": millis(SystemTime::now()),
+            "controller": null,
+            "input": null,
+            "actions": names(actions),
+            "latency_us": null,
+        }));
+    }
+
+    // a full disk shouldn't stop the mapping
+    fn write(&mut self, entry: serde_json::Value) {
+        if let Err(e) = writeln!(self.file, "{}", entry) {
+            println!("event log write failed: {}", e);
+        }
+    }
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+// enigo's types aren't serializable, so actions are logged as they print
+fn names(actions: &[Action]) -> Vec<String> {
+    actions.iter().map(|a| format!("{:?}", a)).collect()
+}
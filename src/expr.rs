@@ -0,0 +1,211 @@
+use crate::bindings::MapperState;
+use crate::input::InputState;
+#[cfg(feature = "expressions")]
+use crate::vars::Vars;
+#[cfg(feature = "expressions")]
+use gilrs::{Axis, Button};
+#[cfg(feature = "expressions")]
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::Deserialize;
+#[cfg(feature = "expressions")]
+use std::cell::RefCell;
+#[cfg(feature = "expressions")]
+use std::collections::HashSet;
+#[cfg(feature = "expressions")]
+use std::rc::Rc;
+
+// a config value that is either a plain number or a small rhai expression
+// evaluated every tick, e.g. `sensitivity = "base * (1.0 + trigger_pull)"`
+// or `sensitivity = "if toggled(\"precise\") { 10 } else { base }"`.
+// `var("name")` reads a binding variable
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Setting {
+    Number(f64),
+    Expr(String),
+}
+
+// a setting ready to be evaluated. `base` is the built-in default and is
+// also what expressions see as the `base` variable
+pub struct Value {
+    base: f64,
+    #[cfg(feature = "expressions")]
+    ast: Option<AST>,
+}
+
+impl Value {
+    pub fn fixed(base: f64) -> Value {
+        Value {
+            base,
+            #[cfg(feature = "expressions")]
+            ast: None,
+        }
+    }
+}
+
+#[cfg(feature = "expressions")]
+pub struct Evaluator {
+    engine: Engine,
+    // names of held buttons, shared with the `held()` function
+    held: Rc<RefCell<HashSet<String>>>,
+    // binding toggles that are on and the active layer, for `toggled()` and
+    // `layer()`
+    toggles: Rc<RefCell<HashSet<String>>>,
+    layer: Rc<RefCell<String>>,
+    // the mapper's variables, for `var()`
+    vars: Rc<RefCell<Vars>>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "expressions")]
+impl Evaluator {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        let held = Rc::new(RefCell::new(HashSet::new()));
+
+        let names = held.clone();
+        engine.register_fn("held", move |name: &str| names.borrow().contains(name));
+
+        let toggles = Rc::new(RefCell::new(HashSet::new()));
+        let on = toggles.clone();
+        engine.register_fn("toggled", move |name: &str| on.borrow().contains(name));
+
+        let layer = Rc::new(RefCell::new(String::new()));
+        let active = layer.clone();
+        engine.register_fn("layer", move || active.borrow().clone());
+
+        let vars = Rc::new(RefCell::new(Vars::default()));
+        let read = vars.clone();
+        engine.register_fn("var", move |name: &str| read.borrow().get(name));
+
+        Evaluator {
+            engine,
+            held,
+            toggles,
+            layer,
+            vars,
+        }
+    }
+
+    // a broken expression is reported once here and then behaves like `base`
+    pub fn compile(&self, name: &str, setting: Option<&Setting>, base: f64) -> Value {
+        match setting {
+            None => Value::fixed(base),
+            Some(Setting::Number(n)) => Value::fixed(*n),
+            Some(Setting::Expr(src)) => match self.engine.compile_expression(src) {
+                Ok(ast) => Value {
+                    base,
+                    ast: Some(ast),
+                },
+                Err(e) => {
+                    println!("invalid expression for {}: {} - using {}", name, e, base);
+                    Value::fixed(base)
+                }
+            },
+        }
+    }
+
+    // why a setting's expression won't compile, for `controller2keys check`
+    pub fn check(&self, setting: Option<&Setting>) -> Result<(), String> {
+        match setting {
+            Some(Setting::Expr(src)) => self
+                .engine
+                .compile_expression(src)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn eval(&self, value: &Value, input: &InputState, state: &MapperState) -> f64 {
+        let ast = match &value.ast {
+            Some(ast) => ast,
+            None => return value.base,
+        };
+
+        {
+            let mut held = self.held.borrow_mut();
+            held.clear();
+            held.extend(input.held().map(|b| format!("{:?}", b)));
+        }
+        {
+            let mut toggles = self.toggles.borrow_mut();
+            toggles.clear();
+            toggles.extend(state.toggles().cloned());
+        }
+        *self.layer.borrow_mut() = state.layer().unwrap_or_default().to_string();
+        self.vars.borrow_mut().clone_from(state.vars());
+
+        let left_trigger = input.button_value(Button::LeftTrigger2) as f64;
+        let right_trigger = input.button_value(Button::RightTrigger2) as f64;
+
+        let mut scope = Scope::new();
+        scope.push("base", value.base);
+        scope.push("left_trigger", left_trigger);
+        scope.push("right_trigger", right_trigger);
+        scope.push("trigger_pull", left_trigger.max(right_trigger));
+        scope.push("lx", input.axis(Axis::LeftStickX) as f64);
+        scope.push("ly", input.axis(Axis::LeftStickY) as f64);
+        scope.push("rx", input.axis(Axis::RightStickX) as f64);
+        scope.push("ry", input.axis(Axis::RightStickY) as f64);
+
+        match self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, ast) {
+            Ok(result) => as_number(&result).unwrap_or(value.base),
+            Err(_) => value.base,
+        }
+    }
+}
+
+// without the `expressions` feature only plain numbers work, and an
+// expression is reported and stands in for `base`
+#[cfg(not(feature = "expressions"))]
+pub struct Evaluator;
+
+#[cfg(not(feature = "expressions"))]
+impl Evaluator {
+    pub fn new() -> Self {
+        Evaluator
+    }
+
+    pub fn compile(&self, name: &str, setting: Option<&Setting>, base: f64) -> Value {
+        match setting {
+            None => Value::fixed(base),
+            Some(Setting::Number(n)) => Value::fixed(*n),
+            Some(Setting::Expr(_)) => {
+                println!(
+                    "expression for {} needs the `expressions` feature - using {}",
+                    name, base
+                );
+                Value::fixed(base)
+            }
+        }
+    }
+
+    pub fn check(&self, setting: Option<&Setting>) -> Result<(), String> {
+        match setting {
+            Some(Setting::Expr(_)) => Err("built without the `expressions` feature".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn eval(&self, value: &Value, _input: &InputState, _state: &MapperState) -> f64 {
+        value.base
+    }
+}
+
+// expressions may produce ints ("2") or floats ("base * 2.0")
+#[cfg(feature = "expressions")]
+fn as_number(value: &Dynamic) -> Option<f64> {
+    if let Ok(f) = value.as_float() {
+        return Some(f);
+    }
+    if let Ok(i) = value.as_int() {
+        return Some(i as f64);
+    }
+    value.as_bool().ok().map(|b| if b { 1.0 } else { 0.0 })
+}
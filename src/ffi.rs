@@ -0,0 +1,181 @@
+// the pointer rules are spelled out once, in the header
+#![allow(clippy::missing_safety_doc)]
+
+use crate::action::Action;
+use crate::engine::Engine;
+use enigo::{Key, MouseButton};
+use gilrs::EventType;
+use serde_json::{json, Value};
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+// the engine as a c abi, for front-ends that aren't written in rust. events
+// go in as json in the form recorded sessions use, and actions come out as a
+// json array of objects named like config targets. include/controller2keys.h
+// has the declarations and the action schema. nothing here is thread-safe; use an engine from one
+// thread at a time
+pub struct C2kEngine {
+    engine: Engine,
+    pending: Vec<Action>,
+}
+
+unsafe fn text<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return Some("");
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+// an engine running `profile` from the given config.toml text, or null with
+// the reason printed. a null or empty profile means the config's default
+#[no_mangle]
+pub unsafe extern "C" fn c2k_create(
+    config: *const c_char,
+    profile: *const c_char,
+) -> *mut C2kEngine {
+    let (Some(config), Some(profile)) = (text(config), text(profile)) else {
+        println!("c2k_create: config and profile must be utf-8");
+        return ptr::null_mut();
+    };
+    match Engine::parse(config, profile) {
+        Ok(engine) => Box::into_raw(Box::new(C2kEngine {
+            engine,
+            pending: Vec::new(),
+        })),
+        Err(e) => {
+            println!("c2k_create: {}", e);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn c2k_destroy(engine: *mut C2kEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+// one controller event, e.g. {"ButtonPressed":["South",<code>]}. returns
+// false if it couldn't be read
+#[no_mangle]
+pub unsafe extern "C" fn c2k_feed_event(engine: *mut C2kEngine, event: *const c_char) -> bool {
+    let Some(engine) = engine.as_mut() else {
+        return false;
+    };
+    let event: EventType = match text(event).map(serde_json::from_str) {
+        Some(Ok(event)) => event,
+        Some(Err(e)) => {
+            println!("c2k_feed_event: {}", e);
+            return false;
+        }
+        None => {
+            println!("c2k_feed_event: event must be utf-8");
+            return false;
+        }
+    };
+    let actions = engine.engine.feed(event);
+    engine.pending.extend(actions);
+    true
+}
+
+// layout keys by their character and named keys by name, both as config
+// files name them; raw keycodes are platform-specific
+fn key(key: Key) -> (&'static str, Value) {
+    match key {
+        Key::Layout(c) => ("key", json!(c.to_string())),
+        Key::Raw(code) => ("raw", json!(code)),
+        key => ("key", json!(format!("{:?}", key))),
+    }
+}
+
+fn button(button: MouseButton) -> String {
+    format!("{:?}", button).to_lowercase()
+}
+
+// one action as an object, its kind and then its fields
+fn action_json(action: &Action) -> Value {
+    let keyed = |kind: &str, k: Key| {
+        let (field, value) = key(k);
+        let mut object = json!({ "kind": kind });
+        object[field] = value;
+        object
+    };
+    match action {
+        Action::KeyDown(k) => keyed("key_down", *k),
+        Action::KeyUp(k) => keyed("key_up", *k),
+        Action::KeyClick(k) => keyed("tap", *k),
+        Action::MouseDown(b) => json!({ "kind": "mouse_down", "button": button(*b) }),
+        Action::MouseUp(b) => json!({ "kind": "mouse_up", "button": button(*b) }),
+        Action::MouseClick(b) => json!({ "kind": "click", "button": button(*b) }),
+        Action::MouseMove(x, y) => json!({ "kind": "move", "x": x, "y": y }),
+        Action::Scroll(amount) => json!({ "kind": "scroll", "amount": amount }),
+        Action::SmoothScroll(x, y) => json!({ "kind": "smooth_scroll", "x": x, "y": y }),
+        Action::Tilt(tilt) => json!({ "kind": "tilt", "direction": tilt }),
+        Action::Paste(text) => json!({ "kind": "paste", "text": text }),
+        Action::Text(text) => json!({ "kind": "text", "text": text }),
+        Action::Obs(command) => json!({ "kind": "obs", "command": command }),
+        Action::Gesture(gesture) => json!({ "kind": "gesture", "gesture": gesture }),
+        Action::Launch(launch) => json!({ "kind": "launch", "launch": launch }),
+        Action::Display(op) => json!({ "kind": "display", "op": op }),
+        Action::PadStick(stick, x, y) => {
+            json!({ "kind": "pad_stick", "stick": stick, "x": x, "y": y })
+        }
+        Action::PadButton(b, pressed) => {
+            json!({ "kind": "pad_button", "button": b, "pressed": pressed })
+        }
+        Action::Plugin(command) => json!({
+            "kind": "plugin",
+            "name": command.name,
+            "command": command.command,
+        }),
+    }
+}
+
+// runs the engine's tick and returns everything to send since the last
+// poll, e.g. [{"kind":"key_down","key":"Space"},{"kind":"move","x":4,"y":0}].
+// call it at the polling rate. free the result with c2k_free_string
+#[no_mangle]
+pub unsafe extern "C" fn c2k_poll_actions(engine: *mut C2kEngine) -> *mut c_char {
+    let Some(engine) = engine.as_mut() else {
+        return ptr::null_mut();
+    };
+    let actions = engine.engine.tick();
+    engine.pending.extend(actions);
+    let actions: Vec<Value> = engine.pending.drain(..).map(|a| action_json(&a)).collect();
+    // json escapes control characters, so there's no nul to fail on
+    let json = serde_json::to_string(&actions).unwrap_or_default();
+    CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn c2k_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_are_objects_with_a_kind() {
+        assert_eq!(
+            action_json(&Action::KeyDown(Key::Layout('w'))),
+            json!({ "kind": "key_down", "key": "w" })
+        );
+        assert_eq!(
+            action_json(&Action::KeyClick(Key::Space)),
+            json!({ "kind": "tap", "key": "Space" })
+        );
+        assert_eq!(
+            action_json(&Action::MouseDown(MouseButton::Left)),
+            json!({ "kind": "mouse_down", "button": "left" })
+        );
+        assert_eq!(
+            action_json(&Action::MouseMove(4, 0)),
+            json!({ "kind": "move", "x": 4, "y": 0 })
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::action::{parse_one_shot, Action};
+use crate::input::InputState;
+use crate::stick::Stick;
+use serde::Deserialize;
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
+// counter-clockwise from right, the order sectors are numbered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Right,
+    UpRight,
+    Up,
+    UpLeft,
+    Left,
+    DownLeft,
+    Down,
+    DownRight,
+}
+
+const DIRECTIONS: [Direction; 8] = [
+    Direction::Right,
+    Direction::UpRight,
+    Direction::Up,
+    Direction::UpLeft,
+    Direction::Left,
+    Direction::DownLeft,
+    Direction::Down,
+    Direction::DownRight,
+];
+
+impl Direction {
+    // radians counter-clockwise from right
+    pub fn angle(self) -> f32 {
+        let i = DIRECTIONS.iter().position(|d| *d == self).unwrap_or(0);
+        i as f32 * PI / 4.0
+    }
+
+    // the nearest of 4 or 8 directions
+    fn from_angle(angle: f32, directions: usize) -> Direction {
+        let sector = 2.0 * PI / directions as f32;
+        let i = (angle / sector).round().rem_euclid(directions as f32) as usize;
+        DIRECTIONS[i * 8 / directions]
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlickBinding {
+    pub stick: Stick,
+    pub direction: Direction,
+    pub key: Option<String>,
+    pub mouse: Option<String>,
+    pub scroll: Option<i32>,
+}
+
+// quick flicks of a stick (out to the edge and back within `max_ms`) as
+// discrete actions, on top of whatever holding the stick does
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FlickConfig {
+    // how far out a flick has to reach
+    pub threshold: f32,
+    // back inside this counts as returned to center
+    pub center: f32,
+    pub max_ms: u64,
+    // 4 or 8
+    pub directions: usize,
+    pub bindings: Vec<FlickBinding>,
+}
+
+impl Default for FlickConfig {
+    fn default() -> Self {
+        FlickConfig {
+            threshold: 0.9,
+            center: 0.3,
+            max_ms: 200,
+            directions: 8,
+            bindings: Vec::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tracking {
+    // when the stick left the center
+    left_center: Option<Instant>,
+    // angle when it first reached the threshold
+    reached: Option<f32>,
+}
+
+pub struct Flicks {
+    config: FlickConfig,
+    bindings: Vec<(Stick, Direction, Action)>,
+    left: Tracking,
+    right: Tracking,
+}
+
+impl Flicks {
+    pub fn new(config: &FlickConfig) -> Flicks {
+        let mut config = config.clone();
+        if config.directions != 4 && config.directions != 8 {
+            println!("flick directions must be 4 or 8, using 8");
+            config.directions = 8;
+        }
+        let mut bindings = Vec::new();
+        for binding in &config.bindings {
+            let action = parse_one_shot(
+                binding.key.as_deref(),
+                binding.mouse.as_deref(),
+                binding.scroll,
+            );
+            match action {
+                Ok(action) => bindings.push((binding.stick, binding.direction, action)),
+                Err(e) => println!(
+                    "ignoring flick {:?} {:?}: {}",
+                    binding.stick, binding.direction, e
+                ),
+            }
+        }
+        Flicks {
+            config,
+            bindings,
+            left: Tracking::default(),
+            right: Tracking::default(),
+        }
+    }
+
+    // call when a stick axis changes
+    pub fn update(&mut self, stick: Stick, input: &InputState) -> Vec<Action> {
+        let (x_axis, y_axis) = stick.axes();
+        let (x, y) = (input.axis(x_axis), input.axis(y_axis));
+        let magnitude = x.hypot(y);
+        let tracking = match stick {
+            Stick::Left => &mut self.left,
+            Stick::Right => &mut self.right,
+        };
+
+        if magnitude >= self.config.center {
+            let left_center = *tracking.left_center.get_or_insert_with(Instant::now);
+            if magnitude >= self.config.threshold
+                && tracking.reached.is_none()
+                && left_center.elapsed() <= Duration::from_millis(self.config.max_ms)
+            {
+                tracking.reached = Some(y.atan2(x));
+            }
+            return vec![];
+        }
+
+        let left_center = tracking.left_center.take();
+        let reached = tracking.reached.take();
+        let (Some(left_center), Some(angle)) = (left_center, reached) else {
+            return vec![];
+        };
+        if left_center.elapsed() > Duration::from_millis(self.config.max_ms) {
+            return vec![]; // held, not flicked
+        }
+
+        let direction = Direction::from_angle(angle, self.config.directions);
+        println!("{:?} stick flicked {:?}", stick, direction);
+        self.bindings
+            .iter()
+            .filter(|(s, d, _)| *s == stick && *d == direction)
+            .map(|(_, _, action)| action.clone())
+            .collect()
+    }
+}
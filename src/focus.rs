@@ -0,0 +1,69 @@
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "linux")]
+mod wayland;
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+// the app that currently has focus. `app` is the bundle identifier on macos
+// (e.g. "com.mojang.minecraftlauncher"), the executable name on windows
+// (e.g. "SkyrimSE.exe") and the WM_CLASS / app_id on linux (e.g. "firefox")
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Focus {
+    pub app: String,
+    // window title, where the platform exposes it
+    pub title: String,
+    // exclusive fullscreen, or a borderless window covering its monitor
+    pub fullscreen: bool,
+}
+
+impl Focus {
+    // the app by name, or any window whose title contains `target`, both
+    // ignoring case
+    pub fn is(&self, target: &str) -> bool {
+        self.app.eq_ignore_ascii_case(target)
+            || self.title.to_lowercase().contains(&target.to_lowercase())
+    }
+}
+
+pub trait FocusWatcher {
+    // the newly focused app, if focus changed since the last call. called
+    // once per tick so it must not block
+    fn poll(&mut self) -> Option<Focus>;
+}
+
+#[cfg(target_os = "macos")]
+pub fn watcher() -> Option<Box<dyn FocusWatcher>> {
+    Some(Box::new(macos::WorkspaceWatcher::start()))
+}
+
+#[cfg(target_os = "windows")]
+pub fn watcher() -> Option<Box<dyn FocusWatcher>> {
+    Some(Box::new(windows::ForegroundWatcher::start()))
+}
+
+// wayland sessions use wlr-foreign-toplevel where the compositor has it, and
+// fall back to x11 (which only sees xwayland windows there) otherwise
+#[cfg(target_os = "linux")]
+pub fn watcher() -> Option<Box<dyn FocusWatcher>> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match wayland::WaylandWatcher::start() {
+            Ok(w) => return Some(Box::new(w)),
+            Err(e) => println!("wayland focus tracking unavailable: {}", e),
+        }
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        match x11::X11Watcher::start() {
+            Ok(w) => return Some(Box::new(w)),
+            Err(e) => println!("x11 focus tracking unavailable: {}", e),
+        }
+    }
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn watcher() -> Option<Box<dyn FocusWatcher>> {
+    None
+}
@@ -0,0 +1,112 @@
+use super::{Focus, FocusWatcher};
+use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
+use objc::declare::ClassDecl;
+use objc::runtime::{Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::{c_char, CStr, CString};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+// bundle id of the most recently activated app, written by the observer
+static ACTIVATED: Mutex<Option<String>> = Mutex::new(None);
+
+unsafe fn nsstring(s: &str) -> *mut Object {
+    let s = CString::new(s).unwrap_or_default();
+    msg_send![class!(NSString), stringWithUTF8String: s.as_ptr()]
+}
+
+unsafe fn bundle_id(app: *mut Object) -> Option<String> {
+    if app.is_null() {
+        return None;
+    }
+    let id: *mut Object = msg_send![app, bundleIdentifier];
+    if id.is_null() {
+        return None;
+    }
+    let ptr: *const c_char = msg_send![id, UTF8String];
+    if ptr.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+// -[C2KFocusObserver appActivated:], called for
+// NSWorkspaceDidActivateApplicationNotification
+extern "C" fn app_activated(_this: &Object, _cmd: Sel, note: *mut Object) {
+    unsafe {
+        let pool: *mut Object = msg_send![class!(NSAutoreleasePool), new];
+        let info: *mut Object = msg_send![note, userInfo];
+        if !info.is_null() {
+            let app: *mut Object =
+                msg_send![info, objectForKey: nsstring("NSWorkspaceApplicationKey")];
+            if let Some(id) = bundle_id(app) {
+                *ACTIVATED.lock().unwrap() = Some(id);
+            }
+        }
+        let _: () = msg_send![pool, drain];
+    }
+}
+
+// follows the frontmost app via NSWorkspace activation notifications rather
+// than polling window titles, which macos doesn't expose without extra
+// permissions anyway
+pub struct WorkspaceWatcher {
+    current: Option<String>,
+}
+
+impl WorkspaceWatcher {
+    pub fn start() -> Self {
+        unsafe {
+            let pool: *mut Object = msg_send![class!(NSAutoreleasePool), new];
+
+            let mut decl = ClassDecl::new("C2KFocusObserver", class!(NSObject))
+                .expect("focus observer registered twice");
+            decl.add_method(
+                sel!(appActivated:),
+                app_activated as extern "C" fn(&Object, Sel, *mut Object),
+            );
+            let observer_class = decl.register();
+            let observer: *mut Object = msg_send![observer_class, new];
+
+            let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: *mut Object = msg_send![workspace, notificationCenter];
+            let name = nsstring("NSWorkspaceDidActivateApplicationNotification");
+            let nil: *mut Object = std::ptr::null_mut();
+            let _: () = msg_send![center, addObserver: observer
+                                          selector: sel!(appActivated:)
+                                          name: name
+                                          object: nil];
+
+            // seed with whatever is frontmost right now
+            let front: *mut Object = msg_send![workspace, frontmostApplication];
+            *ACTIVATED.lock().unwrap() = bundle_id(front);
+
+            let _: () = msg_send![pool, drain];
+        }
+
+        WorkspaceWatcher { current: None }
+    }
+}
+
+impl FocusWatcher for WorkspaceWatcher {
+    fn poll(&mut self) -> Option<Focus> {
+        // notifications are only delivered while a run loop runs and we don't
+        // have one, so give it a non-blocking turn each tick
+        unsafe {
+            CFRunLoop::run_in_mode(kCFRunLoopDefaultMode, Duration::ZERO, true);
+        }
+
+        let latest = ACTIVATED.lock().unwrap().clone();
+        if latest.is_none() || latest == self.current {
+            return None;
+        }
+        self.current = latest.clone();
+        latest.map(|app| Focus {
+            app,
+            ..Default::default()
+        })
+    }
+}
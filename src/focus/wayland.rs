@@ -0,0 +1,170 @@
+use super::{Focus, FocusWatcher};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use wayland_client::backend::ObjectId;
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+
+#[derive(Default)]
+struct Toplevel {
+    app_id: String,
+    title: String,
+    activated: bool,
+    fullscreen: bool,
+}
+
+struct State {
+    manager: Option<ZwlrForeignToplevelManagerV1>,
+    toplevels: HashMap<ObjectId, Toplevel>,
+    last: Option<Focus>,
+    tx: Sender<Focus>,
+}
+
+impl State {
+    // called when a toplevel's batch of changes is complete
+    fn report(&mut self, id: &ObjectId) {
+        let toplevel = match self.toplevels.get(id) {
+            Some(t) if t.activated => t,
+            _ => return,
+        };
+        let focus = Focus {
+            app: toplevel.app_id.clone(),
+            title: toplevel.title.clone(),
+            fullscreen: toplevel.fullscreen,
+        };
+        if self.last.as_ref() != Some(&focus) {
+            self.last = Some(focus.clone());
+            let _ = self.tx.send(focus);
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == ZwlrForeignToplevelManagerV1::interface().name {
+                state.manager = Some(registry.bind(name, version.min(3), qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.insert(toplevel.id(), Toplevel::default());
+        }
+    }
+
+    event_created_child!(State, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::{Event, State as ToplevelState};
+
+        let id = handle.id();
+        match event {
+            Event::Done => state.report(&id),
+            Event::Closed => {
+                state.toplevels.remove(&id);
+                handle.destroy();
+            }
+            event => {
+                let toplevel = state.toplevels.entry(id).or_default();
+                match event {
+                    Event::Title { title } => toplevel.title = title,
+                    Event::AppId { app_id } => toplevel.app_id = app_id,
+                    Event::State { state: raw } => {
+                        // array of native-endian u32 state values
+                        let states: Vec<u32> = raw
+                            .chunks_exact(4)
+                            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                            .collect();
+                        toplevel.activated = states.contains(&(ToplevelState::Activated as u32));
+                        toplevel.fullscreen = states.contains(&(ToplevelState::Fullscreen as u32));
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+// follows the activated toplevel through wlr-foreign-toplevel-management
+// (sway, hyprland, river, wayfire...). the wayland connection lives on its own
+// thread so the main loop only ever does a channel receive
+pub struct WaylandWatcher {
+    rx: Receiver<Focus>,
+}
+
+impl WaylandWatcher {
+    pub fn start() -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::connect_to_env()?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        let (tx, rx) = mpsc::channel();
+        let mut state = State {
+            manager: None,
+            toplevels: HashMap::new(),
+            last: None,
+            tx,
+        };
+        queue.roundtrip(&mut state)?;
+        if state.manager.is_none() {
+            return Err("compositor doesn't support wlr-foreign-toplevel-management".into());
+        }
+
+        thread::spawn(move || loop {
+            if let Err(e) = queue.blocking_dispatch(&mut state) {
+                println!("wayland focus tracking stopped: {}", e);
+                return;
+            }
+        });
+        Ok(WaylandWatcher { rx })
+    }
+}
+
+impl FocusWatcher for WaylandWatcher {
+    fn poll(&mut self) -> Option<Focus> {
+        self.rx.try_iter().last()
+    }
+}
@@ -0,0 +1,124 @@
+use super::{Focus, FocusWatcher};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, HWND, RECT};
+use windows::Win32::Graphics::Gdi::{
+    GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+};
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_RUNNING_D3D_FULL_SCREEN};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId,
+};
+
+// there's no cheap focus-change notification without a message loop, so poll
+// the foreground window a few times a second instead of every tick
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct ForegroundWatcher {
+    current: Option<Focus>,
+    last_poll: Option<Instant>,
+}
+
+impl ForegroundWatcher {
+    pub fn start() -> Self {
+        ForegroundWatcher {
+            current: None,
+            last_poll: None,
+        }
+    }
+}
+
+// executable file name of the process owning `hwnd`, e.g. "SkyrimSE.exe"
+unsafe fn process_name(hwnd: HWND) -> Option<String> {
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    if pid == 0 {
+        return None;
+    }
+
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        PWSTR(buf.as_mut_ptr()),
+        &mut len,
+    );
+    CloseHandle(process);
+    if !ok.as_bool() {
+        return None;
+    }
+
+    let path = String::from_utf16_lossy(&buf[..len as usize]);
+    Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+}
+
+unsafe fn window_title(hwnd: HWND) -> String {
+    let mut buf = [0u16; 512];
+    let len = GetWindowTextW(hwnd, &mut buf);
+    String::from_utf16_lossy(&buf[..len.max(0) as usize])
+}
+
+// exclusive fullscreen (d3d owns the display), or a borderless window
+// covering its whole monitor
+unsafe fn is_fullscreen(hwnd: HWND) -> bool {
+    if let Ok(state) = SHQueryUserNotificationState() {
+        if state == QUNS_RUNNING_D3D_FULL_SCREEN {
+            return true;
+        }
+    }
+
+    let mut rect = RECT::default();
+    if !GetWindowRect(hwnd, &mut rect).as_bool() {
+        return false;
+    }
+    let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+        return false;
+    }
+    let screen = info.rcMonitor;
+    rect.left <= screen.left
+        && rect.top <= screen.top
+        && rect.right >= screen.right
+        && rect.bottom >= screen.bottom
+}
+
+impl FocusWatcher for ForegroundWatcher {
+    fn poll(&mut self) -> Option<Focus> {
+        if let Some(last) = self.last_poll {
+            if last.elapsed() < POLL_INTERVAL {
+                return None;
+            }
+        }
+        self.last_poll = Some(Instant::now());
+
+        let focus = unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0 == 0 {
+                return None;
+            }
+            Focus {
+                app: process_name(hwnd)?,
+                title: window_title(hwnd),
+                fullscreen: is_fullscreen(hwnd),
+            }
+        };
+
+        if self.current.as_ref() == Some(&focus) {
+            return None;
+        }
+        self.current = Some(focus.clone());
+        Some(focus)
+    }
+}
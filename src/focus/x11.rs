@@ -0,0 +1,169 @@
+use super::{Focus, FocusWatcher};
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt, EventMask, Window,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+struct Atoms {
+    active_window: Atom,
+    wm_name: Atom,
+    utf8_string: Atom,
+    wm_state: Atom,
+    wm_state_fullscreen: Atom,
+}
+
+impl Atoms {
+    fn intern(conn: &RustConnection) -> Result<Atoms, Box<dyn Error>> {
+        let atom = |name: &[u8]| -> Result<Atom, Box<dyn Error>> {
+            Ok(conn.intern_atom(false, name)?.reply()?.atom)
+        };
+        Ok(Atoms {
+            active_window: atom(b"_NET_ACTIVE_WINDOW")?,
+            wm_name: atom(b"_NET_WM_NAME")?,
+            utf8_string: atom(b"UTF8_STRING")?,
+            wm_state: atom(b"_NET_WM_STATE")?,
+            wm_state_fullscreen: atom(b"_NET_WM_STATE_FULLSCREEN")?,
+        })
+    }
+}
+
+// follows the EWMH _NET_ACTIVE_WINDOW property on the root window. the x
+// connection lives on its own thread, blocked on property-change events, so
+// the main loop only ever does a channel receive
+pub struct X11Watcher {
+    rx: Receiver<Focus>,
+}
+
+impl X11Watcher {
+    pub fn start() -> Result<Self, Box<dyn Error>> {
+        let (conn, screen) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen].root;
+        let atoms = Atoms::intern(&conn)?;
+        conn.change_window_attributes(
+            root,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+        conn.flush()?;
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            if let Err(e) = watch(&conn, root, &atoms, tx) {
+                println!("x11 focus tracking stopped: {}", e);
+            }
+        });
+        Ok(X11Watcher { rx })
+    }
+}
+
+fn watch(
+    conn: &RustConnection,
+    root: Window,
+    atoms: &Atoms,
+    tx: Sender<Focus>,
+) -> Result<(), Box<dyn Error>> {
+    let mut watched = None;
+    let mut last = None;
+
+    loop {
+        let window = active_window(conn, root, atoms)?;
+
+        // also watch the active window itself so title and fullscreen changes
+        // come through
+        if window != watched {
+            if let Some(w) = window {
+                conn.change_window_attributes(
+                    w,
+                    &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+                )?;
+                conn.flush()?;
+            }
+            watched = window;
+        }
+
+        // the window may already be gone, in which case we just wait for
+        // the next change
+        if let Some(focus) = window.and_then(|w| describe(conn, w, atoms).ok()) {
+            if last.as_ref() != Some(&focus) {
+                last = Some(focus.clone());
+                if tx.send(focus).is_err() {
+                    return Ok(()); // watcher dropped
+                }
+            }
+        }
+
+        // block until something relevant changes
+        loop {
+            if let Event::PropertyNotify(e) = conn.wait_for_event()? {
+                if e.atom == atoms.active_window
+                    || e.atom == atoms.wm_name
+                    || e.atom == atoms.wm_state
+                {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn active_window(
+    conn: &RustConnection,
+    root: Window,
+    atoms: &Atoms,
+) -> Result<Option<Window>, Box<dyn Error>> {
+    let reply = conn
+        .get_property(false, root, atoms.active_window, AtomEnum::WINDOW, 0, 1)?
+        .reply()?;
+    Ok(reply
+        .value32()
+        .and_then(|mut v| v.next())
+        .filter(|&w| w != 0))
+}
+
+fn describe(conn: &RustConnection, window: Window, atoms: &Atoms) -> Result<Focus, Box<dyn Error>> {
+    // WM_CLASS is "instance\0class\0", the class is what users recognise
+    let class = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)?
+        .reply()?;
+    let app = class
+        .value
+        .split(|&b| b == 0)
+        .rfind(|s| !s.is_empty())
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .unwrap_or_default();
+
+    let mut title = conn
+        .get_property(false, window, atoms.wm_name, atoms.utf8_string, 0, 1024)?
+        .reply()?
+        .value;
+    if title.is_empty() {
+        title = conn
+            .get_property(false, window, AtomEnum::WM_NAME, AtomEnum::STRING, 0, 1024)?
+            .reply()?
+            .value;
+    }
+
+    let state = conn
+        .get_property(false, window, atoms.wm_state, AtomEnum::ATOM, 0, 64)?
+        .reply()?;
+    let fullscreen = state
+        .value32()
+        .map(|mut atoms_set| atoms_set.any(|a| a == atoms.wm_state_fullscreen))
+        .unwrap_or(false);
+
+    Ok(Focus {
+        app,
+        title: String::from_utf8_lossy(&title).into_owned(),
+        fullscreen,
+    })
+}
+
+impl FocusWatcher for X11Watcher {
+    fn poll(&mut self) -> Option<Focus> {
+        self.rx.try_iter().last()
+    }
+}
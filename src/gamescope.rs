@@ -0,0 +1,14 @@
+// whether we're inside gamescope, the steam deck's game mode compositor.
+// its xwayland has no xtest, so keys and the mouse have to go in through
+// uinput. run as a service, controller2keys doesn't inherit gamescope's
+// environment, so its socket counts too
+pub fn running() -> bool {
+    if std::env::var_os("GAMESCOPE_WAYLAND_DISPLAY").is_some() {
+        return true;
+    }
+    if std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.eq_ignore_ascii_case("gamescope")) {
+        return true;
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .is_some_and(|dir| std::path::Path::new(&dir).join("gamescope-0").exists())
+}
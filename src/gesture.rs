@@ -0,0 +1,111 @@
+#[cfg(target_os = "windows")]
+mod windows;
+
+use enigo::{Enigo, Key, KeyboardControllable};
+use serde::{Deserialize, Serialize};
+
+// a touch or trackpad gesture for a binding, e.g. `gesture = { pinch = "in" }`
+// or `gesture = { swipe = "left", fingers = 3 }`. windows gets real touch
+// contacts; elsewhere the shortcut the gesture usually stands for is pressed
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Gesture {
+    pub pinch: Option<Pinch>,
+    pub swipe: Option<Direction>,
+    #[serde(default = "default_fingers")]
+    pub fingers: u8,
+}
+
+fn default_fingers() -> u8 {
+    2
+}
+
+// `in` zooms in, fingers spreading apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pinch {
+    In,
+    Out,
+}
+
+// the way the fingers move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Gesture {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pinch.is_some() == self.swipe.is_some() {
+            return Err("a gesture is exactly one of pinch or swipe".to_string());
+        }
+        if !(1..=5).contains(&self.fingers) {
+            return Err("a gesture takes 1 to 5 fingers".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn perform(gesture: &Gesture, _enigo: &mut Enigo) {
+    if let Err(e) = windows::perform(gesture) {
+        println!("gesture failed: {}", e);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn perform(gesture: &Gesture, enigo: &mut Enigo) {
+    let Some(keys) = shortcut(gesture) else {
+        println!("{:?} has no keyboard equivalent here", gesture);
+        return;
+    };
+    let (last, modifiers) = keys.split_last().expect("shortcuts aren't empty");
+    for key in modifiers {
+        enigo.key_down(*key);
+    }
+    enigo.key_click(*last);
+    for key in modifiers.iter().rev() {
+        enigo.key_up(*key);
+    }
+}
+
+// what the gesture does on a macos trackpad: zoom, back and forward with
+// two fingers, spaces and mission control with three or more
+#[cfg(target_os = "macos")]
+fn shortcut(gesture: &Gesture) -> Option<Vec<Key>> {
+    Some(match (gesture.pinch, gesture.swipe, gesture.fingers) {
+        (Some(Pinch::In), ..) => vec![Key::Meta, Key::Layout('=')],
+        (Some(Pinch::Out), ..) => vec![Key::Meta, Key::Layout('-')],
+        (_, Some(Direction::Right), ..=2) => vec![Key::Meta, Key::Layout('[')],
+        (_, Some(Direction::Left), ..=2) => vec![Key::Meta, Key::Layout(']')],
+        (_, Some(Direction::Up), ..=2) => vec![Key::PageDown],
+        (_, Some(Direction::Down), ..=2) => vec![Key::PageUp],
+        (_, Some(Direction::Left), _) => vec![Key::Control, Key::RightArrow],
+        (_, Some(Direction::Right), _) => vec![Key::Control, Key::LeftArrow],
+        (_, Some(Direction::Up), _) => vec![Key::Control, Key::UpArrow],
+        (_, Some(Direction::Down), _) => vec![Key::Control, Key::DownArrow],
+        _ => return None,
+    })
+}
+
+// the same on linux desktops, with workspaces for three or more fingers
+// and the overview for swiping up
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn shortcut(gesture: &Gesture) -> Option<Vec<Key>> {
+    Some(match (gesture.pinch, gesture.swipe, gesture.fingers) {
+        (Some(Pinch::In), ..) => vec![Key::Control, Key::Layout('=')],
+        (Some(Pinch::Out), ..) => vec![Key::Control, Key::Layout('-')],
+        (_, Some(Direction::Right), ..=2) => vec![Key::Alt, Key::LeftArrow],
+        (_, Some(Direction::Left), ..=2) => vec![Key::Alt, Key::RightArrow],
+        (_, Some(Direction::Up), ..=2) => vec![Key::PageDown],
+        (_, Some(Direction::Down), ..=2) => vec![Key::PageUp],
+        (_, Some(Direction::Left), _) => vec![Key::Control, Key::Alt, Key::RightArrow],
+        (_, Some(Direction::Right), _) => vec![Key::Control, Key::Alt, Key::LeftArrow],
+        (_, Some(Direction::Up), _) => vec![Key::Meta],
+        _ => return None,
+    })
+}
@@ -0,0 +1,118 @@
+use super::{Direction, Gesture, Pinch};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use windows::Win32::Foundation::{POINT, RECT};
+use windows::Win32::UI::Input::Pointer::{
+    InitializeTouchInjection, InjectTouchInput, POINTER_FLAGS, POINTER_FLAG_DOWN,
+    POINTER_FLAG_INCONTACT, POINTER_FLAG_INRANGE, POINTER_FLAG_UP, POINTER_FLAG_UPDATE,
+    POINTER_INFO, POINTER_TOUCH_INFO, TOUCH_FEEDBACK_DEFAULT,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetCursorPos, PT_TOUCH, TOUCH_FLAG_NONE, TOUCH_MASK_CONTACTAREA, TOUCH_MASK_PRESSURE,
+};
+
+// frames from touching down to lifting off, and the time between them.
+// short enough not to hold up the main loop for long
+const FRAMES: i32 = 12;
+const FRAME_TIME: Duration = Duration::from_millis(8);
+// how far a swipe travels and how far apart fingers sit, in pixels
+const TRAVEL: i32 = 300;
+const SPREAD: i32 = 60;
+
+fn initialize() -> Result<(), String> {
+    static READY: OnceLock<bool> = OnceLock::new();
+    let ready = *READY
+        .get_or_init(|| unsafe { InitializeTouchInjection(10, TOUCH_FEEDBACK_DEFAULT).as_bool() });
+    if ready {
+        Ok(())
+    } else {
+        Err("touch injection unavailable (needs windows 8 or later)".to_string())
+    }
+}
+
+// each finger's start and end, around the pointer
+fn paths(gesture: &Gesture, center: POINT) -> Vec<((i32, i32), (i32, i32))> {
+    let (x, y) = (center.x, center.y);
+    if let Some(pinch) = gesture.pinch {
+        let (near, far) = (SPREAD / 2, TRAVEL / 2);
+        let (from, to) = match pinch {
+            Pinch::In => (near, far),
+            Pinch::Out => (far, near),
+        };
+        return vec![((x - from, y), (x - to, y)), ((x + from, y), (x + to, y))];
+    }
+    let (dx, dy) = match gesture.swipe {
+        Some(Direction::Left) => (-TRAVEL, 0),
+        Some(Direction::Right) => (TRAVEL, 0),
+        Some(Direction::Up) => (0, -TRAVEL),
+        _ => (0, TRAVEL),
+    };
+    // fingers side by side across the direction of travel
+    let fingers = gesture.fingers as i32;
+    (0..fingers)
+        .map(|i| {
+            let offset = (i * 2 - (fingers - 1)) * SPREAD / 2;
+            let start = if dx == 0 {
+                (x + offset, y)
+            } else {
+                (x, y + offset)
+            };
+            (start, (start.0 + dx, start.1 + dy))
+        })
+        .collect()
+}
+
+fn contact(id: u32, (x, y): (i32, i32), flags: POINTER_FLAGS) -> POINTER_TOUCH_INFO {
+    POINTER_TOUCH_INFO {
+        pointerInfo: POINTER_INFO {
+            pointerType: PT_TOUCH,
+            pointerId: id,
+            pointerFlags: flags,
+            ptPixelLocation: POINT { x, y },
+            ..Default::default()
+        },
+        touchFlags: TOUCH_FLAG_NONE,
+        touchMask: TOUCH_MASK_CONTACTAREA | TOUCH_MASK_PRESSURE,
+        rcContact: RECT {
+            left: x - 4,
+            top: y - 4,
+            right: x + 4,
+            bottom: y + 4,
+        },
+        orientation: 90,
+        pressure: 32000,
+        ..Default::default()
+    }
+}
+
+pub fn perform(gesture: &Gesture) -> Result<(), String> {
+    initialize()?;
+    let mut center = POINT::default();
+    unsafe { GetCursorPos(&mut center) };
+    let paths = paths(gesture, center);
+    let down = POINTER_FLAG_INRANGE | POINTER_FLAG_INCONTACT;
+    for frame in 0..=FRAMES {
+        let flags = match frame {
+            0 => POINTER_FLAG_DOWN | down,
+            FRAMES => POINTER_FLAG_UP,
+            _ => POINTER_FLAG_UPDATE | down,
+        };
+        let contacts: Vec<POINTER_TOUCH_INFO> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, &((x0, y0), (x1, y1)))| {
+                let at = (
+                    x0 + (x1 - x0) * frame / FRAMES,
+                    y0 + (y1 - y0) * frame / FRAMES,
+                );
+                contact(i as u32, at, flags)
+            })
+            .collect();
+        if !unsafe { InjectTouchInput(&contacts) }.as_bool() {
+            return Err(windows::core::Error::from_win32().to_string());
+        }
+        thread::sleep(FRAME_TIME);
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#[cfg(target_os = "linux")]
+mod evdev;
+#[cfg(target_os = "windows")]
+mod hidhide;
+
+#[cfg(target_os = "linux")]
+use evdev::Device;
+use gilrs::{GamepadId, Gilrs};
+#[cfg(target_os = "windows")]
+use hidhide::Device;
+use std::collections::HashMap;
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+use stub::Device;
+
+// keeps games that read the controller natively from seeing it while we
+// map it, so input isn't doubled: an exclusive evdev grab on linux, hidhide
+// on windows
+#[derive(Default)]
+pub struct Grabs {
+    // None when the node couldn't be found or grabbed, so we don't retry
+    // every tick
+    devices: HashMap<GamepadId, Option<Device>>,
+}
+
+impl Grabs {
+    // grab new controllers while `active`, let everything go otherwise, then
+    // pass on whatever the grabbed nodes read
+    pub fn update(&mut self, gilrs: &mut Gilrs, active: bool) {
+        if !active {
+            if !self.devices.is_empty() {
+                println!("releasing controller grab");
+                self.devices.clear();
+            }
+            return;
+        }
+
+        self.devices
+            .retain(|id, _| gilrs.connected_gamepad(*id).is_some());
+        for (id, gamepad) in gilrs.gamepads() {
+            self.devices.entry(id).or_insert_with(|| {
+                match Device::grab(gamepad.os_name(), gamepad.vendor_id(), gamepad.product_id()) {
+                    Ok(device) => {
+                        println!("grabbed {}", gamepad.name());
+                        Some(device)
+                    }
+                    Err(e) => {
+                        println!("can't grab {}: {}", gamepad.name(), e);
+                        None
+                    }
+                }
+            });
+        }
+
+        for (id, device) in self.devices.iter_mut() {
+            if let Some(d) = device {
+                if let Err(e) = d.forward(gilrs, *id) {
+                    println!("controller grab lost: {}", e);
+                    *device = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod stub {
+    use gilrs::{GamepadId, Gilrs};
+
+    pub struct Device;
+
+    impl Device {
+        pub fn grab(
+            _name: &str,
+            _vendor: Option<u16>,
+            _product: Option<u16>,
+        ) -> Result<Device, String> {
+            Err("grabbing is only supported on linux and windows".to_string())
+        }
+
+        pub fn forward(&mut self, _gilrs: &mut Gilrs, _id: GamepadId) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
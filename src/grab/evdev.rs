@@ -0,0 +1,278 @@
+use gilrs::{Axis, Button, Event, EventType, GamepadId, Gilrs};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+// _IOW('E', 0x90, int)
+const EVIOCGRAB: u64 = 0x4004_4590;
+// _IOR('E', 0x02, struct input_id)
+const EVIOCGID: u64 = 0x8008_4502;
+
+// _IOC(_IOC_READ, 'E', 0x06, len)
+fn eviocgname(len: usize) -> u64 {
+    0x8000_4506 | (len as u64) << 16
+}
+
+// _IOR('E', 0x40 + abs, struct input_absinfo)
+fn eviocgabs(abs: u16) -> u64 {
+    0x8018_4540 + abs as u64
+}
+
+// same as gilrs' defaults for analog triggers
+const TRIGGER_PRESS: f32 = 0.75;
+const TRIGGER_RELEASE: f32 = 0.65;
+
+// the kernel's standard gamepad codes (Documentation/input/gamepad.rst)
+fn button(code: u16) -> Option<Button> {
+    Some(match code {
+        0x130 => Button::South,
+        0x131 => Button::East,
+        0x132 => Button::C,
+        0x133 => Button::North,
+        0x134 => Button::West,
+        0x135 => Button::Z,
+        0x136 => Button::LeftTrigger,
+        0x137 => Button::RightTrigger,
+        0x138 => Button::LeftTrigger2,
+        0x139 => Button::RightTrigger2,
+        0x13a => Button::Select,
+        0x13b => Button::Start,
+        0x13c => Button::Mode,
+        0x13d => Button::LeftThumb,
+        0x13e => Button::RightThumb,
+        0x220 => Button::DPadUp,
+        0x221 => Button::DPadDown,
+        0x222 => Button::DPadLeft,
+        0x223 => Button::DPadRight,
+        _ => return None,
+    })
+}
+
+// an exclusively grabbed (EVIOCGRAB) evdev node. it only delivers events
+// to our fd, so gilrs goes quiet too; the events are read here instead and
+// fed back into gilrs
+pub struct Device {
+    file: File,
+    // (min, max) of each absolute axis we translate
+    ranges: [(i32, i32); 6],
+    held: HashSet<Button>,
+}
+
+impl Device {
+    pub fn grab(name: &str, vendor: Option<u16>, product: Option<u16>) -> Result<Device, String> {
+        let entries = std::fs::read_dir("/dev/input").map_err(|e| e.to_string())?;
+        let mut nodes: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("event"))
+            })
+            .collect();
+        nodes.sort();
+
+        let mut error = "no matching /dev/input/event* node".to_string();
+        for path in nodes {
+            let file = match OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path)
+            {
+                Ok(file) => file,
+                Err(e) => {
+                    error = format!("{}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let fd = file.as_raw_fd();
+
+            let mut id: libc::input_id = unsafe { std::mem::zeroed() };
+            let mut raw_name = [0u8; 256];
+            let ok = unsafe {
+                libc::ioctl(fd, EVIOCGID as _, &mut id) >= 0
+                    && libc::ioctl(fd, eviocgname(raw_name.len()) as _, raw_name.as_mut_ptr()) >= 0
+            };
+            let node_name = raw_name.split(|b| *b == 0).next().unwrap_or_default();
+            // controllers with motion sensors or touchpads have extra
+            // nodes with the same ids but a different name
+            if !ok
+                || vendor.is_some_and(|v| v != id.vendor)
+                || product.is_some_and(|p| p != id.product)
+                || node_name != name.as_bytes()
+            {
+                continue;
+            }
+
+            // fails with EBUSY when already grabbed, e.g. by the other of
+            // two identical controllers
+            if unsafe { libc::ioctl(fd, EVIOCGRAB as _, 1 as libc::c_int) } < 0 {
+                error = format!("{}: {}", path.display(), std::io::Error::last_os_error());
+                continue;
+            }
+
+            let mut ranges = [(-1, 1); 6];
+            for (abs, range) in ranges.iter_mut().enumerate() {
+                let mut info: libc::input_absinfo = unsafe { std::mem::zeroed() };
+                if unsafe { libc::ioctl(fd, eviocgabs(abs as u16) as _, &mut info) } >= 0
+                    && info.maximum > info.minimum
+                {
+                    *range = (info.minimum, info.maximum);
+                }
+            }
+            return Ok(Device {
+                file,
+                ranges,
+                held: HashSet::new(),
+            });
+        }
+        Err(error)
+    }
+
+    // reads everything pending and queues it as gilrs events. elements
+    // gilrs has no code for on this controller are dropped
+    pub fn forward(&mut self, gilrs: &mut Gilrs, id: GamepadId) -> Result<(), String> {
+        let size = std::mem::size_of::<libc::input_event>();
+        let mut buf = vec![0u8; size * 64];
+        loop {
+            let n = match self.file.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e.to_string()),
+            };
+            if n == 0 {
+                return Ok(());
+            }
+            for chunk in buf[..n].chunks_exact(size) {
+                let ev: libc::input_event =
+                    unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const _) };
+                let mut changes = Vec::new();
+                self.translate(ev.type_, ev.code, ev.value, &mut changes);
+                let gamepad = gilrs.gamepad(id);
+                let events: Vec<EventType> = changes
+                    .into_iter()
+                    .filter_map(|change| {
+                        Some(match change {
+                            Change::Pressed(b) => {
+                                EventType::ButtonPressed(b, gamepad.button_code(b)?)
+                            }
+                            Change::Released(b) => {
+                                EventType::ButtonReleased(b, gamepad.button_code(b)?)
+                            }
+                            Change::Button(b, v) => {
+                                EventType::ButtonChanged(b, v, gamepad.button_code(b)?)
+                            }
+                            Change::Axis(a, v) => {
+                                EventType::AxisChanged(a, v, gamepad.axis_code(a)?)
+                            }
+                        })
+                    })
+                    .collect();
+                for event in events {
+                    gilrs.insert_event(Event::new(id, event));
+                }
+            }
+        }
+    }
+
+    fn normalize(&self, abs: u16, value: i32) -> f32 {
+        let (min, max) = self.ranges[abs as usize];
+        (value - min) as f32 / (max - min) as f32
+    }
+
+    fn translate(&mut self, kind: u16, code: u16, value: i32, out: &mut Vec<Change>) {
+        match (kind, code) {
+            (EV_KEY, _) => {
+                if let Some(b) = button(code) {
+                    // analog triggers report their value through the axis
+                    let analog = matches!(b, Button::LeftTrigger2 | Button::RightTrigger2);
+                    if !analog && value != 2 {
+                        out.push(Change::Button(b, value as f32));
+                    }
+                    // 2 is autorepeat
+                    if value == 1 {
+                        self.set(b, true, out);
+                    } else if value == 0 {
+                        self.set(b, false, out);
+                    }
+                }
+            }
+            (EV_ABS, ABS_X | ABS_Y | ABS_RX | ABS_RY) => {
+                let axis = match code {
+                    ABS_X => Axis::LeftStickX,
+                    ABS_Y => Axis::LeftStickY,
+                    ABS_RX => Axis::RightStickX,
+                    _ => Axis::RightStickY,
+                };
+                let v = self.normalize(code, value) * 2.0 - 1.0;
+                // evdev's y axes point down, gilrs' up
+                let v = if code == ABS_Y || code == ABS_RY {
+                    -v
+                } else {
+                    v
+                };
+                out.push(Change::Axis(axis, v));
+            }
+            (EV_ABS, ABS_Z | ABS_RZ) => {
+                let b = if code == ABS_Z {
+                    Button::LeftTrigger2
+                } else {
+                    Button::RightTrigger2
+                };
+                let v = self.normalize(code, value);
+                out.push(Change::Button(b, v));
+                if v >= TRIGGER_PRESS {
+                    self.set(b, true, out);
+                } else if v <= TRIGGER_RELEASE {
+                    self.set(b, false, out);
+                }
+            }
+            (EV_ABS, ABS_HAT0X | ABS_HAT0Y) => {
+                let (negative, positive) = if code == ABS_HAT0X {
+                    (Button::DPadLeft, Button::DPadRight)
+                } else {
+                    (Button::DPadUp, Button::DPadDown)
+                };
+                self.set(negative, value < 0, out);
+                self.set(positive, value > 0, out);
+            }
+            _ => (),
+        }
+    }
+
+    // deduplicated, since triggers report both a key and an axis
+    fn set(&mut self, b: Button, pressed: bool, out: &mut Vec<Change>) {
+        if pressed == self.held.contains(&b) {
+            return;
+        }
+        if pressed {
+            self.held.insert(b);
+            out.push(Change::Pressed(b));
+        } else {
+            self.held.remove(&b);
+            out.push(Change::Released(b));
+        }
+    }
+}
+
+// what an evdev event means to gilrs, before looking up its code
+enum Change {
+    Pressed(Button),
+    Released(Button),
+    Button(Button, f32),
+    Axis(Axis, f32),
+}
@@ -0,0 +1,205 @@
+use gilrs::{GamepadId, Gilrs};
+use hidapi::HidApi;
+use std::ffi::c_void;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, GENERIC_READ, HANDLE, HMODULE};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, QueryDosDeviceW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE,
+    OPEN_EXISTING,
+};
+use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+use windows::Win32::System::IO::DeviceIoControl;
+
+// hidhide's control device ioctls, CTL_CODE(0x8001, 2048 + n,
+// METHOD_BUFFERED, FILE_READ_DATA)
+const GET_WHITELIST: u32 = 0x8001_6000;
+const SET_WHITELIST: u32 = 0x8001_6004;
+const GET_BLACKLIST: u32 = 0x8001_6008;
+const SET_BLACKLIST: u32 = 0x8001_600c;
+const GET_ACTIVE: u32 = 0x8001_6010;
+const SET_ACTIVE: u32 = 0x8001_6014;
+
+// hidhide (https://github.com/nefarius/HidHide) hides blacklisted devices
+// from every process not on its whitelist. we whitelist ourselves and
+// blacklist the controller's hid instances until dropped. gilrs keeps
+// reading the controller normally, so there's nothing to forward
+pub struct Device {
+    control: HANDLE,
+    // the instance paths we added, and whether hiding was on before
+    hidden: Vec<String>,
+    was_active: bool,
+}
+
+impl Device {
+    pub fn grab(_name: &str, vendor: Option<u16>, product: Option<u16>) -> Result<Device, String> {
+        let (vendor, product) = vendor.zip(product).ok_or("controller has no usb ids")?;
+        let control = unsafe {
+            CreateFileW(
+                w!("\\\\.\\HidHide"),
+                GENERIC_READ.0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                HANDLE::default(),
+            )
+        }
+        .map_err(|e| format!("hidhide not installed? {}", e))?;
+        let mut device = Device {
+            control,
+            hidden: Vec::new(),
+            was_active: false,
+        };
+        device.was_active = device.get_active()?;
+
+        let exe = own_image_path()?;
+        let mut whitelist = device.get_list(GET_WHITELIST)?;
+        if !whitelist.iter().any(|p| p.eq_ignore_ascii_case(&exe)) {
+            whitelist.push(exe);
+            device.set_list(SET_WHITELIST, &whitelist)?;
+        }
+
+        let api = HidApi::new().map_err(|e| e.to_string())?;
+        let mut blacklist = device.get_list(GET_BLACKLIST)?;
+        for info in api.device_list() {
+            if info.vendor_id() != vendor || info.product_id() != product {
+                continue;
+            }
+            let Some(instance) = info.path().to_str().ok().and_then(instance_path) else {
+                continue;
+            };
+            if !blacklist.iter().any(|p| p.eq_ignore_ascii_case(&instance))
+                && !device.hidden.contains(&instance)
+            {
+                device.hidden.push(instance);
+            }
+        }
+        if device.hidden.is_empty() {
+            return Err("no hid device to hide, or already hidden".to_string());
+        }
+        blacklist.extend(device.hidden.iter().cloned());
+        device.set_list(SET_BLACKLIST, &blacklist)?;
+        device.set_active(true)?;
+        Ok(device)
+    }
+
+    pub fn forward(&mut self, _gilrs: &mut Gilrs, _id: GamepadId) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn ioctl(&self, code: u32, input: &[u8], output: Option<&mut Vec<u8>>) -> Result<u32, String> {
+        let mut returned = 0u32;
+        let (out_ptr, out_len) = match output {
+            Some(buf) => (Some(buf.as_mut_ptr() as *mut c_void), buf.len() as u32),
+            None => (None, 0),
+        };
+        let ok = unsafe {
+            DeviceIoControl(
+                self.control,
+                code,
+                (!input.is_empty()).then_some(input.as_ptr() as *const c_void),
+                input.len() as u32,
+                out_ptr,
+                out_len,
+                Some(&mut returned),
+                None,
+            )
+        };
+        if ok.as_bool() {
+            Ok(returned)
+        } else {
+            Err(std::io::Error::last_os_error().to_string())
+        }
+    }
+
+    // lists are utf-16 multi-strings: each entry nul-terminated, then an
+    // extra nul
+    fn get_list(&self, code: u32) -> Result<Vec<String>, String> {
+        let size = self.ioctl(code, &[], None)?;
+        let mut buf = vec![0u8; size as usize];
+        let size = self.ioctl(code, &[], Some(&mut buf))?;
+        let wide: Vec<u16> = buf[..size as usize]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        Ok(wide
+            .split(|c| *c == 0)
+            .filter(|s| !s.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect())
+    }
+
+    fn set_list(&self, code: u32, list: &[String]) -> Result<(), String> {
+        let mut wide = Vec::new();
+        for entry in list {
+            wide.extend(entry.encode_utf16());
+            wide.push(0);
+        }
+        wide.push(0);
+        let bytes: Vec<u8> = wide.iter().flat_map(|c| c.to_le_bytes()).collect();
+        self.ioctl(code, &bytes, None).map(|_| ())
+    }
+
+    fn get_active(&self) -> Result<bool, String> {
+        let mut buf = vec![0u8; 1];
+        self.ioctl(GET_ACTIVE, &[], Some(&mut buf))?;
+        Ok(buf[0] != 0)
+    }
+
+    fn set_active(&self, active: bool) -> Result<(), String> {
+        self.ioctl(SET_ACTIVE, &[active as u8], None).map(|_| ())
+    }
+
+    fn release(&self) -> Result<(), String> {
+        if self.hidden.is_empty() {
+            return Ok(());
+        }
+        let mut blacklist = self.get_list(GET_BLACKLIST)?;
+        blacklist.retain(|p| !self.hidden.contains(p));
+        self.set_list(SET_BLACKLIST, &blacklist)?;
+        self.set_active(self.was_active)
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        if let Err(e) = self.release() {
+            println!("failed to unhide controller: {}", e);
+        }
+        unsafe {
+            CloseHandle(self.control);
+        }
+    }
+}
+
+// hidapi's interface path, e.g. `\\?\hid#vid_054c&pid_09cc#7&1a2b&0&0000#{guid}`,
+// as the instance path hidhide wants: `HID\VID_054C&PID_09CC\7&1A2B&0&0000`
+fn instance_path(path: &str) -> Option<String> {
+    let path = path.strip_prefix("\\\\?\\")?;
+    let (instance, _guid) = path.rsplit_once("#{")?;
+    Some(instance.replace('#', "\\").to_uppercase())
+}
+
+// our exe in the \Device\HarddiskVolumeN\... form the whitelist uses
+fn own_image_path() -> Result<String, String> {
+    let mut buf = [0u16; 1024];
+    let len = unsafe { GetModuleFileNameW(HMODULE::default(), &mut buf) } as usize;
+    if len == 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    let exe = String::from_utf16_lossy(&buf[..len]);
+    let (drive, rest) = exe
+        .split_at_checked(2)
+        .filter(|(d, _)| d.ends_with(':'))
+        .ok_or_else(|| format!("can't whitelist {}", exe))?;
+
+    let drive: Vec<u16> = drive.encode_utf16().chain([0]).collect();
+    let mut target = [0u16; 1024];
+    let len = unsafe { QueryDosDeviceW(PCWSTR(drive.as_ptr()), Some(&mut target)) } as usize;
+    if len == 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    // the result is a multi-string, the first entry is the device
+    let device = target[..len].split(|c| *c == 0).next().unwrap_or_default();
+    Ok(format!("{}{}", String::from_utf16_lossy(device), rest))
+}
@@ -0,0 +1,357 @@
+use crate::config::Config;
+use crate::edit::{self, Assignment};
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Sense, Stroke, Vec2};
+use gilrs::{Button, EventType, Gilrs};
+use std::collections::HashSet;
+use std::time::Duration;
+
+// where each button sits on the diagram, as fractions of its size
+const LAYOUT: &[(Button, f32, f32, &str)] = &[
+    (Button::LeftTrigger2, 0.20, 0.06, "LT"),
+    (Button::LeftTrigger, 0.20, 0.17, "LB"),
+    (Button::RightTrigger2, 0.80, 0.06, "RT"),
+    (Button::RightTrigger, 0.80, 0.17, "RB"),
+    (Button::Select, 0.41, 0.40, "Back"),
+    (Button::Mode, 0.50, 0.30, "Guide"),
+    (Button::Start, 0.59, 0.40, "Start"),
+    (Button::LeftThumb, 0.24, 0.42, "LS"),
+    (Button::RightThumb, 0.63, 0.68, "RS"),
+    (Button::DPadUp, 0.37, 0.59, "Up"),
+    (Button::DPadDown, 0.37, 0.81, "Down"),
+    (Button::DPadLeft, 0.30, 0.70, "Left"),
+    (Button::DPadRight, 0.44, 0.70, "Right"),
+    (Button::North, 0.78, 0.30, "Y"),
+    (Button::West, 0.71, 0.42, "X"),
+    (Button::East, 0.85, 0.42, "B"),
+    (Button::South, 0.78, 0.54, "A"),
+];
+
+const DIAGRAM: Vec2 = Vec2::new(480.0, 300.0);
+const RADIUS: f32 = 17.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Default,
+    Key,
+    Mouse,
+    Macro,
+}
+
+struct Editor {
+    config: Config,
+    profile: String,
+    gilrs: Option<Gilrs>,
+    // buttons held on the real controller, highlighted on the diagram
+    held: HashSet<Button>,
+    selected: Option<Button>,
+    kind: Kind,
+    // the key chord, mouse button or macro name being edited
+    text: String,
+    // waiting for a key press to fill in `text`
+    capturing: bool,
+    status: String,
+}
+
+// `controller2keys gui`: a controller diagram to click buttons on and bind
+// them to a key, mouse button or macro, saved into config.toml
+pub fn run() -> Result<(), String> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title("controller2keys")
+            .with_inner_size([760.0, 360.0]),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "controller2keys",
+        options,
+        Box::new(|_| Ok(Box::new(Editor::new()))),
+    )
+    .map_err(|e| e.to_string())
+}
+
+impl Editor {
+    fn new() -> Editor {
+        let config = Config::load_or_default();
+        let profile = config.profile.clone();
+        let (gilrs, status) = match Gilrs::new() {
+            Ok(gilrs) => (Some(gilrs), "press a button or click one".to_string()),
+            Err(e) => (None, format!("no live input: {}", e)),
+        };
+        Editor {
+            config,
+            profile,
+            gilrs,
+            held: HashSet::new(),
+            selected: None,
+            kind: Kind::Default,
+            text: String::new(),
+            capturing: false,
+            status,
+        }
+    }
+
+    fn poll(&mut self) {
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+        let mut pressed = None;
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    self.held.insert(button);
+                    pressed = Some(button);
+                }
+                EventType::ButtonReleased(button, _) => {
+                    self.held.remove(&button);
+                }
+                _ => (),
+            }
+        }
+        // pressing a button is the quickest way to find it
+        if let Some(button) = pressed.filter(|_| !self.capturing) {
+            self.select(button);
+        }
+    }
+
+    fn select(&mut self, button: Button) {
+        if self.selected == Some(button) || !LAYOUT.iter().any(|(b, ..)| *b == button) {
+            return;
+        }
+        self.selected = Some(button);
+        self.capturing = false;
+        let profile = self.config.get_profile(&self.profile);
+        (self.kind, self.text) = match Assignment::current(&profile, button) {
+            Assignment::Key(key) => (Kind::Key, key),
+            Assignment::Mouse(mouse) => (Kind::Mouse, mouse),
+            Assignment::Macro(name) => (Kind::Macro, name),
+            Assignment::Default | Assignment::Other => (Kind::Default, String::new()),
+        };
+    }
+
+    fn assignment(&self) -> Assignment {
+        let text = self.text.trim().to_string();
+        match self.kind {
+            Kind::Default => Assignment::Default,
+            Kind::Key => Assignment::Key(text),
+            Kind::Mouse => Assignment::Mouse(text),
+            Kind::Macro => Assignment::Macro(text),
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(button) = self.selected else {
+            return;
+        };
+        let assignment = self.assignment();
+        self.status = match edit::save(&self.profile, button, &assignment) {
+            Ok(()) => {
+                self.config = Config::load_or_default();
+                format!(
+                    "{:?} -> {} in '{}'",
+                    button,
+                    assignment.describe(),
+                    self.profile
+                )
+            }
+            Err(e) => e,
+        };
+    }
+
+    fn diagram(&mut self, ui: &mut egui::Ui) {
+        let (response, painter) = ui.allocate_painter(DIAGRAM, Sense::click());
+        let rect = response.rect;
+        let at = |x: f32, y: f32| {
+            Pos2::new(
+                rect.left() + x * rect.width(),
+                rect.top() + y * rect.height(),
+            )
+        };
+
+        let body = egui::Rect::from_min_max(at(0.08, 0.22), at(0.92, 0.95));
+        painter.rect_filled(body, 60.0, Color32::from_gray(45));
+
+        let profile = self.config.get_profile(&self.profile);
+        for &(button, x, y, label) in LAYOUT {
+            let center = at(x, y);
+            let fill = if self.held.contains(&button) {
+                Color32::from_rgb(220, 160, 40)
+            } else if Assignment::current(&profile, button) != Assignment::Default {
+                Color32::from_rgb(40, 110, 160)
+            } else {
+                Color32::from_gray(80)
+            };
+            let stroke = if self.selected == Some(button) {
+                Stroke::new(3.0, Color32::WHITE)
+            } else {
+                Stroke::new(1.0, Color32::from_gray(20))
+            };
+            painter.circle(center, RADIUS, fill, stroke);
+            painter.text(
+                center,
+                Align2::CENTER_CENTER,
+                label,
+                FontId::proportional(11.0),
+                Color32::WHITE,
+            );
+        }
+
+        if let Some(pos) = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked())
+        {
+            let hit = LAYOUT
+                .iter()
+                .find(|(_, x, y, _)| at(*x, *y).distance(pos) <= RADIUS)
+                .map(|(b, ..)| *b);
+            if let Some(button) = hit {
+                self.select(button);
+            }
+        }
+    }
+
+    fn panel(&mut self, ui: &mut egui::Ui) {
+        let mut names: Vec<String> = self.config.profiles.keys().cloned().collect();
+        if !names.contains(&self.config.profile) {
+            names.insert(0, self.config.profile.clone());
+        }
+        let before = self.profile.clone();
+        egui::ComboBox::from_label("profile")
+            .selected_text(&self.profile)
+            .show_ui(ui, |ui| {
+                for name in names {
+                    ui.selectable_value(&mut self.profile, name.clone(), name);
+                }
+            });
+        if self.profile != before {
+            // reload what the selected button does in the new profile
+            if let Some(button) = self.selected.take() {
+                self.select(button);
+            }
+        }
+        ui.separator();
+
+        let Some(button) = self.selected else {
+            ui.label("select a button");
+            return;
+        };
+        let profile = self.config.get_profile(&self.profile);
+        ui.heading(format!("{:?}", button));
+        ui.label(format!(
+            "now: {}",
+            Assignment::current(&profile, button).describe()
+        ));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.kind, Kind::Default, "built-in");
+            ui.radio_value(&mut self.kind, Kind::Key, "key");
+            ui.radio_value(&mut self.kind, Kind::Mouse, "mouse");
+            ui.radio_value(&mut self.kind, Kind::Macro, "macro");
+        });
+        match self.kind {
+            Kind::Default => (),
+            Kind::Key => {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.text);
+                    let label = if self.capturing {
+                        "press a key..."
+                    } else {
+                        "record"
+                    };
+                    if ui.button(label).clicked() {
+                        self.capturing = !self.capturing;
+                    }
+                });
+            }
+            Kind::Mouse => {
+                ui.horizontal(|ui| {
+                    for name in ["left", "right", "middle"] {
+                        ui.radio_value(&mut self.text, name.to_string(), name);
+                    }
+                });
+            }
+            Kind::Macro => {
+                let macros: Vec<String> = profile.macros.iter().map(|m| m.name.clone()).collect();
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.text);
+                    egui::ComboBox::from_id_salt("macros")
+                        .selected_text("existing")
+                        .show_ui(ui, |ui| {
+                            for name in macros {
+                                ui.selectable_value(&mut self.text, name.clone(), name);
+                            }
+                        });
+                });
+            }
+        }
+        if ui.button("save to profile").clicked() {
+            self.save();
+        }
+    }
+
+    // the next key pressed in the window, with its modifiers, as a chord
+    fn capture(&mut self, ctx: &egui::Context) {
+        if !self.capturing {
+            return;
+        }
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+        let Some((key, modifiers)) = pressed else {
+            return;
+        };
+        self.capturing = false;
+        let Some(name) = key_name(key) else {
+            self.status = format!("{} can't be bound yet", key.name());
+            return;
+        };
+        let mut chord = Vec::new();
+        for (held, modifier) in [
+            (modifiers.ctrl, "ctrl"),
+            (modifiers.alt, "alt"),
+            (modifiers.shift, "shift"),
+            (modifiers.mac_cmd, "cmd"),
+        ] {
+            if held {
+                chord.push(modifier.to_string());
+            }
+        }
+        chord.push(name);
+        self.text = chord.join("+");
+    }
+}
+
+impl eframe::App for Editor {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll();
+        self.capture(ctx);
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| ui.label(&self.status));
+        egui::SidePanel::right("binding")
+            .min_width(240.0)
+            .show(ctx, |ui| self.panel(ui));
+        egui::CentralPanel::default().show(ctx, |ui| self.diagram(ui));
+        // keep highlighting live input without waiting for window events
+        ctx.request_repaint_after(Duration::from_millis(16));
+    }
+}
+
+// egui's name for a key, as our config spells it
+fn key_name(key: egui::Key) -> Option<String> {
+    use egui::Key::*;
+    let name = match key {
+        ArrowUp | ArrowDown | ArrowLeft | ArrowRight => key.name().to_lowercase(),
+        OpenBracket => "leftbracket".to_string(),
+        CloseBracket => "rightbracket".to_string(),
+        _ => match key.symbol_or_name() {
+            symbol if symbol.chars().count() == 1 => symbol.to_lowercase(),
+            _ => key.name().to_lowercase(),
+        },
+    };
+    crate::keymap::parse_key(&name).map(|_| name)
+}
@@ -0,0 +1,158 @@
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Ticks};
+use gilrs::{GamepadId, Gilrs};
+use serde::Deserialize;
+use std::time::Instant;
+
+// a light rumble that follows how fast the stick moves the mouse and how
+// fast the wheel scrolls, e.g. `haptics = { strength = 0.3 }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HapticsConfig {
+    // which output to feel
+    pub mouse: bool,
+    pub scroll: bool,
+    // rumble at full speed, 0 to 1
+    pub strength: f32,
+    // mouse pixels per tick that count as full speed
+    pub full_speed: f32,
+    // how quickly the rumble fades once movement stops
+    pub fade_ms: u64,
+}
+
+impl Default for HapticsConfig {
+    fn default() -> Self {
+        HapticsConfig {
+            mouse: true,
+            scroll: true,
+            strength: 0.4,
+            full_speed: 40.0,
+            fade_ms: 80,
+        }
+    }
+}
+
+// each scroll click adds this much, so fast scrolling buzzes harder
+const SCROLL_BUMP: f32 = 0.5;
+
+pub struct Haptics {
+    config: HapticsConfig,
+    // the running effect, played continuously and scaled through its gain
+    effect: Option<Effect>,
+    // which gamepad the effect was built for, so we don't keep retrying
+    attached: Option<GamepadId>,
+    level: f32,
+    gain: f32,
+    last: Instant,
+}
+
+impl Haptics {
+    pub fn new(config: &HapticsConfig) -> Haptics {
+        Haptics {
+            config: config.clone(),
+            effect: None,
+            attached: None,
+            level: 0.0,
+            gain: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    // feed in what was output since the last tick
+    pub fn tick(&mut self, gilrs: &mut Gilrs, gamepad: Option<GamepadId>, motion: (f32, i32)) {
+        let gamepad = match gamepad {
+            Some(id) => id,
+            None => return,
+        };
+        if self.attached != Some(gamepad) {
+            self.attached = Some(gamepad);
+            self.effect = attach(gilrs, gamepad);
+        }
+        let effect = match &self.effect {
+            Some(effect) => effect,
+            None => return,
+        };
+
+        let (moved, scrolled) = motion;
+        let mut now = 0.0;
+        if self.config.mouse {
+            now += moved / self.config.full_speed.max(1.0);
+        }
+        if self.config.scroll {
+            now += scrolled as f32 * SCROLL_BUMP;
+        }
+
+        let elapsed = self.last.elapsed().as_secs_f32() * 1000.0;
+        self.last = Instant::now();
+        let fade = (-elapsed / self.config.fade_ms.max(1) as f32).exp();
+        self.level = (self.level * fade).max(now.min(1.0));
+
+        // skip tiny changes so we aren't updating the motors every tick
+        let gain = self.level * self.config.strength.clamp(0.0, 1.0);
+        let gain = if gain < 0.02 { 0.0 } else { gain };
+        if (gain - self.gain).abs() >= 0.02 || (gain == 0.0 && self.gain != 0.0) {
+            self.gain = gain;
+            if let Err(e) = effect.set_gain(gain) {
+                println!("rumble failed: {}", e);
+                self.effect = None;
+            }
+        }
+    }
+}
+
+// a short rumble confirming a change made without looking, like a speed
+// tier switch. the effect is kept until the next one, since dropping it
+// stops it
+#[derive(Default)]
+pub struct Buzz {
+    effect: Option<Effect>,
+}
+
+impl Buzz {
+    pub fn play(&mut self, gilrs: &mut Gilrs, gamepad: Option<GamepadId>, ms: u32) {
+        let Some(gamepad) = gamepad else {
+            return;
+        };
+        if !gilrs.gamepad(gamepad).is_ff_supported() {
+            return;
+        }
+        let result = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: u16::MAX / 2,
+                },
+                ..Default::default()
+            })
+            .repeat(Repeat::For(Ticks::from_ms(ms)))
+            .gamepads(&[gamepad])
+            .finish(gilrs);
+        match result.and_then(|effect| effect.play().map(|_| effect)) {
+            Ok(effect) => self.effect = Some(effect),
+            Err(e) => println!("rumble failed: {}", e),
+        }
+    }
+}
+
+fn attach(gilrs: &mut Gilrs, gamepad: GamepadId) -> Option<Effect> {
+    if !gilrs.gamepad(gamepad).is_ff_supported() {
+        println!("controller has no rumble - haptics off");
+        return None;
+    }
+    let result = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Weak {
+                magnitude: u16::MAX,
+            },
+            ..Default::default()
+        })
+        .repeat(Repeat::Infinitely)
+        .gain(0.0)
+        .gamepads(&[gamepad])
+        .finish(gilrs);
+    match result.and_then(|effect| effect.play().map(|_| effect)) {
+        Ok(effect) => Some(effect),
+        Err(e) => {
+            println!("failed to start rumble: {}", e);
+            None
+        }
+    }
+}
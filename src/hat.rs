@@ -0,0 +1,175 @@
+use crate::action::Action;
+use crate::bands::AxisName;
+use crate::devices::AxisNames;
+use crate::keymap::parse_chord;
+use crate::scancode;
+use enigo::Key;
+use gilrs::Axis;
+use serde::Deserialize;
+
+// how far an axis has to go for its direction to count
+const THRESHOLD: f32 = 0.5;
+
+// a pov hat read as one 8-way input from its two axes, e.g.
+// `hats = [{ x = "hat2x", y = "hat2y", up = "f1", down = "f2" }]`. diagonals
+// left out hold both of their neighbours' keys
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HatConfig {
+    pub x: AxisName,
+    pub y: AxisName,
+    // for hats whose up reads as negative
+    #[serde(default)]
+    pub flip_y: bool,
+    pub up: Option<String>,
+    pub down: Option<String>,
+    pub left: Option<String>,
+    pub right: Option<String>,
+    pub up_left: Option<String>,
+    pub up_right: Option<String>,
+    pub down_left: Option<String>,
+    pub down_right: Option<String>,
+}
+
+struct Hat {
+    x: AxisName,
+    y: AxisName,
+    flip_y: bool,
+    // keys for each direction, clockwise from up
+    keys: [Vec<Key>; 8],
+    values: (f32, f32),
+    held: Vec<Key>,
+}
+
+impl Hat {
+    fn new(config: &HatConfig, physical: bool) -> Result<Hat, String> {
+        let keys = |name: &Option<String>| -> Result<Option<Vec<Key>>, String> {
+            let Some(name) = name else {
+                return Ok(None);
+            };
+            let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+            Ok(Some(if physical {
+                keys.into_iter().map(scancode::physical).collect()
+            } else {
+                keys
+            }))
+        };
+        let (up, right, down, left) = (
+            keys(&config.up)?,
+            keys(&config.right)?,
+            keys(&config.down)?,
+            keys(&config.left)?,
+        );
+        let both = |a: &Option<Vec<Key>>, b: &Option<Vec<Key>>| {
+            let mut keys = a.clone().unwrap_or_default();
+            for key in b.iter().flatten() {
+                if !keys.contains(key) {
+                    keys.push(*key);
+                }
+            }
+            keys
+        };
+        let diagonal = |name: &Option<String>, a: &Option<Vec<Key>>, b: &Option<Vec<Key>>| {
+            keys(name).map(|keys| keys.unwrap_or_else(|| both(a, b)))
+        };
+        let keys = [
+            up.clone().unwrap_or_default(),
+            diagonal(&config.up_right, &up, &right)?,
+            right.clone().unwrap_or_default(),
+            diagonal(&config.down_right, &down, &right)?,
+            down.clone().unwrap_or_default(),
+            diagonal(&config.down_left, &down, &left)?,
+            left.clone().unwrap_or_default(),
+            diagonal(&config.up_left, &up, &left)?,
+        ];
+        if keys.iter().all(|k| k.is_empty()) {
+            return Err("no directions have keys".to_string());
+        }
+        Ok(Hat {
+            x: config.x.clone(),
+            y: config.y.clone(),
+            flip_y: config.flip_y,
+            keys,
+            values: (0.0, 0.0),
+            held: Vec::new(),
+        })
+    }
+
+    // the keys for where the hat points now
+    fn wanted(&self) -> &[Key] {
+        let (x, y) = self.values;
+        let y = if self.flip_y { -y } else { y };
+        let step = |v: f32| {
+            if v >= THRESHOLD {
+                1
+            } else if v <= -THRESHOLD {
+                -1
+            } else {
+                0
+            }
+        };
+        let direction = match (step(x), step(y)) {
+            (0, 1) => 0,
+            (1, 1) => 1,
+            (1, 0) => 2,
+            (1, -1) => 3,
+            (0, -1) => 4,
+            (-1, -1) => 5,
+            (-1, 0) => 6,
+            (-1, 1) => 7,
+            _ => return &[],
+        };
+        &self.keys[direction]
+    }
+}
+
+// what's wrong with each hat, for `check`
+pub fn errors(configs: &[HatConfig], physical: bool) -> Vec<String> {
+    configs
+        .iter()
+        .filter_map(|config| Hat::new(config, physical).err().map(|e| (config, e)))
+        .map(|(config, e)| format!("{}/{}: {}", config.x, config.y, e))
+        .collect()
+}
+
+pub struct Hats {
+    hats: Vec<Hat>,
+}
+
+impl Hats {
+    // `physical` is the profile's key_positions, resolved
+    pub fn new(configs: &[HatConfig], physical: bool) -> Hats {
+        let mut hats = Vec::new();
+        for config in configs {
+            match Hat::new(config, physical) {
+                Ok(hat) => hats.push(hat),
+                Err(e) => println!("ignoring hat on {}/{}: {}", config.x, config.y, e),
+            }
+        }
+        Hats { hats }
+    }
+
+    // call when any axis changes. moving between neighbouring directions
+    // keeps the keys they share held
+    pub fn update(&mut self, axis: Axis, value: f32, code: u32, names: &AxisNames) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for hat in &mut self.hats {
+            if hat.x.matches(axis, code, names) {
+                hat.values.0 = value;
+            } else if hat.y.matches(axis, code, names) {
+                hat.values.1 = value;
+            } else {
+                continue;
+            }
+            let wanted = hat.wanted().to_vec();
+            for key in hat.held.iter().rev().filter(|k| !wanted.contains(k)) {
+                actions.push(Action::KeyUp(*key));
+            }
+            for key in wanted.iter().filter(|k| !hat.held.contains(k)) {
+                actions.push(Action::KeyDown(*key));
+            }
+            hat.held = wanted;
+        }
+        actions
+    }
+}
@@ -0,0 +1,202 @@
+use hidapi::{BusType, HidApi, HidDevice};
+
+// raw hid output reports for playstation controllers, for the things gilrs
+// can't do (lightbar color, adaptive triggers). report layouts follow the
+// linux hid-sony and hid-playstation drivers
+const SONY: u16 = 0x054c;
+const DUALSHOCK4: &[u16] = &[0x05c4, 0x09cc, 0x0ba0];
+const DUALSENSE: &[u16] = &[0x0ce6, 0x0df2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    DualShock4,
+    DualSense,
+}
+
+pub struct Pad {
+    device: HidDevice,
+    kind: Kind,
+    bluetooth: bool,
+    // dualsense bluetooth reports carry a rolling sequence number
+    seq: u8,
+    // the whole output state goes out in every report, so remember it.
+    // None is left however the controller has it
+    lightbar: Option<[u8; 3]>,
+    // dualsense adaptive trigger effects, left then right
+    triggers: Option<[TriggerEffect; 2]>,
+}
+
+// raw dualsense trigger effect: a mode byte and its parameters
+pub type TriggerEffect = [u8; 11];
+
+impl Pad {
+    // the first playstation controller found, if any. other controllers
+    // simply have no lightbar
+    pub fn open() -> Option<Pad> {
+        let api = match HidApi::new() {
+            Ok(api) => api,
+            Err(e) => {
+                println!("hid unavailable: {}", e);
+                return None;
+            }
+        };
+        for info in api.device_list() {
+            if info.vendor_id() != SONY {
+                continue;
+            }
+            let kind = if DUALSHOCK4.contains(&info.product_id()) {
+                Kind::DualShock4
+            } else if DUALSENSE.contains(&info.product_id()) {
+                Kind::DualSense
+            } else {
+                continue;
+            };
+            match info.open_device(&api) {
+                Ok(device) => {
+                    let bluetooth = matches!(info.bus_type(), BusType::Bluetooth);
+                    println!(
+                        "opened {:?} over {} for lightbar control",
+                        kind,
+                        if bluetooth { "bluetooth" } else { "usb" }
+                    );
+                    let mut pad = Pad {
+                        device,
+                        kind,
+                        bluetooth,
+                        seq: 0,
+                        lightbar: None,
+                        triggers: None,
+                    };
+                    if kind == Kind::DualSense {
+                        // end the startup animation so the color can be set
+                        let _ = pad.send(true);
+                    }
+                    return Some(pad);
+                }
+                // usually missing permissions on the hidraw node
+                Err(e) => println!("failed to open {:?}: {}", kind, e),
+            }
+        }
+        None
+    }
+
+    // only writes when the color actually changes
+    pub fn set_lightbar(&mut self, color: [u8; 3]) -> Result<(), String> {
+        if Some(color) == self.lightbar {
+            return Ok(());
+        }
+        self.lightbar = Some(color);
+        self.send(false)
+    }
+
+    // dualsense only, other controllers have plain triggers
+    pub fn set_triggers(
+        &mut self,
+        left: TriggerEffect,
+        right: TriggerEffect,
+    ) -> Result<(), String> {
+        if self.kind != Kind::DualSense || Some([left, right]) == self.triggers {
+            return Ok(());
+        }
+        self.triggers = Some([left, right]);
+        self.send(false)
+    }
+
+    fn send(&mut self, setup: bool) -> Result<(), String> {
+        let report = match self.kind {
+            Kind::DualShock4 => self.dualshock4_report(),
+            Kind::DualSense => self.dualsense_report(setup),
+        };
+        self.device
+            .write(&report)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn dualshock4_report(&self) -> Vec<u8> {
+        let (mut report, flags, offset) = if self.bluetooth {
+            let mut report = vec![0u8; 78];
+            report[0] = 0x11;
+            // hid + crc
+            report[1] = 0xc0;
+            (report, 3, 6)
+        } else {
+            let mut report = vec![0u8; 32];
+            report[0] = 0x05;
+            (report, 1, 4)
+        };
+        // rumble comes first, then the color
+        if let Some([r, g, b]) = self.lightbar {
+            report[flags] |= 0x02;
+            report[offset + 2] = r;
+            report[offset + 3] = g;
+            report[offset + 4] = b;
+        }
+        self.finish(report)
+    }
+
+    fn dualsense_report(&mut self, setup: bool) -> Vec<u8> {
+        let (mut report, common) = if self.bluetooth {
+            let mut report = vec![0u8; 78];
+            report[0] = 0x31;
+            report[1] = self.seq << 4;
+            report[2] = 0x10;
+            self.seq = (self.seq + 1) % 16;
+            (report, 3)
+        } else {
+            let mut report = vec![0u8; 63];
+            report[0] = 0x02;
+            (report, 1)
+        };
+        if setup {
+            // lightbar setup valid, lights out
+            report[common + 38] = 0x02;
+            report[common + 41] = 0x02;
+            return self.finish(report);
+        }
+        if let Some([r, g, b]) = self.lightbar {
+            // lightbar color valid
+            report[common + 1] |= 0x04;
+            report[common + 44] = r;
+            report[common + 45] = g;
+            report[common + 46] = b;
+        }
+        if let Some([left, right]) = self.triggers {
+            // right and left trigger motors valid
+            report[common] |= 0x0c;
+            report[common + 10..common + 21].copy_from_slice(&right);
+            report[common + 21..common + 32].copy_from_slice(&left);
+        }
+        self.finish(report)
+    }
+
+    fn finish(&self, mut report: Vec<u8>) -> Vec<u8> {
+        if self.bluetooth {
+            seal(&mut report);
+        }
+        report
+    }
+}
+
+// bluetooth output reports end in a crc32 over a 0xa2 header byte and the
+// rest of the report
+fn seal(report: &mut [u8]) {
+    let end = report.len() - 4;
+    let crc = crc32(std::iter::once(&0xa2).chain(&report[..end]));
+    report[end..].copy_from_slice(&crc.to_le_bytes());
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
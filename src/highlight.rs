@@ -0,0 +1,63 @@
+use crate::lightbar::parse_color;
+use crate::overlay::{self, Ring};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// a bright ring around the pointer while the stick is moving it, because
+// the cursor is hard to find on a tv across the room, e.g.
+// `cursor_ring = { radius = 40, color = "#ff00ff" }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CursorRingConfig {
+    pub radius: u16,
+    pub thickness: u16,
+    // "#rrggbb"
+    pub color: String,
+    // how long the ring stays after the stick stops
+    pub linger_ms: u64,
+}
+
+impl Default for CursorRingConfig {
+    fn default() -> Self {
+        CursorRingConfig {
+            radius: 30,
+            thickness: 4,
+            color: "#ffd000".to_string(),
+            linger_ms: 1500,
+        }
+    }
+}
+
+pub struct CursorRing {
+    ring: Option<Box<dyn Ring>>,
+    linger: Duration,
+    last_moved: Option<Instant>,
+}
+
+impl CursorRing {
+    pub fn new(config: &CursorRingConfig) -> CursorRing {
+        let color = parse_color("cursor_ring", &config.color).unwrap_or([255, 208, 0]);
+        let thickness = config.thickness.clamp(1, config.radius.max(1));
+        CursorRing {
+            ring: overlay::ring(config.radius.max(1), thickness, color),
+            linger: Duration::from_millis(config.linger_ms),
+            last_moved: None,
+        }
+    }
+
+    // `moved` is how far the stick moved the mouse this tick
+    pub fn update(&mut self, moved: f32) {
+        let ring = match self.ring.as_mut() {
+            Some(ring) => ring,
+            None => return,
+        };
+        if moved > 0.0 {
+            self.last_moved = Some(Instant::now());
+        }
+        let show = self.last_moved.is_some_and(|t| t.elapsed() < self.linger);
+        if let Err(e) = ring.follow(show) {
+            println!("cursor ring stopped: {}", e);
+            self.ring = None;
+        }
+    }
+}
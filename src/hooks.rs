@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::thread;
+
+// user commands run on lifecycle events, keyed by event name
+// (e.g. `connected = "notify-send 'controller on'"`)
+pub type Hooks = HashMap<String, String>;
+
+// run the hook for `event`, if any. the command goes through the platform
+// shell and gets C2K_EVENT plus `env` in its environment. it runs detached so
+// a slow script can't stall input handling
+pub fn run(hooks: &Hooks, event: &str, env: &[(&str, String)]) {
+    let command = match hooks.get(event) {
+        Some(command) => command,
+        None => return,
+    };
+
+    println!("running {} hook: {}", event, command);
+    let mut env = env.to_vec();
+    env.push(("C2K_EVENT", event.to_string()));
+    if let Err(e) = spawn(command, &env) {
+        println!("failed to run {} hook: {}", event, e);
+    }
+}
+
+// run a command through the platform shell without waiting for it
+pub fn spawn(command: &str, env: &[(&str, String)]) -> Result<(), String> {
+    let mut cmd = shell(command);
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    // reap it in the background so it doesn't linger as a zombie
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
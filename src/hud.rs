@@ -0,0 +1,84 @@
+use crate::overlay::{self, Corner, Overlay};
+use crate::vars::Vars;
+use serde::Deserialize;
+
+// a corner widget with the profile, layer, mouse speed, variables and held
+// keys, since the console can't be seen once a game is fullscreen, e.g.
+// `hud = { corner = "top_left" }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HudConfig {
+    pub corner: Corner,
+    // list the keys and mouse buttons currently held down
+    pub keys: bool,
+}
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        HudConfig {
+            corner: Corner::TopRight,
+            keys: true,
+        }
+    }
+}
+
+pub struct Hud {
+    overlay: Option<Box<dyn Overlay>>,
+    keys: bool,
+    // what's on screen, so we only redraw on changes
+    shown: Vec<String>,
+}
+
+impl Hud {
+    pub fn new(config: &HudConfig) -> Hud {
+        Hud {
+            overlay: overlay::text(config.corner),
+            keys: config.keys,
+            shown: Vec::new(),
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        profile: &str,
+        layer: Option<&str>,
+        speed: Option<String>,
+        vars: &Vars,
+        mut held: Vec<String>,
+    ) {
+        let overlay = match self.overlay.as_mut() {
+            Some(overlay) => overlay,
+            None => return,
+        };
+
+        let mut lines = vec![format!("profile: {}", profile)];
+        if let Some(layer) = layer {
+            lines.push(format!("layer: {}", layer));
+        }
+        if let Some(speed) = speed {
+            lines.push(format!("speed: {}", speed));
+        }
+        let set: Vec<String> = vars
+            .snapshot()
+            .into_iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        if !set.is_empty() {
+            lines.push(format!("vars: {}", set.join(" ")));
+        }
+        if self.keys && !held.is_empty() {
+            held.sort();
+            lines.push(format!("held: {}", held.join(" ")));
+        }
+
+        let mut result = overlay.tick();
+        if lines != self.shown {
+            result = result.and_then(|_| overlay.show(&lines));
+            self.shown = lines;
+        }
+        if let Err(e) = result {
+            println!("hud stopped: {}", e);
+            self.overlay = None;
+        }
+    }
+}
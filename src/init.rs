@@ -0,0 +1,118 @@
+use crate::config::{config_dir, Config};
+use crate::preset;
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table};
+
+// what `controller2keys init` writes without a preset: the built-in mapping
+// spelled out as a profile, to edit from there
+const STARTER: &str = r#"# controller2keys config, see the README for everything that goes here
+profile = "main"
+
+[profiles.main]
+# "wasd", "arrows", "mouse", "none", or a table for keys or scroll
+left_stick = "wasd"
+right_stick = "mouse"
+sensitivity = 50
+deadzone = 0.15
+
+[[profiles.main.bindings]]
+button = "South"
+key = "space"
+
+[[profiles.main.bindings]]
+button = "East"
+key = "shift"
+
+[[profiles.main.bindings]]
+button = "West"
+key = "e"
+
+[[profiles.main.bindings]]
+button = "RightTrigger2"
+mouse = "left"
+
+[[profiles.main.bindings]]
+button = "LeftTrigger2"
+mouse = "right"
+
+[[profiles.main.bindings]]
+button = "Start"
+key = "escape"
+"#;
+
+// `controller2keys init [--preset <name>]`
+pub fn init(preset: Option<&str>, force: bool) -> Result<(), String> {
+    let path = config_dir().join("config.toml");
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists, pass --force to replace it",
+            path.display()
+        ));
+    }
+    let text = match preset {
+        Some(name) => format!("profile = \"{}\"\n\n{}", name, preset::source(name)?),
+        None => STARTER.to_string(),
+    };
+    fs::create_dir_all(config_dir()).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+// `controller2keys import <file>`: copies the profiles someone shared into
+// config.toml. they're appended as written, comments and all, and nothing
+// else in the file is taken
+pub fn import(file: &Path, force: bool) -> Result<(), String> {
+    // it has to be a config we could run, not just toml
+    Config::load(file)?;
+    let source: DocumentMut = fs::read_to_string(file)
+        .map_err(|e| e.to_string())?
+        .parse()
+        .map_err(|e| format!("failed to parse {}: {}", file.display(), e))?;
+    let profiles = source
+        .get("profiles")
+        .and_then(Item::as_table)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| format!("{} defines no profiles", file.display()))?;
+
+    let path = config_dir().join("config.toml");
+    let mut doc: DocumentMut = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .parse()
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+    let existing = doc.get_mut("profiles").and_then(Item::as_table_mut);
+    let clashes: Vec<&str> = profiles
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| existing.as_ref().is_some_and(|t| t.contains_key(name)))
+        .collect();
+    if !clashes.is_empty() && !force {
+        return Err(format!(
+            "already have {}, pass --force to replace",
+            clashes.join(", ")
+        ));
+    }
+    if let Some(existing) = existing {
+        for name in &clashes {
+            existing.remove(name);
+        }
+    }
+
+    let mut imported = Table::new();
+    imported.set_implicit(true);
+    for (name, item) in profiles.iter() {
+        imported.insert(name, item.clone());
+        println!("imported profile '{}'", name);
+    }
+    let mut fragment = DocumentMut::new();
+    fragment.insert("profiles", Item::Table(imported));
+
+    let mut text = doc.to_string();
+    if !text.is_empty() && !text.ends_with("\n\n") {
+        text.push('\n');
+    }
+    text.push_str(&fragment.to_string());
+    fs::create_dir_all(config_dir()).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,149 @@
+use gilrs::{Axis, Button, EventType, Gamepad, GamepadId};
+use std::collections::{HashMap, HashSet};
+
+// last known state of the controller, built up from events so anything that
+// needs "is X held" or "where is the stick" doesn't have to track it itself
+#[derive(Debug, Default)]
+pub struct InputState {
+    held: HashSet<Button>,
+    axes: HashMap<Axis, f32>,
+    // analog button values (mostly trigger pull), 0.0..=1.0
+    analog: HashMap<Button, f32>,
+}
+
+impl InputState {
+    pub fn update(&mut self, event: &EventType) {
+        match *event {
+            EventType::ButtonPressed(button, _) => {
+                self.held.insert(button);
+            }
+            EventType::ButtonReleased(button, _) => {
+                self.held.remove(&button);
+            }
+            EventType::ButtonChanged(button, value, _) => {
+                self.analog.insert(button, value);
+            }
+            EventType::AxisChanged(axis, value, _) => {
+                self.axes.insert(axis, value);
+            }
+            _ => (),
+        }
+    }
+
+    // tests can't make gilrs events, whose codes are private
+    #[cfg(test)]
+    pub fn set_axis(&mut self, axis: Axis, value: f32) {
+        self.axes.insert(axis, value);
+    }
+
+    #[cfg(test)]
+    pub fn set_value(&mut self, button: Button, value: f32) {
+        self.analog.insert(button, value);
+    }
+
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held.contains(&button)
+    }
+
+    pub fn held(&self) -> impl Iterator<Item = &Button> {
+        self.held.iter()
+    }
+
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    pub fn button_value(&self, button: Button) -> f32 {
+        self.analog.get(&button).copied().unwrap_or(0.0)
+    }
+
+    // the events that would bring a controller back to rest from here, for
+    // one that disconnected before sending its releases
+    pub fn release_events(&self, gamepad: Gamepad) -> Vec<EventType> {
+        let mut events = Vec::new();
+        for &button in &self.held {
+            if let Some(code) = gamepad.button_code(button) {
+                events.push(EventType::ButtonReleased(button, code));
+            }
+        }
+        for (&button, &value) in &self.analog {
+            match gamepad.button_code(button) {
+                Some(code) if value != 0.0 => {
+                    events.push(EventType::ButtonChanged(button, 0.0, code))
+                }
+                _ => (),
+            }
+        }
+        for (&axis, &value) in &self.axes {
+            match gamepad.axis_code(axis) {
+                Some(code) if value != 0.0 => events.push(EventType::AxisChanged(axis, 0.0, code)),
+                _ => (),
+            }
+        }
+        events
+    }
+
+    // nothing held and every stick and trigger within `threshold` of rest
+    pub fn at_rest(&self, threshold: f32) -> bool {
+        self.held.is_empty()
+            && self.axes.values().all(|v| v.abs() < threshold)
+            && self.analog.values().all(|v| *v < threshold)
+    }
+}
+
+// each controller's own state, alongside the InputState they all feed, so
+// one disconnecting lets go of only what it was holding itself
+#[derive(Debug, Default)]
+pub struct PadStates {
+    pads: HashMap<GamepadId, InputState>,
+    // disconnected, so the releases made up for them don't count as state
+    gone: HashSet<GamepadId>,
+}
+
+impl PadStates {
+    pub fn update(&mut self, id: GamepadId, event: &EventType) {
+        if *event == EventType::Connected {
+            self.gone.remove(&id);
+        }
+        if !self.gone.contains(&id) {
+            self.pads.entry(id).or_default().update(event);
+        }
+    }
+
+    // the releases for a controller that disconnected. buttons another
+    // controller still holds stay held, and sticks and triggers go to where
+    // another controller has them instead of to rest
+    pub fn release_events(&mut self, gamepad: Gamepad) -> Vec<EventType> {
+        self.gone.insert(gamepad.id());
+        let Some(state) = self.pads.remove(&gamepad.id()) else {
+            return Vec::new();
+        };
+        let others: Vec<&InputState> = self.pads.values().collect();
+        state
+            .release_events(gamepad)
+            .into_iter()
+            .filter_map(|event| match event {
+                EventType::ButtonReleased(button, _) => {
+                    (!others.iter().any(|s| s.is_held(button))).then_some(event)
+                }
+                EventType::ButtonChanged(button, _, code) => {
+                    let value = others
+                        .iter()
+                        .map(|s| s.button_value(button))
+                        .find(|&v| v != 0.0)
+                        .unwrap_or(0.0);
+                    Some(EventType::ButtonChanged(button, value, code))
+                }
+                EventType::AxisChanged(axis, _, code) => {
+                    let value = others
+                        .iter()
+                        .map(|s| s.axis(axis))
+                        .find(|&v| v != 0.0)
+                        .unwrap_or(0.0);
+                    Some(EventType::AxisChanged(axis, value, code))
+                }
+                event => Some(event),
+            })
+            .collect()
+    }
+}
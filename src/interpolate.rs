@@ -0,0 +1,69 @@
+use crate::priority;
+use crate::uinput;
+use enigo::{Enigo, MouseControllable};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// a tick's worth of mouse movement and how to spread it
+struct Spread {
+    dx: i32,
+    dy: i32,
+    pieces: u32,
+    over: Duration,
+}
+
+// splits each tick's mouse movement into smaller moves spaced evenly across
+// the next tick, e.g. 4 pieces of a 4ms tick for 1000Hz. games reading raw
+// input see a steady stream instead of a jump every 4ms. the moves are
+// injected from a thread of its own so the main loop never waits on them,
+// through the uinput device when keys and the mouse go that way
+pub struct Interpolator {
+    sender: Sender<Spread>,
+}
+
+impl Interpolator {
+    pub fn start(mouse: Option<uinput::Mouse>) -> Interpolator {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || run(receiver, mouse));
+        Interpolator { sender }
+    }
+
+    pub fn send(&self, dx: i32, dy: i32, pieces: u32, over: Duration) {
+        let _ = self.sender.send(Spread {
+            dx,
+            dy,
+            pieces: pieces.max(1),
+            over,
+        });
+    }
+}
+
+fn run(receiver: Receiver<Spread>, mut mouse: Option<uinput::Mouse>) {
+    priority::pin_thread();
+    let mut enigo = Enigo::new();
+    while let Ok(spread) = receiver.recv() {
+        let start = Instant::now();
+        let step = spread.over / spread.pieces;
+        let (mut sent_x, mut sent_y) = (0, 0);
+        for i in 1..=spread.pieces as i32 {
+            // rounded so the pieces add up to exactly the whole move
+            let x = spread.dx * i / spread.pieces as i32;
+            let y = spread.dy * i / spread.pieces as i32;
+            if (x, y) != (sent_x, sent_y) {
+                let (dx, dy) = (x - sent_x, y - sent_y);
+                match mouse.as_mut().map(|m| m.move_by(dx, dy)) {
+                    Some(Err(e)) => println!("uinput injection failed: {}", e),
+                    Some(Ok(())) => (),
+                    None => enigo.mouse_move_relative(dx, dy),
+                }
+                (sent_x, sent_y) = (x, y);
+            }
+            // already late for the next piece means no sleep at all
+            let due = start + step * i as u32;
+            if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+        }
+    }
+}
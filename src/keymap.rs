@@ -0,0 +1,161 @@
+use enigo::Key;
+
+// (names, evdev code, windows virtual-key, macos virtual keycode). None
+// means the platform has no such key
+type RawKey = (
+    &'static [&'static str],
+    Option<u16>,
+    Option<u16>,
+    Option<u16>,
+);
+
+// keys enigo has no portable variant for
+#[rustfmt::skip]
+const RAW_KEYS: &[RawKey] = &[
+    (&["f13"], Some(183), Some(0x7c), Some(0x69)),
+    (&["f14"], Some(184), Some(0x7d), Some(0x6b)),
+    (&["f15"], Some(185), Some(0x7e), Some(0x71)),
+    (&["f16"], Some(186), Some(0x7f), Some(0x6a)),
+    (&["f17"], Some(187), Some(0x80), Some(0x40)),
+    (&["f18"], Some(188), Some(0x81), Some(0x4f)),
+    (&["f19"], Some(189), Some(0x82), Some(0x50)),
+    (&["f20"], Some(190), Some(0x83), Some(0x5a)),
+    (&["f21"], Some(191), Some(0x84), None),
+    (&["f22"], Some(192), Some(0x85), None),
+    (&["f23"], Some(193), Some(0x86), None),
+    (&["f24"], Some(194), Some(0x87), None),
+
+    (&["numpad0", "kp0"], Some(82), Some(0x60), Some(0x52)),
+    (&["numpad1", "kp1"], Some(79), Some(0x61), Some(0x53)),
+    (&["numpad2", "kp2"], Some(80), Some(0x62), Some(0x54)),
+    (&["numpad3", "kp3"], Some(81), Some(0x63), Some(0x55)),
+    (&["numpad4", "kp4"], Some(75), Some(0x64), Some(0x56)),
+    (&["numpad5", "kp5"], Some(76), Some(0x65), Some(0x57)),
+    (&["numpad6", "kp6"], Some(77), Some(0x66), Some(0x58)),
+    (&["numpad7", "kp7"], Some(71), Some(0x67), Some(0x59)),
+    (&["numpad8", "kp8"], Some(72), Some(0x68), Some(0x5b)),
+    (&["numpad9", "kp9"], Some(73), Some(0x69), Some(0x5c)),
+    (&["numpadadd", "numpadplus"], Some(78), Some(0x6b), Some(0x45)),
+    (&["numpadsubtract", "numpadminus"], Some(74), Some(0x6d), Some(0x4e)),
+    (&["numpadmultiply"], Some(55), Some(0x6a), Some(0x43)),
+    (&["numpaddivide"], Some(98), Some(0x6f), Some(0x4b)),
+    (&["numpaddecimal", "numpadperiod"], Some(83), Some(0x6e), Some(0x41)),
+    (&["numpadenter"], Some(96), Some(0x0d), Some(0x4c)),
+    (&["numlock"], Some(69), Some(0x90), Some(0x47)),
+
+    (&["insert", "ins"], Some(110), Some(0x2d), Some(0x72)),
+    (&["printscreen", "print", "prtsc"], Some(99), Some(0x2c), None),
+    (&["scrolllock"], Some(70), Some(0x91), None),
+    (&["pause", "break"], Some(119), Some(0x13), None),
+    (&["menu", "apps", "contextmenu"], Some(127), Some(0x5d), None),
+
+    (&["playpause", "mediaplaypause", "play"], Some(164), Some(0xb3), None),
+    (&["nexttrack", "medianext"], Some(163), Some(0xb0), None),
+    (&["prevtrack", "previoustrack", "mediaprev"], Some(165), Some(0xb1), None),
+    (&["stop", "mediastop"], Some(166), Some(0xb2), None),
+    (&["browserback"], Some(158), Some(0xa6), None),
+    (&["browserforward"], Some(159), Some(0xa7), None),
+    (&["browserrefresh"], Some(173), Some(0xa8), None),
+    (&["browserhome"], Some(172), Some(0xac), None),
+    (&["mail"], Some(155), Some(0xb4), None),
+    (&["calculator"], Some(140), Some(0xb7), None),
+];
+
+#[cfg(target_os = "windows")]
+fn raw(_evdev: Option<u16>, vk: Option<u16>, _mac: Option<u16>) -> Option<Key> {
+    vk.map(Key::Raw)
+}
+
+#[cfg(target_os = "macos")]
+fn raw(_evdev: Option<u16>, _vk: Option<u16>, mac: Option<u16>) -> Option<Key> {
+    mac.map(Key::Raw)
+}
+
+// xdo takes x keycodes, which are evdev codes plus 8
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn raw(evdev: Option<u16>, _vk: Option<u16>, _mac: Option<u16>) -> Option<Key> {
+    evdev.map(|code| Key::Raw(code + 8))
+}
+
+// key names as written in scripts/config, case-insensitive and ignoring
+// `_`, `-` and spaces, so "page_down", "PageDown" and "page-down" are the
+// same key. single characters map to the layout key for that character
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Layout(c));
+    }
+
+    let normalized: String = name
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect();
+    let key = match normalized.as_str() {
+        "space" => Key::Space,
+        "shift" => Key::Shift,
+        "ctrl" | "control" => Key::Control,
+        "alt" | "option" => Key::Alt,
+        "meta" | "super" | "cmd" | "command" | "win" | "windows" => Key::Meta,
+        "tab" => Key::Tab,
+        "enter" | "return" => Key::Return,
+        "esc" | "escape" => Key::Escape,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "capslock" => Key::CapsLock,
+        "up" | "uparrow" => Key::UpArrow,
+        "down" | "downarrow" => Key::DownArrow,
+        "left" | "leftarrow" => Key::LeftArrow,
+        "right" | "rightarrow" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" | "pgup" => Key::PageUp,
+        "pagedown" | "pgdn" => Key::PageDown,
+        "volumeup" => Key::VolumeUp,
+        "volumedown" => Key::VolumeDown,
+        "mute" | "volumemute" => Key::VolumeMute,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+
+        // punctuation by name, for configs where the character itself is
+        // awkward to quote
+        "minus" | "dash" => Key::Layout('-'),
+        "equal" | "equals" => Key::Layout('='),
+        "leftbracket" => Key::Layout('['),
+        "rightbracket" => Key::Layout(']'),
+        "semicolon" => Key::Layout(';'),
+        "quote" | "apostrophe" => Key::Layout('\''),
+        "grave" | "backtick" => Key::Layout('`'),
+        "backslash" => Key::Layout('\\'),
+        "comma" => Key::Layout(','),
+        "period" | "dot" => Key::Layout('.'),
+        "slash" => Key::Layout('/'),
+
+        _ => {
+            let &(_, evdev, vk, mac) = RAW_KEYS
+                .iter()
+                .find(|(names, _, _, _)| names.contains(&normalized.as_str()))?;
+            return raw(evdev, vk, mac);
+        }
+    };
+    Some(key)
+}
+
+// a key or a chord of keys joined with `+`, like "ctrl+shift+tab". a lone
+// "+" is the plus key
+pub fn parse_chord(name: &str) -> Option<Vec<Key>> {
+    if name == "+" || !name.contains('+') {
+        return parse_key(name).map(|key| vec![key]);
+    }
+    name.split('+').map(|part| parse_key(part.trim())).collect()
+}
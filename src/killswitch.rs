@@ -0,0 +1,68 @@
+use crate::input::InputState;
+use gilrs::Button;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// a chord that lets go of everything and pauses the mapping, for when a bad
+// mapping is fighting you for the machine. holding it again resumes. on by
+// default with both stick clicks and both bumpers held for a second, e.g.
+// `kill_switch = { buttons = ["Select", "Start"], hold_ms = 2000 }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KillSwitchConfig {
+    pub enabled: bool,
+    pub buttons: Vec<Button>,
+    pub hold_ms: u64,
+}
+
+impl Default for KillSwitchConfig {
+    fn default() -> Self {
+        KillSwitchConfig {
+            enabled: true,
+            buttons: vec![
+                Button::LeftThumb,
+                Button::RightThumb,
+                Button::LeftTrigger,
+                Button::RightTrigger,
+            ],
+            hold_ms: 1000,
+        }
+    }
+}
+
+pub struct KillSwitch {
+    buttons: Vec<Button>,
+    hold: Duration,
+    // when the whole chord went down, and whether this hold already fired
+    since: Option<Instant>,
+    fired: bool,
+}
+
+impl KillSwitch {
+    pub fn new(config: &KillSwitchConfig) -> Option<KillSwitch> {
+        if !config.enabled || config.buttons.is_empty() {
+            return None;
+        }
+        Some(KillSwitch {
+            buttons: config.buttons.clone(),
+            hold: Duration::from_millis(config.hold_ms),
+            since: None,
+            fired: false,
+        })
+    }
+
+    // true once per hold, when the chord has been down long enough
+    pub fn update(&mut self, input: &InputState) -> bool {
+        if !self.buttons.iter().all(|b| input.is_held(*b)) {
+            self.since = None;
+            self.fired = false;
+            return false;
+        }
+        let since = *self.since.get_or_insert_with(Instant::now);
+        if self.fired || since.elapsed() < self.hold {
+            return false;
+        }
+        self.fired = true;
+        true
+    }
+}
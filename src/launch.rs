@@ -0,0 +1,99 @@
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use crate::config;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how long a launched program gets to show a window to focus
+const WINDOW_WAIT: Duration = Duration::from_secs(10);
+
+// a program started from a binding or the action board, e.g.
+// `launch = { program = "retroarch", args = ["--fullscreen"], focus = true }`.
+// there's no shell in between, so arguments need no quoting
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Launch {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // working directory, relative to the config dir
+    pub cwd: Option<PathBuf>,
+    // added to our own environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // bring its window to the front once it shows up
+    #[serde(default)]
+    pub focus: bool,
+}
+
+impl Launch {
+    pub fn spawn(&self) -> Result<(), String> {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).envs(&self.env);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(config::resolve(cwd));
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("{}: {}", self.program, e))?;
+        println!("launched {} (pid {})", self.program, child.id());
+        let focus = self.focus;
+        // reaped in the background so it doesn't linger as a zombie
+        thread::spawn(move || {
+            if focus {
+                focus_window(&mut child);
+            }
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+}
+
+// waits for a window of the child's to appear and raises it. programs that
+// hand off to another process and exit (some launchers do) can't be followed
+fn focus_window(child: &mut Child) {
+    let pid = child.id();
+    let started = Instant::now();
+    while started.elapsed() < WINDOW_WAIT {
+        if child.try_wait().ok().flatten().is_some() {
+            return;
+        }
+        match raise(pid) {
+            Ok(true) => return,
+            Ok(false) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                println!("can't focus launched program: {}", e);
+                return;
+            }
+        }
+    }
+    println!("pid {} showed no window to focus", pid);
+}
+
+#[cfg(target_os = "macos")]
+use macos::raise;
+#[cfg(target_os = "windows")]
+use windows::raise;
+
+// x11 only: wayland doesn't let clients raise each other's windows
+#[cfg(target_os = "linux")]
+fn raise(pid: u32) -> Result<bool, String> {
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err("needs an x11 session".to_string());
+    }
+    x11::raise(pid).map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn raise(_pid: u32) -> Result<bool, String> {
+    Err("not supported on this platform".to_string())
+}
@@ -0,0 +1,21 @@
+use objc::runtime::{Object, BOOL, NO};
+use objc::{class, msg_send, sel, sel_impl};
+
+// NSApplicationActivateIgnoringOtherApps
+const IGNORING_OTHER_APPS: u64 = 1 << 1;
+
+// activates the app with this pid, once it has registered with the window
+// server. false until then
+pub fn raise(pid: u32) -> Result<bool, String> {
+    unsafe {
+        let app: *mut Object = msg_send![
+            class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: pid as i32
+        ];
+        if app.is_null() {
+            return Ok(false);
+        }
+        let activated: BOOL = msg_send![app, activateWithOptions: IGNORING_OTHER_APPS];
+        Ok(activated != NO)
+    }
+}
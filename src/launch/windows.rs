@@ -0,0 +1,40 @@
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindow, GetWindowThreadProcessId, IsWindowVisible, SetForegroundWindow,
+    GW_OWNER,
+};
+
+// what the EnumWindows callback is looking for, and what it found
+struct Search {
+    pid: u32,
+    found: Option<HWND>,
+}
+
+unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let search = &mut *(lparam.0 as *mut Search);
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    // a visible top-level window, not a tool window or dialog owned by one
+    if pid == search.pid && IsWindowVisible(hwnd).as_bool() && GetWindow(hwnd, GW_OWNER).0 == 0 {
+        search.found = Some(hwnd);
+        return false.into();
+    }
+    true.into()
+}
+
+// brings the pid's main window to the front. false while it has none yet
+pub fn raise(pid: u32) -> Result<bool, String> {
+    let mut search = Search { pid, found: None };
+    unsafe {
+        // stopping early makes EnumWindows report failure, so its result
+        // says nothing
+        let _ = EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize));
+        match search.found {
+            Some(hwnd) => {
+                SetForegroundWindow(hwnd);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
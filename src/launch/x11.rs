@@ -0,0 +1,42 @@
+use std::error::Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt, EventMask};
+
+// finds a managed window with the pid in _NET_WM_PID and asks the window
+// manager to activate it. false while there's no such window yet
+pub fn raise(pid: u32) -> Result<bool, Box<dyn Error>> {
+    let (conn, screen) = x11rb::connect(None)?;
+    let root = conn.setup().roots[screen].root;
+    let atom = |name: &[u8]| -> Result<u32, Box<dyn Error>> {
+        Ok(conn.intern_atom(false, name)?.reply()?.atom)
+    };
+    let client_list = atom(b"_NET_CLIENT_LIST")?;
+    let wm_pid = atom(b"_NET_WM_PID")?;
+    let active_window = atom(b"_NET_ACTIVE_WINDOW")?;
+
+    let clients = conn
+        .get_property(false, root, client_list, AtomEnum::WINDOW, 0, 4096)?
+        .reply()?;
+    let Some(windows) = clients.value32() else {
+        return Ok(false);
+    };
+    for window in windows {
+        let owner = conn
+            .get_property(false, window, wm_pid, AtomEnum::CARDINAL, 0, 1)?
+            .reply()?;
+        if owner.value32().and_then(|mut v| v.next()) != Some(pid) {
+            continue;
+        }
+        // source 2 is a pager, which window managers don't second-guess
+        let event = ClientMessageEvent::new(32, window, active_window, [2, 0, 0, 0, 0]);
+        conn.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        conn.flush()?;
+        return Ok(true);
+    }
+    Ok(false)
+}
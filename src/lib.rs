@@ -0,0 +1,103 @@
+// the mapping engine. the controller2keys binary is a thin shell around it;
+// `engine` runs a profile without injecting anything, `Controller2Keys` runs
+// one on its own thread, and with the `ffi` feature the same is exported as
+// a c abi (see include/controller2keys.h)
+
+pub mod action;
+pub mod adaptive;
+pub mod bands;
+pub mod bindings;
+pub mod board;
+pub mod calibrate;
+pub mod capture;
+pub mod check;
+pub mod cli;
+pub mod clipboard;
+pub mod combo;
+pub mod config;
+pub mod ctl;
+pub mod devices;
+pub mod dial;
+pub mod display;
+pub mod doctor;
+#[cfg(any(feature = "gui", feature = "web", feature = "tui"))]
+pub mod edit;
+pub mod embed;
+pub mod engine;
+pub mod error;
+pub mod eventlog;
+pub mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod flick;
+pub mod focus;
+pub mod gamescope;
+pub mod gesture;
+pub mod grab;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod haptics;
+pub mod hat;
+pub mod hid;
+pub mod highlight;
+pub mod hooks;
+pub mod hud;
+pub mod init;
+pub mod input;
+pub mod interpolate;
+pub mod keymap;
+pub mod killswitch;
+pub mod launch;
+pub mod lightbar;
+pub mod lint;
+pub mod list;
+pub mod macros;
+pub mod magnifier;
+pub mod merge;
+pub mod mode;
+pub mod notify;
+pub mod obs;
+pub mod overlay;
+pub mod pad;
+pub mod plugin;
+pub mod pointer;
+pub mod preset;
+pub mod priority;
+pub mod privilege;
+pub mod process;
+pub mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod reverse;
+pub mod ring;
+pub mod scancode;
+pub mod script;
+pub mod scroll;
+pub mod session;
+pub mod simulate;
+pub mod smoothing;
+pub mod socd;
+pub mod sound;
+pub mod speech;
+pub mod split;
+pub mod steam;
+pub mod stick;
+pub mod transition;
+pub mod trigger;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod typing;
+pub mod udev;
+pub mod uinput;
+pub mod vars;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod watchdog;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod wheel;
+pub mod window;
+pub mod zoom;
+
+pub use action::Action;
+pub use embed::{Builder, Controller2Keys};
@@ -0,0 +1,96 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+// lightbar color for playstation controllers, so the live mapping shows at
+// a glance, e.g.
+// `lightbar = { color = "#0040ff", layers = { build = "#ff8000" } }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LightbarConfig {
+    // the profile's color, "#rrggbb"
+    pub color: Option<String>,
+    // colors while a binding layer is active, by layer name
+    pub layers: BTreeMap<String, String>,
+    // pulse while the battery is at or below this percentage, 0 to never
+    pub low_battery: u8,
+}
+
+impl Default for LightbarConfig {
+    fn default() -> Self {
+        LightbarConfig {
+            color: None,
+            layers: BTreeMap::new(),
+            low_battery: 20,
+        }
+    }
+}
+
+// one full pulse, and how many brightness steps it's split into so the
+// controller isn't written to every tick
+const PULSE_MS: u128 = 1500;
+const PULSE_STEPS: u128 = 16;
+
+pub struct Lightbar {
+    color: Option<[u8; 3]>,
+    layers: BTreeMap<String, [u8; 3]>,
+    low_battery: u8,
+    started: Instant,
+}
+
+impl Lightbar {
+    pub fn new(config: &LightbarConfig) -> Lightbar {
+        let color = config
+            .color
+            .as_deref()
+            .and_then(|c| parse_color("color", c));
+        let layers = config
+            .layers
+            .iter()
+            .filter_map(|(layer, c)| Some((layer.clone(), parse_color(layer, c)?)))
+            .collect();
+        Lightbar {
+            color,
+            layers,
+            low_battery: config.low_battery,
+            started: Instant::now(),
+        }
+    }
+
+    // the color to show for the active layer and battery level. None leaves
+    // the lightbar alone
+    pub fn color(&self, layer: Option<&str>, battery: Option<u8>) -> Option<[u8; 3]> {
+        let color = layer
+            .and_then(|l| self.layers.get(l))
+            .or(self.color.as_ref())
+            .copied()?;
+        match battery {
+            Some(level) if level <= self.low_battery => {
+                // fade down to a fifth and back
+                let phase = self.started.elapsed().as_millis() % PULSE_MS;
+                let step = phase * PULSE_STEPS / PULSE_MS;
+                let half = PULSE_STEPS / 2;
+                let distance = step.abs_diff(half) as f32 / half as f32;
+                let brightness = 0.2 + 0.8 * distance;
+                Some(color.map(|c| (c as f32 * brightness) as u8))
+            }
+            _ => Some(color),
+        }
+    }
+}
+
+pub fn parse_color(name: &str, color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    let value = if hex.len() == 6 {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    };
+    match value {
+        Some(v) => Some([(v >> 16) as u8, (v >> 8) as u8, v as u8]),
+        None => {
+            println!("invalid lightbar color for {}: '{}'", name, color);
+            None
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::action::parse_mouse_button;
+use crate::bindings::{BindingConfig, Condition};
+use crate::config::{config_dir, Config, Profile};
+use crate::keymap::parse_chord;
+use gilrs::{Button, Gilrs};
+use std::collections::HashSet;
+use std::fs;
+use toml_edit::ImDocument;
+
+// every button a controller might have, for the coverage notes
+const BUTTONS: &[Button] = &[
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::C,
+    Button::Z,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+// `controller2keys lint`: where `check` finds what a run would reject, this
+// finds what a run would accept but probably isn't meant: bindings that can
+// never fire, two buttons sending the same thing, layers and toggles that
+// can't be reached or left. warnings point at the binding's line. buttons
+// the connected controllers have but the profile doesn't bind are listed
+// as notes. returns whether there were no warnings
+pub fn run(profile: Option<&str>) -> bool {
+    let path = config_dir().join("config.toml");
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) => {
+            println!("no config at {}, nothing to lint", path.display());
+            return true;
+        }
+    };
+    let config: Config = match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("failed to parse {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    if let Some(name) = profile {
+        if name != config.profile && !config.profiles.contains_key(name) {
+            println!("no profile named '{}'", name);
+            return false;
+        }
+    }
+    // only for line numbers; the config above is what counts
+    let document = ImDocument::parse(text.as_str()).ok();
+    let locate = |profile: &str, index: usize| {
+        let bindings = document
+            .as_ref()?
+            .get("profiles")?
+            .get(profile)?
+            .get("bindings")?;
+        let span = match bindings.as_array_of_tables() {
+            Some(tables) => tables.get(index)?.span()?,
+            None => bindings.as_array()?.get(index)?.span()?,
+        };
+        Some(text[..span.start].lines().count() + 1)
+    };
+
+    let mut warnings = 0;
+    for (name, p) in &config.profiles {
+        if profile.is_some_and(|only| only != name) {
+            continue;
+        }
+        for (index, message) in lint(&config, p, |i| locate(name, i)) {
+            let location = match locate(name, index) {
+                Some(line) => format!("{}:{}", path.display(), line),
+                None => path.display().to_string(),
+            };
+            println!("{}: profile '{}': {}", location, name, message);
+            warnings += 1;
+        }
+    }
+
+    let selected = profile.unwrap_or(&config.profile);
+    coverage(selected, &config.get_profile(selected));
+
+    if warnings == 0 {
+        println!("{} has no warnings", path.display());
+    } else {
+        println!("{} warnings", warnings);
+    }
+    warnings == 0
+}
+
+// warnings for one profile's bindings, by binding index
+fn lint(
+    config: &Config,
+    profile: &Profile,
+    line: impl Fn(usize) -> Option<usize>,
+) -> Vec<(usize, String)> {
+    let bindings = &profile.bindings;
+    let at = |i: usize| match line(i) {
+        Some(line) => format!("the one on line {}", line),
+        None => format!("binding #{}", i + 1),
+    };
+    let when = |b: &BindingConfig| format!("{:?}", b.when);
+    let mut warnings = Vec::new();
+
+    // the first binding whose condition holds wins, so a later one with the
+    // same condition (or after one with none) never fires
+    for (j, later) in bindings.iter().enumerate() {
+        let shadow = bindings[..j].iter().position(|b| {
+            let always = b.when.is_none() || when(b) == when(later);
+            b.button == later.button && always
+        });
+        if let Some(i) = shadow {
+            warnings.push((
+                j,
+                format!(
+                    "{:?}: never used, {} always matches first",
+                    later.button,
+                    at(i)
+                ),
+            ));
+        }
+    }
+
+    // buttons something else takes before the bindings see them
+    let mut taken = Vec::new();
+    if let Some(button) = config.enable_button {
+        taken.push((button, "the enable button".to_string()));
+    }
+    for m in &profile.macros {
+        for button in [m.record, m.play].into_iter().flatten() {
+            taken.push((button, format!("a button of macro '{}'", m.name)));
+        }
+    }
+    for (i, binding) in bindings.iter().enumerate() {
+        if let Some((_, what)) = taken.iter().find(|(b, _)| *b == binding.button) {
+            warnings.push((
+                i,
+                format!("{:?}: never used, it's {}", binding.button, what),
+            ));
+        }
+    }
+
+    // the same key or mouse button from two buttons under the same condition.
+    // releasing either lets go of it while the other is still held
+    let sends = |b: &BindingConfig| match (&b.key, &b.mouse) {
+        (Some(key), _) => parse_chord(key).map(|keys| format!("{:?}", keys)),
+        (None, Some(mouse)) => parse_mouse_button(mouse).map(|m| format!("{:?}", m)),
+        _ => None,
+    };
+    for (j, later) in bindings.iter().enumerate() {
+        let Some(target) = sends(later) else {
+            continue;
+        };
+        let same = bindings[..j].iter().position(|b| {
+            let together = b.button != later.button && when(b) == when(later);
+            together && sends(b).as_ref() == Some(&target)
+        });
+        if let Some(i) = same {
+            warnings.push((
+                j,
+                format!(
+                    "{:?}: sends the same as {} ({:?})",
+                    later.button,
+                    at(i),
+                    bindings[i].button
+                ),
+            ));
+        }
+    }
+
+    // layers and toggles conditions wait for that nothing switches on
+    let layers: HashSet<&str> = bindings
+        .iter()
+        .flat_map(|b| {
+            [
+                b.layer.as_deref(),
+                b.activate.as_ref().map(|t| t.layer.as_str()),
+            ]
+        })
+        .flatten()
+        .collect();
+    let toggles: HashSet<&str> = bindings
+        .iter()
+        .filter_map(|b| b.toggle.as_deref())
+        .collect();
+    for (i, binding) in bindings.iter().enumerate() {
+        let Some(condition) = &binding.when else {
+            continue;
+        };
+        let mut needed = Vec::new();
+        needs(condition, &mut needed);
+        for need in needed {
+            match need {
+                Condition::Layer(layer) if !layers.contains(layer.as_str()) => warnings.push((
+                    i,
+                    format!(
+                        "{:?}: layer '{}' is never switched on",
+                        binding.button, layer
+                    ),
+                )),
+                Condition::Toggle(toggle) if !toggles.contains(toggle.as_str()) => warnings.push((
+                    i,
+                    format!(
+                        "{:?}: toggle '{}' is never switched on",
+                        binding.button, toggle
+                    ),
+                )),
+                _ => (),
+            }
+        }
+    }
+
+    // a toggle only its own "off" state can flip stays on once it's on,
+    // and a layer binding that needs its own layer is never pressed
+    for toggle in &toggles {
+        let flips: Vec<_> = (0..bindings.len())
+            .filter(|&i| bindings[i].toggle.as_deref() == Some(*toggle))
+            .collect();
+        let stuck = flips.iter().all(|&i| {
+            let mut needed = Vec::new();
+            if let Some(condition) = &bindings[i].when {
+                needs(condition, &mut needed);
+            }
+            needed.iter().any(|c| match c {
+                Condition::Not(inner) => {
+                    matches!(&**inner, Condition::Toggle(t) if t.as_str() == *toggle)
+                }
+                _ => false,
+            })
+        });
+        if stuck {
+            warnings.push((
+                flips[0],
+                format!("toggle '{}' can be switched on but never off", toggle),
+            ));
+        }
+    }
+    for (i, binding) in bindings.iter().enumerate() {
+        let (Some(layer), Some(condition)) = (&binding.layer, &binding.when) else {
+            continue;
+        };
+        let mut needed = Vec::new();
+        needs(condition, &mut needed);
+        if needed
+            .iter()
+            .any(|c| matches!(c, Condition::Layer(l) if l == layer))
+        {
+            warnings.push((
+                i,
+                format!(
+                    "{:?}: only works inside layer '{}', which it switches on",
+                    binding.button, layer
+                ),
+            ));
+        }
+    }
+
+    warnings.sort_by_key(|(i, _)| *i);
+    warnings
+}
+
+// what must hold for a condition to be true: itself, or every part of an
+// `all`. `any` and `not` of anything else can be satisfied more than one way
+fn needs<'a>(condition: &'a Condition, needed: &mut Vec<&'a Condition>) {
+    match condition {
+        Condition::All(parts) => parts.iter().for_each(|c| needs(c, needed)),
+        other => needed.push(other),
+    }
+}
+
+// buttons the connected controllers report that the profile leaves to the
+// built-in mapping
+fn coverage(name: &str, profile: &Profile) {
+    let Ok(gilrs) = Gilrs::new() else {
+        return;
+    };
+    let bound: HashSet<Button> = profile.bindings.iter().map(|b| b.button).collect();
+    for (_, gamepad) in gilrs.gamepads() {
+        let unbound: Vec<String> = BUTTONS
+            .iter()
+            .filter(|b| gamepad.button_code(**b).is_some() && !bound.contains(b))
+            .map(|b| format!("{:?}", b))
+            .collect();
+        if !unbound.is_empty() {
+            println!(
+                "note: {} has buttons profile '{}' doesn't bind (the built-in mapping applies): {}",
+                gamepad.name(),
+                name,
+                unbound.join(", ")
+            );
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::config::Config;
+use crate::preset;
+use gilrs::Gilrs;
+
+// `controller2keys list`: what there is to map and what it can be mapped with
+pub fn run() -> Result<(), String> {
+    let gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+    println!("controllers:");
+    let mut any = false;
+    for (id, gamepad) in gilrs.gamepads() {
+        println!("  {}: {} ({:?})", id, gamepad.name(), gamepad.power_info());
+        any = true;
+    }
+    if !any {
+        println!("  none connected");
+    }
+
+    let config = Config::load_or_default();
+    println!("profiles:");
+    for name in config.profiles.keys() {
+        let default = if *name == config.profile {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("  {}{}", name, default);
+    }
+    if config.profiles.is_empty() {
+        println!("  none, the built-in mapping is used");
+    }
+
+    println!("presets:");
+    for name in preset::names() {
+        println!("  {}", name);
+    }
+    Ok(())
+}
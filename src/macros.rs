@@ -0,0 +1,252 @@
+use crate::action::{parse_mouse_button, Action, Output};
+use crate::config;
+use crate::wheel::Tilt;
+use enigo::MouseButton;
+use gilrs::Button;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// a recordable mouse macro. pressing `record` starts capturing the mouse
+// movement and clicks the controller produces, pressing it again saves them
+// to <config dir>/macros/<name>.macro, and `play` replays them with the
+// original timing
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MacroConfig {
+    pub name: String,
+    pub record: Option<Button>,
+    pub play: Option<Button>,
+    // steps played no closer together than this, pushing the rest back
+    #[serde(default)]
+    pub min_event_gap_ms: u64,
+}
+
+type Steps = Vec<(Duration, Action)>;
+
+fn macro_path(name: &str) -> PathBuf {
+    config::config_dir()
+        .join("macros")
+        .join(format!("{}.macro", name))
+}
+
+fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        _ => "left",
+    }
+}
+
+// one step per line: "<ms since start> <action> <args...>"
+fn format_steps(steps: &Steps) -> String {
+    let mut text = String::new();
+    for (at, action) in steps {
+        let step = match *action {
+            Action::MouseMove(x, y) => format!("move {} {}", x, y),
+            Action::MouseDown(b) => format!("down {}", button_name(b)),
+            Action::MouseUp(b) => format!("up {}", button_name(b)),
+            Action::MouseClick(b) => format!("click {}", button_name(b)),
+            Action::Scroll(n) => format!("scroll {}", n),
+            Action::SmoothScroll(x, y) => format!("smooth {} {}", x, y),
+            Action::Tilt(Tilt::Left) => "tilt left".to_string(),
+            Action::Tilt(Tilt::Right) => "tilt right".to_string(),
+            _ => continue, // only mouse actions are recorded
+        };
+        text.push_str(&format!("{} {}\n", at.as_millis(), step));
+    }
+    text
+}
+
+fn parse_steps(text: &str) -> Result<Steps, String> {
+    let mut steps = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let bad = || format!("line {}: can't parse '{}'", i + 1, line);
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+        let at = parts[0].parse::<u64>().map_err(|_| bad())?;
+        let int = |i: usize| {
+            parts
+                .get(i)
+                .and_then(|s| s.parse::<i32>().ok())
+                .ok_or_else(bad)
+        };
+        let mouse = || {
+            parts
+                .get(2)
+                .and_then(|s| parse_mouse_button(s))
+                .ok_or_else(bad)
+        };
+        let action = match parts.get(1).copied() {
+            Some("move") => Action::MouseMove(int(2)?, int(3)?),
+            Some("down") => Action::MouseDown(mouse()?),
+            Some("up") => Action::MouseUp(mouse()?),
+            Some("click") => Action::MouseClick(mouse()?),
+            Some("scroll") => Action::Scroll(int(2)?),
+            Some("smooth") => Action::SmoothScroll(int(2)?, int(3)?),
+            Some("tilt") => match parts.get(2).copied() {
+                Some("left") => Action::Tilt(Tilt::Left),
+                Some("right") => Action::Tilt(Tilt::Right),
+                _ => return Err(bad()),
+            },
+            _ => return Err(bad()),
+        };
+        steps.push((Duration::from_millis(at), action));
+    }
+    Ok(steps)
+}
+
+struct Slot {
+    config: MacroConfig,
+    steps: Steps,
+}
+
+struct Playback {
+    slot: usize,
+    start: Instant,
+    next: usize,
+    // when the last step went out, and how far the rest have been pushed
+    // back to keep the gap
+    last: Option<Instant>,
+    delay: Duration,
+}
+
+pub struct Macros {
+    slots: Vec<Slot>,
+    recording: Option<usize>,
+    playing: Option<Playback>,
+}
+
+impl Macros {
+    pub fn new(configs: &[MacroConfig]) -> Macros {
+        let slots = configs
+            .iter()
+            .map(|config| {
+                let path = macro_path(&config.name);
+                let steps = match fs::read_to_string(&path) {
+                    Ok(text) => parse_steps(&text).unwrap_or_else(|e| {
+                        println!("failed to load macro {}: {}", path.display(), e);
+                        Vec::new()
+                    }),
+                    Err(_) => Vec::new(), // not recorded yet
+                };
+                Slot {
+                    config: config.clone(),
+                    steps,
+                }
+            })
+            .collect();
+        Macros {
+            slots,
+            recording: None,
+            playing: None,
+        }
+    }
+
+    // record and play buttons are taken away from the mapping. returns
+    // whether the button was one of them
+    pub fn button(&mut self, button: Button, pressed: bool, output: &mut Output) -> bool {
+        let recorder = self
+            .slots
+            .iter()
+            .position(|s| s.config.record == Some(button));
+        let player = self
+            .slots
+            .iter()
+            .position(|s| s.config.play == Some(button));
+        if !pressed {
+            return recorder.is_some() || player.is_some();
+        }
+
+        if let Some(i) = recorder {
+            match self.recording.take() {
+                Some(recording) => self.save(recording, output.stop_recording()),
+                None => {
+                    println!("recording macro '{}'", self.slots[i].config.name);
+                    output.start_recording();
+                    self.recording = Some(i);
+                }
+            }
+            return true;
+        }
+
+        if let Some(i) = player {
+            self.start(i);
+            return true;
+        }
+        false
+    }
+
+    // play a macro by name, e.g. from the action board. false if the
+    // profile has no such macro
+    pub fn play(&mut self, name: &str) -> bool {
+        match self.slots.iter().position(|s| s.config.name == name) {
+            Some(i) => {
+                self.start(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn start(&mut self, slot: usize) {
+        if self.recording.is_none() {
+            println!("playing macro '{}'", self.slots[slot].config.name);
+            self.playing = Some(Playback {
+                slot,
+                start: Instant::now(),
+                next: 0,
+                last: None,
+                delay: Duration::ZERO,
+            });
+        }
+    }
+
+    fn save(&mut self, slot: usize, steps: Steps) {
+        let slot = &mut self.slots[slot];
+        let path = macro_path(&slot.config.name);
+        let result = fs::create_dir_all(path.parent().unwrap())
+            .and_then(|_| fs::write(&path, format_steps(&steps)));
+        match result {
+            Ok(()) => println!("saved macro {} ({} steps)", path.display(), steps.len()),
+            Err(e) => println!("failed to save macro {}: {}", path.display(), e),
+        }
+        slot.steps = steps;
+    }
+
+    // steps of the playing macro that are due
+    pub fn tick(&mut self) -> Vec<Action> {
+        let playback = match &mut self.playing {
+            Some(playback) => playback,
+            None => return vec![],
+        };
+        let slot = &self.slots[playback.slot];
+        let gap = Duration::from_millis(slot.config.min_event_gap_ms);
+        let elapsed = playback.start.elapsed();
+        let mut actions = Vec::new();
+        while let Some((at, action)) = slot.steps.get(playback.next) {
+            if *at + playback.delay > elapsed {
+                break;
+            }
+            if let Some(last) = playback.last.filter(|_| !gap.is_zero()) {
+                let wait = gap.saturating_sub(last.elapsed());
+                if !wait.is_zero() {
+                    playback.delay += wait;
+                    break;
+                }
+            }
+            actions.push(action.clone());
+            playback.last = Some(Instant::now());
+            playback.next += 1;
+        }
+        let steps = &slot.steps;
+        if playback.next >= steps.len() {
+            self.playing = None;
+        }
+        actions
+    }
+}
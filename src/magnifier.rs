@@ -0,0 +1,173 @@
+use crate::action::Action;
+use crate::bindings::chord_click;
+use crate::input::InputState;
+use crate::stick::Stick;
+use enigo::Key;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+use crate::window::{desktop, Desktop};
+
+// the os screen magnifier from a binding, e.g. `magnifier = "toggle"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MagnifierOp {
+    Toggle,
+    ZoomIn,
+    ZoomOut,
+}
+
+// whether we turned the magnifier on, for toggling where on and off are
+// separate shortcuts. zooming in turns it on too
+static ON: AtomicBool = AtomicBool::new(false);
+
+pub fn actions(op: MagnifierOp) -> Vec<Action> {
+    let on = match op {
+        MagnifierOp::Toggle => !ON.load(Ordering::Relaxed),
+        MagnifierOp::ZoomIn => true,
+        MagnifierOp::ZoomOut => ON.load(Ordering::Relaxed),
+    };
+    let keys = shortcut(op, ON.swap(on, Ordering::Relaxed));
+    chord_click(&keys)
+}
+
+// win+plus opens magnifier and zooms, win+esc closes it
+#[cfg(target_os = "windows")]
+fn shortcut(op: MagnifierOp, on: bool) -> Vec<Key> {
+    match op {
+        MagnifierOp::Toggle if on => vec![Key::Meta, Key::Escape],
+        MagnifierOp::Toggle | MagnifierOp::ZoomIn => vec![Key::Meta, Key::Layout('=')],
+        MagnifierOp::ZoomOut => vec![Key::Meta, Key::Layout('-')],
+    }
+}
+
+// accessibility zoom, once "use keyboard shortcuts to zoom" is ticked
+#[cfg(target_os = "macos")]
+fn shortcut(op: MagnifierOp, _on: bool) -> Vec<Key> {
+    let key = match op {
+        MagnifierOp::Toggle => '8',
+        MagnifierOp::ZoomIn => '=',
+        MagnifierOp::ZoomOut => '-',
+    };
+    vec![Key::Alt, Key::Meta, Key::Layout(key)]
+}
+
+// gnome's zoom, or kwin's zoom effect which has no toggle (meta+0 goes back
+// to 1x)
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn shortcut(op: MagnifierOp, on: bool) -> Vec<Key> {
+    match (op, desktop()) {
+        (MagnifierOp::Toggle, Desktop::Kde) if on => vec![Key::Meta, Key::Layout('0')],
+        (MagnifierOp::Toggle | MagnifierOp::ZoomIn, Desktop::Kde) => {
+            vec![Key::Meta, Key::Layout('=')]
+        }
+        (MagnifierOp::ZoomOut, Desktop::Kde) => vec![Key::Meta, Key::Layout('-')],
+        (MagnifierOp::Toggle, _) => vec![Key::Meta, Key::Alt, Key::Layout('8')],
+        (MagnifierOp::ZoomIn, _) => vec![Key::Meta, Key::Alt, Key::Layout('=')],
+        (MagnifierOp::ZoomOut, _) => vec![Key::Meta, Key::Alt, Key::Layout('-')],
+    }
+}
+
+// a stick panning the magnified view, e.g.
+// `right_stick = { pan = { speed = 400 } }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickPan {
+    // pixels a second with the stick all the way out
+    pub speed: f32,
+    pub deadzone: f32,
+}
+
+impl Default for StickPan {
+    fn default() -> Self {
+        StickPan {
+            speed: 600.0,
+            deadzone: 0.2,
+        }
+    }
+}
+
+// windows' magnifier pans about this far per ctrl+alt+arrow
+#[cfg(target_os = "windows")]
+const PIXELS_PER_STEP: f32 = 50.0;
+// a long gap between ticks isn't panned through in one go
+const MAX_TICK: Duration = Duration::from_millis(50);
+
+// windows pans with ctrl+alt+arrows, repeated faster the further the stick
+// is pushed. macos and linux zoom follows the pointer, so there it moves
+// the pointer instead, at a pace suited to reading
+pub struct Panner {
+    config: StickPan,
+    last: Option<Instant>,
+    // panned but not yet sent
+    partial: (f32, f32),
+}
+
+impl Panner {
+    pub fn new(config: &StickPan) -> Panner {
+        Panner {
+            config: config.clone(),
+            last: None,
+            partial: (0.0, 0.0),
+        }
+    }
+
+    // panning since the last tick, called once per loop
+    pub fn tick(&mut self, input: &InputState, stick: Stick) -> Vec<Action> {
+        let now = Instant::now();
+        let elapsed = self
+            .last
+            .replace(now)
+            .map_or(Duration::ZERO, |last| (now - last).min(MAX_TICK));
+        let deadzone = self.config.deadzone.clamp(0.0, 0.99);
+        let rate = |value: f32| {
+            let out = ((value.abs() - deadzone) / (1.0 - deadzone)).max(0.0);
+            value.signum() * out * self.config.speed * elapsed.as_secs_f32()
+        };
+        let (x_axis, y_axis) = stick.axes();
+        let (x, y) = (input.axis(x_axis), input.axis(y_axis));
+        if x.hypot(y) <= deadzone {
+            // nothing left over to jump with when it's pushed again
+            self.partial = (0.0, 0.0);
+            return vec![];
+        }
+        self.partial.0 += rate(x);
+        // stick up is screen up
+        self.partial.1 -= rate(y);
+        self.take()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn take(&mut self) -> Vec<Action> {
+        let steps = (
+            (self.partial.0 / PIXELS_PER_STEP).trunc(),
+            (self.partial.1 / PIXELS_PER_STEP).trunc(),
+        );
+        self.partial.0 -= steps.0 * PIXELS_PER_STEP;
+        self.partial.1 -= steps.1 * PIXELS_PER_STEP;
+        let mut actions = Vec::new();
+        for (steps, negative, positive) in [
+            (steps.0, Key::LeftArrow, Key::RightArrow),
+            (steps.1, Key::UpArrow, Key::DownArrow),
+        ] {
+            let arrow = if steps < 0.0 { negative } else { positive };
+            for _ in 0..steps.abs() as usize {
+                actions.extend(chord_click(&[Key::Control, Key::Alt, arrow]));
+            }
+        }
+        actions
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn take(&mut self) -> Vec<Action> {
+        let (x, y) = (self.partial.0.trunc(), self.partial.1.trunc());
+        self.partial.0 -= x;
+        self.partial.1 -= y;
+        if (x, y) == (0.0, 0.0) {
+            return vec![];
+        }
+        vec![Action::MouseMove(x as i32, y as i32)]
+    }
+}
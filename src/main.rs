@@ -1,11 +1,23 @@
-use enigo::{Enigo, KeyboardControllable, MouseControllable};
-use gilrs::{Button, Event, EventType, Gilrs};
+use action::Action;
+use clap::{CommandFactory, Parser};
+use cli::{Command, PresetCommand};
+#[cfg(feature = "gui")]
+use controller2keys::gui;
+#[cfg(feature = "tui")]
+use controller2keys::tui;
+#[cfg(feature = "web")]
+use controller2keys::web;
+use controller2keys::{
+    action, board, calibrate, check, cli, config, ctl, devices, doctor, error, eventlog, expr,
+    focus, grab, haptics, hid, highlight, hooks, hud, init, input, killswitch, lint, list, merge,
+    mode, obs, plugin, pointer, preset, priority, privilege, profile, reverse, session, simulate,
+    socd, sound, speech, split, steam, stick, transition, typing, udev, watchdog,
+};
+use gilrs::{Button, Event, EventType};
 use lazy_static::lazy_static;
-use std::collections::HashMap;
-use std::{thread, time::Duration};
-
-#[cfg(target_os = "macos")]
-use libc;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::{Duration, Instant};
 
 lazy_static! {
     static ref BUTTON_MAP: HashMap<Button, enigo::Key> = {
@@ -42,37 +54,215 @@ lazy_static! {
 }
 
 fn main() {
+    let cli = cli::Cli::parse();
+    let result = match cli.command {
+        None => {
+            run(cli.run, None);
+            Ok(())
+        }
+        Some(Command::Run(args)) => {
+            run(args, None);
+            Ok(())
+        }
+        Some(Command::Replay { file, run: args }) => session::Player::load(&file).map(|player| {
+            println!("replaying {}", file.display());
+            run(args, Some(player))
+        }),
+        Some(Command::List) => list::run(),
+        Some(Command::Check) => std::process::exit(if check::run() { 0 } else { 1 }),
+        Some(Command::Lint { profile }) => {
+            std::process::exit(if lint::run(profile.as_deref()) { 0 } else { 1 })
+        }
+        Some(Command::Init { preset, force }) => init::init(preset.as_deref(), force),
+        Some(Command::Import { file, force }) => init::import(&file, force),
+        Some(Command::Monitor) => session::monitor(),
+        Some(Command::Calibrate) => calibrate::run(),
+        Some(Command::Record { file }) => session::record(&file),
+        Some(Command::Simulate {
+            config,
+            input,
+            profile,
+            fast,
+        }) => simulate::run(config.as_deref(), &input, profile.as_deref(), fast),
+        Some(Command::Ctl { command }) => ctl::send(&command).map(|reply| println!("{}", reply)),
+        Some(Command::Preset {
+            command: PresetCommand::List,
+        }) => {
+            for name in preset::names() {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        Some(Command::Preset {
+            command: PresetCommand::Export { name },
+        }) => preset::source(&name).map(|text| print!("{}", text)),
+        Some(Command::Doctor) => std::process::exit(if doctor::run() { 0 } else { 1 }),
+        Some(Command::SetupUdev) => udev::run(),
+        Some(Command::Reverse) => {
+            let config = config::Config::load_or_default();
+            reverse::run(&config.reverse.unwrap_or_default())
+        }
+        Some(Command::Completions { shell }) => {
+            let mut command = cli::Cli::command();
+            clap_complete::generate(
+                shell,
+                &mut command,
+                "controller2keys",
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+        #[cfg(feature = "gui")]
+        Some(Command::Gui) => gui::run(),
+        #[cfg(feature = "web")]
+        Some(Command::Web { port }) => web::run(port),
+        #[cfg(feature = "tui")]
+        Some(Command::Tui) => tui::run(),
+    };
+    if let Err(e) = result {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+// the mapping itself. runs until interrupted, or until a replayed session
+// runs out
+fn run(args: cli::RunArgs, mut replay: Option<session::Player>) {
     // set high priority for this process
-    #[cfg(target_os = "linux")]
-    unsafe {
-        // Set Linux real-time priority
-        let pid = libc::getpid();
-        let mut param: libc::sched_param = std::mem::zeroed();
-        param.sched_priority = libc::sched_get_priority_max(libc::SCHED_RR);
-        libc::sched_setscheduler(pid, libc::SCHED_RR, &param);
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        use windows::Win32::System::Threading::{
-            GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_TIME_CRITICAL,
-        };
-        unsafe {
-            SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_TIME_CRITICAL);
+    if !args.no_rt {
+        priority::raise();
+    }
+    if let Some(core) = args.cpu_affinity {
+        priority::pin(core);
+    }
+
+    let mut gilrs = error::init_gilrs().unwrap_or_else(|e| e.exit());
+    if let Err(e) = error::check_access() {
+        e.exit();
+    }
+    let mut output = action::Output::new().unwrap_or_else(|e| e.exit());
+    // playstation controllers, for lightbar and adaptive trigger output
+    let mut pad = hid::Pad::open();
+
+    privilege::use_invoking_home();
+    let mut config = config::Config::load_or_default();
+    if let Some(name) = &args.preset {
+        if let Err(e) = preset::apply(&mut config, name) {
+            println!("{}", e);
+            std::process::exit(1);
         }
+        println!("using preset '{}'", name);
     }
+    // the virtual gamepad and hi-res wheel are uinput devices, which may
+    // need root to make
+    output.open_devices(config.uses_pad(), config.uses_wheel());
+    // everything privileged is done, stop being root if we were
+    privilege::drop_root().unwrap_or_else(|e| e.exit());
 
-    #[cfg(target_os = "macos")]
-    unsafe {
-        let thread_id = libc::pthread_self();
-        let policy = libc::SCHED_RR;
-        let mut param: libc::sched_param = std::mem::zeroed();
-        param.sched_priority = libc::sched_get_priority_max(policy);
-        libc::pthread_setschedparam(thread_id, policy, &param);
+    // load any native plugins for custom actions
+    let mut plugins = plugin::load_plugins(&plugin::plugins_dir());
+    // a replay's readings weren't made by the controllers here
+    let mut calibration = match replay {
+        None => calibrate::Calibration::load(&gilrs),
+        Some(_) => calibrate::Calibration::default(),
+    };
+    if config.auto_calibrate && replay.is_none() {
+        calibration.sample(&mut gilrs);
     }
+    if let Some(obs) = &config.obs {
+        output.set_obs(obs::Obs::start(obs));
+    }
+    output.set_event_gap(Duration::from_millis(config.min_event_gap_ms));
+    if let Some(socd) = &config.socd {
+        output.set_socd(socd::Socd::new(socd));
+    }
+    if let Some(pointer) = &config.pointer {
+        match pointer::Pointer::new(pointer) {
+            Ok(pointer) => output.set_pointer(pointer),
+            Err(e) => println!("pointer output unavailable, using the mouse: {}", e),
+        }
+    }
+    let mut event_log = args.json_events.as_ref().map(|path| {
+        output.keep_executed();
+        eventlog::EventLog::create(path).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        })
+    });
+
+    // per-tick settings, possibly driven by expressions in the profile
+    let evaluator = expr::Evaluator::new();
+    let mut profile = profile::ActiveProfile::load(
+        &config.profile,
+        &config.get_profile(&config.profile),
+        &evaluator,
+    );
+    let mut split = load_split(&config, &config.profile, &evaluator);
+    transition::run(
+        &config.get_profile(&config.profile).on_activate,
+        "on_activate",
+        &config.profile,
+        &mut output,
+    );
+    let mut input = input::InputState::default();
+    // what each controller holds, for letting go when one disconnects
+    let mut pad_states = input::PadStates::default();
+    let mut cues = sound::Cues::new(&config.sounds);
+    let mut announcer = config.speech.as_ref().map(speech::Announcer::new);
+    let mut hud = config.hud.as_ref().map(hud::Hud::new);
+    // speed tier changes are confirmed with a buzz, since they're made
+    // without looking
+    let mut buzz = haptics::Buzz::default();
+    let mut speed = None;
+    let mut cursor_ring = config.cursor_ring.as_ref().map(highlight::CursorRing::new);
+    let mut watchdog = config.watchdog.as_ref().map(watchdog::Watchdog::new);
+    let mut kill_switch = killswitch::KillSwitch::new(&config.kill_switch);
+    let mut typing_guard = config.typing_guard.as_ref().and_then(|guard| {
+        typing::TypingGuard::start(guard)
+            .map_err(|e| println!("typing guard unavailable: {}", e))
+            .ok()
+    });
+
+    // switch profiles based on the focused app, where the platform supports it
+    let mut focus_watcher = focus::watcher();
+    let mut focus = None;
+    let mut paused = false;
+    let mut grabs = grab::Grabs::default();
+    // a replay's controllers aren't connected, so there's nothing to merge
+    // or match device rules against
+    let mut merger = match replay {
+        Some(_) => None,
+        None => config.merge.as_ref().map(merge::Merger::new),
+    };
+    // our own virtual gamepad, which shows up as a controller
+    let mut own_pads = HashSet::new();
+    // replays too, since bands and hats go by them
+    let axis_names = devices::AxisNames::new(&config.devices);
+    let mut devices = match replay {
+        Some(_) => None,
+        None => Some(devices::Devices::new(&config.devices, axis_names.clone())),
+    };
+    // replays too, since it goes by axis names
+    let remap = devices::Remap::new(&config.remap);
+
+    // `controller2keys ctl` pauses and pins profiles on top of the automatic
+    // behavior
+    let mut ctl = ctl::Server::start();
+    let mut user_paused = false;
+    let mut outside_apps = false;
+    let mut pinned: Option<String> = None;
+
+    // desktop/game mode detection
+    let mut mode_detector = config.auto_mode.as_ref().map(mode::ModeDetector::start);
+    let mut mode = None;
 
-    let mut gilrs = Gilrs::new().expect("failed to initialize gilrs");
-    let mut enigo = Enigo::new();
+    // the running steam game, only looked for when a profile is keyed to one
+    let mut steam_watcher = config
+        .profiles
+        .values()
+        .any(|p| !p.steam_apps.is_empty())
+        .then(steam::SteamWatcher::start);
+    let mut steam_app = None;
 
     // reduce sleep time even further for more frequent polling
     let poll_rate = Duration::from_millis(4); // ~250Hz polling
@@ -81,120 +271,747 @@ fn main() {
 
     // track active gamepad
     let mut active_gamepad = None;
+    let started = Instant::now();
+    let mut last_event = Instant::now();
 
     loop {
         if active_gamepad.is_none() {
             active_gamepad = gilrs.gamepads().next().map(|(id, _)| id);
+            if let Some(timeout) = config.controller_timeout {
+                let waiting = active_gamepad.is_none() && replay.is_none();
+                if waiting && started.elapsed().as_secs() >= timeout {
+                    error::Error::NoController(timeout).exit();
+                }
+            }
+        }
+
+        let refocused = match focus_watcher.as_mut().and_then(|w| w.poll()) {
+            Some(f) => {
+                focus = Some(f);
+                true
+            }
+            None => false,
+        };
+        let app = focus.as_ref().map(|f| f.app.clone()).unwrap_or_default();
+
+        // desktop vs game mode, when enabled
+        let mut mode_switched = false;
+        if let (Some(auto), Some(detector)) = (&config.auto_mode, mode_detector.as_mut()) {
+            if let Some(m) = detector.poll(auto, focus.as_ref()) {
+                println!("switching to {:?} mode", m);
+                mode = Some(m);
+                mode_switched = true;
+            }
+        }
+
+        let mut steam_switched = false;
+        if let Some(app) = steam_watcher.as_mut().and_then(|w| w.poll()) {
+            match app {
+                Some(app) => println!("steam game {} is running", app),
+                None => println!("steam game closed"),
+            }
+            steam_app = app;
+            steam_switched = true;
+        }
+
+        let mut reselect = false;
+        if let Some((command, mut connection)) = ctl.as_mut().and_then(|s| s.poll()) {
+            match command {
+                cli::CtlCommand::Status => connection.reply(&format!(
+                    "profile '{}', {}",
+                    profile.name,
+                    if paused { "paused" } else { "running" }
+                )),
+                cli::CtlCommand::Pause => {
+                    user_paused = true;
+                    connection.reply("paused");
+                }
+                cli::CtlCommand::Resume => {
+                    user_paused = false;
+                    connection.reply(if outside_apps {
+                        "resumed, but paused outside apps with a profile"
+                    } else {
+                        "resumed"
+                    });
+                }
+                cli::CtlCommand::Profile { name: Some(name) }
+                    if !config.profiles.contains_key(&name) && name != config.profile =>
+                {
+                    connection.reply(&format!("no profile named '{}'", name));
+                }
+                cli::CtlCommand::Profile { name } => {
+                    connection.reply(&match &name {
+                        Some(name) => format!("switched to '{}'", name),
+                        None => "back to automatic profile selection".to_string(),
+                    });
+                    pinned = name;
+                    reselect = true;
+                }
+                cli::CtlCommand::Release => {
+                    output.release_all();
+                    connection.reply("released");
+                }
+                cli::CtlCommand::State => {
+                    let mut profiles = vec![config.profile.clone()];
+                    profiles.extend(
+                        config
+                            .profiles
+                            .keys()
+                            .filter(|name| **name != config.profile)
+                            .cloned(),
+                    );
+                    let (_, mouse_speed) = profile.settings(&evaluator, &input);
+                    let state = serde_json::json!({
+                        "profile": profile.name,
+                        "pinned": pinned,
+                        "paused": paused,
+                        "profiles": profiles,
+                        "sensitivity": mouse_speed,
+                    });
+                    connection.reply(&state.to_string());
+                }
+                cli::CtlCommand::Sensitivity { value } => {
+                    profile.sensitivity = expr::Value::fixed(value as f64);
+                    connection.reply(&format!("sensitivity {} until the profile changes", value));
+                }
+            }
         }
 
+        if refocused || mode_switched || reselect || steam_switched {
+            // a pinned profile wins, then an app-specific profile, then the
+            // running steam game's, then the mode's profile, then the default
+            let matched = focus
+                .as_ref()
+                .and_then(|f| config.match_profile(f))
+                .or_else(|| config.match_steam_app(steam_app?));
+            let name = pinned
+                .as_deref()
+                .or(matched)
+                .or_else(|| Some(config.auto_mode.as_ref()?.profile(mode?)))
+                .unwrap_or(&config.profile);
+            if name != profile.name {
+                println!("{} focused -> switching to profile '{}'", app, name);
+                transition::run(
+                    &config.get_profile(&profile.name).on_deactivate,
+                    "on_deactivate",
+                    &profile.name,
+                    &mut output,
+                );
+                // the old profile's bindings can't release what they pressed
+                output.release_all();
+                // an unfinished macro recording belongs to the old profile
+                output.stop_recording();
+                let vars = profile.mapper.take_vars();
+                profile = profile::ActiveProfile::load(name, &config.get_profile(name), &evaluator);
+                profile.mapper.set_vars(vars);
+                let split_vars = split.as_mut().map(|s| s.profile.mapper.take_vars());
+                split = load_split(&config, name, &evaluator);
+                if let (Some(split), Some(vars)) = (split.as_mut(), split_vars) {
+                    split.profile.mapper.set_vars(vars);
+                }
+                transition::run(
+                    &config.get_profile(name).on_activate,
+                    "on_activate",
+                    name,
+                    &mut output,
+                );
+                cues.reset();
+                cues.play("profile_switched");
+                if let Some(announcer) = announcer.as_mut() {
+                    announcer.profile(name);
+                }
+                hooks::run(
+                    &config.hooks,
+                    "profile_switched",
+                    &[("C2K_PROFILE", name.to_string()), ("C2K_APP", app.clone())],
+                );
+            }
+
+            // only inject while an app we have a profile for is focused
+            outside_apps = config.pause_outside_apps && matched.is_none();
+        }
+
+        if kill_switch.as_mut().is_some_and(|k| k.update(&input)) {
+            output.release_all();
+            user_paused = !user_paused;
+            println!(
+                "kill switch -> {}",
+                if user_paused {
+                    "released everything, hold again to resume"
+                } else {
+                    "resuming"
+                }
+            );
+        }
+
+        if let Some(up) = typing_guard.as_mut().and_then(|g| g.poll()) {
+            output.set_typing(up);
+            println!(
+                "keyboard {}",
+                if up {
+                    "in use -> holding controller keys back"
+                } else {
+                    "idle -> controller keys back on"
+                }
+            );
+        }
+
+        // with an enable button, only inject while it's held
+        let disabled = config.enable_button.is_some_and(|b| !input.is_held(b));
+        // a profile tied to one window holds off while anything else has it.
+        // without focus tracking there's nothing to go by
+        let unfocused = match (&profile.only_when_focused, &focus) {
+            (Some(target), Some(focus)) => !focus.is(target),
+            (Some(_), None) => focus_watcher.is_some(),
+            (None, _) => false,
+        };
+        let pause = user_paused || outside_apps || disabled || unfocused;
+        if pause != paused {
+            paused = pause;
+            output.set_paused(paused);
+            cues.play(if paused { "paused" } else { "resumed" });
+            println!("mapping {}", if paused { "paused" } else { "resumed" });
+            hooks::run(
+                &config.hooks,
+                if paused { "paused" } else { "resumed" },
+                &[("C2K_PROFILE", profile.name.clone()), ("C2K_APP", app)],
+            );
+        }
+
+        // handle analog inputs with a smaller deadzone for better responsiveness
+        let settings = profile.settings(&evaluator, &input);
+        let split_settings = split
+            .as_ref()
+            .map(|split| split.profile.settings(&evaluator, &input));
+
+        if config.grab {
+            grabs.update(&mut gilrs, !paused);
+        }
+
+        // what the events and the tick produce is sent together, in
+        // priority order, at the flush below
+        output.set_batching(true);
+
         // handle events
-        while let Some(Event { id, event, time: _ }) = gilrs.next_event() {
-            match event {
-                EventType::ButtonPressed(button, _) => {
-                    match button {
-                        Button::LeftTrigger => {
-                            // scroll left/down (negative)
-                            enigo.mouse_scroll_y(-1);
-                            println!("left shoulder pressed -> simulating scroll down");
-                        }
-                        Button::RightTrigger => {
-                            // scroll right/up (positive)
-                            enigo.mouse_scroll_y(1);
-                            println!("right shoulder pressed -> simulating scroll up");
-                        }
-                        _ => {
-                            if let Some(&key) = BUTTON_MAP.get(&button) {
-                                enigo.key_down(key);
-                                println!(
-                                    "button {:?} pressed -> simulating key down {:?}",
-                                    button, key
-                                );
-                            } else if let Some(&mouse_button) = MOUSE_BUTTON_MAP.get(&button) {
-                                enigo.mouse_down(mouse_button);
-                                println!(
-                                    "button {:?} pressed -> simulating mouse down {:?}",
-                                    button, mouse_button
-                                );
-                            }
+        while let Some(Event { id, event, time }) =
+            gilrs.next_event().or_else(|| replay.as_mut()?.next())
+        {
+            // the virtual gamepad's events are our own output
+            if event == EventType::Connected && output.claim_pad() {
+                println!("ignoring the virtual gamepad");
+                own_pads.insert(id);
+            }
+            if own_pads.contains(&id) {
+                continue;
+            }
+            // per-controller fixes first, so everything after sees the
+            // inputs under the names they were given
+            let event = match devices.as_mut() {
+                Some(devices) => match devices.filter(&gilrs, id, event) {
+                    Some(event) => event,
+                    None => continue,
+                },
+                None => event,
+            };
+            let event = remap.apply(calibration.apply(id, event));
+            // several controllers as one, when merging
+            let event = match merger.as_mut() {
+                Some(merger) => match merger.filter(&mut gilrs, id, event) {
+                    Some(event) => event,
+                    None => continue,
+                },
+                None => event,
+            };
+            input.update(&event);
+            pad_states.update(id, &event);
+            last_event = Instant::now();
+
+            // plugins see every raw event before (and regardless of) the built-in mapping
+            for p in plugins.iter_mut() {
+                match event {
+                    EventType::ButtonPressed(button, _) => p.on_button(button, true),
+                    EventType::ButtonReleased(button, _) => p.on_button(button, false),
+                    EventType::AxisChanged(axis, value, _) => p.on_axis(axis, value),
+                    _ => (),
+                }
+            }
+
+            // the enable button does nothing but enable
+            let enabling = match event {
+                EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _) => {
+                    config.enable_button == Some(button)
+                }
+                _ => false,
+            };
+            // a split controller's other half has a profile of its own
+            match (split.as_mut(), split_settings) {
+                _ if enabling => (),
+                (Some(split), Some(split_settings)) if split.takes(&event) => handle(
+                    &mut split.profile,
+                    event,
+                    &input,
+                    &mut output,
+                    split_settings,
+                    &axis_names,
+                ),
+                _ => handle(
+                    &mut profile,
+                    event,
+                    &input,
+                    &mut output,
+                    settings,
+                    &axis_names,
+                ),
+            }
+            if let Some(log) = event_log.as_mut() {
+                log.event(id, &event, time, &output.take_executed());
+            }
+
+            if let EventType::Connected | EventType::Disconnected = event {
+                let name = gilrs.gamepad(id).name().to_string();
+                let hook = if event == EventType::Connected {
+                    println!("controller connected: {}", name);
+                    calibration.connect(&gilrs, id);
+                    if pad.is_none() {
+                        pad = hid::Pad::open();
+                    }
+                    "connected"
+                } else {
+                    println!("controller disconnected: {}", name);
+                    // its releases will never come, so what it held would
+                    // stay down. they're made up from its own state and go
+                    // through the mapping like real ones, so only its
+                    // bindings let go and what other controllers hold stays
+                    // down. the merger does this itself for the controllers
+                    // it merges
+                    if merger.is_none() {
+                        for event in pad_states.release_events(gilrs.gamepad(id)) {
+                            gilrs.insert_event(Event::new(id, event));
                         }
                     }
+                    "disconnected"
+                };
+                hooks::run(
+                    &config.hooks,
+                    hook,
+                    &[
+                        ("C2K_CONTROLLER", name),
+                        ("C2K_PROFILE", profile.name.clone()),
+                    ],
+                );
+            }
+        }
+
+        if let Some(idle) = config.idle_release {
+            if output.holding() && last_event.elapsed().as_secs() >= idle {
+                println!(
+                    "no controller input for {}s -> releasing {}",
+                    idle,
+                    output.held().join(", ")
+                );
+                output.release_all();
+            }
+        }
+        if let Some(watchdog) = watchdog.as_mut() {
+            watchdog.check(&input, &mut output);
+        }
+
+        tick(&mut profile, &input, &mut output);
+        if let Some(split) = split.as_mut() {
+            tick(&mut split.profile, &input, &mut output);
+        }
+        output.tick_pointer(&input);
+        output.set_batching(false);
+        output.flush();
+        output.set_interpolation(profile.mouse_interpolation);
+        output.send_interpolated(poll_rate);
+
+        let commands = output.take_plugin_commands();
+        for command in &commands {
+            if !plugins.iter().any(|p| p.name() == command.name) {
+                println!("no plugin named '{}'", command.name);
+            }
+        }
+        for p in plugins.iter_mut() {
+            for command in &commands {
+                if command.name == p.name() {
+                    p.on_command(&command.command);
                 }
-                EventType::ButtonReleased(button, _) => {
+            }
+            p.on_tick();
+            for action in p.take_actions() {
+                output.execute(&action);
+                println!("plugin {} -> {:?}", p.name(), action);
+            }
+        }
+        if let Some(log) = event_log.as_mut() {
+            log.tick(&output.take_executed());
+        }
+
+        cues.watch(profile.mapper.state());
+
+        let battery = active_gamepad.and_then(|id| match gilrs.gamepad(id).power_info() {
+            gilrs::PowerInfo::Discharging(level) => Some(level),
+            _ => None,
+        });
+        let speed_now = profile.mapper.state().speed_label();
+        if speed_now != speed {
+            println!("mouse speed: {}", speed_now.as_deref().unwrap_or("base"));
+            buzz.play(&mut gilrs, active_gamepad, 60);
+            speed = speed_now;
+        }
+        if let Some(announcer) = announcer.as_mut() {
+            announcer.watch(profile.mapper.state(), battery);
+        }
+        if let Some(hud) = hud.as_mut() {
+            hud.update(
+                &profile.name,
+                profile.mapper.state().layer(),
+                profile.mapper.state().speed_label(),
+                profile.mapper.state().vars(),
+                output.held(),
+            );
+        }
+
+        let motion = output.take_motion();
+        if let Some(ring) = cursor_ring.as_mut() {
+            ring.update(motion.0);
+        }
+        if let Some(haptics) = profile.haptics.as_mut() {
+            haptics.tick(&mut gilrs, active_gamepad, motion);
+        }
+
+        // lightbar and adaptive triggers on playstation controllers
+        if let Some(p) = pad.as_mut() {
+            let (left, right) = profile.adaptive_triggers;
+            let mut result = p.set_triggers(left, right);
+            if let Some(lightbar) = profile.lightbar.as_ref() {
+                if let Some(color) = lightbar.color(profile.mapper.state().layer(), battery) {
+                    result = result.and_then(|_| p.set_lightbar(color));
+                }
+            }
+            if let Err(e) = result {
+                // unplugged; reopened on the next connect
+                println!("controller output failed: {}", e);
+                pad = None;
+            }
+        }
+
+        if replay.as_ref().is_some_and(|r| r.finished()) {
+            output.release_all();
+            println!("replay finished");
+            return;
+        }
+
+        // prevent CPU from maxing out but keep responsive
+        thread::sleep(poll_rate);
+    }
+}
+
+// the other half of profile `name`'s controller, if it's split
+fn load_split(
+    config: &config::Config,
+    name: &str,
+    evaluator: &expr::Evaluator,
+) -> Option<split::Split> {
+    let split = config.get_profile(name).split?;
+    Some(split::Split::load(&split, config, evaluator))
+}
+
+// one event through a profile's mapping. with a split controller each half
+// has its own profile
+fn handle(
+    profile: &mut profile::ActiveProfile,
+    event: EventType,
+    input: &input::InputState,
+    output: &mut action::Output,
+    (deadzone, mouse_speed): (f32, f32),
+    axis_names: &devices::AxisNames,
+) {
+    for script in &profile.scripts {
+        let result = match event {
+            EventType::ButtonPressed(button, _) => script.on_button(button, true),
+            EventType::ButtonReleased(button, _) => script.on_button(button, false),
+            EventType::AxisChanged(axis, value, _) => script.on_axis(axis, value),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            println!("script {} error: {}", script.name(), e);
+        }
+    }
+
+    // trigger thresholds apply to the mapping, not to what plugins and scripts see
+    let event = match profile.triggers.as_mut() {
+        Some(triggers) => match triggers.filter(event) {
+            Some(event) => event,
+            None => return,
+        },
+        None => event,
+    };
+
+    // trigger zoom owns the triggers' presses; it reads their pull each tick
+    if let EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _) = event {
+        if profile.zoom.as_ref().is_some_and(|z| z.handles(button)) {
+            return;
+        }
+        // and so does pen or touch output, for pressure
+        if output.pointer_takes(button) {
+            return;
+        }
+    }
+
+    // the action board takes presses while it's open
+    if let (Some(board), EventType::ButtonPressed(button, _)) = (profile.board.as_mut(), event) {
+        if board.press(button, input) {
+            match board.take_pick() {
+                Some(board::Pick::Actions(actions)) => {
+                    for action in actions {
+                        output.execute(&action);
+                    }
+                }
+                Some(board::Pick::Exec(command)) => {
+                    if let Err(e) = hooks::spawn(&command, &[]) {
+                        println!("failed to run '{}': {}", command, e);
+                    }
+                }
+                Some(board::Pick::Macro(name)) if !profile.macros.play(&name) => {
+                    println!("no macro named '{}' in this profile", name);
+                }
+                _ => (),
+            }
+            return;
+        }
+    }
+
+    // macro record/play buttons
+    let consumed = match event {
+        EventType::ButtonPressed(button, _) => profile.macros.button(button, true, output),
+        EventType::ButtonReleased(button, _) => profile.macros.button(button, false, output),
+        _ => false,
+    };
+    if consumed {
+        return;
+    }
+
+    // stick clicks muting a volume stick
+    let clicked = match event {
+        EventType::ButtonPressed(button, _) => profile.stick_click(button, true),
+        EventType::ButtonReleased(button, _) => profile.stick_click(button, false),
+        _ => None,
+    };
+    if let Some(actions) = clicked {
+        for action in actions {
+            output.execute(&action);
+            println!("stick click -> {:?}", action);
+        }
+        return;
+    }
+
+    // combos watch presses without taking them from the mapping
+    if let EventType::ButtonPressed(button, _) = event {
+        for action in profile.combos.press(button) {
+            output.execute(&action);
+        }
+    }
+
+    // flicks and the outer ring fire alongside the stick's usual mapping
+    let moved = match event {
+        EventType::AxisChanged(gilrs::Axis::LeftStickX | gilrs::Axis::LeftStickY, _, _) => {
+            Some(stick::Stick::Left)
+        }
+        EventType::AxisChanged(gilrs::Axis::RightStickX | gilrs::Axis::RightStickY, _, _) => {
+            Some(stick::Stick::Right)
+        }
+        _ => None,
+    };
+    if let Some(stick) = moved {
+        if let Some(flicks) = profile.flicks.as_mut() {
+            for action in flicks.update(stick, input) {
+                output.execute(&action);
+            }
+        }
+        for action in profile.rings.update(stick, input) {
+            output.execute(&action);
+            println!("{:?} stick outer ring -> {:?}", stick, action);
+        }
+    }
+
+    // bands on throttles, rudders and other axes, and pov hats, alongside
+    // anything else the axis does
+    if let EventType::AxisChanged(axis, value, code) = event {
+        let code = code.into_u32();
+        for action in profile.bands.update(axis, value, code, axis_names) {
+            output.execute(&action);
+            println!("{:?} axis band -> {:?}", axis, action);
+        }
+        for action in profile.hats.update(axis, value, code, axis_names) {
+            output.execute(&action);
+            println!("{:?} hat -> {:?}", axis, action);
+        }
+    }
+
+    match event {
+        EventType::ButtonPressed(button, _) if profile.mapper.handles(button) => {
+            for action in profile.mapper.press(button, input) {
+                output.execute(&action);
+                println!("button {:?} pressed -> {:?}", button, action);
+            }
+        }
+        EventType::ButtonReleased(button, _) if profile.mapper.handles(button) => {
+            for action in profile.mapper.release(button) {
+                output.execute(&action);
+                println!("button {:?} released -> {:?}", button, action);
+            }
+        }
+        EventType::ButtonPressed(button, _) => {
+            match button {
+                Button::LeftTrigger | Button::RightTrigger => {
+                    // left scrolls up (negative), right scrolls down (positive)
+                    let direction = if button == Button::LeftTrigger { -1 } else { 1 };
+                    let clicks = match profile.scroll.as_mut() {
+                        Some(accel) => accel.tap(direction),
+                        None => direction,
+                    };
+                    output.execute(&Action::Scroll(clicks));
+                    println!(
+                        "shoulder {:?} pressed -> simulating scroll {}",
+                        button, clicks
+                    );
+                }
+                _ => {
                     if let Some(&key) = BUTTON_MAP.get(&button) {
-                        enigo.key_up(key);
+                        output.execute(&Action::KeyDown(profile.key(key)));
                         println!(
-                            "button {:?} released -> simulating key up {:?}",
+                            "button {:?} pressed -> simulating key down {:?}",
                             button, key
                         );
                     } else if let Some(&mouse_button) = MOUSE_BUTTON_MAP.get(&button) {
-                        enigo.mouse_up(mouse_button);
+                        output.execute(&Action::MouseDown(mouse_button));
                         println!(
-                            "button {:?} released -> simulating mouse up {:?}",
+                            "button {:?} pressed -> simulating mouse down {:?}",
                             button, mouse_button
                         );
                     }
                 }
-                EventType::AxisChanged(axis, value, _) => {
-                    // handle analog inputs with a smaller deadzone for better responsiveness
-                    let deadzone = 0.15; // reduced deadzone for more sensitive input
-                    match axis {
-                        gilrs::Axis::LeftStickX => {
-                            if value.abs() > deadzone {
-                                if value > 0.0 {
-                                    enigo.key_down(enigo::Key::Layout('d'));
-                                    enigo.key_up(enigo::Key::Layout('a')); // ensure opposite key is released
-                                } else {
-                                    enigo.key_down(enigo::Key::Layout('a'));
-                                    enigo.key_up(enigo::Key::Layout('d')); // ensure opposite key is released
-                                }
-                            } else {
-                                // in deadzone - release both keys
-                                enigo.key_up(enigo::Key::Layout('d'));
-                                enigo.key_up(enigo::Key::Layout('a'));
-                            }
+            }
+        }
+        EventType::ButtonReleased(button, _) => {
+            if let Some(&key) = BUTTON_MAP.get(&button) {
+                output.execute(&Action::KeyUp(profile.key(key)));
+                println!(
+                    "button {:?} released -> simulating key up {:?}",
+                    button, key
+                );
+            } else if let Some(&mouse_button) = MOUSE_BUTTON_MAP.get(&button) {
+                output.execute(&Action::MouseUp(mouse_button));
+                println!(
+                    "button {:?} released -> simulating mouse up {:?}",
+                    button, mouse_button
+                );
+            }
+        }
+        EventType::AxisChanged(axis, value, _) => {
+            match axis {
+                gilrs::Axis::RightStickX | gilrs::Axis::RightStickY
+                    if profile.dial.as_ref().is_some_and(|d| d.active(input)) =>
+                {
+                    if let Some(dial) = profile.dial.as_mut() {
+                        for action in dial.update(input) {
+                            output.execute(&action);
+                            println!("right stick dial -> {:?}", action);
                         }
-                        gilrs::Axis::LeftStickY => {
-                            if value.abs() > deadzone {
-                                if value > 0.0 {
-                                    enigo.key_down(enigo::Key::Layout('w'));
-                                    enigo.key_up(enigo::Key::Layout('s')); // ensure opposite key is released
-                                } else {
-                                    enigo.key_down(enigo::Key::Layout('s'));
-                                    enigo.key_up(enigo::Key::Layout('w')); // ensure opposite key is released
-                                }
-                            } else {
-                                // in deadzone - release both keys
-                                enigo.key_up(enigo::Key::Layout('w'));
-                                enigo.key_up(enigo::Key::Layout('s'));
+                    }
+                }
+                gilrs::Axis::LeftStickX
+                | gilrs::Axis::LeftStickY
+                | gilrs::Axis::RightStickX
+                | gilrs::Axis::RightStickY => {
+                    let stick = match axis {
+                        gilrs::Axis::LeftStickX | gilrs::Axis::LeftStickY => stick::Stick::Left,
+                        _ => stick::Stick::Right,
+                    };
+                    let horizontal =
+                        matches!(axis, gilrs::Axis::LeftStickX | gilrs::Axis::RightStickX);
+                    // smoothing only applies to mouse motion
+                    let mouse = matches!(profile.stick(stick), stick::StickOutput::Mouse);
+                    let value = match profile.smoothing.as_mut() {
+                        Some(smoother) if mouse => smoother.filter(axis, value),
+                        _ => value,
+                    };
+                    match profile.stick_mut(stick) {
+                        stick::StickOutput::Held(keys) => {
+                            for action in keys.update(horizontal, value, deadzone) {
+                                output.execute(&action);
                             }
                         }
-                        gilrs::Axis::RightStickX => {
-                            if value.abs() > deadzone {
-                                // increase sensitivity and use linear response for more direct control
-                                let mouse_speed = 50.0; // significantly increased sensitivity
-                                let movement = (value * mouse_speed) as i32;
-                                enigo.mouse_move_relative(movement, 0);
-                                println!("Right X: {} -> Mouse X: {}", value, movement);
-                                // debug output
+                        stick::StickOutput::Analog(analog) => {
+                            let pad = output.has_pad();
+                            for action in analog.update(horizontal, value, deadzone, pad) {
+                                output.execute(&action);
                             }
                         }
-                        gilrs::Axis::RightStickY => {
-                            if value.abs() > deadzone {
-                                let mouse_speed = 50.0; // significantly increased sensitivity
-                                let movement = (-value * mouse_speed) as i32;
-                                enigo.mouse_move_relative(0, movement);
-                                println!("Right Y: {} -> Mouse Y: {}", value, movement);
-                                // debug output
-                            }
+                        stick::StickOutput::Mouse if value.abs() > deadzone => {
+                            // linear response for more direct control
+                            let movement = (value * mouse_speed) as i32;
+                            let (dx, dy) = if horizontal {
+                                (movement, 0)
+                            } else {
+                                (0, -movement)
+                            };
+                            output.execute(&Action::MouseMove(dx, dy));
+                            println!("{:?}: {} -> Mouse: {}, {}", axis, value, dx, dy);
+                            // debug output
                         }
+                        // keys mode repeats from the tick instead
                         _ => (),
                     }
                 }
-                _ => (), // ignore other events
+                _ => (),
             }
         }
+        _ => (),
+    }
+}
 
-        // prevent CPU from maxing out but keep responsive
-        thread::sleep(poll_rate);
+// the per-tick part of a profile's mapping: repeats, timers, scripts
+fn tick(
+    profile: &mut profile::ActiveProfile,
+    input: &input::InputState,
+    output: &mut action::Output,
+) {
+    for action in profile.tick_sticks(input) {
+        output.execute(&action);
+    }
+
+    for action in profile.bands.tick() {
+        output.execute(&action);
+    }
+
+    if let Some(board) = profile.board.as_mut() {
+        board.tick();
+    }
+
+    if let Some(zoom) = profile.zoom.as_mut() {
+        for action in zoom.tick(input) {
+            output.execute(&action);
+        }
+    }
+
+    for action in profile.macros.tick() {
+        output.execute(&action);
+    }
+
+    for action in profile.mapper.tick() {
+        output.execute(&action);
+    }
+
+    for script in &profile.scripts {
+        if let Err(e) = script.on_tick() {
+            println!("script {} error: {}", script.name(), e);
+        }
+        for action in script.take_actions() {
+            output.execute(&action);
+            println!("script {} -> {:?}", script.name(), action);
+        }
     }
 }
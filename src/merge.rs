@@ -0,0 +1,218 @@
+use gilrs::{Axis, Button, Event, EventType, GamepadId, Gilrs};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+// several controllers acting as one, e.g. a pair of joy-cons or an
+// accessibility rig with buttons spread over devices. from `[merge]`:
+// `controllers = [{ name = "Joy-Con (L)" }, { name = "Joy-Con (R)" }]`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergeConfig {
+    // empty merges every controller
+    pub controllers: Vec<MergeSource>,
+    // how the controllers' sticks and triggers add up
+    pub combine: Combine,
+}
+
+// `furthest` follows whichever controller pushes furthest from rest.
+// `sum` adds them up, clamped to the full range, like the xbox's copilot:
+// a carer can nudge the player's stick instead of taking it over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Combine {
+    #[default]
+    Furthest,
+    Sum,
+}
+
+impl Combine {
+    // axes run from -1 to 1 and analog buttons from 0 to 1
+    fn apply(self, values: &HashMap<GamepadId, f32>) -> f32 {
+        match self {
+            Combine::Furthest => {
+                values
+                    .values()
+                    .copied()
+                    .fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a })
+            }
+            Combine::Sum => values.values().sum::<f32>().clamp(-1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeSource {
+    // part of the controller's name, case-insensitive
+    pub name: String,
+    // only take these from this controller, when set
+    pub buttons: Option<Vec<Button>>,
+    pub axes: Option<Vec<Axis>>,
+}
+
+// a button is held while any controller holds it and an axis reads
+// whichever controller pushes it furthest (or all of them added up), so
+// one idle pad can't cancel the other
+#[derive(Default)]
+pub struct Merger {
+    sources: Vec<MergeSource>,
+    combine: Combine,
+    // which source each controller matched, None for ignored controllers
+    matched: HashMap<GamepadId, Option<MergeSource>>,
+    held: HashMap<Button, HashSet<GamepadId>>,
+    values: HashMap<Button, HashMap<GamepadId, f32>>,
+    axes: HashMap<Axis, HashMap<GamepadId, f32>>,
+    // releases, trigger values and axis values queued for a disconnected
+    // controller, let through as is
+    releasing: HashSet<Button>,
+    easing: HashSet<Button>,
+    settling: HashSet<Axis>,
+}
+
+impl Merger {
+    pub fn new(config: &MergeConfig) -> Merger {
+        Merger {
+            sources: config.controllers.clone(),
+            combine: config.combine,
+            ..Merger::default()
+        }
+    }
+
+    // the merged event, or None when it changes nothing
+    pub fn filter(
+        &mut self,
+        gilrs: &mut Gilrs,
+        id: GamepadId,
+        event: EventType,
+    ) -> Option<EventType> {
+        match event {
+            EventType::Connected => return Some(event),
+            EventType::Disconnected => {
+                self.disconnect(gilrs, id);
+                self.matched.remove(&id);
+                return Some(event);
+            }
+            EventType::ButtonReleased(button, _) if self.releasing.remove(&button) => {
+                return Some(event)
+            }
+            EventType::ButtonChanged(button, _, _) if self.easing.remove(&button) => {
+                return Some(event)
+            }
+            EventType::AxisChanged(axis, _, _) if self.settling.remove(&axis) => {
+                return Some(event)
+            }
+            _ => (),
+        }
+
+        let source = self.source(gilrs, id)?;
+        let wanted = |b: Button| source.buttons.as_ref().is_none_or(|list| list.contains(&b));
+        match event {
+            EventType::ButtonPressed(button, _) if wanted(button) => {
+                let holders = self.held.entry(button).or_default();
+                let first = holders.is_empty();
+                holders.insert(id);
+                first.then_some(event)
+            }
+            EventType::ButtonReleased(button, _) if wanted(button) => {
+                let holders = self.held.entry(button).or_default();
+                let held = holders.remove(&id);
+                (held && holders.is_empty()).then_some(event)
+            }
+            EventType::ButtonChanged(button, value, code) if wanted(button) => {
+                let merged = merged(
+                    self.combine,
+                    self.values.entry(button).or_default(),
+                    id,
+                    value,
+                )?;
+                Some(EventType::ButtonChanged(button, merged, code))
+            }
+            EventType::AxisChanged(axis, value, code)
+                if source.axes.as_ref().is_none_or(|list| list.contains(&axis)) =>
+            {
+                let merged = merged(self.combine, self.axes.entry(axis).or_default(), id, value)?;
+                Some(EventType::AxisChanged(axis, merged, code))
+            }
+            _ => None,
+        }
+    }
+
+    fn source(&mut self, gilrs: &Gilrs, id: GamepadId) -> Option<MergeSource> {
+        let sources = &self.sources;
+        self.matched
+            .entry(id)
+            .or_insert_with(|| {
+                let gamepad = gilrs.gamepad(id);
+                let name = gamepad.name();
+                if sources.is_empty() {
+                    return Some(MergeSource {
+                        name: name.to_string(),
+                        buttons: None,
+                        axes: None,
+                    });
+                }
+                let source = sources
+                    .iter()
+                    .find(|s| name.to_lowercase().contains(&s.name.to_lowercase()));
+                match source {
+                    Some(_) => println!("merging {}", name),
+                    None => println!("ignoring {}, not in [merge]", name),
+                }
+                source.cloned()
+            })
+            .clone()
+    }
+
+    // drops everything the controller contributed, queueing releases for
+    // buttons only it was holding and new values for triggers and axes it
+    // moved
+    fn disconnect(&mut self, gilrs: &mut Gilrs, id: GamepadId) {
+        for (button, holders) in self.held.iter_mut() {
+            if holders.remove(&id) && holders.is_empty() {
+                if let Some(code) = gilrs.gamepad(id).button_code(*button) {
+                    self.releasing.insert(*button);
+                    gilrs.insert_event(Event::new(id, EventType::ButtonReleased(*button, code)));
+                }
+            }
+        }
+        for (axis, values) in self.axes.iter_mut() {
+            let before = self.combine.apply(values);
+            values.remove(&id);
+            let after = self.combine.apply(values);
+            if before != after {
+                if let Some(code) = gilrs.gamepad(id).axis_code(*axis) {
+                    self.settling.insert(*axis);
+                    gilrs.insert_event(Event::new(id, EventType::AxisChanged(*axis, after, code)));
+                }
+            }
+        }
+        for (button, values) in self.values.iter_mut() {
+            let before = self.combine.apply(values);
+            values.remove(&id);
+            let after = self.combine.apply(values);
+            if before != after {
+                if let Some(code) = gilrs.gamepad(id).button_code(*button) {
+                    self.easing.insert(*button);
+                    gilrs.insert_event(Event::new(
+                        id,
+                        EventType::ButtonChanged(*button, after, code),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// records `value` for `id` and returns the combined value across
+// controllers, or None when that didn't change
+fn merged(
+    combine: Combine,
+    values: &mut HashMap<GamepadId, f32>,
+    id: GamepadId,
+    value: f32,
+) -> Option<f32> {
+    let before = combine.apply(values);
+    values.insert(id, value);
+    let after = combine.apply(values);
+    (before != after).then_some(after)
+}
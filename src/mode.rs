@@ -0,0 +1,118 @@
+use crate::focus::Focus;
+use crate::process;
+use serde::Deserialize;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+// scanning every process is too slow for every tick, so it runs on its own
+// thread this often
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+// picks between a desktop and a game profile when no app-specific profile
+// matches. enabled by having an `[auto_mode]` table in the config
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AutoMode {
+    pub desktop_profile: String,
+    pub game_profile: String,
+    // a running process with one of these names means a game is being played
+    pub game_processes: Vec<String>,
+    // never treated as games, even fullscreen (video players, browsers...)
+    pub desktop_processes: Vec<String>,
+    // treat any fullscreen app that isn't a desktop process as a game
+    pub fullscreen_is_game: bool,
+}
+
+impl Default for AutoMode {
+    fn default() -> Self {
+        AutoMode {
+            desktop_profile: "desktop".to_string(),
+            game_profile: "game".to_string(),
+            game_processes: Vec::new(),
+            desktop_processes: Vec::new(),
+            fullscreen_is_game: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Desktop,
+    Game,
+}
+
+impl AutoMode {
+    pub fn profile(&self, mode: Mode) -> &str {
+        match mode {
+            Mode::Desktop => &self.desktop_profile,
+            Mode::Game => &self.game_profile,
+        }
+    }
+}
+
+fn listed(names: &[String], name: &str) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+pub struct ModeDetector {
+    // whether a game process is running, when there are any to look for
+    rx: Option<Receiver<bool>>,
+    game_running: bool,
+    mode: Option<Mode>,
+}
+
+impl ModeDetector {
+    pub fn start(auto: &AutoMode) -> ModeDetector {
+        let rx = (!auto.game_processes.is_empty()).then(|| {
+            let games = auto.game_processes.clone();
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let mut last = None;
+                loop {
+                    let running = process::names().iter().any(|p| listed(&games, p));
+                    if last != Some(running) {
+                        last = Some(running);
+                        if tx.send(running).is_err() {
+                            return; // detector dropped
+                        }
+                    }
+                    thread::sleep(SCAN_INTERVAL);
+                }
+            });
+            rx
+        });
+        ModeDetector {
+            rx,
+            game_running: false,
+            mode: None,
+        }
+    }
+
+    // the new mode if it changed since the last call
+    pub fn poll(&mut self, auto: &AutoMode, focus: Option<&Focus>) -> Option<Mode> {
+        if let Some(running) = self.rx.as_ref().and_then(|rx| rx.try_iter().last()) {
+            self.game_running = running;
+        }
+
+        let fullscreen_game = auto.fullscreen_is_game
+            && focus
+                .map(|f| f.fullscreen && !listed(&auto.desktop_processes, &f.app))
+                .unwrap_or(false);
+        let focused_desktop = focus
+            .map(|f| listed(&auto.desktop_processes, &f.app))
+            .unwrap_or(false);
+
+        let mode = if (self.game_running && !focused_desktop) || fullscreen_game {
+            Mode::Game
+        } else {
+            Mode::Desktop
+        };
+
+        if self.mode == Some(mode) {
+            return None;
+        }
+        self.mode = Some(mode);
+        Some(mode)
+    }
+}
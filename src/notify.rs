@@ -0,0 +1,59 @@
+use std::process::Command;
+use std::thread;
+
+// a desktop notification through the platform's own tool, shown from a
+// thread of its own since the tools can take a moment to start
+pub fn show(text: &str) {
+    let mut command = command(text);
+    thread::spawn(move || match command.output() {
+        Ok(output) if output.status.success() => (),
+        Ok(output) => println!(
+            "notification failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => println!("notification failed: {}", e),
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn command(text: &str) -> Command {
+    // applescript strings escape quotes and backslashes
+    let text = text.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut command = Command::new("osascript");
+    command.args([
+        "-e",
+        &format!(
+            "display notification \"{}\" with title \"controller2keys\"",
+            text
+        ),
+    ]);
+    command
+}
+
+// a toast through the winrt api, sent as powershell's own app id since ours
+// isn't registered with the start menu
+#[cfg(target_os = "windows")]
+fn command(text: &str) -> Command {
+    const SCRIPT: &str = "
+        [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+        $toast = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+        $lines = $toast.GetElementsByTagName('text')
+        $lines.Item(0).AppendChild($toast.CreateTextNode('controller2keys')) > $null
+        $lines.Item(1).AppendChild($toast.CreateTextNode($env:C2K_NOTIFICATION)) > $null
+        $app = '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe'
+        [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($app).Show([Windows.UI.Notifications.ToastNotification]::new($toast))
+    ";
+    let mut command = Command::new("powershell");
+    // the text goes through the environment so it needs no escaping
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("C2K_NOTIFICATION", text);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn command(text: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name=controller2keys", "controller2keys", text]);
+    command
+}
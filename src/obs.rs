@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// where obs-websocket (built into obs 28+) listens, from the top-level
+// `[obs]` table
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObsConfig {
+    pub host: String,
+    pub port: u16,
+    pub password: Option<String>,
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        ObsConfig {
+            host: "localhost".to_string(),
+            port: 4455,
+            password: None,
+        }
+    }
+}
+
+// what a binding can ask obs to do, e.g. `obs = { scene = "Gameplay" }` or
+// `obs = "toggle_recording"`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObsCommand {
+    Scene(String),
+    // an audio input by name, e.g. "Mic/Aux"
+    ToggleMute(String),
+    StartRecording,
+    StopRecording,
+    ToggleRecording,
+    StartStreaming,
+    StopStreaming,
+    ToggleStreaming,
+}
+
+// requests go to a background thread that owns the websocket, so a slow or
+// missing obs never stalls input handling
+pub struct Obs {
+    tx: Sender<ObsCommand>,
+}
+
+impl Obs {
+    pub fn start(config: &ObsConfig) -> Obs {
+        let (tx, rx) = mpsc::channel();
+        let config = config.clone();
+        thread::spawn(move || run(config, rx));
+        Obs { tx }
+    }
+
+    pub fn send(&self, command: ObsCommand) {
+        let _ = self.tx.send(command);
+    }
+}
+
+#[cfg(feature = "obs")]
+fn run(config: ObsConfig, rx: Receiver<ObsCommand>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            println!("obs unavailable: {}", e);
+            return;
+        }
+    };
+
+    // connect on first use and again after any failure, since obs may be
+    // started (or restarted) after us
+    let mut client = None;
+    for command in rx {
+        runtime.block_on(async {
+            if client.is_none() {
+                match obws::Client::connect(&config.host, config.port, config.password.as_ref())
+                    .await
+                {
+                    Ok(c) => {
+                        println!("connected to obs at {}:{}", config.host, config.port);
+                        client = Some(c);
+                    }
+                    Err(e) => {
+                        println!("failed to connect to obs: {}", e);
+                        return;
+                    }
+                }
+            }
+            let c = client.as_ref().expect("connected above");
+            if let Err(e) = execute(c, &command).await {
+                println!("obs {:?} failed: {}", command, e);
+                client = None;
+            }
+        });
+    }
+}
+
+#[cfg(feature = "obs")]
+async fn execute(client: &obws::Client, command: &ObsCommand) -> Result<(), obws::error::Error> {
+    use obws::requests::inputs::InputId;
+    use obws::requests::scenes::SceneId;
+
+    match command {
+        ObsCommand::Scene(name) => {
+            client
+                .scenes()
+                .set_current_program_scene(SceneId::Name(name))
+                .await
+        }
+        ObsCommand::ToggleMute(name) => client
+            .inputs()
+            .toggle_mute(InputId::Name(name))
+            .await
+            .map(|_| ()),
+        ObsCommand::StartRecording => client.recording().start().await,
+        ObsCommand::StopRecording => client.recording().stop().await.map(|_| ()),
+        ObsCommand::ToggleRecording => client.recording().toggle().await.map(|_| ()),
+        ObsCommand::StartStreaming => client.streaming().start().await,
+        ObsCommand::StopStreaming => client.streaming().stop().await,
+        ObsCommand::ToggleStreaming => client.streaming().toggle().await.map(|_| ()),
+    }
+}
+
+#[cfg(not(feature = "obs"))]
+fn run(_config: ObsConfig, rx: Receiver<ObsCommand>) {
+    println!("obs configured but built without the `obs` feature");
+    for command in rx {
+        println!("ignoring obs {:?}", command);
+    }
+}
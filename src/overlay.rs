@@ -0,0 +1,118 @@
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use serde::Deserialize;
+
+// which screen corner an overlay sits in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    // top-left position of a `width` x `height` box in this corner of a
+    // `screen_width` x `screen_height` screen, `margin` in from the edges
+    pub fn place(self, size: (i32, i32), screen: (i32, i32), margin: i32) -> (i32, i32) {
+        let (width, height) = size;
+        let (screen_width, screen_height) = screen;
+        let left = margin;
+        let right = screen_width - width - margin;
+        let top = margin;
+        let bottom = screen_height - height - margin;
+        match self {
+            Corner::TopLeft => (left, top),
+            Corner::TopRight => (right, top),
+            Corner::BottomLeft => (left, bottom),
+            Corner::BottomRight => (right, bottom),
+        }
+    }
+}
+
+// a small always-on-top, click-through window showing a few lines of text
+pub trait Overlay {
+    // replace what's shown. no lines hides the window
+    fn show(&mut self, lines: &[String]) -> Result<(), String>;
+    // handle window system events (repaints), once per tick. must not block
+    fn tick(&mut self) -> Result<(), String>;
+}
+
+// a ring drawn around the mouse pointer
+pub trait Ring {
+    // move onto the pointer, or hide. called once per tick, must not block
+    fn follow(&mut self, show: bool) -> Result<(), String>;
+}
+
+#[cfg(target_os = "macos")]
+pub fn text(corner: Corner) -> Option<Box<dyn Overlay>> {
+    Some(Box::new(macos::AppKitOverlay::start(corner)))
+}
+
+#[cfg(target_os = "windows")]
+pub fn text(corner: Corner) -> Option<Box<dyn Overlay>> {
+    match windows::LayeredOverlay::start(corner) {
+        Ok(o) => Some(Box::new(o)),
+        Err(e) => {
+            println!("overlay unavailable: {}", e);
+            None
+        }
+    }
+}
+
+// wayland has no way for a plain client to float a window over others, but
+// xwayland override-redirect windows do the job on most compositors
+#[cfg(target_os = "linux")]
+pub fn text(corner: Corner) -> Option<Box<dyn Overlay>> {
+    match x11::X11Overlay::start(corner) {
+        Ok(o) => Some(Box::new(o)),
+        Err(e) => {
+            println!("overlay unavailable: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn text(_corner: Corner) -> Option<Box<dyn Overlay>> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+pub fn ring(radius: u16, thickness: u16, color: [u8; 3]) -> Option<Box<dyn Ring>> {
+    Some(Box::new(macos::AppKitRing::start(radius, thickness, color)))
+}
+
+#[cfg(target_os = "windows")]
+pub fn ring(radius: u16, thickness: u16, color: [u8; 3]) -> Option<Box<dyn Ring>> {
+    match windows::RegionRing::start(radius, thickness, color) {
+        Ok(r) => Some(Box::new(r)),
+        Err(e) => {
+            println!("cursor ring unavailable: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn ring(radius: u16, thickness: u16, color: [u8; 3]) -> Option<Box<dyn Ring>> {
+    match x11::X11Ring::start(radius, thickness, color) {
+        Ok(r) => Some(Box::new(r)),
+        Err(e) => {
+            println!("cursor ring unavailable: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn ring(_radius: u16, _thickness: u16, _color: [u8; 3]) -> Option<Box<dyn Ring>> {
+    None
+}
@@ -0,0 +1,224 @@
+use super::{Corner, Overlay, Ring};
+use objc::runtime::{Object, BOOL, NO, YES};
+use objc::{class, msg_send, sel, sel_impl, Encode, Encoding};
+use std::ffi::CString;
+
+const PADDING: f64 = 6.0;
+const MARGIN: f64 = 16.0;
+const CHAR_WIDTH: f64 = 8.0;
+const LINE_HEIGHT: f64 = 16.0;
+
+// NSStatusWindowLevel, above normal and fullscreen app windows
+const STATUS_LEVEL: i64 = 25;
+// NSWindowCollectionBehaviorCanJoinAllSpaces | FullScreenAuxiliary
+const ALL_SPACES: u64 = 1 | 1 << 8;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct NSRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+unsafe impl Encode for NSRect {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGRect={CGPoint=dd}{CGSize=dd}}") }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq)]
+struct NSPoint {
+    x: f64,
+    y: f64,
+}
+
+unsafe impl Encode for NSPoint {
+    fn encode() -> Encoding {
+        unsafe { Encoding::from_str("{CGPoint=dd}") }
+    }
+}
+
+unsafe fn nsstring(s: &str) -> *mut Object {
+    let s = CString::new(s.replace('\0', "")).unwrap_or_default();
+    msg_send![class!(NSString), stringWithUTF8String: s.as_ptr()]
+}
+
+// a borderless NSWindow holding a label. it ignores the mouse and joins
+// every space, including fullscreen ones. drawing happens when the focus
+// watcher turns the run loop
+pub struct AppKitOverlay {
+    window: *mut Object,
+    label: *mut Object,
+    corner: Corner,
+}
+
+impl AppKitOverlay {
+    pub fn start(corner: Corner) -> Self {
+        unsafe {
+            // an accessory app: no dock icon or menu bar
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: BOOL = msg_send![app, setActivationPolicy: 1i64];
+
+            let rect = NSRect {
+                x: 0.0,
+                y: 0.0,
+                width: 1.0,
+                height: 1.0,
+            };
+            let window: *mut Object = msg_send![class!(NSWindow), alloc];
+            let window: *mut Object = msg_send![window, initWithContentRect: rect
+                                                        styleMask: 0u64
+                                                        backing: 2u64
+                                                        defer: NO];
+            let _: () = msg_send![window, setLevel: STATUS_LEVEL];
+            let _: () = msg_send![window, setOpaque: NO];
+            let _: () = msg_send![window, setIgnoresMouseEvents: YES];
+            let _: () = msg_send![window, setCollectionBehavior: ALL_SPACES];
+            let background: *mut Object =
+                msg_send![class!(NSColor), colorWithCalibratedWhite: 0.0f64 alpha: 0.8f64];
+            let _: () = msg_send![window, setBackgroundColor: background];
+
+            let label: *mut Object = msg_send![class!(NSTextField), alloc];
+            let label: *mut Object = msg_send![label, initWithFrame: rect];
+            let _: () = msg_send![label, setEditable: NO];
+            let _: () = msg_send![label, setBordered: NO];
+            let _: () = msg_send![label, setDrawsBackground: NO];
+            let white: *mut Object = msg_send![class!(NSColor), whiteColor];
+            let _: () = msg_send![label, setTextColor: white];
+            let font: *mut Object = msg_send![class!(NSFont), userFixedPitchFontOfSize: 13.0f64];
+            let _: () = msg_send![label, setFont: font];
+            let _: () = msg_send![window, setContentView: label];
+
+            AppKitOverlay {
+                window,
+                label,
+                corner,
+            }
+        }
+    }
+}
+
+impl Overlay for AppKitOverlay {
+    fn show(&mut self, lines: &[String]) -> Result<(), String> {
+        unsafe {
+            let nil: *mut Object = std::ptr::null_mut();
+            if lines.is_empty() {
+                let _: () = msg_send![self.window, orderOut: nil];
+                return Ok(());
+            }
+            let _: () = msg_send![self.label, setStringValue: nsstring(&lines.join("\n"))];
+
+            let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            let size = (
+                (columns as f64 * CHAR_WIDTH + 2.0 * PADDING) as i32,
+                (lines.len() as f64 * LINE_HEIGHT + 2.0 * PADDING) as i32,
+            );
+            let main: *mut Object = msg_send![class!(NSScreen), mainScreen];
+            if main.is_null() {
+                return Err("no screen".to_string());
+            }
+            let frame: NSRect = msg_send![main, frame];
+            let screen = (frame.width as i32, frame.height as i32);
+            let (x, y) = self.corner.place(size, screen, MARGIN as i32);
+            // appkit's y axis points up
+            let rect = NSRect {
+                x: x as f64,
+                y: frame.height - y as f64 - size.1 as f64,
+                width: size.0 as f64,
+                height: size.1 as f64,
+            };
+            let _: () = msg_send![self.window, setFrame: rect display: YES];
+            let _: () = msg_send![self.window, orderFrontRegardless];
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// a clear borderless window whose layer draws a rounded border, which at a
+// corner radius of half its size is a ring
+pub struct AppKitRing {
+    window: *mut Object,
+    radius: f64,
+    at: Option<NSPoint>,
+}
+
+impl AppKitRing {
+    pub fn start(radius: u16, thickness: u16, color: [u8; 3]) -> Self {
+        let radius = radius as f64;
+        let [r, g, b] = color.map(|c| c as f64 / 255.0);
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: BOOL = msg_send![app, setActivationPolicy: 1i64];
+
+            let rect = NSRect {
+                x: 0.0,
+                y: 0.0,
+                width: radius * 2.0,
+                height: radius * 2.0,
+            };
+            let window: *mut Object = msg_send![class!(NSWindow), alloc];
+            let window: *mut Object = msg_send![window, initWithContentRect: rect
+                                                        styleMask: 0u64
+                                                        backing: 2u64
+                                                        defer: NO];
+            let _: () = msg_send![window, setLevel: STATUS_LEVEL];
+            let _: () = msg_send![window, setOpaque: NO];
+            let _: () = msg_send![window, setHasShadow: NO];
+            let _: () = msg_send![window, setIgnoresMouseEvents: YES];
+            let _: () = msg_send![window, setCollectionBehavior: ALL_SPACES];
+            let clear: *mut Object = msg_send![class!(NSColor), clearColor];
+            let _: () = msg_send![window, setBackgroundColor: clear];
+
+            let view: *mut Object = msg_send![window, contentView];
+            let _: () = msg_send![view, setWantsLayer: YES];
+            let layer: *mut Object = msg_send![view, layer];
+            let color: *mut Object = msg_send![class!(NSColor), colorWithCalibratedRed: r
+                                                               green: g
+                                                               blue: b
+                                                               alpha: 1.0f64];
+            let cg: *mut Object = msg_send![color, CGColor];
+            let _: () = msg_send![layer, setBorderColor: cg];
+            let _: () = msg_send![layer, setBorderWidth: thickness as f64];
+            let _: () = msg_send![layer, setCornerRadius: radius];
+
+            AppKitRing {
+                window,
+                radius,
+                at: None,
+            }
+        }
+    }
+}
+
+impl Ring for AppKitRing {
+    fn follow(&mut self, show: bool) -> Result<(), String> {
+        unsafe {
+            if !show {
+                if self.at.take().is_some() {
+                    let nil: *mut Object = std::ptr::null_mut();
+                    let _: () = msg_send![self.window, orderOut: nil];
+                }
+                return Ok(());
+            }
+            // screen coordinates, y up, same as window frames
+            let at: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+            if self.at != Some(at) {
+                self.at = Some(at);
+                let origin = NSPoint {
+                    x: at.x - self.radius,
+                    y: at.y - self.radius,
+                };
+                let _: () = msg_send![self.window, setFrameOrigin: origin];
+                let _: () = msg_send![self.window, orderFrontRegardless];
+            }
+        }
+        Ok(())
+    }
+}
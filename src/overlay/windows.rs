@@ -0,0 +1,247 @@
+use super::{Corner, Overlay, Ring};
+use std::cell::RefCell;
+use windows::core::w;
+use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, POINT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    BeginPaint, CombineRgn, CreateEllipticRgn, CreateSolidBrush, DeleteObject, EndPaint, FillRect,
+    GetStockObject, InvalidateRect, SetBkMode, SetTextColor, SetWindowRgn, TextOutW, BLACK_BRUSH,
+    HBRUSH, PAINTSTRUCT, RGN_DIFF, TRANSPARENT,
+};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetCursorPos, GetSystemMetrics,
+    PeekMessageW, RegisterClassW, SetLayeredWindowAttributes, SetWindowPos, ShowWindow,
+    HWND_TOPMOST, LWA_ALPHA, MSG, PM_REMOVE, SM_CXSCREEN, SM_CYSCREEN, SWP_NOACTIVATE, SWP_NOSIZE,
+    SWP_SHOWWINDOW, SW_HIDE, WM_PAINT, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE,
+    WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
+};
+
+const PADDING: i32 = 6;
+const MARGIN: i32 = 16;
+// the default gui font, near enough
+const CHAR_WIDTH: i32 = 8;
+const LINE_HEIGHT: i32 = 16;
+
+thread_local! {
+    // what WM_PAINT draws. the window belongs to the main thread
+    static LINES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        if msg != WM_PAINT {
+            return DefWindowProcW(hwnd, msg, wparam, lparam);
+        }
+        let mut paint = PAINTSTRUCT::default();
+        let hdc = BeginPaint(hwnd, &mut paint);
+        FillRect(hdc, &paint.rcPaint, HBRUSH(GetStockObject(BLACK_BRUSH).0));
+        SetBkMode(hdc, TRANSPARENT);
+        SetTextColor(hdc, COLORREF(0x00ff_ffff));
+        LINES.with(|lines| {
+            for (i, line) in lines.borrow().iter().enumerate() {
+                let text: Vec<u16> = line.encode_utf16().collect();
+                TextOutW(hdc, PADDING, PADDING + i as i32 * LINE_HEIGHT, &text);
+            }
+        });
+        EndPaint(hwnd, &paint);
+        LRESULT(0)
+    }
+}
+
+// a topmost layered popup: translucent, click-through (WS_EX_TRANSPARENT)
+// and kept out of the taskbar and alt-tab
+pub struct LayeredOverlay {
+    hwnd: HWND,
+    corner: Corner,
+}
+
+impl LayeredOverlay {
+    pub fn start(corner: Corner) -> Result<Self, String> {
+        unsafe {
+            let instance = GetModuleHandleW(None).map_err(|e| e.to_string())?;
+            let class = w!("C2KOverlay");
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(wndproc),
+                hInstance: instance,
+                lpszClassName: class,
+                ..Default::default()
+            };
+            RegisterClassW(&wc);
+
+            let hwnd = CreateWindowExW(
+                WS_EX_LAYERED
+                    | WS_EX_TOPMOST
+                    | WS_EX_TRANSPARENT
+                    | WS_EX_TOOLWINDOW
+                    | WS_EX_NOACTIVATE,
+                class,
+                w!("controller2keys"),
+                WS_POPUP,
+                0,
+                0,
+                1,
+                1,
+                None,
+                None,
+                instance,
+                None,
+            );
+            if hwnd.0 == 0 {
+                return Err("failed to create overlay window".to_string());
+            }
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), 204, LWA_ALPHA);
+            Ok(LayeredOverlay { hwnd, corner })
+        }
+    }
+}
+
+impl Overlay for LayeredOverlay {
+    fn show(&mut self, lines: &[String]) -> Result<(), String> {
+        LINES.with(|l| *l.borrow_mut() = lines.to_vec());
+        unsafe {
+            if lines.is_empty() {
+                ShowWindow(self.hwnd, SW_HIDE);
+                return Ok(());
+            }
+            let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+            let size = (
+                columns * CHAR_WIDTH + 2 * PADDING,
+                lines.len() as i32 * LINE_HEIGHT + 2 * PADDING,
+            );
+            let screen = (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN));
+            let (x, y) = self.corner.place(size, screen, MARGIN);
+            SetWindowPos(
+                self.hwnd,
+                HWND_TOPMOST,
+                x,
+                y,
+                size.0,
+                size.1,
+                SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+            InvalidateRect(self.hwnd, None, true);
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) -> Result<(), String> {
+        pump(self.hwnd);
+        Ok(())
+    }
+}
+
+// the window's messages, without blocking
+fn pump(hwnd: HWND) {
+    unsafe {
+        let mut msg = MSG::default();
+        while PeekMessageW(&mut msg, hwnd, 0, 0, PM_REMOVE).as_bool() {
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+extern "system" fn ring_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
+
+// a popup filled with the ring color and clipped to a ring with a window
+// region, so the middle is really not there
+pub struct RegionRing {
+    hwnd: HWND,
+    radius: i32,
+    at: Option<(i32, i32)>,
+}
+
+impl RegionRing {
+    pub fn start(radius: u16, thickness: u16, color: [u8; 3]) -> Result<Self, String> {
+        let [r, g, b] = color;
+        let radius = radius as i32;
+        let inner = radius - thickness as i32;
+        unsafe {
+            let instance = GetModuleHandleW(None).map_err(|e| e.to_string())?;
+            let class = w!("C2KRing");
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(ring_proc),
+                hInstance: instance,
+                lpszClassName: class,
+                // colorref is 0x00bbggrr
+                hbrBackground: CreateSolidBrush(COLORREF(u32::from_le_bytes([r, g, b, 0]))),
+                ..Default::default()
+            };
+            RegisterClassW(&wc);
+
+            let hwnd = CreateWindowExW(
+                WS_EX_LAYERED
+                    | WS_EX_TOPMOST
+                    | WS_EX_TRANSPARENT
+                    | WS_EX_TOOLWINDOW
+                    | WS_EX_NOACTIVATE,
+                class,
+                w!("controller2keys cursor"),
+                WS_POPUP,
+                0,
+                0,
+                radius * 2,
+                radius * 2,
+                None,
+                None,
+                instance,
+                None,
+            );
+            if hwnd.0 == 0 {
+                return Err("failed to create cursor ring window".to_string());
+            }
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, LWA_ALPHA);
+
+            let outer = CreateEllipticRgn(0, 0, radius * 2, radius * 2);
+            let hole = CreateEllipticRgn(
+                radius - inner,
+                radius - inner,
+                radius + inner,
+                radius + inner,
+            );
+            CombineRgn(outer, outer, hole, RGN_DIFF);
+            DeleteObject(hole);
+            // the window owns the region from here on
+            SetWindowRgn(hwnd, outer, true);
+
+            Ok(RegionRing {
+                hwnd,
+                radius,
+                at: None,
+            })
+        }
+    }
+}
+
+impl Ring for RegionRing {
+    fn follow(&mut self, show: bool) -> Result<(), String> {
+        pump(self.hwnd);
+        unsafe {
+            if !show {
+                if self.at.take().is_some() {
+                    ShowWindow(self.hwnd, SW_HIDE);
+                }
+                return Ok(());
+            }
+            let mut point = POINT::default();
+            if !GetCursorPos(&mut point).as_bool() {
+                return Ok(());
+            }
+            let at = (point.x, point.y);
+            if self.at != Some(at) {
+                self.at = Some(at);
+                SetWindowPos(
+                    self.hwnd,
+                    HWND_TOPMOST,
+                    at.0 - self.radius,
+                    at.1 - self.radius,
+                    0,
+                    0,
+                    SWP_NOSIZE | SWP_NOACTIVATE | SWP_SHOWWINDOW,
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+use super::{Corner, Overlay, Ring};
+use std::error::Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::shape::{ConnectionExt as _, SK, SO};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClipOrdering, ConfigureWindowAux, ConnectionExt, CreateGCAux, CreateWindowAux,
+    EventMask, Gcontext, PropMode, Rectangle, StackMode, Window, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+const PADDING: i32 = 6;
+const MARGIN: i32 = 16;
+
+// an override-redirect window (so the window manager leaves it alone and it
+// stays above normal windows) drawn with the core "fixed" font
+pub struct X11Overlay {
+    conn: RustConnection,
+    window: Window,
+    gc: Gcontext,
+    corner: Corner,
+    screen: (i32, i32),
+    // "fixed" font metrics
+    char_width: i32,
+    line_height: i32,
+    ascent: i32,
+    lines: Vec<String>,
+}
+
+impl X11Overlay {
+    pub fn start(corner: Corner) -> Result<Self, Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = conn.setup().roots[screen_num].clone();
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new()
+                .background_pixel(screen.black_pixel)
+                .override_redirect(1)
+                .event_mask(EventMask::EXPOSURE),
+        )?;
+
+        // translucent under a compositor, and never in the way of clicks
+        let opacity = conn
+            .intern_atom(false, b"_NET_WM_WINDOW_OPACITY")?
+            .reply()?
+            .atom;
+        conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            opacity,
+            AtomEnum::CARDINAL,
+            &[0xcccc_cccc],
+        )?;
+        conn.shape_rectangles(
+            SO::SET,
+            SK::INPUT,
+            ClipOrdering::UNSORTED,
+            window,
+            0,
+            0,
+            &[],
+        )?;
+
+        let font = conn.generate_id()?;
+        conn.open_font(font, b"fixed")?;
+        let metrics = conn.query_font(font)?.reply()?;
+        let gc = conn.generate_id()?;
+        conn.create_gc(
+            gc,
+            window,
+            &CreateGCAux::new()
+                .foreground(screen.white_pixel)
+                .background(screen.black_pixel)
+                .font(font),
+        )?;
+        conn.flush()?;
+
+        Ok(X11Overlay {
+            conn,
+            window,
+            gc,
+            corner,
+            screen: (
+                screen.width_in_pixels as i32,
+                screen.height_in_pixels as i32,
+            ),
+            char_width: metrics.max_bounds.character_width as i32,
+            line_height: (metrics.font_ascent + metrics.font_descent) as i32,
+            ascent: metrics.font_ascent as i32,
+            lines: Vec::new(),
+        })
+    }
+
+    fn draw(&self) -> Result<(), Box<dyn Error>> {
+        self.conn.clear_area(false, self.window, 0, 0, 0, 0)?;
+        for (i, line) in self.lines.iter().enumerate() {
+            // core fonts are latin-1
+            let text: Vec<u8> = line
+                .chars()
+                .map(|c| if (c as u32) < 256 { c as u8 } else { b'?' })
+                .collect();
+            let y = PADDING + self.ascent + i as i32 * self.line_height;
+            self.conn
+                .image_text8(self.window, self.gc, PADDING as i16, y as i16, &text)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn update(&mut self, lines: &[String]) -> Result<(), Box<dyn Error>> {
+        self.lines = lines.to_vec();
+        if lines.is_empty() {
+            self.conn.unmap_window(self.window)?;
+            self.conn.flush()?;
+            return Ok(());
+        }
+
+        let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+        let size = (
+            columns * self.char_width + 2 * PADDING,
+            lines.len() as i32 * self.line_height + 2 * PADDING,
+        );
+        let (x, y) = self.corner.place(size, self.screen, MARGIN);
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(size.0 as u32)
+                .height(size.1 as u32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        self.conn.map_window(self.window)?;
+        self.draw()
+    }
+}
+
+impl Overlay for X11Overlay {
+    fn show(&mut self, lines: &[String]) -> Result<(), String> {
+        self.update(lines).map_err(|e| e.to_string())
+    }
+
+    fn tick(&mut self) -> Result<(), String> {
+        let mut exposed = false;
+        while let Some(event) = self.conn.poll_for_event().map_err(|e| e.to_string())? {
+            if let Event::Expose(_) = event {
+                exposed = true;
+            }
+        }
+        if exposed {
+            self.draw().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+// a window shaped into a ring with the shape extension, so it needs no
+// compositor for the see-through middle
+pub struct X11Ring {
+    conn: RustConnection,
+    root: Window,
+    window: Window,
+    radius: i16,
+    at: Option<(i16, i16)>,
+}
+
+impl X11Ring {
+    pub fn start(radius: u16, thickness: u16, color: [u8; 3]) -> Result<Self, Box<dyn Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = conn.setup().roots[screen_num].clone();
+        let [r, g, b] = color;
+
+        let window = conn.generate_id()?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            radius * 2,
+            radius * 2,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new()
+                // truecolor visuals take the rgb value as the pixel
+                .background_pixel(u32::from_be_bytes([0, r, g, b]))
+                .override_redirect(1),
+        )?;
+        conn.shape_rectangles(
+            SO::SET,
+            SK::BOUNDING,
+            ClipOrdering::UNSORTED,
+            window,
+            0,
+            0,
+            &annulus(radius as i16, thickness as i16),
+        )?;
+        conn.shape_rectangles(
+            SO::SET,
+            SK::INPUT,
+            ClipOrdering::UNSORTED,
+            window,
+            0,
+            0,
+            &[],
+        )?;
+        conn.flush()?;
+
+        Ok(X11Ring {
+            conn,
+            root: screen.root,
+            window,
+            radius: radius as i16,
+            at: None,
+        })
+    }
+
+    fn update(&mut self, show: bool) -> Result<(), Box<dyn Error>> {
+        if !show {
+            if self.at.take().is_some() {
+                self.conn.unmap_window(self.window)?;
+                self.conn.flush()?;
+            }
+            return Ok(());
+        }
+
+        let pointer = self.conn.query_pointer(self.root)?.reply()?;
+        let at = (pointer.root_x, pointer.root_y);
+        if self.at == Some(at) {
+            return Ok(());
+        }
+        let mapped = self.at.is_some();
+        self.at = Some(at);
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new()
+                .x((at.0 - self.radius) as i32)
+                .y((at.1 - self.radius) as i32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        if !mapped {
+            self.conn.map_window(self.window)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+// one or two spans per row covering the ring between `radius` and
+// `radius - thickness` from the centre
+fn annulus(radius: i16, thickness: i16) -> Vec<Rectangle> {
+    let outer = radius as f32;
+    let inner = (radius - thickness).max(0) as f32;
+    let half_width = |r: f32, dy: f32| (r * r - dy * dy).max(0.0).sqrt().round() as i16;
+    let mut spans = Vec::new();
+    for row in 0..radius * 2 {
+        let dy = row as f32 - outer + 0.5;
+        if dy.abs() >= outer {
+            continue;
+        }
+        let out = half_width(outer, dy);
+        let span = |x: i16, width: i16| Rectangle {
+            x,
+            y: row,
+            width: width as u16,
+            height: 1,
+        };
+        if dy.abs() < inner {
+            let inn = half_width(inner, dy);
+            spans.push(span(radius - out, out - inn));
+            spans.push(span(radius + inn, out - inn));
+        } else {
+            spans.push(span(radius - out, out * 2));
+        }
+    }
+    spans
+}
+
+impl Ring for X11Ring {
+    fn follow(&mut self, show: bool) -> Result<(), String> {
+        self.update(show).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::reverse::{Pad, Report, Stick};
+use gilrs::Button;
+use std::time::{Duration, Instant};
+
+// a controller connecting this soon after the pad is created is taken to
+// be the pad itself showing up
+const SHOW_UP: Duration = Duration::from_secs(3);
+
+// the virtual gamepad (uinput on linux, vigembus on windows) the mapping
+// drives alongside the keyboard and mouse, for games that read analog
+// movement from a controller
+pub struct VirtualPad {
+    pad: Pad,
+    report: Report,
+    created: Instant,
+    claimed: bool,
+}
+
+impl VirtualPad {
+    pub fn create() -> Result<VirtualPad, String> {
+        Ok(VirtualPad {
+            pad: Pad::create()?,
+            report: Report::default(),
+            created: Instant::now(),
+            claimed: false,
+        })
+    }
+
+    // sticks are -1..1 with y up
+    pub fn stick(&mut self, stick: Stick, x: f32, y: f32) -> Result<(), String> {
+        let position = (x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0));
+        match stick {
+            Stick::Left => self.report.left = position,
+            Stick::Right => self.report.right = position,
+        }
+        self.pad.send(&self.report)
+    }
+
+    // the analog triggers are all or nothing from a button
+    pub fn button(&mut self, button: Button, pressed: bool) -> Result<(), String> {
+        let pull = if pressed { 1.0 } else { 0.0 };
+        match button {
+            Button::LeftTrigger2 => self.report.left_trigger = pull,
+            Button::RightTrigger2 => self.report.right_trigger = pull,
+            _ => {
+                self.report.buttons.retain(|b| *b != button);
+                if pressed {
+                    self.report.buttons.push(button);
+                }
+            }
+        }
+        self.pad.send(&self.report)
+    }
+
+    // everything back to rest
+    pub fn reset(&mut self) {
+        if self.report != Report::default() {
+            self.report = Report::default();
+            if let Err(e) = self.pad.send(&self.report) {
+                println!("virtual pad failed: {}", e);
+            }
+        }
+    }
+
+    // whether a controller connecting now is this pad, so the mapping
+    // doesn't read back its own output. true once
+    pub fn claim(&mut self) -> bool {
+        let ours = !self.claimed && self.created.elapsed() < SHOW_UP;
+        self.claimed |= ours;
+        ours
+    }
+}
@@ -0,0 +1,282 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::keymap::parse_key;
+use gilrs::{Axis, Button};
+use libloading::Library;
+use serde::Deserialize;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// a binding handing a plugin a command on press, e.g.
+// `plugin = { name = "rcon", command = "say gg" }`. what the command means
+// is up to the plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginCommand {
+    pub name: String,
+    #[serde(default)]
+    pub command: String,
+}
+
+// hooks a plugin can implement. everything but `name` is optional so a plugin
+// that only cares about ticks doesn't have to stub out the rest
+pub trait Plugin {
+    fn name(&self) -> &str;
+    fn on_button(&mut self, _button: Button, _pressed: bool) {}
+    fn on_axis(&mut self, _axis: Axis, _value: f32) {}
+    fn on_tick(&mut self) {}
+    // a binding's `plugin` command for this plugin
+    fn on_command(&mut self, _command: &str) {}
+
+    // actions emitted since the last call, for plugins that can't inject
+    // output themselves (sandboxed ones)
+    fn take_actions(&mut self) -> Vec<Action> {
+        Vec::new()
+    }
+}
+
+// native plugins are shared libraries exporting a plain c abi, so they can be
+// written in anything (or in rust built with a different compiler) without
+// linking against this crate. only `c2k_plugin_name` is required:
+//
+//   const char *c2k_plugin_name(void);
+//   void c2k_plugin_init(void *host, c2k_emit emit);
+//   void c2k_plugin_on_button(uint16_t button, bool pressed);
+//   void c2k_plugin_on_axis(uint16_t axis, float value);
+//   void c2k_plugin_on_tick(void);
+//   void c2k_plugin_on_command(const char *command);
+//
+// button/axis codes are gilrs' `Button as u16` / `Axis as u16` values.
+// `init` is called once after loading with a callback for emitting actions,
+// named like the wasm plugins' imports:
+//
+//   typedef void (*c2k_emit)(void *host, const char *action,
+//                            const char *name, int32_t x, int32_t y);
+//
+//   emit(host, "tap", "f5", 0, 0);  emit(host, "move", NULL, 10, -4);
+//
+// `key_down`, `key_up`, `tap`, `mouse_down`, `mouse_up` and `click` take
+// `name`, `move` takes x and y and `scroll` takes x. call it only from
+// inside a hook, on the thread that called it
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type EmitFn = unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char, i32, i32);
+type InitFn = unsafe extern "C" fn(*mut c_void, EmitFn);
+type ButtonFn = unsafe extern "C" fn(u16, bool);
+type AxisFn = unsafe extern "C" fn(u16, f32);
+type TickFn = unsafe extern "C" fn();
+type CommandFn = unsafe extern "C" fn(*const c_char);
+
+// what a native plugin emitted since the last take_actions, handed to it as
+// `host`
+#[derive(Default)]
+struct Host {
+    actions: Vec<Action>,
+}
+
+unsafe extern "C" fn emit(
+    host: *mut c_void,
+    action: *const c_char,
+    name: *const c_char,
+    x: i32,
+    y: i32,
+) {
+    if host.is_null() || action.is_null() {
+        return;
+    }
+    let host = &mut *(host as *mut Host);
+    let action = CStr::from_ptr(action).to_string_lossy();
+    let name = if name.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(name).to_string_lossy().into_owned()
+    };
+    match emitted(&action, &name, x, y) {
+        Some(action) => host.actions.push(action),
+        None => println!("ignoring plugin action {} '{}'", action, name),
+    }
+}
+
+// an emitted action, or None for an unknown action, key or mouse button
+fn emitted(action: &str, name: &str, x: i32, y: i32) -> Option<Action> {
+    let key = || parse_key(name);
+    let button = || parse_mouse_button(name);
+    Some(match action {
+        "key_down" => Action::KeyDown(key()?),
+        "key_up" => Action::KeyUp(key()?),
+        "tap" => Action::KeyClick(key()?),
+        "mouse_down" => Action::MouseDown(button()?),
+        "mouse_up" => Action::MouseUp(button()?),
+        "click" => Action::MouseClick(button()?),
+        "move" => Action::MouseMove(x, y),
+        "scroll" => Action::Scroll(x),
+        _ => return None,
+    })
+}
+
+pub struct NativePlugin {
+    name: String,
+    on_button: Option<ButtonFn>,
+    on_axis: Option<AxisFn>,
+    on_tick: Option<TickFn>,
+    on_command: Option<CommandFn>,
+    // boxed so the pointer the plugin holds stays put. freed on drop
+    host: *mut Host,
+    // must outlive the fn pointers above, so it's dropped last
+    _lib: Library,
+}
+
+impl NativePlugin {
+    pub fn load(path: &Path) -> Result<NativePlugin, libloading::Error> {
+        unsafe {
+            let lib = Library::new(path)?;
+            let name_fn = *lib.get::<NameFn>(b"c2k_plugin_name\0")?;
+            let name_ptr = name_fn();
+            let name = if name_ptr.is_null() {
+                path.display().to_string()
+            } else {
+                CStr::from_ptr(name_ptr).to_string_lossy().into_owned()
+            };
+
+            let on_button = lib
+                .get::<ButtonFn>(b"c2k_plugin_on_button\0")
+                .ok()
+                .map(|s| *s);
+            let on_axis = lib.get::<AxisFn>(b"c2k_plugin_on_axis\0").ok().map(|s| *s);
+            let on_tick = lib.get::<TickFn>(b"c2k_plugin_on_tick\0").ok().map(|s| *s);
+            let on_command = lib
+                .get::<CommandFn>(b"c2k_plugin_on_command\0")
+                .ok()
+                .map(|s| *s);
+
+            let host = Box::into_raw(Box::<Host>::default());
+            if let Ok(init) = lib.get::<InitFn>(b"c2k_plugin_init\0") {
+                init(host as *mut c_void, emit);
+            }
+
+            Ok(NativePlugin {
+                name,
+                on_button,
+                on_axis,
+                on_tick,
+                on_command,
+                host,
+                _lib: lib,
+            })
+        }
+    }
+}
+
+impl Plugin for NativePlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_button(&mut self, button: Button, pressed: bool) {
+        if let Some(f) = self.on_button {
+            unsafe { f(button as u16, pressed) }
+        }
+    }
+
+    fn on_axis(&mut self, axis: Axis, value: f32) {
+        if let Some(f) = self.on_axis {
+            unsafe { f(axis as u16, value) }
+        }
+    }
+
+    fn on_tick(&mut self) {
+        if let Some(f) = self.on_tick {
+            unsafe { f() }
+        }
+    }
+
+    fn on_command(&mut self, command: &str) {
+        if let (Some(f), Ok(command)) = (self.on_command, CString::new(command)) {
+            unsafe { f(command.as_ptr()) }
+        }
+    }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        unsafe { std::mem::take(&mut (*self.host).actions) }
+    }
+}
+
+impl Drop for NativePlugin {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.host) });
+    }
+}
+
+// plugins live in <config dir>/plugins unless overridden
+pub fn plugins_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("C2K_PLUGIN_DIR") {
+        return PathBuf::from(dir);
+    }
+    crate::config::config_dir().join("plugins")
+}
+
+// load every shared library (and, with the `wasm` feature, every .wasm module)
+// in `dir`. a broken plugin is reported and skipped rather than taking the
+// whole mapper down
+pub fn load_plugins(dir: &Path) -> Vec<Box<dyn Plugin>> {
+    let mut plugins: Vec<Box<dyn Plugin>> = Vec::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return plugins, // no plugins dir is the normal case
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+        let loaded: Result<Box<dyn Plugin>, String> = if ext == std::env::consts::DLL_EXTENSION {
+            NativePlugin::load(&path)
+                .map(|p| Box::new(p) as Box<dyn Plugin>)
+                .map_err(|e| e.to_string())
+        } else if ext == "wasm" {
+            load_wasm(&path)
+        } else {
+            continue;
+        };
+
+        match loaded {
+            Ok(plugin) => {
+                println!("loaded plugin '{}' from {}", plugin.name(), path.display());
+                plugins.push(plugin);
+            }
+            Err(e) => println!("failed to load plugin {}: {}", path.display(), e),
+        }
+    }
+
+    plugins
+}
+
+#[cfg(feature = "wasm")]
+fn load_wasm(path: &Path) -> Result<Box<dyn Plugin>, String> {
+    crate::wasm::WasmPlugin::load(path)
+        .map(|p| Box::new(p) as Box<dyn Plugin>)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "wasm"))]
+fn load_wasm(_path: &Path) -> Result<Box<dyn Plugin>, String> {
+    Err("built without the `wasm` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigo::{Key, MouseButton};
+
+    #[test]
+    fn emitted_actions_are_named_like_the_wasm_imports() {
+        assert_eq!(emitted("tap", "f5", 0, 0), Some(Action::KeyClick(Key::F5)));
+        assert_eq!(
+            emitted("click", "left", 0, 0),
+            Some(Action::MouseClick(MouseButton::Left))
+        );
+        assert_eq!(emitted("move", "", 10, -4), Some(Action::MouseMove(10, -4)));
+        assert_eq!(emitted("tap", "nokey", 0, 0), None);
+        assert_eq!(emitted("explode", "", 0, 0), None);
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(target_os = "windows")]
+mod windows;
+
+use crate::input::InputState;
+use gilrs::Button;
+use serde::Deserialize;
+
+#[cfg(target_os = "windows")]
+use windows::Device;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerKind {
+    #[default]
+    Pen,
+    Touch,
+}
+
+// the stick drives a synthetic pen or finger instead of the mouse, touching
+// down while a trigger is pulled and pressing as hard as it's pulled, for art
+// programs and touch-only kiosk apps, e.g. a `[pointer]` table with
+// `kind = "pen"` and `pressure = "RightTrigger2"`. windows only
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PointerConfig {
+    pub kind: PointerKind,
+    pub pressure: Button,
+    // pull below this is no contact
+    pub threshold: f32,
+}
+
+impl Default for PointerConfig {
+    fn default() -> Self {
+        PointerConfig {
+            kind: PointerKind::Pen,
+            pressure: Button::RightTrigger2,
+            threshold: 0.05,
+        }
+    }
+}
+
+// one injected pointer frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    Hover,
+    Down,
+    Drag,
+    Up,
+}
+
+// where the pointer is and how hard it presses. a pen hovers between
+// strokes; a finger only exists while touching
+pub struct Pointer {
+    device: Device,
+    kind: PointerKind,
+    button: Button,
+    threshold: f32,
+    position: (i32, i32),
+    // 0 is off the surface
+    pressure: f32,
+    moved: bool,
+}
+
+impl Pointer {
+    pub fn new(config: &PointerConfig) -> Result<Pointer, String> {
+        let device = Device::create(config.kind)?;
+        Ok(Pointer {
+            position: device.cursor(),
+            device,
+            kind: config.kind,
+            button: config.pressure,
+            threshold: config.threshold,
+            pressure: 0.0,
+            moved: true,
+        })
+    }
+
+    // the trigger pressing the pointer into the screen, taken from the
+    // mapping
+    pub fn takes(&self, button: Button) -> bool {
+        button == self.button
+    }
+
+    pub fn move_by(&mut self, dx: i32, dy: i32) {
+        self.position.0 += dx;
+        self.position.1 += dy;
+        self.moved = true;
+    }
+
+    // reads the trigger and sends a frame when anything changed, once per
+    // tick
+    pub fn update(&mut self, input: &InputState) -> Result<(), String> {
+        let pull = input.button_value(self.button);
+        let pressure = if pull < self.threshold { 0.0 } else { pull };
+        if !self.moved && pressure == self.pressure {
+            return Ok(());
+        }
+        let frame = match (self.pressure > 0.0, pressure > 0.0) {
+            (false, true) => Frame::Down,
+            (true, true) => Frame::Drag,
+            (true, false) => Frame::Up,
+            (false, false) => Frame::Hover,
+        };
+        self.pressure = pressure;
+        self.moved = false;
+        self.position = self.device.clamp(self.position);
+        // fingers don't hover
+        if frame == Frame::Hover && self.kind == PointerKind::Touch {
+            return Ok(());
+        }
+        self.device.inject(frame, self.position, pressure)
+    }
+
+    // off the surface, e.g. on pause. the next pull touches down again
+    pub fn lift(&mut self) {
+        if self.pressure > 0.0 {
+            self.pressure = 0.0;
+            if let Err(e) = self.device.inject(Frame::Up, self.position, 0.0) {
+                println!("failed to lift the {:?}: {}", self.kind, e);
+            }
+        }
+    }
+}
+
+// lifting off, so nothing stays pressed into the canvas
+impl Drop for Pointer {
+    fn drop(&mut self) {
+        self.lift();
+    }
+}
+
+// nothing to inject into elsewhere, so there's never a device
+#[cfg(not(target_os = "windows"))]
+enum Device {}
+
+#[cfg(not(target_os = "windows"))]
+impl Device {
+    fn create(_kind: PointerKind) -> Result<Device, String> {
+        Err("pen and touch output needs windows".to_string())
+    }
+
+    fn cursor(&self) -> (i32, i32) {
+        match *self {}
+    }
+
+    fn clamp(&self, _position: (i32, i32)) -> (i32, i32) {
+        match *self {}
+    }
+
+    fn inject(&self, _frame: Frame, _at: (i32, i32), _pressure: f32) -> Result<(), String> {
+        match *self {}
+    }
+}
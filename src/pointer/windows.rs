@@ -0,0 +1,123 @@
+use super::{Frame, PointerKind};
+use windows::Win32::Foundation::{POINT, RECT};
+use windows::Win32::UI::Controls::{
+    CreateSyntheticPointerDevice, DestroySyntheticPointerDevice, InjectSyntheticPointerInput,
+    HSYNTHETICPOINTERDEVICE, POINTER_FEEDBACK_DEFAULT, POINTER_TYPE_INFO, POINTER_TYPE_INFO_0,
+};
+use windows::Win32::UI::Input::Pointer::{
+    POINTER_FLAGS, POINTER_FLAG_DOWN, POINTER_FLAG_INCONTACT, POINTER_FLAG_INRANGE,
+    POINTER_FLAG_UP, POINTER_FLAG_UPDATE, POINTER_INFO, POINTER_PEN_INFO, POINTER_TOUCH_INFO,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetCursorPos, GetSystemMetrics, PEN_FLAG_NONE, PEN_MASK_PRESSURE, PT_PEN, PT_TOUCH,
+    SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, TOUCH_FLAG_NONE,
+    TOUCH_MASK_CONTACTAREA, TOUCH_MASK_PRESSURE,
+};
+
+// a synthetic pen or touch digitizer (windows 10 1809 or later)
+pub struct Device {
+    handle: HSYNTHETICPOINTERDEVICE,
+    kind: PointerKind,
+}
+
+impl Device {
+    pub fn create(kind: PointerKind) -> Result<Device, String> {
+        let pointer_type = match kind {
+            PointerKind::Pen => PT_PEN,
+            PointerKind::Touch => PT_TOUCH,
+        };
+        let handle =
+            unsafe { CreateSyntheticPointerDevice(pointer_type, 1, POINTER_FEEDBACK_DEFAULT) }
+                .map_err(|e| format!("failed to create a synthetic {:?}: {}", kind, e))?;
+        Ok(Device { handle, kind })
+    }
+
+    // start where the mouse is
+    pub fn cursor(&self) -> (i32, i32) {
+        let mut point = POINT::default();
+        unsafe { GetCursorPos(&mut point) };
+        (point.x, point.y)
+    }
+
+    // keep to the desktop, across all monitors
+    pub fn clamp(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let (left, top, width, height) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN),
+                GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN),
+                GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+        (
+            x.clamp(left, left + width - 1),
+            y.clamp(top, top + height - 1),
+        )
+    }
+
+    pub fn inject(&self, frame: Frame, (x, y): (i32, i32), pressure: f32) -> Result<(), String> {
+        let flags: POINTER_FLAGS = match frame {
+            Frame::Hover => POINTER_FLAG_INRANGE | POINTER_FLAG_UPDATE,
+            Frame::Down => POINTER_FLAG_INRANGE | POINTER_FLAG_INCONTACT | POINTER_FLAG_DOWN,
+            Frame::Drag => POINTER_FLAG_INRANGE | POINTER_FLAG_INCONTACT | POINTER_FLAG_UPDATE,
+            Frame::Up => POINTER_FLAG_UP,
+        };
+        let pointer = POINTER_INFO {
+            pointerId: 0,
+            pointerFlags: flags,
+            ptPixelLocation: POINT { x, y },
+            ..Default::default()
+        };
+        let info = match self.kind {
+            PointerKind::Pen => POINTER_TYPE_INFO {
+                r#type: PT_PEN,
+                Anonymous: POINTER_TYPE_INFO_0 {
+                    penInfo: POINTER_PEN_INFO {
+                        pointerInfo: POINTER_INFO {
+                            pointerType: PT_PEN,
+                            ..pointer
+                        },
+                        penFlags: PEN_FLAG_NONE,
+                        penMask: PEN_MASK_PRESSURE,
+                        // 0 to 1024
+                        pressure: (pressure.clamp(0.0, 1.0) * 1024.0) as u32,
+                        ..Default::default()
+                    },
+                },
+            },
+            PointerKind::Touch => POINTER_TYPE_INFO {
+                r#type: PT_TOUCH,
+                Anonymous: POINTER_TYPE_INFO_0 {
+                    touchInfo: POINTER_TOUCH_INFO {
+                        pointerInfo: POINTER_INFO {
+                            pointerType: PT_TOUCH,
+                            ..pointer
+                        },
+                        touchFlags: TOUCH_FLAG_NONE,
+                        touchMask: TOUCH_MASK_CONTACTAREA | TOUCH_MASK_PRESSURE,
+                        rcContact: RECT {
+                            left: x - 4,
+                            top: y - 4,
+                            right: x + 4,
+                            bottom: y + 4,
+                        },
+                        orientation: 90,
+                        pressure: (pressure.clamp(0.0, 1.0) * 1024.0) as u32,
+                        ..Default::default()
+                    },
+                },
+            },
+        };
+        if unsafe { InjectSyntheticPointerInput(self.handle, &[info]) }.as_bool() {
+            Ok(())
+        } else {
+            Err(windows::core::Error::from_win32().to_string())
+        }
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { DestroySyntheticPointerDevice(self.handle) };
+    }
+}
@@ -0,0 +1,50 @@
+use crate::config::{Config, Profile};
+
+// presets shipped in the binary. each is a config snippet defining a single
+// profile named after the preset, so exporting one gives something that can
+// be pasted straight into config.toml
+const PRESETS: &[(&str, &str)] = &[
+    ("fps", include_str!("../presets/fps.toml")),
+    ("minecraft", include_str!("../presets/minecraft.toml")),
+    ("desktop", include_str!("../presets/desktop.toml")),
+    ("browser", include_str!("../presets/browser.toml")),
+    ("media", include_str!("../presets/media.toml")),
+    ("presentation", include_str!("../presets/presentation.toml")),
+];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|(name, _)| *name)
+}
+
+pub fn source(name: &str) -> Result<&'static str, String> {
+    PRESETS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, text)| *text)
+        .ok_or_else(|| {
+            format!(
+                "unknown preset '{}' (available: {})",
+                name,
+                names().collect::<Vec<_>>().join(", ")
+            )
+        })
+}
+
+pub fn load(name: &str) -> Result<Profile, String> {
+    let config: Config = toml::from_str(source(name)?)
+        .map_err(|e| format!("failed to parse preset '{}': {}", name, e))?;
+    let mut profiles = config.profiles;
+    profiles
+        .remove(name)
+        .ok_or_else(|| format!("preset '{}' doesn't define its profile", name))
+}
+
+// runs with the preset as the default profile. a profile of the same name in
+// the user's config wins, so presets can be tweaked after exporting them
+pub fn apply(config: &mut Config, name: &str) -> Result<(), String> {
+    if !config.profiles.contains_key(name) {
+        config.profiles.insert(name.to_string(), load(name)?);
+    }
+    config.profile = name.to_string();
+    Ok(())
+}
@@ -0,0 +1,133 @@
+// real-time scheduling keeps input latency steady when the machine is busy.
+// most desktops don't grant it to normal users, so failing is expected and
+// only costs a warning
+
+use std::sync::OnceLock;
+
+// the core from --cpu-affinity, for threads started later
+static CORE: OnceLock<usize> = OnceLock::new();
+
+pub fn raise() {
+    if let Err(e) = try_raise() {
+        println!(
+            "couldn't get real-time priority ({}), running at normal priority",
+            e
+        );
+        println!("{}", HINT);
+    }
+}
+
+#[cfg(target_os = "linux")]
+const HINT: &str = "  to allow it, add `@input - rtprio 99` to /etc/security/limits.conf \
+or run `sudo setcap cap_sys_nice+ep` on the binary (rtkit only helps apps that ask it over dbus); \
+pass --no-rt to skip this";
+
+#[cfg(target_os = "macos")]
+const HINT: &str = "  pass --no-rt to skip this";
+
+#[cfg(target_os = "windows")]
+const HINT: &str = "  run as administrator to allow it, or pass --no-rt to skip this";
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+const HINT: &str = "";
+
+#[cfg(target_os = "linux")]
+fn try_raise() -> Result<(), std::io::Error> {
+    unsafe {
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = libc::sched_get_priority_max(libc::SCHED_RR);
+        if libc::sched_setscheduler(0, libc::SCHED_RR, &param) != 0 {
+            let e = std::io::Error::last_os_error();
+            // a limits.conf nice limit may still allow a smaller boost
+            libc::setpriority(libc::PRIO_PROCESS, 0, -10);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn try_raise() -> Result<(), std::io::Error> {
+    unsafe {
+        let policy = libc::SCHED_RR;
+        let mut param: libc::sched_param = std::mem::zeroed();
+        param.sched_priority = libc::sched_get_priority_max(policy);
+        // pthread functions return the error instead of setting errno
+        match libc::pthread_setschedparam(libc::pthread_self(), policy, &param) {
+            0 => Ok(()),
+            code => Err(std::io::Error::from_raw_os_error(code)),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn try_raise() -> Result<(), std::io::Error> {
+    use windows::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_TIME_CRITICAL,
+    };
+    let ok = unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_TIME_CRITICAL) };
+    if ok.as_bool() {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn try_raise() -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+// keeps the event loop on one cpu core, so it isn't moved around (and its
+// caches emptied) on a busy machine. threads that inject on their own, like
+// the mouse interpolator, call pin_thread to join it
+pub fn pin(core: usize) {
+    let _ = CORE.set(core);
+    pin_thread();
+}
+
+// puts the calling thread on the --cpu-affinity core, if there is one
+pub fn pin_thread() {
+    if let Some(&core) = CORE.get() {
+        if let Err(e) = try_pin(core) {
+            println!(
+                "couldn't pin to cpu core {} ({}), running unpinned",
+                core, e
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn try_pin(core: usize) -> Result<(), std::io::Error> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if core >= 8 * std::mem::size_of::<libc::cpu_set_t>() {
+            return Err(std::io::Error::from_raw_os_error(libc::EINVAL));
+        }
+        libc::CPU_SET(core, &mut set);
+        // 0 is the calling thread, not the whole process
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn try_pin(core: usize) -> Result<(), std::io::Error> {
+    use windows::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+    if core >= usize::BITS as usize {
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput));
+    }
+    match unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << core) } {
+        0 => Err(std::io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+// macos only takes affinity hints, and ignores them on apple silicon
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn try_pin(_core: usize) -> Result<(), std::io::Error> {
+    Err(std::io::Error::from(std::io::ErrorKind::Unsupported))
+}
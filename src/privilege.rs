@@ -0,0 +1,91 @@
+use crate::error::Error;
+
+// when started as root (usually through sudo, for uinput or real-time
+// priority), switch to the user who ran sudo once devices are open, so
+// bindings, scripts and plugins never run as root. controllers plugged in
+// later are opened as that user, which `setup-udev` makes possible
+#[cfg(unix)]
+pub fn drop_root() -> Result<(), Error> {
+    if unsafe { libc::geteuid() } != 0 {
+        return Ok(());
+    }
+    let Some(uid) = invoking_uid() else {
+        println!("running as root with no sudo user to switch to");
+        return Ok(());
+    };
+    let user = unsafe { libc::getpwuid(uid) };
+    if user.is_null() {
+        return Err(Error::Permission(format!(
+            "no passwd entry for uid {}",
+            uid
+        )));
+    }
+    let (name, home, gid) = unsafe {
+        let user = &*user;
+        (
+            std::ffi::CStr::from_ptr(user.pw_name).to_owned(),
+            std::ffi::CStr::from_ptr(user.pw_dir).to_owned(),
+            user.pw_gid,
+        )
+    };
+
+    // groups first, then gid, then uid: each step needs the privilege the
+    // next one gives up
+    let dropped = unsafe {
+        libc::initgroups(name.as_ptr(), gid as _) == 0
+            && libc::setgid(gid) == 0
+            && libc::setuid(uid) == 0
+    };
+    if !dropped {
+        return Err(Error::Permission(format!(
+            "couldn't switch to {}: {}",
+            name.to_string_lossy(),
+            std::io::Error::last_os_error()
+        )));
+    }
+    // make sure there's no way back
+    if unsafe { libc::setuid(0) } == 0 {
+        return Err(Error::Permission(
+            "root could be regained after dropping it".to_string(),
+        ));
+    }
+    // config, plugins and scripts should come from the user's home
+    std::env::set_var("HOME", home.to_string_lossy().as_ref());
+    println!("dropped root, running as {}", name.to_string_lossy());
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn drop_root() -> Result<(), Error> {
+    Ok(())
+}
+
+// while still root, point HOME at the sudo user's home so the config read
+// before dropping root (to see which devices to open) is theirs
+#[cfg(unix)]
+pub fn use_invoking_home() {
+    if unsafe { libc::geteuid() } != 0 {
+        return;
+    }
+    let Some(uid) = invoking_uid() else {
+        return;
+    };
+    let user = unsafe { libc::getpwuid(uid) };
+    if user.is_null() {
+        return;
+    }
+    let home = unsafe { std::ffi::CStr::from_ptr((*user).pw_dir) };
+    std::env::set_var("HOME", home.to_string_lossy().as_ref());
+}
+
+#[cfg(not(unix))]
+pub fn use_invoking_home() {}
+
+// sudo and pkexec both leave the caller's uid in the environment
+#[cfg(unix)]
+fn invoking_uid() -> Option<libc::uid_t> {
+    ["SUDO_UID", "PKEXEC_UID"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok()?.parse().ok())
+        .filter(|&uid| uid != 0)
+}
@@ -0,0 +1,98 @@
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+// executable names of running processes. argv[0] rather than comm, since comm
+// is truncated to 15 characters and shows wine games as "wine64-preloader"
+#[cfg(target_os = "linux")]
+pub fn names() -> Vec<String> {
+    let mut names = Vec::new();
+    for dir in proc_dirs() {
+        if let Ok(cmdline) = std::fs::read(dir.join("cmdline")) {
+            let argv0 = cmdline.split(|&b| b == 0).next().unwrap_or_default();
+            let argv0 = String::from_utf8_lossy(argv0);
+            if let Some(name) = argv0.rsplit(['/', '\\']).next().filter(|n| !n.is_empty()) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+// the /proc directory of every running process
+#[cfg(target_os = "linux")]
+pub fn proc_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|s| s.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+pub fn names() -> Vec<String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let mut names = Vec::new();
+    unsafe {
+        let snapshot = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+            Ok(snapshot) => snapshot,
+            Err(_) => return names,
+        };
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut more = Process32FirstW(snapshot, &mut entry).as_bool();
+        while more {
+            let len = entry
+                .szExeFile
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            names.push(String::from_utf16_lossy(&entry.szExeFile[..len]));
+            more = Process32NextW(snapshot, &mut entry).as_bool();
+        }
+        CloseHandle(snapshot);
+    }
+    names
+}
+
+#[cfg(target_os = "macos")]
+pub fn names() -> Vec<String> {
+    let mut names = Vec::new();
+    unsafe {
+        let count = libc::proc_listallpids(std::ptr::null_mut(), 0);
+        if count <= 0 {
+            return names;
+        }
+        // leave room for processes started in between the two calls
+        let mut pids = vec![0 as libc::pid_t; count as usize + 32];
+        let bytes = (pids.len() * std::mem::size_of::<libc::pid_t>()) as libc::c_int;
+        let count = libc::proc_listallpids(pids.as_mut_ptr() as *mut libc::c_void, bytes);
+        for &pid in pids.iter().take(count.max(0) as usize) {
+            let mut buf = [0u8; 256];
+            let len = libc::proc_name(pid, buf.as_mut_ptr() as *mut libc::c_void, buf.len() as u32);
+            if len > 0 {
+                names.push(String::from_utf8_lossy(&buf[..len as usize]).into_owned());
+            }
+        }
+    }
+    names
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub fn names() -> Vec<String> {
+    Vec::new()
+}
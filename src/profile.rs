@@ -0,0 +1,201 @@
+use crate::action::Action;
+use crate::bands::AxisBands;
+use crate::bindings::Mapper;
+use crate::board::Board;
+use crate::combo::ComboMatcher;
+use crate::config::{self, Profile};
+use crate::dial::Dial;
+use crate::expr::{Evaluator, Value};
+use crate::flick::Flicks;
+use crate::haptics::Haptics;
+use crate::hat::Hats;
+use crate::hid::TriggerEffect;
+use crate::input::InputState;
+use crate::lightbar::Lightbar;
+use crate::macros::Macros;
+use crate::ring::OuterRings;
+use crate::scancode;
+use crate::script::Script;
+use crate::scroll::ScrollAccelerator;
+use crate::smoothing::Smoother;
+use crate::stick::{Stick, StickMode, StickOutput};
+use crate::trigger::Triggers;
+use crate::zoom::TriggerZoom;
+use enigo::Key;
+use gilrs::Button;
+use std::collections::BTreeMap;
+
+// a profile ready to run: scripts loaded and per-tick settings compiled.
+// switching profiles means building a new one of these
+pub struct ActiveProfile {
+    pub name: String,
+    // injection waits for this app or window to have focus
+    pub only_when_focused: Option<String>,
+    pub scripts: Vec<Script>,
+    pub sensitivity: Value,
+    pub deadzone: Value,
+    pub speed_tiers: BTreeMap<String, f32>,
+    pub smoothing: Option<Smoother>,
+    // pieces each tick's mouse movement is split into
+    pub mouse_interpolation: u32,
+    pub mapper: Mapper,
+    // character keys go by us qwerty position rather than the layout
+    pub physical_keys: bool,
+    pub left_stick: StickOutput,
+    pub right_stick: StickOutput,
+    pub triggers: Option<Triggers>,
+    pub scroll: Option<ScrollAccelerator>,
+    pub dial: Option<Dial>,
+    pub zoom: Option<TriggerZoom>,
+    pub flicks: Option<Flicks>,
+    pub rings: OuterRings,
+    pub bands: AxisBands,
+    pub hats: Hats,
+    pub combos: ComboMatcher,
+    pub macros: Macros,
+    pub lightbar: Option<Lightbar>,
+    pub haptics: Option<Haptics>,
+    // left and right dualsense trigger effects, free unless configured
+    pub adaptive_triggers: (TriggerEffect, TriggerEffect),
+    pub board: Option<Board>,
+}
+
+impl ActiveProfile {
+    pub fn load(name: &str, profile: &Profile, evaluator: &Evaluator) -> ActiveProfile {
+        // lua scripts attached to the profile
+        let mut scripts = Vec::new();
+        for path in &profile.scripts {
+            let path = config::resolve(path);
+            match Script::load(&path) {
+                Ok(script) => {
+                    println!("loaded script {}", path.display());
+                    scripts.push(script);
+                }
+                Err(e) => println!("failed to load script {}: {}", path.display(), e),
+            }
+        }
+
+        let physical_keys = profile.key_positions.physical();
+        if physical_keys {
+            println!("profile '{}' maps keys by physical position", name);
+        }
+
+        ActiveProfile {
+            name: name.to_string(),
+            only_when_focused: profile.only_when_focused.clone(),
+            scripts,
+            sensitivity: evaluator.compile("sensitivity", profile.sensitivity.as_ref(), 50.0),
+            deadzone: evaluator.compile("deadzone", profile.deadzone.as_ref(), 0.15),
+            speed_tiers: profile.speed_tiers.clone(),
+            smoothing: profile.mouse_smoothing.as_ref().map(Smoother::new),
+            mouse_interpolation: profile.mouse_interpolation.unwrap_or(1),
+            mapper: Mapper::new(&profile.bindings, physical_keys),
+            physical_keys,
+            left_stick: StickOutput::new(
+                &profile.left_stick,
+                physical_keys,
+                profile.left_stick_thresholds,
+            ),
+            right_stick: StickOutput::new(
+                profile.right_stick.as_ref().unwrap_or(&StickMode::Mouse),
+                physical_keys,
+                profile.right_stick_thresholds,
+            ),
+            triggers: profile.triggers.as_ref().map(Triggers::new),
+            scroll: profile
+                .scroll_acceleration
+                .as_ref()
+                .map(ScrollAccelerator::new),
+            dial: profile.dial.as_ref().map(Dial::new),
+            zoom: profile.trigger_zoom.as_ref().map(TriggerZoom::new),
+            flicks: profile.flicks.as_ref().map(Flicks::new),
+            rings: OuterRings::new(&profile.outer_ring, physical_keys),
+            bands: AxisBands::new(&profile.axis_bands, physical_keys),
+            hats: Hats::new(&profile.hats, physical_keys),
+            combos: ComboMatcher::new(&profile.combos),
+            macros: Macros::new(&profile.macros),
+            lightbar: profile.lightbar.as_ref().map(Lightbar::new),
+            haptics: profile.haptics.as_ref().map(Haptics::new),
+            adaptive_triggers: profile
+                .adaptive_triggers
+                .clone()
+                .unwrap_or_default()
+                .effects(),
+            board: profile.board.as_ref().map(Board::new),
+        }
+    }
+
+    // a key from the built-in mapping, moved to its physical position if the
+    // profile wants that
+    pub fn key(&self, key: enigo::Key) -> enigo::Key {
+        if self.physical_keys {
+            scancode::physical(key)
+        } else {
+            key
+        }
+    }
+
+    pub fn stick(&self, stick: Stick) -> &StickOutput {
+        match stick {
+            Stick::Left => &self.left_stick,
+            Stick::Right => &self.right_stick,
+        }
+    }
+
+    pub fn stick_mut(&mut self, stick: Stick) -> &mut StickOutput {
+        match stick {
+            Stick::Left => &mut self.left_stick,
+            Stick::Right => &mut self.right_stick,
+        }
+    }
+
+    // a stick in volume mode mutes with its click, taking the button from
+    // the mapping. None when the button isn't one of those
+    pub fn stick_click(&self, button: Button, pressed: bool) -> Option<Vec<Action>> {
+        [Stick::Left, Stick::Right]
+            .into_iter()
+            .find_map(|stick| match self.stick(stick) {
+                StickOutput::Volume(knob) if knob.mutes() && stick.click() == button => {
+                    Some(if pressed {
+                        vec![Action::KeyClick(Key::VolumeMute)]
+                    } else {
+                        vec![]
+                    })
+                }
+                _ => None,
+            })
+    }
+
+    // the deadzone and stick-mouse speed, possibly from expressions. a
+    // picked speed tier stands in for the sensitivity
+    pub fn settings(&self, evaluator: &Evaluator, input: &InputState) -> (f32, f32) {
+        let state = self.mapper.state();
+        let speed = match state.speed().and_then(|t| self.speed_tiers.get(t)) {
+            Some(speed) => *speed,
+            None => evaluator.eval(&self.sensitivity, input, state) as f32,
+        };
+        (
+            evaluator.eval(&self.deadzone, input, state) as f32,
+            speed * state.speed_scale(),
+        )
+    }
+
+    // key repeats from sticks in `keys` mode, and scrolling, panning and
+    // volume steps from sticks in `scroll`, `pan` and `volume` mode
+    pub fn tick_sticks(&mut self, input: &InputState) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for (stick, output) in [
+            (Stick::Left, &mut self.left_stick),
+            (Stick::Right, &mut self.right_stick),
+        ] {
+            match output {
+                StickOutput::Repeating(repeater) => actions.extend(repeater.tick(input, stick)),
+                StickOutput::Scrolling(scroller) => actions.extend(scroller.tick(input, stick)),
+                StickOutput::Panning(panner) => actions.extend(panner.tick(input, stick)),
+                StickOutput::Volume(knob) => actions.extend(knob.tick(input, stick)),
+                _ => (),
+            }
+        }
+        actions
+    }
+}
@@ -0,0 +1,169 @@
+use crate::action::{parse_mouse_button, Action, Output};
+use crate::config::Config;
+use crate::engine::Engine;
+use crate::keymap::parse_key;
+use gilrs::EventType;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+// python bindings, built into a `controller2keys` module by maturin (see
+// pyproject.toml): the engine for translating events, and the output for
+// injecting, so a mapping can be prototyped in python on the same plumbing.
+// both stay on the thread that made them
+
+// one action, as the engine produces it and the output takes it
+#[pyclass(name = "Action", module = "controller2keys", frozen)]
+#[derive(Clone)]
+struct PyAction(Action);
+
+#[pymethods]
+impl PyAction {
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+fn actions(actions: Vec<Action>) -> Vec<PyAction> {
+    actions.into_iter().map(PyAction).collect()
+}
+
+// Engine(profile=None, config=None): `config` is the text of a config.toml,
+// or the user's config file when left out
+#[pyclass(name = "Engine", module = "controller2keys", unsendable)]
+struct PyEngine(Engine);
+
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (profile = None, config = None))]
+    fn new(profile: Option<&str>, config: Option<&str>) -> PyResult<Self> {
+        let engine = match config {
+            Some(text) => Engine::parse(text, profile.unwrap_or("")),
+            None => {
+                let config = Config::load_or_default();
+                Engine::new(&config, profile.unwrap_or(&config.profile))
+            }
+        };
+        engine.map(PyEngine).map_err(PyValueError::new_err)
+    }
+
+    #[getter]
+    fn profile(&self) -> &str {
+        self.0.profile()
+    }
+
+    // a controller event as json, as `controller2keys record` writes them
+    fn feed(&mut self, event: &str) -> PyResult<Vec<PyAction>> {
+        let event: EventType =
+            serde_json::from_str(event).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(actions(self.0.feed(event)))
+    }
+
+    fn tick(&mut self) -> Vec<PyAction> {
+        actions(self.0.tick())
+    }
+}
+
+// Output(): injects through the platform's backend. the key and mouse button
+// names are the config's
+#[pyclass(name = "Output", module = "controller2keys", unsendable)]
+struct PyOutput(Output);
+
+fn key(name: &str) -> PyResult<enigo::Key> {
+    parse_key(name).ok_or_else(|| PyValueError::new_err(format!("unknown key '{}'", name)))
+}
+
+fn mouse_button(name: &str) -> PyResult<enigo::MouseButton> {
+    parse_mouse_button(name)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown mouse button '{}'", name)))
+}
+
+#[pymethods]
+impl PyOutput {
+    #[new]
+    fn new() -> PyResult<Self> {
+        Output::new()
+            .map(PyOutput)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn execute(&mut self, action: &PyAction) {
+        self.0.execute(&action.0);
+    }
+
+    fn key_down(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::KeyDown(key(name)?));
+        Ok(())
+    }
+
+    fn key_up(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::KeyUp(key(name)?));
+        Ok(())
+    }
+
+    fn tap(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::KeyClick(key(name)?));
+        Ok(())
+    }
+
+    fn mouse_down(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::MouseDown(mouse_button(name)?));
+        Ok(())
+    }
+
+    fn mouse_up(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::MouseUp(mouse_button(name)?));
+        Ok(())
+    }
+
+    fn click(&mut self, name: &str) -> PyResult<()> {
+        self.0.execute(&Action::MouseClick(mouse_button(name)?));
+        Ok(())
+    }
+
+    #[pyo3(name = "move")]
+    fn move_by(&mut self, dx: i32, dy: i32) {
+        self.0.execute(&Action::MouseMove(dx, dy));
+    }
+
+    fn scroll(&mut self, amount: i32) {
+        self.0.execute(&Action::Scroll(amount));
+    }
+
+    fn text(&mut self, text: String) {
+        self.0.execute(&Action::Text(text));
+    }
+
+    fn paste(&mut self, text: String) {
+        self.0.execute(&Action::Paste(text));
+    }
+
+    // lets go of everything this output is holding down
+    fn release_all(&mut self) {
+        self.0.release_all();
+    }
+}
+
+// the profile names in the user's config file, default first
+#[pyfunction]
+fn profiles() -> Vec<String> {
+    let config = Config::load_or_default();
+    let mut names = vec![config.profile.clone()];
+    names.extend(
+        config
+            .profiles
+            .into_keys()
+            .filter(|name| *name != config.profile),
+    );
+    names
+}
+
+#[pymodule]
+#[pyo3(name = "controller2keys")]
+fn module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyAction>()?;
+    m.add_class::<PyEngine>()?;
+    m.add_class::<PyOutput>()?;
+    m.add_function(wrap_pyfunction!(profiles, m)?)?;
+    Ok(())
+}
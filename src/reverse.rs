@@ -0,0 +1,328 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "linux")]
+use linux::Capture;
+#[cfg(target_os = "linux")]
+pub use linux::Pad;
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+use stub::Capture;
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub use stub::Pad;
+#[cfg(target_os = "windows")]
+use windows::Capture;
+#[cfg(target_os = "windows")]
+pub use windows::Pad;
+
+use gilrs::Button;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+// how far the mouse stick moves toward each tick's mouse motion, so mice
+// polling slower than we tick don't make the stick flicker back to centre
+const MOUSE_SMOOTHING: f32 = 0.5;
+
+// `controller2keys reverse`: keyboard and mouse in, virtual gamepad out,
+// for playing gamepad-only games without one. from the `[reverse]` table
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReverseConfig {
+    // keep the desktop from seeing the keyboard and mouse while running.
+    // on windows only the pointer is held in place
+    pub grab: bool,
+    // stops reverse mode
+    pub quit: String,
+    pub mouse_stick: Option<Stick>,
+    // stick deflection per count of mouse motion in a tick
+    pub mouse_sensitivity: f32,
+    // key (or `mouse_left`, `mouse_right`...) to gamepad button or stick
+    // direction
+    pub keys: BTreeMap<String, Target>,
+}
+
+impl Default for ReverseConfig {
+    fn default() -> Self {
+        let keys = [
+            ("w", Target::Stick(Direction::LeftStickUp)),
+            ("a", Target::Stick(Direction::LeftStickLeft)),
+            ("s", Target::Stick(Direction::LeftStickDown)),
+            ("d", Target::Stick(Direction::LeftStickRight)),
+            ("space", Target::Button(Button::South)),
+            ("c", Target::Button(Button::East)),
+            ("r", Target::Button(Button::West)),
+            ("e", Target::Button(Button::North)),
+            ("q", Target::Button(Button::LeftTrigger)),
+            ("f", Target::Button(Button::RightTrigger)),
+            ("mouse_right", Target::Button(Button::LeftTrigger2)),
+            ("mouse_left", Target::Button(Button::RightTrigger2)),
+            ("shift", Target::Button(Button::LeftThumb)),
+            ("v", Target::Button(Button::RightThumb)),
+            ("tab", Target::Button(Button::Select)),
+            ("escape", Target::Button(Button::Start)),
+            ("up", Target::Button(Button::DPadUp)),
+            ("down", Target::Button(Button::DPadDown)),
+            ("left", Target::Button(Button::DPadLeft)),
+            ("right", Target::Button(Button::DPadRight)),
+        ];
+        ReverseConfig {
+            grab: false,
+            quit: "f12".to_string(),
+            mouse_stick: Some(Stick::Right),
+            mouse_sensitivity: 0.05,
+            keys: keys
+                .into_iter()
+                .map(|(key, target)| (key.to_string(), target))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    LeftStickUp,
+    LeftStickDown,
+    LeftStickLeft,
+    LeftStickRight,
+    RightStickUp,
+    RightStickDown,
+    RightStickLeft,
+    RightStickRight,
+}
+
+// a gamepad button by its gilrs name ("South", "LeftTrigger2"...) or a
+// stick direction ("left_stick_up"...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Target {
+    Button(Button),
+    Stick(Direction),
+}
+
+// the whole virtual gamepad. sticks are -1..1 with y up, triggers 0..1
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub buttons: Vec<Button>,
+    pub left: (f32, f32),
+    pub right: (f32, f32),
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+
+// (names, evdev code, windows virtual-key) of the physical keys (us layout)
+// and mouse buttons reverse mode can read
+#[rustfmt::skip]
+const KEYS: &[(&[&str], u16, u16)] = &[
+    (&["a"], 30, 0x41), (&["b"], 48, 0x42), (&["c"], 46, 0x43), (&["d"], 32, 0x44),
+    (&["e"], 18, 0x45), (&["f"], 33, 0x46), (&["g"], 34, 0x47), (&["h"], 35, 0x48),
+    (&["i"], 23, 0x49), (&["j"], 36, 0x4a), (&["k"], 37, 0x4b), (&["l"], 38, 0x4c),
+    (&["m"], 50, 0x4d), (&["n"], 49, 0x4e), (&["o"], 24, 0x4f), (&["p"], 25, 0x50),
+    (&["q"], 16, 0x51), (&["r"], 19, 0x52), (&["s"], 31, 0x53), (&["t"], 20, 0x54),
+    (&["u"], 22, 0x55), (&["v"], 47, 0x56), (&["w"], 17, 0x57), (&["x"], 45, 0x58),
+    (&["y"], 21, 0x59), (&["z"], 44, 0x5a),
+    (&["1"], 2, 0x31), (&["2"], 3, 0x32), (&["3"], 4, 0x33), (&["4"], 5, 0x34),
+    (&["5"], 6, 0x35), (&["6"], 7, 0x36), (&["7"], 8, 0x37), (&["8"], 9, 0x38),
+    (&["9"], 10, 0x39), (&["0"], 11, 0x30),
+    (&["f1"], 59, 0x70), (&["f2"], 60, 0x71), (&["f3"], 61, 0x72), (&["f4"], 62, 0x73),
+    (&["f5"], 63, 0x74), (&["f6"], 64, 0x75), (&["f7"], 65, 0x76), (&["f8"], 66, 0x77),
+    (&["f9"], 67, 0x78), (&["f10"], 68, 0x79), (&["f11"], 87, 0x7a), (&["f12"], 88, 0x7b),
+    (&["space"], 57, 0x20),
+    (&["tab"], 15, 0x09),
+    (&["enter", "return"], 28, 0x0d),
+    (&["esc", "escape"], 1, 0x1b),
+    (&["backspace"], 14, 0x08),
+    (&["capslock"], 58, 0x14),
+    (&["shift", "leftshift"], 42, 0xa0),
+    (&["rightshift"], 54, 0xa1),
+    (&["ctrl", "control", "leftctrl"], 29, 0xa2),
+    (&["rightctrl"], 97, 0xa3),
+    (&["alt", "leftalt"], 56, 0xa4),
+    (&["rightalt", "altgr"], 100, 0xa5),
+    (&["up", "uparrow"], 103, 0x26),
+    (&["down", "downarrow"], 108, 0x28),
+    (&["left", "leftarrow"], 105, 0x25),
+    (&["right", "rightarrow"], 106, 0x27),
+    (&["insert", "ins"], 110, 0x2d),
+    (&["delete", "del"], 111, 0x2e),
+    (&["home"], 102, 0x24),
+    (&["end"], 107, 0x23),
+    (&["pageup", "pgup"], 104, 0x21),
+    (&["pagedown", "pgdn"], 109, 0x22),
+    (&["pause", "break"], 119, 0x13),
+    (&["minus", "dash"], 12, 0xbd),
+    (&["equal", "equals"], 13, 0xbb),
+    (&["leftbracket"], 26, 0xdb),
+    (&["rightbracket"], 27, 0xdd),
+    (&["semicolon"], 39, 0xba),
+    (&["quote", "apostrophe"], 40, 0xde),
+    (&["grave", "backtick"], 41, 0xc0),
+    (&["backslash"], 43, 0xdc),
+    (&["comma"], 51, 0xbc),
+    (&["period", "dot"], 52, 0xbe),
+    (&["slash"], 53, 0xbf),
+    (&["mouseleft"], 0x110, 0x01),
+    (&["mouseright"], 0x111, 0x02),
+    (&["mousemiddle"], 0x112, 0x04),
+    (&["mouseback"], 0x113, 0x05),
+    (&["mouseforward"], 0x114, 0x06),
+];
+
+// the platform's code for a key name, normalized like `keymap::parse_key`
+fn code(name: &str) -> Option<u16> {
+    let normalized: String = name
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect();
+    let &(_, evdev, vk) = KEYS
+        .iter()
+        .find(|(names, _, _)| names.contains(&normalized.as_str()))?;
+    Some(if cfg!(target_os = "windows") {
+        vk
+    } else {
+        evdev
+    })
+}
+
+// deflection from opposing directions cancels; diagonals are clamped to the
+// unit circle
+fn stick(up: bool, down: bool, left: bool, right: bool) -> (f32, f32) {
+    let axis = |negative: bool, positive: bool| positive as i8 as f32 - negative as i8 as f32;
+    clamp((axis(left, right), axis(down, up)))
+}
+
+fn clamp((x, y): (f32, f32)) -> (f32, f32) {
+    let length = (x * x + y * y).sqrt();
+    if length > 1.0 {
+        (x / length, y / length)
+    } else {
+        (x, y)
+    }
+}
+
+pub fn run(config: &ReverseConfig) -> Result<(), String> {
+    let quit = code(&config.quit).ok_or_else(|| format!("unknown quit key '{}'", config.quit))?;
+    let mut keys = Vec::new();
+    for (name, target) in &config.keys {
+        match code(name) {
+            Some(c) => keys.push((c, *target)),
+            None => println!("ignoring unknown key '{}'", name),
+        }
+    }
+
+    let watched: Vec<u16> = keys.iter().map(|(c, _)| *c).chain([quit]).collect();
+    let mut capture = Capture::open(&watched, config.grab)?;
+    let mut pad = Pad::create()?;
+    crate::privilege::drop_root().map_err(|e| e.to_string())?;
+    println!(
+        "reverse mode: keyboard and mouse -> virtual gamepad, {} to quit",
+        config.quit
+    );
+
+    let mut held = HashSet::new();
+    let mut mouse = (0.0, 0.0);
+    let mut last = None;
+    loop {
+        let (dx, dy) = capture.poll(&mut held)?;
+        if held.contains(&quit) {
+            println!("leaving reverse mode");
+            return Ok(());
+        }
+
+        let mut report = Report::default();
+        let mut directions = HashSet::new();
+        for (c, target) in &keys {
+            if !held.contains(c) {
+                continue;
+            }
+            match target {
+                Target::Button(Button::LeftTrigger2) => report.left_trigger = 1.0,
+                Target::Button(Button::RightTrigger2) => report.right_trigger = 1.0,
+                Target::Button(b) => {
+                    if !report.buttons.contains(b) {
+                        report.buttons.push(*b);
+                    }
+                }
+                Target::Stick(d) => {
+                    directions.insert(*d);
+                }
+            }
+        }
+        let on = |d| directions.contains(&d);
+        report.left = stick(
+            on(Direction::LeftStickUp),
+            on(Direction::LeftStickDown),
+            on(Direction::LeftStickLeft),
+            on(Direction::LeftStickRight),
+        );
+        report.right = stick(
+            on(Direction::RightStickUp),
+            on(Direction::RightStickDown),
+            on(Direction::RightStickLeft),
+            on(Direction::RightStickRight),
+        );
+
+        // mouse y grows downwards, stick y upwards
+        let target = clamp((
+            dx as f32 * config.mouse_sensitivity,
+            -dy as f32 * config.mouse_sensitivity,
+        ));
+        mouse.0 += (target.0 - mouse.0) * MOUSE_SMOOTHING;
+        mouse.1 += (target.1 - mouse.1) * MOUSE_SMOOTHING;
+        match config.mouse_stick {
+            Some(Stick::Left) => {
+                report.left = clamp((report.left.0 + mouse.0, report.left.1 + mouse.1))
+            }
+            Some(Stick::Right) => {
+                report.right = clamp((report.right.0 + mouse.0, report.right.1 + mouse.1))
+            }
+            None => (),
+        }
+
+        if last.as_ref() != Some(&report) {
+            pad.send(&report)?;
+            last = Some(report);
+        }
+        std::thread::sleep(Duration::from_millis(4));
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod stub {
+    use super::Report;
+    use std::collections::HashSet;
+
+    pub struct Capture;
+
+    impl Capture {
+        pub fn open(_watched: &[u16], _grab: bool) -> Result<Capture, String> {
+            Err("reverse mode is only supported on linux and windows".to_string())
+        }
+
+        pub fn poll(&mut self, _held: &mut HashSet<u16>) -> Result<(i32, i32), String> {
+            Ok((0, 0))
+        }
+    }
+
+    pub struct Pad;
+
+    impl Pad {
+        pub fn create() -> Result<Pad, String> {
+            Err("the virtual gamepad is only supported on linux and windows".to_string())
+        }
+
+        pub fn send(&mut self, _report: &Report) -> Result<(), String> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use super::Report;
+use crate::uinput::{Layout, Node, BTN_LEFT, EV_ABS, EV_KEY, EV_REL, EV_SYN, REL_X, REL_Y};
+use gilrs::Button;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+const KEY_A: u16 = 30;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+// _IOW('E', 0x90, int)
+const EVIOCGRAB: u64 = 0x4004_4590;
+
+// _IOC(_IOC_READ, 'E', 0x20 + ev, len)
+fn eviocgbit(ev: u16, len: usize) -> u64 {
+    0x8000_4520 | (len as u64) << 16 | ev as u64
+}
+
+// whether `code` is set in an EVIOCGBIT bitmask
+fn has(bits: &[u8], code: u16) -> bool {
+    bits.get(code as usize / 8)
+        .is_some_and(|b| b & (1 << (code % 8)) != 0)
+}
+
+// every keyboard and mouse evdev node
+pub struct Capture {
+    devices: Vec<File>,
+}
+
+impl Capture {
+    pub fn open(_watched: &[u16], grab: bool) -> Result<Capture, String> {
+        if grab {
+            // let go of the key that started us first, or the desktop never
+            // sees it released
+            std::thread::sleep(Duration::from_millis(500));
+        }
+
+        let entries = std::fs::read_dir("/dev/input").map_err(|e| e.to_string())?;
+        let mut devices = Vec::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if !path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("event"))
+            {
+                continue;
+            }
+            let Ok(file) = OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path)
+            else {
+                continue;
+            };
+            let fd = file.as_raw_fd();
+
+            let mut keys = [0u8; 96];
+            let mut rel = [0u8; 4];
+            unsafe {
+                libc::ioctl(fd, eviocgbit(EV_KEY, keys.len()) as _, keys.as_mut_ptr());
+                libc::ioctl(fd, eviocgbit(EV_REL, rel.len()) as _, rel.as_mut_ptr());
+            }
+            let keyboard = has(&keys, KEY_A);
+            let mouse = has(&keys, BTN_LEFT) && has(&rel, REL_X);
+            if !keyboard && !mouse {
+                continue;
+            }
+            if grab && unsafe { libc::ioctl(fd, EVIOCGRAB as _, 1 as libc::c_int) } < 0 {
+                println!(
+                    "can't grab {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                );
+            }
+            devices.push(file);
+        }
+        if devices.is_empty() {
+            return Err(
+                "no readable keyboard or mouse in /dev/input (is your user in the input group?)"
+                    .to_string(),
+            );
+        }
+        Ok(Capture { devices })
+    }
+
+    // updates `held` with the keys and mouse buttons pressed or released
+    // since the last poll and returns the mouse motion
+    pub fn poll(&mut self, held: &mut HashSet<u16>) -> Result<(i32, i32), String> {
+        let size = std::mem::size_of::<libc::input_event>();
+        let mut buf = vec![0u8; size * 64];
+        let mut motion = (0, 0);
+        for device in &mut self.devices {
+            loop {
+                let n = match device.read(&mut buf) {
+                    Ok(n) => n,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(e.to_string()),
+                };
+                if n == 0 {
+                    break;
+                }
+                for chunk in buf[..n].chunks_exact(size) {
+                    let ev: libc::input_event =
+                        unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const _) };
+                    match (ev.type_, ev.code) {
+                        // 2 is autorepeat
+                        (EV_KEY, code) if ev.value == 1 => {
+                            held.insert(code);
+                        }
+                        (EV_KEY, code) if ev.value == 0 => {
+                            held.remove(&code);
+                        }
+                        (EV_REL, REL_X) => motion.0 += ev.value,
+                        (EV_REL, REL_Y) => motion.1 += ev.value,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(motion)
+    }
+}
+
+// evdev's gamepad codes for each button, except the d-pad which is a hat
+fn button_code(button: Button) -> Option<u16> {
+    Some(match button {
+        Button::South => 0x130,
+        Button::East => 0x131,
+        Button::North => 0x133,
+        Button::West => 0x134,
+        Button::LeftTrigger => 0x136,
+        Button::RightTrigger => 0x137,
+        Button::Select => 0x13a,
+        Button::Start => 0x13b,
+        Button::Mode => 0x13c,
+        Button::LeftThumb => 0x13d,
+        Button::RightThumb => 0x13e,
+        _ => return None,
+    })
+}
+
+const BUTTONS: &[Button] = &[
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+];
+
+// a uinput device laid out like an xbox 360 pad, which games and sdl
+// recognise without a mapping
+pub struct Pad {
+    node: Node,
+}
+
+impl Pad {
+    pub fn create() -> Result<Pad, String> {
+        let keys: Vec<u16> = BUTTONS.iter().filter_map(|b| button_code(*b)).collect();
+        let node = Node::create(&Layout {
+            name: "controller2keys virtual pad",
+            id: (0x03, 0x045e, 0x028e), // BUS_USB, an xbox 360 pad
+            keys: &keys,
+            rels: &[],
+            abs: &[
+                (ABS_X, -32768, 32767),
+                (ABS_Y, -32768, 32767),
+                (ABS_RX, -32768, 32767),
+                (ABS_RY, -32768, 32767),
+                (ABS_Z, 0, 255),
+                (ABS_RZ, 0, 255),
+                (ABS_HAT0X, -1, 1),
+                (ABS_HAT0Y, -1, 1),
+            ],
+        })?;
+        Ok(Pad { node })
+    }
+
+    pub fn send(&mut self, report: &Report) -> Result<(), String> {
+        let stick = |v: f32| (v * 32767.0).round() as i32;
+        let trigger = |v: f32| (v * 255.0).round() as i32;
+        let hat = |negative, positive| {
+            report.buttons.contains(&positive) as i32 - report.buttons.contains(&negative) as i32
+        };
+
+        let mut events = Vec::new();
+        for b in BUTTONS {
+            let code = button_code(*b).expect("every listed button has a code");
+            events.push((EV_KEY, code, report.buttons.contains(b) as i32));
+        }
+        // evdev's y axes point down
+        events.extend([
+            (EV_ABS, ABS_X, stick(report.left.0)),
+            (EV_ABS, ABS_Y, -stick(report.left.1)),
+            (EV_ABS, ABS_RX, stick(report.right.0)),
+            (EV_ABS, ABS_RY, -stick(report.right.1)),
+            (EV_ABS, ABS_Z, trigger(report.left_trigger)),
+            (EV_ABS, ABS_RZ, trigger(report.right_trigger)),
+            (EV_ABS, ABS_HAT0X, hat(Button::DPadLeft, Button::DPadRight)),
+            (EV_ABS, ABS_HAT0Y, hat(Button::DPadUp, Button::DPadDown)),
+            (EV_SYN, 0, 0),
+        ]);
+        self.node.send(&events)
+    }
+}
@@ -0,0 +1,130 @@
+use super::Report;
+#[cfg(feature = "vigem")]
+use gilrs::Button;
+use std::collections::HashSet;
+#[cfg(feature = "vigem")]
+use vigem_client::{Client, TargetId, XButtons, XGamepad, Xbox360Wired};
+use windows::Win32::Foundation::POINT;
+use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetCursorPos, GetSystemMetrics, SetCursorPos, SM_CXSCREEN, SM_CYSCREEN,
+};
+
+// key state is polled, so the desktop still sees the keys. with `grab` the
+// pointer is put back in the middle of the screen every poll so it can't
+// wander off into other windows
+pub struct Capture {
+    watched: Vec<u16>,
+    grab: bool,
+    last: Option<POINT>,
+}
+
+impl Capture {
+    pub fn open(watched: &[u16], grab: bool) -> Result<Capture, String> {
+        Ok(Capture {
+            watched: watched.to_vec(),
+            grab,
+            last: None,
+        })
+    }
+
+    pub fn poll(&mut self, held: &mut HashSet<u16>) -> Result<(i32, i32), String> {
+        for vk in &self.watched {
+            // the high bit is set while the key is down
+            if unsafe { GetAsyncKeyState(*vk as i32) } < 0 {
+                held.insert(*vk);
+            } else {
+                held.remove(vk);
+            }
+        }
+
+        let mut point = POINT::default();
+        if !unsafe { GetCursorPos(&mut point) }.as_bool() {
+            return Ok((0, 0));
+        }
+        let motion = match self.last {
+            Some(last) => (point.x - last.x, point.y - last.y),
+            None => (0, 0),
+        };
+        if self.grab {
+            let centre = unsafe {
+                POINT {
+                    x: GetSystemMetrics(SM_CXSCREEN) / 2,
+                    y: GetSystemMetrics(SM_CYSCREEN) / 2,
+                }
+            };
+            unsafe { SetCursorPos(centre.x, centre.y) };
+            self.last = Some(centre);
+        } else {
+            self.last = Some(point);
+        }
+        Ok(motion)
+    }
+}
+
+// an xbox 360 pad on the vigembus driver
+#[cfg(feature = "vigem")]
+pub struct Pad {
+    target: Xbox360Wired<Client>,
+}
+
+#[cfg(feature = "vigem")]
+impl Pad {
+    pub fn create() -> Result<Pad, String> {
+        let client = Client::connect().map_err(|e| format!("vigembus not installed? {}", e))?;
+        let mut target = Xbox360Wired::new(client, TargetId::XBOX360_WIRED);
+        target.plugin().map_err(|e| e.to_string())?;
+        target.wait_ready().map_err(|e| e.to_string())?;
+        Ok(Pad { target })
+    }
+
+    pub fn send(&mut self, report: &Report) -> Result<(), String> {
+        let mut buttons = 0;
+        for b in &report.buttons {
+            buttons |= match b {
+                Button::DPadUp => XButtons::UP,
+                Button::DPadDown => XButtons::DOWN,
+                Button::DPadLeft => XButtons::LEFT,
+                Button::DPadRight => XButtons::RIGHT,
+                Button::Start => XButtons::START,
+                Button::Select => XButtons::BACK,
+                Button::LeftThumb => XButtons::LTHUMB,
+                Button::RightThumb => XButtons::RTHUMB,
+                Button::LeftTrigger => XButtons::LB,
+                Button::RightTrigger => XButtons::RB,
+                Button::Mode => XButtons::GUIDE,
+                Button::South => XButtons::A,
+                Button::East => XButtons::B,
+                Button::West => XButtons::X,
+                Button::North => XButtons::Y,
+                _ => 0,
+            };
+        }
+        let stick = |v: f32| (v * 32767.0).round() as i16;
+        let trigger = |v: f32| (v * 255.0).round() as u8;
+        let gamepad = XGamepad {
+            buttons: XButtons { raw: buttons },
+            left_trigger: trigger(report.left_trigger),
+            right_trigger: trigger(report.right_trigger),
+            thumb_lx: stick(report.left.0),
+            thumb_ly: stick(report.left.1),
+            thumb_rx: stick(report.right.0),
+            thumb_ry: stick(report.right.1),
+        };
+        self.target.update(&gamepad).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(feature = "vigem"))]
+pub struct Pad;
+
+#[cfg(not(feature = "vigem"))]
+impl Pad {
+    pub fn create() -> Result<Pad, String> {
+        Err("built without the `vigem` feature".to_string())
+    }
+
+    pub fn send(&mut self, _report: &Report) -> Result<(), String> {
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::input::InputState;
+use crate::keymap::parse_chord;
+use crate::scancode;
+use crate::stick::Stick;
+use serde::Deserialize;
+
+// a key or mouse button held while a stick is pushed all the way out, in
+// whatever direction, on top of what the stick does anyway, e.g.
+// `outer_ring = [{ stick = "left", key = "ctrl" }]` to sprint while the
+// stick keeps walking
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RingConfig {
+    pub stick: Stick,
+    // how far out the stick has to go
+    #[serde(default = "default_threshold")]
+    pub threshold: f32,
+    // back inside this lets go. a little inside the threshold, so resting
+    // right on the edge doesn't chatter
+    #[serde(default = "default_release")]
+    pub release: f32,
+    pub key: Option<String>,
+    pub mouse: Option<String>,
+}
+
+fn default_threshold() -> f32 {
+    0.95
+}
+
+fn default_release() -> f32 {
+    0.85
+}
+
+struct Ring {
+    stick: Stick,
+    threshold: f32,
+    release: f32,
+    press: Vec<Action>,
+    unpress: Vec<Action>,
+    out: bool,
+}
+
+impl Ring {
+    fn new(config: &RingConfig, physical: bool) -> Result<Ring, String> {
+        let (press, unpress) = match (&config.key, &config.mouse) {
+            (Some(name), None) => {
+                let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+                let keys: Vec<_> = if physical {
+                    keys.into_iter().map(scancode::physical).collect()
+                } else {
+                    keys
+                };
+                (
+                    keys.iter().map(|k| Action::KeyDown(*k)).collect(),
+                    keys.iter().rev().map(|k| Action::KeyUp(*k)).collect(),
+                )
+            }
+            (None, Some(name)) => {
+                let button = parse_mouse_button(name)
+                    .ok_or_else(|| format!("unknown mouse button '{}'", name))?;
+                (
+                    vec![Action::MouseDown(button)],
+                    vec![Action::MouseUp(button)],
+                )
+            }
+            _ => return Err("exactly one of key or mouse must be set".to_string()),
+        };
+        Ok(Ring {
+            stick: config.stick,
+            threshold: config.threshold,
+            release: config.release.min(config.threshold),
+            press,
+            unpress,
+            out: false,
+        })
+    }
+}
+
+pub struct OuterRings {
+    rings: Vec<Ring>,
+}
+
+impl OuterRings {
+    // `physical` is the profile's key_positions, resolved
+    pub fn new(configs: &[RingConfig], physical: bool) -> OuterRings {
+        let mut rings = Vec::new();
+        for config in configs {
+            match Ring::new(config, physical) {
+                Ok(ring) => rings.push(ring),
+                Err(e) => println!("ignoring outer ring on {:?} stick: {}", config.stick, e),
+            }
+        }
+        OuterRings { rings }
+    }
+
+    // call when a stick axis changes
+    pub fn update(&mut self, stick: Stick, input: &InputState) -> Vec<Action> {
+        let (x, y) = stick.axes();
+        let magnitude = input.axis(x).hypot(input.axis(y));
+        let mut actions = Vec::new();
+        for ring in self.rings.iter_mut().filter(|r| r.stick == stick) {
+            if !ring.out && magnitude >= ring.threshold {
+                ring.out = true;
+                actions.extend(ring.press.iter().cloned());
+            } else if ring.out && magnitude < ring.release {
+                ring.out = false;
+                actions.extend(ring.unpress.iter().cloned());
+            }
+        }
+        actions
+    }
+}
@@ -0,0 +1,160 @@
+use enigo::Key;
+use serde::Deserialize;
+
+// whether single-character keys mean "the key that types this character" or
+// "the key in this character's position on a us qwerty keyboard". the
+// difference matters for wasd on azerty, dvorak and friends
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPositions {
+    #[default]
+    Layout,
+    Physical,
+    // physical when the active keyboard layout looks like it isn't qwerty
+    Auto,
+}
+
+impl KeyPositions {
+    pub fn physical(self) -> bool {
+        match self {
+            KeyPositions::Layout => false,
+            KeyPositions::Physical => true,
+            KeyPositions::Auto => non_qwerty_layout(),
+        }
+    }
+}
+
+// us qwerty character -> (evdev / set 1 scancode, macos virtual keycode)
+const POSITIONS: &[(char, u16, u16)] = &[
+    ('1', 2, 0x12),
+    ('2', 3, 0x13),
+    ('3', 4, 0x14),
+    ('4', 5, 0x15),
+    ('5', 6, 0x17),
+    ('6', 7, 0x16),
+    ('7', 8, 0x1a),
+    ('8', 9, 0x1c),
+    ('9', 10, 0x19),
+    ('0', 11, 0x1d),
+    ('-', 12, 0x1b),
+    ('=', 13, 0x18),
+    ('q', 16, 0x0c),
+    ('w', 17, 0x0d),
+    ('e', 18, 0x0e),
+    ('r', 19, 0x0f),
+    ('t', 20, 0x11),
+    ('y', 21, 0x10),
+    ('u', 22, 0x20),
+    ('i', 23, 0x22),
+    ('o', 24, 0x1f),
+    ('p', 25, 0x23),
+    ('[', 26, 0x21),
+    (']', 27, 0x1e),
+    ('a', 30, 0x00),
+    ('s', 31, 0x01),
+    ('d', 32, 0x02),
+    ('f', 33, 0x03),
+    ('g', 34, 0x05),
+    ('h', 35, 0x04),
+    ('j', 36, 0x26),
+    ('k', 37, 0x28),
+    ('l', 38, 0x25),
+    (';', 39, 0x29),
+    ('\'', 40, 0x27),
+    ('`', 41, 0x32),
+    ('\\', 43, 0x2a),
+    ('z', 44, 0x06),
+    ('x', 45, 0x07),
+    ('c', 46, 0x08),
+    ('v', 47, 0x09),
+    ('b', 48, 0x0b),
+    ('n', 49, 0x2d),
+    ('m', 50, 0x2e),
+    (',', 51, 0x2b),
+    ('.', 52, 0x2f),
+    ('/', 53, 0x2c),
+];
+
+// the key at `c`'s us qwerty position, as a raw key for the backend.
+// anything that isn't a layout character is passed through unchanged
+pub fn physical(key: Key) -> Key {
+    let c = match key {
+        Key::Layout(c) => c.to_ascii_lowercase(),
+        key => return key,
+    };
+    match POSITIONS.iter().find(|(ch, _, _)| *ch == c) {
+        Some(&(_, scancode, mac)) => raw_key(scancode, mac),
+        None => key,
+    }
+}
+
+// the evdev code of the key at `c`'s us qwerty position
+pub fn evdev(c: char) -> Option<u16> {
+    let c = c.to_ascii_lowercase();
+    POSITIONS
+        .iter()
+        .find(|(ch, _, _)| *ch == c)
+        .map(|&(_, scancode, _)| scancode)
+}
+
+// macos raw keys are virtual keycodes, which are positional already
+#[cfg(target_os = "macos")]
+fn raw_key(_scancode: u16, mac: u16) -> Key {
+    Key::Raw(mac)
+}
+
+// windows raw keys are virtual-key codes, which follow the layout, so ask
+// windows which one sits at the scancode right now
+#[cfg(target_os = "windows")]
+fn raw_key(scancode: u16, _mac: u16) -> Key {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VSC_TO_VK};
+    Key::Raw(unsafe { MapVirtualKeyW(scancode as u32, MAPVK_VSC_TO_VK) } as u16)
+}
+
+// xdo treats a number as an x keycode, which is the evdev code plus 8
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn raw_key(scancode: u16, _mac: u16) -> Key {
+    Key::Raw(scancode + 8)
+}
+
+// checks whether w, a, s and d are where qwerty has them
+#[cfg(target_os = "windows")]
+fn non_qwerty_layout() -> bool {
+    ['w', 'a', 's', 'd']
+        .iter()
+        .any(|&c| physical(Key::Layout(c)) != Key::Raw(c.to_ascii_uppercase() as u16))
+}
+
+// asks xkb for the layout. layouts that only move keys away from wasd are
+// all that matter here
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn non_qwerty_layout() -> bool {
+    let output = match std::process::Command::new("setxkbmap")
+        .arg("-query")
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(_) => return false,
+    };
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|l| l.strip_prefix(name))
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_default()
+    };
+    let (layout, variant) = (field("layout:"), field("variant:"));
+    let first_layout = layout.split(',').next().unwrap_or_default();
+    let first_variant = variant.split(',').next().unwrap_or_default();
+    matches!(first_layout, "fr" | "be")
+        || ["dvorak", "colemak", "azerty", "workman", "bepo"]
+            .iter()
+            .any(|v| first_variant.contains(v))
+}
+
+// no cheap way to read the input source without carbon, so auto stays on
+// the layout
+#[cfg(target_os = "macos")]
+fn non_qwerty_layout() -> bool {
+    false
+}
@@ -0,0 +1,194 @@
+#[cfg(feature = "lua")]
+use crate::action::parse_mouse_button;
+use crate::action::Action;
+#[cfg(feature = "lua")]
+use crate::keymap::parse_key;
+use gilrs::{Axis, Button};
+#[cfg(feature = "lua")]
+use mlua::{Function, Lua};
+#[cfg(feature = "lua")]
+use std::cell::RefCell;
+#[cfg(feature = "lua")]
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "lua")]
+use std::rc::Rc;
+#[cfg(feature = "lua")]
+use std::time::Instant;
+
+// a lua script attached to a profile. scripts define any of the global
+// functions `on_button(name, pressed)`, `on_axis(name, value)` and `on_tick()`,
+// keep whatever state they like in globals, and emit actions through the
+// `c2k` table:
+//
+//   c2k.key_down("space")  c2k.key_up("space")  c2k.tap("e")
+//   c2k.mouse_down("left") c2k.mouse_up("left") c2k.click("right")
+//   c2k.move(dx, dy)       c2k.scroll(n)        c2k.now() -- ms since start
+//   c2k.paste("text")      c2k.text("ä ñ 🎮") -- typed as unicode
+#[cfg(feature = "lua")]
+pub struct Script {
+    name: String,
+    lua: Lua,
+    actions: Rc<RefCell<Vec<Action>>>,
+}
+
+#[cfg(feature = "lua")]
+impl Script {
+    pub fn load(path: &Path) -> Result<Script, String> {
+        Script::load_lua(path).map_err(|e| e.to_string())
+    }
+
+    fn load_lua(path: &Path) -> mlua::Result<Script> {
+        let lua = Lua::new();
+        let actions = Rc::new(RefCell::new(Vec::new()));
+        let started = Instant::now();
+
+        let c2k = lua.create_table()?;
+
+        let key_fn = |make: fn(enigo::Key) -> Action| {
+            let queue = actions.clone();
+            lua.create_function(move |_, name: String| {
+                let key = parse_key(&name)
+                    .ok_or_else(|| mlua::Error::RuntimeError(format!("unknown key '{}'", name)))?;
+                queue.borrow_mut().push(make(key));
+                Ok(())
+            })
+        };
+        c2k.set("key_down", key_fn(Action::KeyDown)?)?;
+        c2k.set("key_up", key_fn(Action::KeyUp)?)?;
+        c2k.set("tap", key_fn(Action::KeyClick)?)?;
+
+        let mouse_fn = |make: fn(enigo::MouseButton) -> Action| {
+            let queue = actions.clone();
+            lua.create_function(move |_, name: String| {
+                let button = parse_mouse_button(&name).ok_or_else(|| {
+                    mlua::Error::RuntimeError(format!("unknown mouse button '{}'", name))
+                })?;
+                queue.borrow_mut().push(make(button));
+                Ok(())
+            })
+        };
+        c2k.set("mouse_down", mouse_fn(Action::MouseDown)?)?;
+        c2k.set("mouse_up", mouse_fn(Action::MouseUp)?)?;
+        c2k.set("click", mouse_fn(Action::MouseClick)?)?;
+
+        let queue = actions.clone();
+        c2k.set(
+            "move",
+            lua.create_function(move |_, (x, y): (i32, i32)| {
+                queue.borrow_mut().push(Action::MouseMove(x, y));
+                Ok(())
+            })?,
+        )?;
+
+        let queue = actions.clone();
+        c2k.set(
+            "scroll",
+            lua.create_function(move |_, amount: i32| {
+                queue.borrow_mut().push(Action::Scroll(amount));
+                Ok(())
+            })?,
+        )?;
+
+        let queue = actions.clone();
+        c2k.set(
+            "paste",
+            lua.create_function(move |_, text: String| {
+                queue.borrow_mut().push(Action::Paste(text));
+                Ok(())
+            })?,
+        )?;
+
+        let queue = actions.clone();
+        c2k.set(
+            "text",
+            lua.create_function(move |_, text: String| {
+                queue.borrow_mut().push(Action::Text(text));
+                Ok(())
+            })?,
+        )?;
+
+        c2k.set(
+            "now",
+            lua.create_function(move |_, ()| Ok(started.elapsed().as_millis() as u64))?,
+        )?;
+
+        lua.globals().set("c2k", c2k)?;
+
+        let source = fs::read_to_string(path).map_err(mlua::Error::external)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        lua.load(&source).set_name(name.as_str()).exec()?;
+
+        Ok(Script { name, lua, actions })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // call a global hook if the script defines it
+    fn call<'lua, A: mlua::IntoLuaMulti<'lua>>(
+        &'lua self,
+        hook: &str,
+        args: A,
+    ) -> mlua::Result<()> {
+        match self.lua.globals().get::<_, Option<Function>>(hook)? {
+            Some(f) => f.call::<_, ()>(args),
+            None => Ok(()),
+        }
+    }
+
+    pub fn on_button(&self, button: Button, pressed: bool) -> Result<(), String> {
+        self.call("on_button", (format!("{:?}", button), pressed))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn on_axis(&self, axis: Axis, value: f32) -> Result<(), String> {
+        self.call("on_axis", (format!("{:?}", axis), value))
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn on_tick(&self) -> Result<(), String> {
+        self.call("on_tick", ()).map_err(|e| e.to_string())
+    }
+
+    // actions emitted since the last call
+    pub fn take_actions(&self) -> Vec<Action> {
+        self.actions.borrow_mut().drain(..).collect()
+    }
+}
+
+// without the `lua` feature scripts fail to load, so profiles run without
+// them
+#[cfg(not(feature = "lua"))]
+pub struct Script;
+
+#[cfg(not(feature = "lua"))]
+impl Script {
+    pub fn load(_path: &Path) -> Result<Script, String> {
+        Err("built without the `lua` feature".to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        ""
+    }
+
+    pub fn on_button(&self, _button: Button, _pressed: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn on_axis(&self, _axis: Axis, _value: f32) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn on_tick(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn take_actions(&self) -> Vec<Action> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,60 @@
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// scroll faster the quicker the bumpers are tapped, e.g.
+// `scroll_acceleration = { window_ms = 300, growth = 1.5, max = 10 }`.
+// a single tap always scrolls one click
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScrollAcceleration {
+    // taps closer together than this count as one run
+    pub window_ms: u64,
+    // each extra tap in a run multiplies the scroll amount by this
+    pub growth: f32,
+    // most clicks a single tap can scroll
+    pub max: u32,
+}
+
+impl Default for ScrollAcceleration {
+    fn default() -> Self {
+        ScrollAcceleration {
+            window_ms: 300,
+            growth: 1.5,
+            max: 10,
+        }
+    }
+}
+
+pub struct ScrollAccelerator {
+    config: ScrollAcceleration,
+    last: Option<(Instant, i32)>,
+    amount: f32,
+}
+
+impl ScrollAccelerator {
+    pub fn new(config: &ScrollAcceleration) -> ScrollAccelerator {
+        ScrollAccelerator {
+            config: config.clone(),
+            last: None,
+            amount: 1.0,
+        }
+    }
+
+    // wheel clicks for a tap in `direction` (1 or -1). changing direction
+    // starts a new run, so reversing is always precise
+    pub fn tap(&mut self, direction: i32) -> i32 {
+        let now = Instant::now();
+        let window = Duration::from_millis(self.config.window_ms);
+        let continuing = matches!(
+            self.last,
+            Some((at, dir)) if dir == direction && now - at <= window
+        );
+        self.amount = if continuing {
+            (self.amount * self.config.growth).min(self.config.max.max(1) as f32)
+        } else {
+            1.0
+        };
+        self.last = Some((now, direction));
+        direction * self.amount.round() as i32
+    }
+}
@@ -0,0 +1,135 @@
+use gilrs::{Event, EventType, GamepadId, Gilrs};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// a recorded session: controller input as json lines, one event per line
+// with the milliseconds since recording started. codes in the events are
+// platform-specific, but nothing in the mapping looks at them
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    ms: u64,
+    id: GamepadId,
+    event: EventType,
+}
+
+// only what the mapping reacts to; connects and disconnects would refer to
+// controllers that aren't there on replay
+fn recorded(event: &EventType) -> bool {
+    matches!(
+        event,
+        EventType::ButtonPressed(..)
+            | EventType::ButtonReleased(..)
+            | EventType::ButtonChanged(..)
+            | EventType::AxisChanged(..)
+    )
+}
+
+// `controller2keys record <file>`. lines are flushed as they're written, so
+// stopping with ctrl+c loses nothing
+pub fn record(path: &Path) -> Result<(), String> {
+    let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut file = LineWriter::new(file);
+    println!("recording to {}, ctrl+c to stop", path.display());
+    let started = Instant::now();
+    loop {
+        let Some(Event { id, event, .. }) = gilrs.next_event_blocking(None) else {
+            continue;
+        };
+        if !recorded(&event) {
+            continue;
+        }
+        let entry = Entry {
+            ms: started.elapsed().as_millis() as u64,
+            id,
+            event,
+        };
+        let line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    }
+}
+
+// `controller2keys monitor`: raw events, for finding out what a controller
+// actually reports
+pub fn monitor() -> Result<(), String> {
+    let mut gilrs = Gilrs::new().map_err(|e| e.to_string())?;
+    for (id, gamepad) in gilrs.gamepads() {
+        println!("{}: {} ({:?})", id, gamepad.name(), gamepad.power_info());
+    }
+    println!("watching controller events, ctrl+c to stop");
+    loop {
+        let Some(Event { id, event, .. }) = gilrs.next_event_blocking(None) else {
+            continue;
+        };
+        match event {
+            EventType::ButtonPressed(button, code) => {
+                println!("{}: {:?} pressed ({})", id, button, code)
+            }
+            EventType::ButtonReleased(button, code) => {
+                println!("{}: {:?} released ({})", id, button, code)
+            }
+            EventType::ButtonChanged(button, value, code) => {
+                println!("{}: {:?} {:.3} ({})", id, button, value, code)
+            }
+            EventType::AxisChanged(axis, value, code) => {
+                println!("{}: {:?} {:.3} ({})", id, axis, value, code)
+            }
+            EventType::Connected => println!("{}: connected {}", id, gilrs.gamepad(id).name()),
+            EventType::Disconnected => println!("{}: disconnected", id),
+            other => println!("{}: {:?}", id, other),
+        }
+    }
+}
+
+// plays a recorded session back into the main loop with its original
+// timing, for `controller2keys replay`
+pub struct Player {
+    entries: VecDeque<Entry>,
+    // set on the first poll, so loading time doesn't count
+    started: Option<Instant>,
+}
+
+impl Player {
+    pub fn load(path: &Path) -> Result<Player, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut entries = VecDeque::new();
+        for (n, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line)
+                .map_err(|e| format!("{} line {}: {}", path.display(), n + 1, e))?;
+            entries.push_back(entry);
+        }
+        Ok(Player {
+            entries,
+            started: None,
+        })
+    }
+
+    // the next event that's due, if any
+    pub fn next(&mut self) -> Option<Event> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let due = self.entries.front()?.ms <= started.elapsed().as_millis() as u64;
+        if !due {
+            return None;
+        }
+        let entry = self.entries.pop_front()?;
+        Some(Event::new(entry.id, entry.event))
+    }
+
+    // the next event and when it happened, however long that is from now
+    pub fn pop(&mut self) -> Option<(Duration, EventType)> {
+        let entry = self.entries.pop_front()?;
+        Some((Duration::from_millis(entry.ms), entry.event))
+    }
+
+    pub fn finished(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::action::Action;
+use crate::config::Config;
+use crate::engine::Engine;
+use crate::session::Player;
+use gilrs::EventType;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how often the engine ticks while waiting for the next event, as the main
+// loop does
+const TICK: Duration = Duration::from_millis(4);
+
+// `controller2keys simulate --input <session>`: a recorded session through
+// a config's mapping, printed as a timeline instead of injected. it takes
+// as long as the session did, so holds, repeats and timers come out as they
+// would live; `--fast` skips the waiting and with it everything timed
+pub fn run(
+    config: Option<&Path>,
+    input: &Path,
+    profile: Option<&str>,
+    fast: bool,
+) -> Result<(), String> {
+    let config = match config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_or_default(),
+    };
+    let profile = profile.unwrap_or(&config.profile);
+    let mut engine = Engine::new(&config, profile)?;
+    let mut player = Player::load(input)?;
+    println!("simulating {} with profile '{}'", input.display(), profile);
+
+    let started = Instant::now();
+    let mut count = 0;
+    while let Some((at, event)) = player.pop() {
+        if !fast {
+            while started.elapsed() + TICK < at {
+                count += print(started.elapsed(), None, engine.tick());
+                thread::sleep(TICK);
+            }
+        }
+        let now = if fast { at } else { started.elapsed().max(at) };
+        count += print(now, Some(&event), engine.feed(event));
+        if fast {
+            count += print(now, None, engine.tick());
+        }
+    }
+    println!("{} actions", count);
+    Ok(())
+}
+
+// one line per action, with the event that caused it. tick output has none
+fn print(at: Duration, event: Option<&EventType>, actions: Vec<Action>) -> usize {
+    let cause = match event {
+        Some(EventType::ButtonPressed(button, _)) => format!("{:?} pressed", button),
+        Some(EventType::ButtonReleased(button, _)) => format!("{:?} released", button),
+        Some(EventType::ButtonChanged(button, value, _)) => format!("{:?} {:.2}", button, value),
+        Some(EventType::AxisChanged(axis, value, _)) => format!("{:?} {:.2}", axis, value),
+        Some(other) => format!("{:?}", other),
+        None => "tick".to_string(),
+    };
+    for action in &actions {
+        println!("{:>9.3}s  {:<22} -> {:?}", at.as_secs_f64(), cause, action);
+    }
+    actions.len()
+}
@@ -0,0 +1,106 @@
+use gilrs::Axis;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::time::Instant;
+
+// filters stick readings before they become mouse motion, so a shaky hand or
+// a noisy stick doesn't jitter the aim, e.g. `mouse_smoothing = { ema = 0.4 }`
+// or `mouse_smoothing = { one_euro = { min_cutoff = 1.0, beta = 0.05 } }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SmoothingConfig {
+    // weight of each new reading, 0 to 1. lower is smoother but lags more
+    Ema(f32),
+    // smooths hard while the stick is still and hardly at all while it moves
+    // fast, so holding steady doesn't cost flick speed
+    OneEuro(OneEuroConfig),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OneEuroConfig {
+    // hz. lower is smoother while holding steady
+    pub min_cutoff: f32,
+    // how quickly smoothing lets up as the stick speeds up
+    pub beta: f32,
+}
+
+impl Default for OneEuroConfig {
+    fn default() -> Self {
+        OneEuroConfig {
+            min_cutoff: 1.0,
+            beta: 0.05,
+        }
+    }
+}
+
+// cutoff for the speed estimate, from the one euro paper
+const DERIVATIVE_CUTOFF: f32 = 1.0;
+
+// one axis's filter state
+struct Filter {
+    value: f32,
+    // smoothed speed, in full deflections per second
+    speed: f32,
+    last: Instant,
+}
+
+// a filter per stick axis, fed from the axis events
+pub struct Smoother {
+    config: SmoothingConfig,
+    filters: HashMap<Axis, Filter>,
+}
+
+impl Smoother {
+    pub fn new(config: &SmoothingConfig) -> Smoother {
+        Smoother {
+            config: config.clone(),
+            filters: HashMap::new(),
+        }
+    }
+
+    pub fn filter(&mut self, axis: Axis, value: f32) -> f32 {
+        let now = Instant::now();
+        let Some(filter) = self.filters.get_mut(&axis) else {
+            self.filters.insert(
+                axis,
+                Filter {
+                    value,
+                    speed: 0.0,
+                    last: now,
+                },
+            );
+            return value;
+        };
+        // back at rest stops at once, rather than drifting on after release
+        if value == 0.0 {
+            filter.value = 0.0;
+            filter.speed = 0.0;
+            filter.last = now;
+            return 0.0;
+        }
+
+        match &self.config {
+            SmoothingConfig::Ema(weight) => {
+                filter.value += weight.clamp(0.01, 1.0) * (value - filter.value);
+            }
+            SmoothingConfig::OneEuro(config) => {
+                // events can come in faster than the clock ticks
+                let dt = (now - filter.last).as_secs_f32().max(0.001);
+                let speed = (value - filter.value) / dt;
+                filter.speed += alpha(DERIVATIVE_CUTOFF, dt) * (speed - filter.speed);
+                let cutoff = config.min_cutoff.max(0.01) + config.beta * filter.speed.abs();
+                filter.value += alpha(cutoff, dt) * (value - filter.value);
+            }
+        }
+        filter.last = now;
+        filter.value
+    }
+}
+
+// low-pass weight for a new sample `dt` seconds after the last
+fn alpha(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
@@ -0,0 +1,202 @@
+use crate::action::Action;
+use crate::keymap::parse_key;
+use crate::scancode;
+use enigo::Key;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocdMode {
+    // both held is neither
+    #[default]
+    Neutral,
+    // the newer one wins, and the older one comes back when it's let go
+    Last,
+    // the older one wins until it's let go
+    First,
+}
+
+// what happens when opposite directions are held at once, say the d-pad
+// holding `a` while the stick holds `d`. from `[socd]`:
+// `mode = "last"`, `pairs = [["w", "s"], ["a", "d"]]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SocdConfig {
+    pub mode: SocdMode,
+    pub pairs: Vec<[String; 2]>,
+}
+
+impl Default for SocdConfig {
+    fn default() -> Self {
+        let pair = |a: &str, b: &str| [a.to_string(), b.to_string()];
+        SocdConfig {
+            mode: SocdMode::Neutral,
+            pairs: vec![
+                pair("w", "s"),
+                pair("a", "d"),
+                pair("up", "down"),
+                pair("left", "right"),
+            ],
+        }
+    }
+}
+
+// sits in front of the output, turning key presses and releases from every
+// source into what should really be held
+pub struct Socd {
+    mode: SocdMode,
+    pairs: Vec<(Key, Key)>,
+    // paired keys something asked to hold, whether or not they're down,
+    // with when they were asked for
+    wanted: HashMap<Key, u64>,
+    asked: u64,
+}
+
+impl Socd {
+    pub fn new(config: &SocdConfig) -> Socd {
+        let mut pairs = Vec::new();
+        for [a, b] in &config.pairs {
+            match (parse_key(a), parse_key(b)) {
+                (Some(a), Some(b)) => {
+                    pairs.push((a, b));
+                    // profiles mapping by key position send these instead
+                    let physical = (scancode::physical(a), scancode::physical(b));
+                    if physical != (a, b) {
+                        pairs.push(physical);
+                    }
+                }
+                _ => println!("ignoring socd pair {} / {}: unknown key", a, b),
+            }
+        }
+        Socd {
+            mode: config.mode,
+            pairs,
+            wanted: HashMap::new(),
+            asked: 0,
+        }
+    }
+
+    fn opposite(&self, key: Key) -> Option<Key> {
+        self.pairs.iter().find_map(|&(a, b)| match key {
+            k if k == a => Some(b),
+            k if k == b => Some(a),
+            _ => None,
+        })
+    }
+
+    // whether `key` should be down given what's wanted
+    fn down(&self, key: Key, opposite: Key) -> bool {
+        let Some(&at) = self.wanted.get(&key) else {
+            return false;
+        };
+        match self.wanted.get(&opposite) {
+            None => true,
+            Some(&other) => match self.mode {
+                SocdMode::Neutral => false,
+                SocdMode::Last => at > other,
+                SocdMode::First => at < other,
+            },
+        }
+    }
+
+    // what to send for a press or release of a paired key, or None to send
+    // the action as is
+    pub fn resolve(&mut self, action: &Action) -> Option<Vec<Action>> {
+        let (key, press) = match *action {
+            Action::KeyDown(key) => (key, true),
+            Action::KeyUp(key) => (key, false),
+            _ => return None,
+        };
+        let opposite = self.opposite(key)?;
+
+        let before = [self.down(key, opposite), self.down(opposite, key)];
+        if press {
+            if !self.wanted.contains_key(&key) {
+                self.asked += 1;
+                self.wanted.insert(key, self.asked);
+            }
+        } else if self.wanted.remove(&key).is_none() {
+            // nothing asked for it through here, so it's up already
+            return Some(vec![Action::KeyUp(key)]);
+        }
+        let after = [self.down(key, opposite), self.down(opposite, key)];
+
+        // releases first, so both are never down together
+        let changes: Vec<_> = [key, opposite]
+            .into_iter()
+            .zip(before.into_iter().zip(after))
+            .filter(|(_, (was, is))| was != is)
+            .collect();
+        let mut actions: Vec<_> = changes
+            .iter()
+            .filter(|(_, (was, _))| *was)
+            .map(|(k, _)| Action::KeyUp(*k))
+            .collect();
+        actions.extend(
+            changes
+                .iter()
+                .filter(|(_, (_, is))| *is)
+                .map(|(k, _)| Action::KeyDown(*k)),
+        );
+        Some(actions)
+    }
+
+    pub fn clear(&mut self) {
+        self.wanted.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Action::{KeyDown, KeyUp};
+
+    const A: Key = Key::Layout('a');
+    const D: Key = Key::Layout('d');
+
+    fn socd(mode: SocdMode) -> Socd {
+        Socd::new(&SocdConfig {
+            mode,
+            ..SocdConfig::default()
+        })
+    }
+
+    #[test]
+    fn neutral_lets_go_of_both() {
+        let mut socd = socd(SocdMode::Neutral);
+        assert_eq!(socd.resolve(&KeyDown(A)), Some(vec![KeyDown(A)]));
+        assert_eq!(socd.resolve(&KeyDown(D)), Some(vec![KeyUp(A)]));
+        assert_eq!(socd.resolve(&KeyUp(A)), Some(vec![KeyDown(D)]));
+        assert_eq!(socd.resolve(&KeyUp(D)), Some(vec![KeyUp(D)]));
+    }
+
+    #[test]
+    fn last_brings_the_older_one_back() {
+        let mut socd = socd(SocdMode::Last);
+        socd.resolve(&KeyDown(A));
+        assert_eq!(socd.resolve(&KeyDown(D)), Some(vec![KeyUp(A), KeyDown(D)]));
+        assert_eq!(socd.resolve(&KeyUp(D)), Some(vec![KeyUp(D), KeyDown(A)]));
+    }
+
+    #[test]
+    fn first_keeps_the_older_one() {
+        let mut socd = socd(SocdMode::First);
+        socd.resolve(&KeyDown(A));
+        assert_eq!(socd.resolve(&KeyDown(D)), Some(vec![]));
+        assert_eq!(socd.resolve(&KeyUp(A)), Some(vec![KeyUp(A), KeyDown(D)]));
+    }
+
+    #[test]
+    fn unpaired_keys_pass_through() {
+        let mut socd = socd(SocdMode::Neutral);
+        assert_eq!(socd.resolve(&KeyDown(Key::Space)), None);
+        assert_eq!(socd.resolve(&Action::Scroll(1)), None);
+    }
+
+    #[test]
+    fn releasing_what_was_never_asked_for_still_releases() {
+        let mut socd = socd(SocdMode::Last);
+        assert_eq!(socd.resolve(&KeyUp(A)), Some(vec![KeyUp(A)]));
+    }
+}
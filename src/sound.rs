@@ -0,0 +1,104 @@
+use crate::bindings::MapperState;
+use crate::config;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// short sounds played on state changes, keyed by event name like hooks
+// (e.g. `layer_on = "sounds/up.wav"`), relative to the config dir. events
+// are profile_switched, layer_on, layer_off, toggle_on, toggle_off, paused
+// and resumed
+pub type Sounds = HashMap<String, PathBuf>;
+
+pub struct Cues {
+    sounds: Sounds,
+    // the audio device, only opened when there's something to play
+    #[cfg(feature = "audio")]
+    stream: Option<(rodio::OutputStream, rodio::OutputStreamHandle)>,
+    // what the mapper looked like last tick, to notice changes
+    layer: Option<String>,
+    toggles: usize,
+}
+
+impl Cues {
+    pub fn new(sounds: &Sounds) -> Cues {
+        #[cfg(feature = "audio")]
+        let stream = if sounds.is_empty() {
+            None
+        } else {
+            match rodio::OutputStream::try_default() {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    println!("no audio output for sounds: {}", e);
+                    None
+                }
+            }
+        };
+        #[cfg(not(feature = "audio"))]
+        if !sounds.is_empty() {
+            println!("sounds configured but built without the `audio` feature");
+        }
+
+        Cues {
+            sounds: sounds.clone(),
+            #[cfg(feature = "audio")]
+            stream,
+            layer: None,
+            toggles: 0,
+        }
+    }
+
+    // plays without waiting for the sound to finish
+    pub fn play(&self, event: &str) {
+        let path = match self.sounds.get(event) {
+            Some(path) => config::resolve(path),
+            None => return,
+        };
+
+        #[cfg(feature = "audio")]
+        if let Some((_, handle)) = &self.stream {
+            let result = std::fs::File::open(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| {
+                    handle
+                        .play_once(std::io::BufReader::new(file))
+                        .map_err(|e| e.to_string())
+                });
+            match result {
+                Ok(sink) => sink.detach(),
+                Err(e) => println!("failed to play {}: {}", path.display(), e),
+            }
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = path;
+    }
+
+    // layer and toggle cues, checked once per tick
+    pub fn watch(&mut self, state: &MapperState) {
+        let layer = state.layer().map(|l| l.to_string());
+        if layer != self.layer {
+            self.play(if layer.is_some() {
+                "layer_on"
+            } else {
+                "layer_off"
+            });
+            self.layer = layer;
+        }
+
+        let toggles = state.toggles().count();
+        if toggles != self.toggles {
+            self.play(if toggles > self.toggles {
+                "toggle_on"
+            } else {
+                "toggle_off"
+            });
+            self.toggles = toggles;
+        }
+    }
+
+    // a new profile starts with no layers or toggles, which isn't a change
+    // worth a sound
+    pub fn reset(&mut self) {
+        self.layer = None;
+        self.toggles = 0;
+    }
+}
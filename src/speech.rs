@@ -0,0 +1,99 @@
+use crate::bindings::MapperState;
+use serde::Deserialize;
+
+// spoken announcements through the platform's text-to-speech (speech
+// dispatcher, sapi/winrt, avfoundation), for driving the desktop without
+// looking at the screen
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeechConfig {
+    // say the profile name on every switch
+    pub profiles: bool,
+    // say the layer name when one becomes active
+    pub layers: bool,
+    // warn once when the battery drops to this percentage, 0 to never
+    pub battery: u8,
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        SpeechConfig {
+            profiles: true,
+            layers: true,
+            battery: 20,
+        }
+    }
+}
+
+pub struct Announcer {
+    config: SpeechConfig,
+    #[cfg(feature = "speech")]
+    tts: Option<tts::Tts>,
+    layer: Option<String>,
+    // the low battery warning has been given for this discharge
+    warned: bool,
+}
+
+impl Announcer {
+    pub fn new(config: &SpeechConfig) -> Announcer {
+        #[cfg(feature = "speech")]
+        let tts = match tts::Tts::default() {
+            Ok(tts) => Some(tts),
+            Err(e) => {
+                println!("text-to-speech unavailable: {}", e);
+                None
+            }
+        };
+        #[cfg(not(feature = "speech"))]
+        println!("speech configured but built without the `speech` feature");
+
+        Announcer {
+            config: config.clone(),
+            #[cfg(feature = "speech")]
+            tts,
+            layer: None,
+            warned: false,
+        }
+    }
+
+    // interrupts whatever is being said, so quick changes don't queue up
+    pub fn say(&mut self, text: &str) {
+        println!("announcing: {}", text);
+        #[cfg(feature = "speech")]
+        if let Some(tts) = self.tts.as_mut() {
+            if let Err(e) = tts.speak(text, true) {
+                println!("text-to-speech failed: {}", e);
+            }
+        }
+    }
+
+    pub fn profile(&mut self, name: &str) {
+        // the new profile starts without layers
+        self.layer = None;
+        if self.config.profiles {
+            self.say(&format!("{} profile", name));
+        }
+    }
+
+    // layer changes and the battery, checked once per tick
+    pub fn watch(&mut self, state: &MapperState, battery: Option<u8>) {
+        let layer = state.layer().map(|l| l.to_string());
+        if layer != self.layer {
+            if let (true, Some(name)) = (self.config.layers, &layer) {
+                self.say(&format!("{} layer", name));
+            }
+            self.layer = layer;
+        }
+
+        match battery {
+            Some(level) if level <= self.config.battery => {
+                if !self.warned {
+                    self.warned = true;
+                    self.say(&format!("controller battery at {} percent", level));
+                }
+            }
+            // charging or topped up again
+            _ => self.warned = false,
+        }
+    }
+}
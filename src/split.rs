@@ -0,0 +1,70 @@
+use crate::config::Config;
+use crate::expr::Evaluator;
+use crate::profile::ActiveProfile;
+use gilrs::{Axis, Button, EventType};
+use serde::Deserialize;
+
+// one controller as two, e.g. the left half running a media player profile
+// and the right half a slideshow's. the profile it's set on keeps
+// everything not listed here; `split = { profile = "slides" }` gives the
+// right half away
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SplitConfig {
+    // the profile driving the listed buttons and axes
+    pub profile: String,
+    pub buttons: Vec<Button>,
+    pub axes: Vec<Axis>,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        SplitConfig {
+            profile: String::new(),
+            buttons: vec![
+                Button::South,
+                Button::East,
+                Button::North,
+                Button::West,
+                Button::RightTrigger,
+                Button::RightTrigger2,
+                Button::RightThumb,
+                Button::Start,
+            ],
+            axes: vec![Axis::RightStickX, Axis::RightStickY, Axis::RightZ],
+        }
+    }
+}
+
+pub struct Split {
+    pub profile: ActiveProfile,
+    buttons: Vec<Button>,
+    axes: Vec<Axis>,
+}
+
+impl Split {
+    pub fn load(split: &SplitConfig, config: &Config, evaluator: &Evaluator) -> Split {
+        println!("splitting the controller with profile '{}'", split.profile);
+        Split {
+            profile: ActiveProfile::load(
+                &split.profile,
+                &config.get_profile(&split.profile),
+                evaluator,
+            ),
+            buttons: split.buttons.clone(),
+            axes: split.axes.clone(),
+        }
+    }
+
+    // whether `event` belongs to this half
+    pub fn takes(&self, event: &EventType) -> bool {
+        match *event {
+            EventType::ButtonPressed(button, _)
+            | EventType::ButtonReleased(button, _)
+            | EventType::ButtonRepeated(button, _)
+            | EventType::ButtonChanged(button, _, _) => self.buttons.contains(&button),
+            EventType::AxisChanged(axis, _, _) => self.axes.contains(&axis),
+            _ => false,
+        }
+    }
+}
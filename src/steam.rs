@@ -0,0 +1,129 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+// looking for the game is a process scan or a file read, too slow for every
+// tick, so it runs on its own thread this often
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+// which steam game is running, by appid, so a profile keyed to it follows
+// the game through renamed executables, launchers and changing titles
+pub struct SteamWatcher {
+    rx: Receiver<Option<u32>>,
+}
+
+impl SteamWatcher {
+    pub fn start() -> SteamWatcher {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut last = None;
+            loop {
+                let app = running_app();
+                if app != last {
+                    last = app;
+                    if tx.send(app).is_err() {
+                        return; // watcher dropped
+                    }
+                }
+                thread::sleep(SCAN_INTERVAL);
+            }
+        });
+        SteamWatcher { rx }
+    }
+
+    // the running game's appid if it changed since the last call, with
+    // Some(None) once no game is running
+    pub fn poll(&mut self) -> Option<Option<u32>> {
+        self.rx.try_iter().last()
+    }
+}
+
+// steam starts every game under its reaper as `reaper SteamLaunch AppId=N
+// -- game`, and sets SteamAppId in the game's environment. reading another
+// process's environment only works for our own user's, which games are
+#[cfg(target_os = "linux")]
+fn running_app() -> Option<u32> {
+    for dir in crate::process::proc_dirs() {
+        let Ok(cmdline) = std::fs::read(dir.join("cmdline")) else {
+            continue;
+        };
+        let launch = cmdline.split(|&b| b == 0).any(|arg| arg == b"SteamLaunch");
+        if launch {
+            if let Some(app) = find(&cmdline, "AppId=") {
+                return Some(app);
+            }
+        }
+        if let Ok(environ) = std::fs::read(dir.join("environ")) {
+            if let Some(app) = find(&environ, "SteamAppId=") {
+                return Some(app);
+            }
+        }
+    }
+    None
+}
+
+// a nonzero number after `prefix` in one of the nul-separated strings
+#[cfg(target_os = "linux")]
+fn find(strings: &[u8], prefix: &str) -> Option<u32> {
+    strings
+        .split(|&b| b == 0)
+        .filter_map(|s| s.strip_prefix(prefix.as_bytes()))
+        .filter_map(|n| std::str::from_utf8(n).ok()?.parse().ok())
+        .find(|&app| app != 0)
+}
+
+// steam keeps the running game in its registry key, 0 between games
+#[cfg(target_os = "windows")]
+fn running_app() -> Option<u32> {
+    use windows::core::w;
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut app: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Valve\\Steam"),
+            w!("RunningAppID"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut app as *mut u32 as *mut std::ffi::c_void),
+            Some(&mut size as *mut u32),
+        )
+    };
+    (result == ERROR_SUCCESS && app != 0).then_some(app)
+}
+
+// the same value, kept in a text copy of the registry
+#[cfg(target_os = "macos")]
+fn running_app() -> Option<u32> {
+    let path = dirs::data_dir()?.join("Steam").join("registry.vdf");
+    let text = std::fs::read_to_string(path).ok()?;
+    text.lines()
+        .find(|line| line.trim_start().starts_with("\"RunningAppID\""))
+        .and_then(|line| line.split('"').nth(3)?.parse().ok())
+        .filter(|&app| app != 0)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn running_app() -> Option<u32> {
+    None
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::find;
+
+    #[test]
+    fn finds_the_appid_among_the_arguments() {
+        let cmdline = b"reaper\0SteamLaunch\0AppId=1245620\0--\0game.exe\0";
+        assert_eq!(find(cmdline, "AppId="), Some(1245620));
+    }
+
+    #[test]
+    fn skips_steam_itself() {
+        let environ = b"SteamAppId=0\0HOME=/home/me\0";
+        assert_eq!(find(environ, "SteamAppId="), None);
+    }
+}
@@ -0,0 +1,494 @@
+use crate::action::Action;
+use crate::bindings::Repeat;
+use crate::input::InputState;
+use crate::keymap::parse_key;
+use crate::magnifier::{Panner, StickPan};
+use crate::reverse;
+use crate::scancode;
+use crate::wheel::NOTCH;
+use enigo::Key;
+use gilrs::{Axis, Button};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    pub fn axes(self) -> (Axis, Axis) {
+        match self {
+            Stick::Left => (Axis::LeftStickX, Axis::LeftStickY),
+            Stick::Right => (Axis::RightStickX, Axis::RightStickY),
+        }
+    }
+
+    // clicking the stick in
+    pub fn click(self) -> Button {
+        match self {
+            Stick::Left => Button::LeftThumb,
+            Stick::Right => Button::RightThumb,
+        }
+    }
+}
+
+// what a stick does, e.g. `left_stick = "arrows"` or
+// `right_stick = { keys = { up = "volume_up", down = "volume_down" } }`
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickMode {
+    // held keys while deflected past the deadzone
+    #[default]
+    Wasd,
+    Arrows,
+    Mouse,
+    None,
+    // repeated key taps, for menus and on-screen displays that want one
+    // step per press
+    Keys(StickKeys),
+    // smooth scrolling, faster the further it's pushed
+    Scroll(StickScroll),
+    // panning the os magnifier's view
+    Pan(StickPan),
+    // volume up and down, faster the further it's pushed
+    Volume(StickVolume),
+    // analog movement through a virtual gamepad's stick, for games that
+    // take analog wasd from one
+    Analog(StickAnalog),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickKeys {
+    pub up: Option<String>,
+    pub down: Option<String>,
+    pub left: Option<String>,
+    pub right: Option<String>,
+    // deflection that counts as pushing in a direction
+    pub threshold: f32,
+    pub repeat: Repeat,
+}
+
+impl Default for StickKeys {
+    fn default() -> Self {
+        StickKeys {
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            threshold: 0.5,
+            repeat: Repeat::default(),
+        }
+    }
+}
+
+// e.g. `right_stick = { scroll = { speed = 30 } }`. scrolls by fractions of
+// a notch where the platform allows, so documents glide
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickScroll {
+    // notches a second with the stick all the way out
+    pub speed: f32,
+    pub deadzone: f32,
+    // whether left and right scroll sideways
+    pub horizontal: bool,
+}
+
+impl Default for StickScroll {
+    fn default() -> Self {
+        StickScroll {
+            speed: 20.0,
+            deadzone: 0.15,
+            horizontal: true,
+        }
+    }
+}
+
+// e.g. `right_stick = { volume = { speed = 10 } }`, a volume knob for a
+// media pc. up turns it up
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickVolume {
+    // volume steps a second with the stick all the way out
+    pub speed: f32,
+    pub deadzone: f32,
+    // clicking the stick in toggles mute instead of going to the mapping
+    pub mute: bool,
+}
+
+impl Default for StickVolume {
+    fn default() -> Self {
+        StickVolume {
+            speed: 10.0,
+            deadzone: 0.2,
+            mute: true,
+        }
+    }
+}
+
+// e.g. `left_stick = { analog = { to = "left", fallback = "wasd" } }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StickAnalog {
+    // the virtual gamepad's stick it drives
+    pub to: reverse::Stick,
+    // held keys instead, where there's no virtual gamepad
+    pub fallback: Fallback,
+}
+
+impl Default for StickAnalog {
+    fn default() -> Self {
+        StickAnalog {
+            to: reverse::Stick::Left,
+            fallback: Fallback::Wasd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fallback {
+    Wasd,
+    Arrows,
+}
+
+// where a stick in wasd or arrows mode presses its keys and where it lets
+// go, e.g. `left_stick_thresholds = { press = 0.3, release = 0.2 }`. the gap
+// keeps a stick resting right on the edge from chattering the key. without
+// it both are the deadzone
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StickThresholds {
+    pub press: f32,
+    pub release: f32,
+}
+
+// a stick holding one key per direction
+pub struct HeldKeys {
+    // up, down, left, right
+    keys: [Key; 4],
+    thresholds: Option<StickThresholds>,
+    // which way each axis is pressing, x then y: -1, 0 or 1
+    pressing: [i8; 2],
+}
+
+impl HeldKeys {
+    fn new(keys: [Key; 4], thresholds: Option<StickThresholds>) -> HeldKeys {
+        HeldKeys {
+            keys,
+            thresholds,
+            pressing: [0, 0],
+        }
+    }
+
+    // call when one of the stick's axes changes
+    pub fn update(&mut self, horizontal: bool, value: f32, deadzone: f32) -> Vec<Action> {
+        let [up, down, left, right] = self.keys;
+        let (positive, negative, pressing) = if horizontal {
+            (right, left, &mut self.pressing[0])
+        } else {
+            (up, down, &mut self.pressing[1])
+        };
+        let (press, release) = match self.thresholds {
+            Some(t) => (t.press, t.release.min(t.press)),
+            None => (deadzone, deadzone),
+        };
+        let direction = if value > 0.0 { 1 } else { -1 };
+        *pressing = if value.abs() > press || (*pressing == direction && value.abs() > release) {
+            direction
+        } else {
+            0
+        };
+        // ensure opposite key is released
+        match *pressing {
+            1 => vec![Action::KeyDown(positive), Action::KeyUp(negative)],
+            -1 => vec![Action::KeyDown(negative), Action::KeyUp(positive)],
+            _ => vec![Action::KeyUp(positive), Action::KeyUp(negative)],
+        }
+    }
+}
+
+// a stick mode ready to run
+pub enum StickOutput {
+    Held(HeldKeys),
+    Mouse,
+    Repeating(KeyRepeater),
+    Scrolling(Scroller),
+    Panning(Panner),
+    Volume(VolumeKnob),
+    Analog(AnalogStick),
+    None,
+}
+
+impl StickOutput {
+    pub fn new(
+        mode: &StickMode,
+        physical: bool,
+        thresholds: Option<StickThresholds>,
+    ) -> StickOutput {
+        let wasd = || {
+            HeldKeys::new(
+                ['w', 's', 'a', 'd'].map(|c| {
+                    let key = Key::Layout(c);
+                    if physical {
+                        scancode::physical(key)
+                    } else {
+                        key
+                    }
+                }),
+                thresholds,
+            )
+        };
+        let arrows = || {
+            HeldKeys::new(
+                [
+                    Key::UpArrow,
+                    Key::DownArrow,
+                    Key::LeftArrow,
+                    Key::RightArrow,
+                ],
+                thresholds,
+            )
+        };
+        match mode {
+            StickMode::Wasd => StickOutput::Held(wasd()),
+            StickMode::Arrows => StickOutput::Held(arrows()),
+            StickMode::Mouse => StickOutput::Mouse,
+            StickMode::None => StickOutput::None,
+            StickMode::Keys(keys) => StickOutput::Repeating(KeyRepeater::new(keys)),
+            StickMode::Scroll(scroll) => StickOutput::Scrolling(Scroller::new(scroll)),
+            StickMode::Pan(pan) => StickOutput::Panning(Panner::new(pan)),
+            StickMode::Volume(volume) => StickOutput::Volume(VolumeKnob::new(volume)),
+            StickMode::Analog(analog) => StickOutput::Analog(AnalogStick {
+                to: analog.to,
+                position: (0.0, 0.0),
+                fallback: match analog.fallback {
+                    Fallback::Wasd => wasd(),
+                    Fallback::Arrows => arrows(),
+                },
+            }),
+        }
+    }
+}
+
+// a stick passed on to the virtual gamepad, or held keys without one
+pub struct AnalogStick {
+    to: reverse::Stick,
+    // past the deadzone, rescaled to the full range
+    position: (f32, f32),
+    fallback: HeldKeys,
+}
+
+impl AnalogStick {
+    // call when one of the stick's axes changes
+    pub fn update(
+        &mut self,
+        horizontal: bool,
+        value: f32,
+        deadzone: f32,
+        pad: bool,
+    ) -> Vec<Action> {
+        if !pad {
+            return self.fallback.update(horizontal, value, deadzone);
+        }
+        let deadzone = deadzone.clamp(0.0, 0.99);
+        let out = value.signum() * ((value.abs() - deadzone) / (1.0 - deadzone)).max(0.0);
+        if horizontal {
+            self.position.0 = out;
+        } else {
+            self.position.1 = out;
+        }
+        vec![Action::PadStick(self.to, self.position.0, self.position.1)]
+    }
+}
+
+// the key one axis is repeating, and when
+struct Repeating {
+    key: Key,
+    since: Instant,
+    next: Instant,
+}
+
+pub struct KeyRepeater {
+    // up, down, left, right
+    keys: [Option<Key>; 4],
+    threshold: f32,
+    repeat: Repeat,
+    x: Option<Repeating>,
+    y: Option<Repeating>,
+}
+
+impl KeyRepeater {
+    fn new(config: &StickKeys) -> KeyRepeater {
+        let parse = |name: &Option<String>| {
+            let name = name.as_ref()?;
+            let key = parse_key(name);
+            if key.is_none() {
+                println!("ignoring unknown stick key '{}'", name);
+            }
+            key
+        };
+        KeyRepeater {
+            keys: [
+                parse(&config.up),
+                parse(&config.down),
+                parse(&config.left),
+                parse(&config.right),
+            ],
+            threshold: config.threshold,
+            repeat: config.repeat.clone(),
+            x: None,
+            y: None,
+        }
+    }
+
+    // taps that are due, called once per loop
+    pub fn tick(&mut self, input: &InputState, stick: Stick) -> Vec<Action> {
+        let (x_axis, y_axis) = stick.axes();
+        let [up, down, left, right] = self.keys;
+        let now = Instant::now();
+        let mut actions = Vec::new();
+
+        for (value, positive, negative, slot) in [
+            (input.axis(x_axis), right, left, &mut self.x),
+            (input.axis(y_axis), up, down, &mut self.y),
+        ] {
+            let key = if value > self.threshold {
+                positive
+            } else if value < -self.threshold {
+                negative
+            } else {
+                None
+            };
+
+            match (slot.as_mut(), key) {
+                (Some(current), Some(key)) if current.key == key => {
+                    if now >= current.next {
+                        actions.push(Action::KeyClick(key));
+                        current.next = now + self.repeat.interval(now - current.since);
+                    }
+                }
+                (_, Some(key)) => {
+                    actions.push(Action::KeyClick(key));
+                    *slot = Some(Repeating {
+                        key,
+                        since: now,
+                        next: now + Duration::from_millis(self.repeat.delay_ms),
+                    });
+                }
+                (_, None) => *slot = None,
+            }
+        }
+        actions
+    }
+}
+
+// a long gap between ticks (a pause, a profile switch) isn't scrolled
+// through in one go
+const MAX_TICK: Duration = Duration::from_millis(50);
+
+pub struct Scroller {
+    config: StickScroll,
+    last: Option<Instant>,
+    // scrolled but not yet sent, in 120ths of a notch
+    partial: (f32, f32),
+}
+
+impl Scroller {
+    fn new(config: &StickScroll) -> Scroller {
+        Scroller {
+            config: config.clone(),
+            last: None,
+            partial: (0.0, 0.0),
+        }
+    }
+
+    // scrolling since the last tick, called once per loop
+    pub fn tick(&mut self, input: &InputState, stick: Stick) -> Vec<Action> {
+        let now = Instant::now();
+        let elapsed = self
+            .last
+            .replace(now)
+            .map_or(Duration::ZERO, |last| (now - last).min(MAX_TICK));
+        let deadzone = self.config.deadzone.clamp(0.0, 0.99);
+        // squared past the deadzone, so a light push reads slowly
+        let rate = |value: f32| {
+            let out = ((value.abs() - deadzone) / (1.0 - deadzone)).max(0.0);
+            value.signum() * out * out * self.config.speed * NOTCH as f32
+        };
+        let (x_axis, y_axis) = stick.axes();
+        if self.config.horizontal {
+            self.partial.0 += rate(input.axis(x_axis)) * elapsed.as_secs_f32();
+        }
+        // pushing up scrolls up
+        self.partial.1 -= rate(input.axis(y_axis)) * elapsed.as_secs_f32();
+
+        let (x, y) = (self.partial.0.trunc(), self.partial.1.trunc());
+        self.partial.0 -= x;
+        self.partial.1 -= y;
+        if (x, y) == (0.0, 0.0) {
+            return vec![];
+        }
+        vec![Action::SmoothScroll(x as i32, y as i32)]
+    }
+}
+
+pub struct VolumeKnob {
+    config: StickVolume,
+    last: Option<Instant>,
+    // steps turned but not yet sent
+    partial: f32,
+    pushed: bool,
+}
+
+impl VolumeKnob {
+    fn new(config: &StickVolume) -> VolumeKnob {
+        VolumeKnob {
+            config: config.clone(),
+            last: None,
+            partial: 0.0,
+            pushed: false,
+        }
+    }
+
+    // whether the stick's click is taken for muting
+    pub fn mutes(&self) -> bool {
+        self.config.mute
+    }
+
+    // volume steps since the last tick, called once per loop
+    pub fn tick(&mut self, input: &InputState, stick: Stick) -> Vec<Action> {
+        let now = Instant::now();
+        let elapsed = self
+            .last
+            .replace(now)
+            .map_or(Duration::ZERO, |last| (now - last).min(MAX_TICK));
+        let deadzone = self.config.deadzone.clamp(0.0, 0.99);
+        let value = input.axis(stick.axes().1);
+        let out = ((value.abs() - deadzone) / (1.0 - deadzone)).max(0.0);
+        if out == 0.0 {
+            self.pushed = false;
+            self.partial = 0.0;
+            return vec![];
+        }
+        if !self.pushed {
+            // a fresh push steps right away
+            self.pushed = true;
+            self.partial = value.signum();
+        }
+        self.partial += value.signum() * out * self.config.speed * elapsed.as_secs_f32();
+        let steps = self.partial.trunc();
+        self.partial -= steps;
+        let key = if steps > 0.0 {
+            Key::VolumeUp
+        } else {
+            Key::VolumeDown
+        };
+        (0..steps.abs() as usize)
+            .map(|_| Action::KeyClick(key))
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+use crate::action::{Action, Output};
+use crate::bindings::chord_click;
+use crate::hooks;
+use crate::keymap::parse_chord;
+use crate::launch::Launch;
+use crate::notify;
+use crate::obs::ObsCommand;
+use serde::Deserialize;
+
+// one step of a profile's `on_activate` or `on_deactivate` list, e.g.
+// `on_activate = [{ launch = { program = "simhub" } }, { notify = "sim racing" }]`.
+// exactly one of the targets is set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    // a key or chord, tapped
+    pub key: Option<String>,
+    pub text: Option<String>,
+    pub obs: Option<ObsCommand>,
+    // a shell command, run detached
+    pub exec: Option<String>,
+    pub launch: Option<Launch>,
+    // a desktop notification
+    pub notify: Option<String>,
+}
+
+// what a step asks for
+enum Run {
+    Actions(Vec<Action>),
+    Exec(String),
+    Notify(String),
+}
+
+impl Step {
+    fn compile(&self) -> Result<Run, String> {
+        let mut runs = Vec::new();
+        if let Some(name) = &self.key {
+            let keys = parse_chord(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+            runs.push(Run::Actions(chord_click(&keys)));
+        }
+        if let Some(text) = &self.text {
+            runs.push(Run::Actions(vec![Action::Text(text.clone())]));
+        }
+        if let Some(command) = &self.obs {
+            runs.push(Run::Actions(vec![Action::Obs(command.clone())]));
+        }
+        if let Some(command) = &self.exec {
+            runs.push(Run::Exec(command.clone()));
+        }
+        if let Some(launch) = &self.launch {
+            runs.push(Run::Actions(vec![Action::Launch(launch.clone())]));
+        }
+        if let Some(text) = &self.notify {
+            runs.push(Run::Notify(text.clone()));
+        }
+        match runs.len() {
+            1 => Ok(runs.remove(0)),
+            _ => {
+                Err("exactly one of key, text, obs, exec, launch or notify must be set".to_string())
+            }
+        }
+    }
+}
+
+// runs a profile's steps in order. `event` is on_activate or on_deactivate,
+// and exec commands get it and the profile in C2K_EVENT and C2K_PROFILE
+pub fn run(steps: &[Step], event: &str, profile: &str, output: &mut Output) {
+    for (i, step) in steps.iter().enumerate() {
+        match step.compile() {
+            Ok(Run::Actions(actions)) => {
+                for action in actions {
+                    output.execute(&action);
+                }
+            }
+            Ok(Run::Exec(command)) => {
+                let env = [
+                    ("C2K_EVENT", event.to_string()),
+                    ("C2K_PROFILE", profile.to_string()),
+                ];
+                if let Err(e) = hooks::spawn(&command, &env) {
+                    println!("failed to run '{}': {}", command, e);
+                }
+            }
+            Ok(Run::Notify(text)) => notify::show(&text),
+            Err(e) => println!("skipping {} step {} of '{}': {}", event, i + 1, profile, e),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use gilrs::{Button, EventType};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// analog thresholds for using the triggers as buttons, e.g.
+// `triggers = { press = 0.6, release = 0.3 }`. gilrs's own digital trigger
+// events flip back and forth when a finger relaxes slightly mid-drag
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TriggerConfig {
+    // pull needed to press
+    pub press: f32,
+    // pull below which a press is released, lower than `press`
+    pub release: f32,
+    // rapid-trigger mode: press once the pull rises this far from its lowest
+    // point and release once it falls this far from its highest point,
+    // wherever in the travel that happens
+    pub hair_trigger: Option<f32>,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        TriggerConfig {
+            press: 0.5,
+            release: 0.3,
+            hair_trigger: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TriggerState {
+    pressed: bool,
+    // lowest pull while released, highest while pressed
+    extreme: f32,
+}
+
+pub struct Triggers {
+    config: TriggerConfig,
+    state: HashMap<Button, TriggerState>,
+}
+
+fn is_trigger(button: Button) -> bool {
+    matches!(button, Button::LeftTrigger2 | Button::RightTrigger2)
+}
+
+impl Triggers {
+    pub fn new(config: &TriggerConfig) -> Triggers {
+        let mut config = config.clone();
+        if config.release > config.press {
+            println!(
+                "trigger release threshold {} is above press threshold {}, using {}",
+                config.release, config.press, config.press
+            );
+            config.release = config.press;
+        }
+        Triggers {
+            config,
+            state: HashMap::new(),
+        }
+    }
+
+    // replaces gilrs's digital trigger events with ones derived from the
+    // analog value. returns None for events that should be dropped
+    pub fn filter(&mut self, event: EventType) -> Option<EventType> {
+        match event {
+            EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _)
+                if is_trigger(button) =>
+            {
+                None
+            }
+            EventType::ButtonChanged(button, value, code) if is_trigger(button) => {
+                match self.update(button, value)? {
+                    true => Some(EventType::ButtonPressed(button, code)),
+                    false => Some(EventType::ButtonReleased(button, code)),
+                }
+            }
+            event => Some(event),
+        }
+    }
+
+    // Some(pressed) when the trigger changes state
+    fn update(&mut self, button: Button, value: f32) -> Option<bool> {
+        let config = &self.config;
+        let state = self.state.entry(button).or_default();
+
+        let pressed = match config.hair_trigger {
+            Some(travel) if state.pressed => {
+                state.extreme = state.extreme.max(value);
+                value > config.release && value > state.extreme - travel
+            }
+            Some(travel) => {
+                state.extreme = state.extreme.min(value);
+                value >= config.release && value >= state.extreme + travel
+            }
+            None if state.pressed => value > config.release,
+            None => value >= config.press,
+        };
+
+        if pressed == state.pressed {
+            return None;
+        }
+        state.pressed = pressed;
+        state.extreme = value;
+        Some(pressed)
+    }
+}
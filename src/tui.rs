@@ -0,0 +1,316 @@
+use crate::config::{Config, Profile};
+use crate::edit::{self, Assignment, BUTTONS};
+use crate::expr::Setting;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+
+// a number setting edited with a slider: (config key, label, min, max,
+// step, built-in default)
+type Slider = (&'static str, &'static str, f64, f64, f64, f64);
+
+const SLIDERS: &[Slider] = &[
+    ("deadzone", "stick deadzone", 0.0, 0.9, 0.01, 0.15),
+    ("sensitivity", "mouse speed", 1.0, 200.0, 1.0, 50.0),
+];
+
+const HELP: &str =
+    "enter: bind key  m: mouse  d: built-in  left/right: slide  tab: profile  s: save  q: quit";
+
+struct Editor {
+    config: Config,
+    profile: String,
+    // buttons, then sliders
+    list: ListState,
+    // unsaved changes, by button and by setting key
+    assignments: BTreeMap<usize, Assignment>,
+    settings: BTreeMap<&'static str, f64>,
+    // waiting for the key to bind to the selected button
+    capturing: bool,
+    status: String,
+}
+
+// `controller2keys tui`: edits bindings and stick settings in the terminal,
+// for ssh sessions and machines without a desktop
+pub fn run() -> Result<(), String> {
+    let mut terminal = ratatui::try_init().map_err(|e| e.to_string())?;
+    let result = Editor::new().run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Editor {
+    fn new() -> Editor {
+        let config = Config::load_or_default();
+        let profile = config.profile.clone();
+        Editor {
+            config,
+            profile,
+            list: ListState::default().with_selected(Some(0)),
+            assignments: BTreeMap::new(),
+            settings: BTreeMap::new(),
+            capturing: false,
+            status: HELP.to_string(),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| e.to_string())?;
+            let Event::Key(key) = event::read().map_err(|e| e.to_string())? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.capturing {
+                self.capture(key);
+            } else if !self.handle(key) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or(0)
+    }
+
+    fn profile(&self) -> Profile {
+        self.config.get_profile(&self.profile)
+    }
+
+    fn assignment(&self, index: usize) -> Assignment {
+        self.assignments
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| Assignment::current(&self.profile(), BUTTONS[index]))
+    }
+
+    // the slider's value, or None while the profile sets it with an
+    // expression and it hasn't been moved
+    fn setting(&self, slider: &Slider) -> Option<f64> {
+        let &(key, _, _, _, _, default) = slider;
+        if let Some(value) = self.settings.get(key) {
+            return Some(*value);
+        }
+        let profile = self.profile();
+        let setting = if key == "deadzone" {
+            profile.deadzone
+        } else {
+            profile.sensitivity
+        };
+        match setting {
+            None => Some(default),
+            Some(Setting::Number(n)) => Some(n),
+            Some(Setting::Expr(_)) => None,
+        }
+    }
+
+    // false to quit
+    fn handle(&mut self, key: KeyEvent) -> bool {
+        let index = self.selected();
+        let rows = BUTTONS.len() + SLIDERS.len();
+        let slider = index.checked_sub(BUTTONS.len()).map(|i| &SLIDERS[i]);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return self.quit(),
+            KeyCode::Up => self.list.select(Some((index + rows - 1) % rows)),
+            KeyCode::Down => self.list.select(Some((index + 1) % rows)),
+            KeyCode::Tab => self.next_profile(),
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Enter if slider.is_none() => {
+                self.capturing = true;
+                self.status = format!("press the key for {:?} (esc cancels)", BUTTONS[index]);
+            }
+            KeyCode::Char('m') if slider.is_none() => {
+                let next = match self.assignment(index) {
+                    Assignment::Mouse(m) if m == "left" => "right",
+                    Assignment::Mouse(m) if m == "right" => "middle",
+                    _ => "left",
+                };
+                self.assignments
+                    .insert(index, Assignment::Mouse(next.to_string()));
+            }
+            KeyCode::Char('d') if slider.is_none() => {
+                self.assignments.insert(index, Assignment::Default);
+            }
+            KeyCode::Left | KeyCode::Right => {
+                if let Some(slider) = slider {
+                    let &(key_name, _, min, max, step, default) = slider;
+                    let direction = if key.code == KeyCode::Left { -1.0 } else { 1.0 };
+                    let value = self.setting(slider).unwrap_or(default);
+                    let value = (value + direction * step).clamp(min, max);
+                    self.settings.insert(key_name, value);
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    fn quit(&mut self) -> bool {
+        if self.assignments.is_empty() && self.settings.is_empty() {
+            return false;
+        }
+        self.assignments.clear();
+        self.settings.clear();
+        self.status = "unsaved changes dropped, q again to quit".to_string();
+        true
+    }
+
+    fn next_profile(&mut self) {
+        if !self.assignments.is_empty() || !self.settings.is_empty() {
+            self.status = "save (s) or drop (q) changes before switching profile".to_string();
+            return;
+        }
+        let mut names: Vec<&String> = self.config.profiles.keys().collect();
+        if !names.contains(&&self.config.profile) {
+            names.insert(0, &self.config.profile);
+        }
+        let at = names.iter().position(|n| **n == self.profile).unwrap_or(0);
+        self.profile = names[(at + 1) % names.len()].clone();
+    }
+
+    fn capture(&mut self, key: KeyEvent) {
+        self.capturing = false;
+        if key.code == KeyCode::Esc {
+            self.status = "cancelled".to_string();
+            return;
+        }
+        let Some(name) = key_name(key.code) else {
+            self.status = format!("{:?} can't be bound from here", key.code);
+            return;
+        };
+        let mut chord = Vec::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+        ] {
+            // shifted characters already arrive as the shifted character
+            let char_key = matches!(key.code, KeyCode::Char(_));
+            if key.modifiers.contains(modifier) && !(char_key && name == "shift") {
+                chord.push(name.to_string());
+            }
+        }
+        chord.push(name);
+        let assignment = Assignment::Key(chord.join("+"));
+        self.status = match assignment.validate() {
+            Ok(()) => format!(
+                "{:?} -> {}",
+                BUTTONS[self.selected()],
+                assignment.describe()
+            ),
+            Err(e) => e,
+        };
+        self.assignments.insert(self.selected(), assignment);
+    }
+
+    fn save(&mut self) {
+        let mut errors = Vec::new();
+        for (index, assignment) in &self.assignments {
+            if let Err(e) = edit::save(&self.profile, BUTTONS[*index], assignment) {
+                errors.push(format!("{:?}: {}", BUTTONS[*index], e));
+            }
+        }
+        for (key, value) in &self.settings {
+            if let Err(e) = edit::save_setting(&self.profile, key, *value) {
+                errors.push(e);
+            }
+        }
+        self.status = if errors.is_empty() {
+            format!("saved '{}'", self.profile)
+        } else {
+            errors.join("; ")
+        };
+        self.assignments.clear();
+        self.settings.clear();
+        self.config = Config::load_or_default();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [buttons, sliders] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(main);
+
+        let mut items: Vec<ListItem> = (0..BUTTONS.len())
+            .map(|i| {
+                let changed = if self.assignments.contains_key(&i) {
+                    "*"
+                } else {
+                    " "
+                };
+                ListItem::new(format!(
+                    "{}{:<14} {}",
+                    changed,
+                    format!("{:?}", BUTTONS[i]),
+                    self.assignment(i).describe()
+                ))
+            })
+            .collect();
+        items.extend(SLIDERS.iter().map(|&(key, label, ..)| {
+            let changed = if self.settings.contains_key(key) {
+                "*"
+            } else {
+                " "
+            };
+            ListItem::new(format!("{}{}", changed, label))
+        }));
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" profile: {} ", self.profile)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, buttons, &mut self.list);
+
+        let rows = Layout::vertical(SLIDERS.iter().map(|_| Constraint::Length(3))).split(sliders);
+        for (slider, area) in SLIDERS.iter().zip(rows.iter()) {
+            let &(_, label, min, max, ..) = slider;
+            let block = Block::bordered().title(format!(" {} ", label));
+            match self.setting(slider) {
+                Some(value) => frame.render_widget(
+                    Gauge::default()
+                        .block(block)
+                        .gauge_style(Style::default().fg(Color::Cyan))
+                        .ratio(((value - min) / (max - min)).clamp(0.0, 1.0))
+                        .label(format!("{}", (value * 100.0).round() / 100.0)),
+                    *area,
+                ),
+                None => frame.render_widget(
+                    Paragraph::new("set by an expression, move to replace").block(block),
+                    *area,
+                ),
+            }
+        }
+
+        frame.render_widget(Paragraph::new(self.status.as_str()), status);
+    }
+}
+
+// a terminal key as our config spells it
+fn key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Tab | KeyCode::BackTab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        _ => return None,
+    };
+    crate::keymap::parse_key(&name).map(|_| name)
+}
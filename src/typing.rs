@@ -0,0 +1,75 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "linux")]
+use linux::Keyboards;
+#[cfg(target_os = "windows")]
+use windows::Keyboards;
+
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// holds the controller's keys back while the real keyboard is being typed
+// on, so a controller resting on a lap can't type into the middle of a
+// sentence. from `[typing_guard]`: `grace_ms = 1500`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypingGuardConfig {
+    // how long after the last physical key press the guard stays up
+    pub grace_ms: u64,
+}
+
+impl Default for TypingGuardConfig {
+    fn default() -> Self {
+        TypingGuardConfig { grace_ms: 1000 }
+    }
+}
+
+pub struct TypingGuard {
+    keyboards: Keyboards,
+    grace: Duration,
+    last_typed: Option<Instant>,
+    up: bool,
+}
+
+impl TypingGuard {
+    pub fn start(config: &TypingGuardConfig) -> Result<TypingGuard, String> {
+        Ok(TypingGuard {
+            keyboards: Keyboards::open()?,
+            grace: Duration::from_millis(config.grace_ms),
+            last_typed: None,
+            up: false,
+        })
+    }
+
+    // whether the guard went up or came down since the last call, once per
+    // tick
+    pub fn poll(&mut self) -> Option<bool> {
+        if self.keyboards.typed() {
+            self.last_typed = Some(Instant::now());
+        }
+        let up = self.last_typed.is_some_and(|t| t.elapsed() < self.grace);
+        if up == self.up {
+            return None;
+        }
+        self.up = up;
+        Some(up)
+    }
+}
+
+// no way to tell physical keys from injected ones here
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+enum Keyboards {}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+impl Keyboards {
+    fn open() -> Result<Keyboards, String> {
+        Err("the typing guard needs linux or windows".to_string())
+    }
+
+    fn typed(&mut self) -> bool {
+        match *self {}
+    }
+}
@@ -0,0 +1,87 @@
+use crate::uinput;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+const EV_KEY: u16 = 0x01;
+const KEY_A: u16 = 30;
+
+// _IOC(_IOC_READ, 'E', 0x20 + EV_KEY, len)
+fn eviocgbit_key(len: usize) -> u64 {
+    0x8000_4520 | (len as u64) << 16 | EV_KEY as u64
+}
+
+// every keyboard's evdev node, read without grabbing. what we inject goes
+// through xtest, or inside gamescope through our own uinput keyboard, which
+// is left out, so only real typing shows up
+pub struct Keyboards {
+    devices: Vec<File>,
+}
+
+impl Keyboards {
+    pub fn open() -> Result<Keyboards, String> {
+        let entries = std::fs::read_dir("/dev/input").map_err(|e| e.to_string())?;
+        let mut devices = Vec::new();
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if !path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("event"))
+            {
+                continue;
+            }
+            let Ok(file) = OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(&path)
+            else {
+                continue;
+            };
+            let mut keys = [0u8; 96];
+            unsafe {
+                libc::ioctl(
+                    file.as_raw_fd(),
+                    eviocgbit_key(keys.len()) as _,
+                    keys.as_mut_ptr(),
+                );
+            }
+            if keys[KEY_A as usize / 8] & (1 << (KEY_A % 8)) != 0 && !uinput::ours(&file) {
+                devices.push(file);
+            }
+        }
+        if devices.is_empty() {
+            return Err(
+                "no readable keyboard in /dev/input (is your user in the input group?)".to_string(),
+            );
+        }
+        Ok(Keyboards { devices })
+    }
+
+    // whether any key went down since the last call
+    pub fn typed(&mut self) -> bool {
+        let size = std::mem::size_of::<libc::input_event>();
+        let mut buf = vec![0u8; size * 64];
+        let mut typed = false;
+        // a keyboard that's been unplugged stops reading; drop it
+        self.devices.retain_mut(|device| loop {
+            let n = match device.read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(_) => return false,
+            };
+            if n == 0 {
+                return true;
+            }
+            for chunk in buf[..n].chunks_exact(size) {
+                let ev: libc::input_event =
+                    unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const _) };
+                // 1 is a press, 2 autorepeat
+                if ev.type_ == EV_KEY && ev.value != 0 {
+                    typed = true;
+                }
+            }
+        });
+        typed
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, GetMessageW, SetWindowsHookExW, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, MSG,
+    WH_KEYBOARD_LL, WM_KEYDOWN, WM_SYSKEYDOWN,
+};
+
+// set by the hook on a physical key press, taken by `typed`
+static TYPED: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let down = wparam.0 == WM_KEYDOWN as usize || wparam.0 == WM_SYSKEYDOWN as usize;
+    if code >= 0 && down {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        // what we (or anything else) inject is flagged
+        if info.flags.0 & LLKHF_INJECTED.0 == 0 {
+            TYPED.store(true, Ordering::Relaxed);
+        }
+    }
+    CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+}
+
+// a low-level keyboard hook, which needs a thread pumping messages
+pub struct Keyboards;
+
+impl Keyboards {
+    pub fn open() -> Result<Keyboards, String> {
+        let (started, result) = std::sync::mpsc::channel();
+        thread::spawn(move || unsafe {
+            match SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook), HINSTANCE::default(), 0) {
+                Ok(_) => {
+                    let _ = started.send(Ok(()));
+                    let mut msg = MSG::default();
+                    while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {}
+                }
+                Err(e) => {
+                    let _ = started.send(Err(format!("keyboard hook failed: {}", e)));
+                }
+            }
+        });
+        result.recv().map_err(|e| e.to_string())?.map(|_| Keyboards)
+    }
+
+    pub fn typed(&mut self) -> bool {
+        TYPED.swap(false, Ordering::Relaxed)
+    }
+}
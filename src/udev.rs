@@ -0,0 +1,109 @@
+// `controller2keys setup-udev`: installs udev rules so the logged-in user can
+// read controllers and write /dev/uinput without being in the input group
+
+#[cfg(target_os = "linux")]
+use std::io::Write;
+#[cfg(target_os = "linux")]
+use std::process::{Command, Stdio};
+
+#[cfg(target_os = "linux")]
+const RULES_PATH: &str = "/etc/udev/rules.d/70-controller2keys.rules";
+#[cfg(target_os = "linux")]
+const MODULES_PATH: &str = "/etc/modules-load.d/controller2keys.conf";
+
+// uaccess gives the nodes to whoever is logged in at the seat, so it works
+// without logging out and back in. hidraw is limited to sony, microsoft and
+// nintendo pads since it's only used for lightbars and rumble
+#[cfg(target_os = "linux")]
+const RULES: &str = r#"# installed by `controller2keys setup-udev`
+SUBSYSTEM=="input", KERNEL=="event*", ENV{ID_INPUT_JOYSTICK}=="1", TAG+="uaccess"
+SUBSYSTEM=="hidraw", ATTRS{idVendor}=="054c", TAG+="uaccess"
+SUBSYSTEM=="hidraw", ATTRS{idVendor}=="045e", TAG+="uaccess"
+SUBSYSTEM=="hidraw", ATTRS{idVendor}=="057e", TAG+="uaccess"
+SUBSYSTEM=="misc", KERNEL=="uinput", OPTIONS+="static_node=uinput", TAG+="uaccess"
+"#;
+
+#[cfg(target_os = "linux")]
+pub fn run() -> Result<(), String> {
+    let root = unsafe { libc::geteuid() } == 0;
+    for (path, contents) in [(RULES_PATH, RULES), (MODULES_PATH, "uinput\n")] {
+        if let Err(e) = install(path, contents, root) {
+            print_instructions();
+            return Err(e);
+        }
+        println!("wrote {}", path);
+    }
+
+    let reload: [&[&str]; 3] = [
+        &["modprobe", "uinput"],
+        &["udevadm", "control", "--reload-rules"],
+        &["udevadm", "trigger"],
+    ];
+    for args in reload {
+        let ok = privileged(args, root)
+            .status()
+            .is_ok_and(|status| status.success());
+        if !ok {
+            return Err(format!(
+                "`{}` failed; the rules apply after a reboot",
+                args.join(" ")
+            ));
+        }
+    }
+    println!("done; reconnect the controller if it was already plugged in");
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn run() -> Result<(), String> {
+    Err("setup-udev is only needed on linux".to_string())
+}
+
+// writes straight to the file as root, otherwise through `sudo tee` so
+// sudo can prompt for a password on the terminal
+#[cfg(target_os = "linux")]
+fn install(path: &str, contents: &str, root: bool) -> Result<(), String> {
+    if root {
+        return std::fs::write(path, contents)
+            .map_err(|e| format!("couldn't write {}: {}", path, e));
+    }
+    let mut child = Command::new("sudo")
+        .args(["tee", path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("couldn't run sudo: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(contents.as_bytes());
+    }
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(format!("couldn't write {}", path)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn privileged(args: &[&str], root: bool) -> Command {
+    if root {
+        let mut cmd = Command::new(args[0]);
+        cmd.args(&args[1..]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sudo");
+        cmd.args(args);
+        cmd
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn print_instructions() {
+    println!("to set it up by hand, as root:");
+    println!();
+    println!("cat > {} <<'EOF'", RULES_PATH);
+    print!("{}", RULES);
+    println!("EOF");
+    println!("echo uinput > {}", MODULES_PATH);
+    println!("modprobe uinput");
+    println!("udevadm control --reload-rules && udevadm trigger");
+    println!();
+}
@@ -0,0 +1,127 @@
+use enigo::Key;
+
+// a virtual keyboard and mouse through /dev/uinput, for when xtest isn't
+// there to send keys through (gamescope). it takes keys and mouse buttons,
+// relative motion and the wheel; text, pastes and gestures still go
+// through enigo. outside gamescope it only does the hi-res wheel. the
+// virtual pad and reverse mode make their devices with the same Node
+#[cfg(target_os = "linux")]
+mod linux;
+
+#[cfg(target_os = "linux")]
+pub use linux::{
+    ours, Device, Layout, Mouse, Node, BTN_LEFT, EV_ABS, EV_KEY, EV_REL, EV_SYN, REL_X, REL_Y,
+};
+
+#[cfg(not(target_os = "linux"))]
+pub struct Device;
+
+#[cfg(not(target_os = "linux"))]
+pub struct Mouse;
+
+#[cfg(not(target_os = "linux"))]
+impl Mouse {
+    pub fn move_by(&mut self, _dx: i32, _dy: i32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Device {
+    pub fn open() -> Result<Device, String> {
+        Err("uinput is linux-only".to_string())
+    }
+
+    pub fn mouse(&self) -> Result<Mouse, String> {
+        Err("uinput is linux-only".to_string())
+    }
+
+    pub fn key(&mut self, _key: Key, _down: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn button(&mut self, _button: enigo::MouseButton, _down: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn move_by(&mut self, _dx: i32, _dy: i32) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn scroll(&mut self, _clicks: i32) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn smooth_scroll(&mut self, _dx: i32, _dy: i32) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn tilt(&mut self, _direction: i32) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// the evdev code for a key. layout characters go by their us qwerty
+// position, and raw keys are x keycodes on linux
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn evdev(key: Key) -> Option<u16> {
+    Some(match key {
+        Key::Layout(' ') => 57,
+        Key::Layout(c) => return crate::scancode::evdev(c),
+        Key::Raw(code) => return code.checked_sub(8),
+        Key::Escape => 1,
+        Key::Backspace => 14,
+        Key::Tab => 15,
+        Key::Return => 28,
+        Key::Control => 29,
+        Key::Shift => 42,
+        Key::Alt => 56,
+        Key::Space => 57,
+        Key::CapsLock => 58,
+        Key::F1 => 59,
+        Key::F2 => 60,
+        Key::F3 => 61,
+        Key::F4 => 62,
+        Key::F5 => 63,
+        Key::F6 => 64,
+        Key::F7 => 65,
+        Key::F8 => 66,
+        Key::F9 => 67,
+        Key::F10 => 68,
+        Key::F11 => 87,
+        Key::F12 => 88,
+        Key::Home => 102,
+        Key::UpArrow => 103,
+        Key::PageUp => 104,
+        Key::LeftArrow => 105,
+        Key::RightArrow => 106,
+        Key::End => 107,
+        Key::DownArrow => 108,
+        Key::PageDown => 109,
+        Key::Delete => 111,
+        Key::VolumeMute => 113,
+        Key::VolumeDown => 114,
+        Key::VolumeUp => 115,
+        Key::Meta => 125,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_go_by_their_evdev_codes() {
+        assert_eq!(evdev(Key::Layout('a')), Some(30));
+        assert_eq!(evdev(Key::Layout(' ')), Some(57));
+        assert_eq!(evdev(Key::Return), Some(28));
+        assert_eq!(evdev(Key::Meta), Some(125));
+    }
+
+    #[test]
+    fn raw_keys_are_x_keycodes() {
+        assert_eq!(evdev(Key::Raw(38)), Some(30));
+        assert_eq!(evdev(Key::Raw(3)), None);
+    }
+}
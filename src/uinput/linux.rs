@@ -0,0 +1,308 @@
+use super::evdev;
+use crate::wheel::NOTCH;
+use enigo::{Key, MouseButton};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+pub const EV_ABS: u16 = 0x03;
+pub const REL_X: u16 = 0x00;
+pub const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+const REL_WHEEL_HI_RES: u16 = 0x0b;
+const REL_HWHEEL_HI_RES: u16 = 0x0c;
+pub const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+// every keyboard key below the multimedia ones
+const KEY_MAX: u16 = 0xf8;
+
+// _IOW('U', 100..103, int)
+const UI_SET_EVBIT: u64 = 0x4004_5564;
+const UI_SET_KEYBIT: u64 = 0x4004_5565;
+const UI_SET_RELBIT: u64 = 0x4004_5566;
+const UI_SET_ABSBIT: u64 = 0x4004_5567;
+// _IO('U', 1)
+const UI_DEV_CREATE: u64 = 0x5501;
+const UI_DEV_DESTROY: u64 = 0x5502;
+
+// _IOC(_IOC_READ, 'E', 0x06, len)
+fn eviocgname(len: usize) -> u64 {
+    0x8000_4506 | (len as u64) << 16
+}
+
+// whether an evdev node is one of the devices made here, going by the
+// name they all start with
+pub fn ours(node: &File) -> bool {
+    let mut name = [0u8; 80];
+    let len = unsafe {
+        libc::ioctl(
+            node.as_raw_fd(),
+            eviocgname(name.len()) as _,
+            name.as_mut_ptr(),
+        )
+    };
+    len > 0 && name.starts_with(b"controller2keys ")
+}
+
+// what a virtual device calls itself and which events it can send
+pub struct Layout<'a> {
+    pub name: &'a str,
+    // bus type, vendor and product
+    pub id: (u16, u16, u16),
+    pub keys: &'a [u16],
+    pub rels: &'a [u16],
+    // absolute axes with their ranges
+    pub abs: &'a [(u16, i32, i32)],
+}
+
+// a device made through /dev/uinput, gone again when dropped
+pub struct Node {
+    file: File,
+}
+
+impl Node {
+    pub fn create(layout: &Layout) -> Result<Node, String> {
+        if !cfg!(feature = "uinput") {
+            return Err("built without the `uinput` feature".to_string());
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .map_err(|e| format!("/dev/uinput: {}", e))?;
+        let fd = file.as_raw_fd();
+
+        let mut dev: libc::uinput_user_dev = unsafe { std::mem::zeroed() };
+        for (i, b) in layout.name.bytes().take(dev.name.len() - 1).enumerate() {
+            dev.name[i] = b as libc::c_char;
+        }
+        (dev.id.bustype, dev.id.vendor, dev.id.product) = layout.id;
+        dev.id.version = 1;
+
+        unsafe {
+            for (ev, codes) in [(EV_KEY, layout.keys), (EV_REL, layout.rels)] {
+                if !codes.is_empty() {
+                    libc::ioctl(fd, UI_SET_EVBIT as _, ev as libc::c_int);
+                }
+            }
+            if !layout.abs.is_empty() {
+                libc::ioctl(fd, UI_SET_EVBIT as _, EV_ABS as libc::c_int);
+            }
+            for &key in layout.keys {
+                libc::ioctl(fd, UI_SET_KEYBIT as _, key as libc::c_int);
+            }
+            for &rel in layout.rels {
+                libc::ioctl(fd, UI_SET_RELBIT as _, rel as libc::c_int);
+            }
+            for &(abs, min, max) in layout.abs {
+                libc::ioctl(fd, UI_SET_ABSBIT as _, abs as libc::c_int);
+                dev.absmin[abs as usize] = min;
+                dev.absmax[abs as usize] = max;
+            }
+        }
+
+        let mut node = Node { file };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &dev as *const _ as *const u8,
+                std::mem::size_of::<libc::uinput_user_dev>(),
+            )
+        };
+        node.file.write_all(bytes).map_err(|e| e.to_string())?;
+        if unsafe { libc::ioctl(fd, UI_DEV_CREATE as _) } < 0 {
+            return Err(format!(
+                "failed to create {}: {}",
+                layout.name,
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(node)
+    }
+
+    // (type, code, value) events, written in one go
+    pub fn send(&mut self, events: &[(u16, u16, i32)]) -> Result<(), String> {
+        write(&mut self.file, events)
+    }
+}
+
+fn write(file: &mut File, events: &[(u16, u16, i32)]) -> Result<(), String> {
+    let size = std::mem::size_of::<libc::input_event>();
+    let mut bytes = Vec::with_capacity(events.len() * size);
+    for &(type_, code, value) in events {
+        let mut ev: libc::input_event = unsafe { std::mem::zeroed() };
+        ev.type_ = type_;
+        ev.code = code;
+        ev.value = value;
+        bytes.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(&ev as *const _ as *const u8, size)
+        });
+    }
+    file.write_all(&bytes).map_err(|e| e.to_string())
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        unsafe {
+            libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY as _);
+        }
+    }
+}
+
+// a second handle on a Device's motion, for the interpolator's thread.
+// dropping it leaves the device alone
+pub struct Mouse {
+    file: File,
+}
+
+impl Mouse {
+    pub fn move_by(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        write(
+            &mut self.file,
+            &[(EV_REL, REL_X, dx), (EV_REL, REL_Y, dy), (EV_SYN, 0, 0)],
+        )
+    }
+}
+
+// keys, mouse buttons, motion and a hi-res wheel, all on one device
+pub struct Device {
+    node: Node,
+    // hi-res scrolling not yet a whole notch. apps that don't read
+    // REL_WHEEL_HI_RES still see the notches, like with a real mouse
+    partial: (i32, i32),
+}
+
+impl Device {
+    pub fn open() -> Result<Device, String> {
+        let keys: Vec<u16> = (1..KEY_MAX)
+            .chain([BTN_LEFT, BTN_RIGHT, BTN_MIDDLE])
+            .collect();
+        let node = Node::create(&Layout {
+            name: "controller2keys keyboard",
+            id: (0x06, 0, 0), // BUS_VIRTUAL
+            keys: &keys,
+            rels: &[
+                REL_X,
+                REL_Y,
+                REL_WHEEL,
+                REL_HWHEEL,
+                REL_WHEEL_HI_RES,
+                REL_HWHEEL_HI_RES,
+            ],
+            abs: &[],
+        })?;
+        Ok(Device {
+            node,
+            partial: (0, 0),
+        })
+    }
+
+    pub fn mouse(&self) -> Result<Mouse, String> {
+        let file = self.node.file.try_clone().map_err(|e| e.to_string())?;
+        Ok(Mouse { file })
+    }
+
+    pub fn key(&mut self, key: Key, down: bool) -> Result<(), String> {
+        let code = evdev(key).ok_or_else(|| format!("no evdev code for {:?}", key))?;
+        self.node
+            .send(&[(EV_KEY, code, down as i32), (EV_SYN, 0, 0)])
+    }
+
+    pub fn button(&mut self, button: MouseButton, down: bool) -> Result<(), String> {
+        let code = match button {
+            MouseButton::Left => BTN_LEFT,
+            MouseButton::Right => BTN_RIGHT,
+            MouseButton::Middle => BTN_MIDDLE,
+            other => return Err(format!("no evdev code for {:?}", other)),
+        };
+        self.node
+            .send(&[(EV_KEY, code, down as i32), (EV_SYN, 0, 0)])
+    }
+
+    pub fn move_by(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        self.node
+            .send(&[(EV_REL, REL_X, dx), (EV_REL, REL_Y, dy), (EV_SYN, 0, 0)])
+    }
+
+    // whole wheel clicks, positive is down like enigo's
+    pub fn scroll(&mut self, clicks: i32) -> Result<(), String> {
+        self.smooth_scroll(0, clicks * NOTCH)
+    }
+
+    // in 120ths of a notch, positive is right and down
+    pub fn smooth_scroll(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        let events = wheel_events(&mut self.partial, dx, dy);
+        self.node.send(&events)
+    }
+
+    // one whole horizontal notch, the way a tilting mouse wheel reports it.
+    // doesn't touch what smooth scrolling has in progress
+    pub fn tilt(&mut self, direction: i32) -> Result<(), String> {
+        self.node.send(&[
+            (EV_REL, REL_HWHEEL_HI_RES, direction * NOTCH),
+            (EV_REL, REL_HWHEEL, direction),
+            (EV_SYN, 0, 0),
+        ])
+    }
+}
+
+// the hi-res wheel events for a scroll, with a notch event each time the
+// partial scroll adds up to a whole one
+fn wheel_events(partial: &mut (i32, i32), dx: i32, dy: i32) -> Vec<(u16, u16, i32)> {
+    // evdev's vertical wheel points up
+    let dy = -dy;
+    partial.0 += dx;
+    partial.1 += dy;
+    let notches = (partial.0 / NOTCH, partial.1 / NOTCH);
+    partial.0 -= notches.0 * NOTCH;
+    partial.1 -= notches.1 * NOTCH;
+
+    let mut events = Vec::new();
+    if dx != 0 {
+        events.push((EV_REL, REL_HWHEEL_HI_RES, dx));
+    }
+    if dy != 0 {
+        events.push((EV_REL, REL_WHEEL_HI_RES, dy));
+    }
+    if notches.0 != 0 {
+        events.push((EV_REL, REL_HWHEEL, notches.0));
+    }
+    if notches.1 != 0 {
+        events.push((EV_REL, REL_WHEEL, notches.1));
+    }
+    events.push((EV_SYN, 0, 0));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrolling_down_turns_the_wheel_back() {
+        let events = wheel_events(&mut (0, 0), 0, NOTCH);
+        assert_eq!(
+            events,
+            [
+                (EV_REL, REL_WHEEL_HI_RES, -NOTCH),
+                (EV_REL, REL_WHEEL, -1),
+                (EV_SYN, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_notches_add_up() {
+        let mut partial = (0, 0);
+        let halves: Vec<_> = (0..2)
+            .map(|_| wheel_events(&mut partial, 0, -NOTCH / 2))
+            .collect();
+        assert!(!halves[0].contains(&(EV_REL, REL_WHEEL, 1)));
+        assert!(halves[1].contains(&(EV_REL, REL_WHEEL, 1)));
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+// named integer variables that bindings change and conditions read, e.g. a
+// "build" flag flipped by Select that the face buttons check. each mapper
+// has its own, handed on to the next one when the profile switches, and
+// unset ones read as 0
+#[derive(Debug, Clone, Default)]
+pub struct Vars(BTreeMap<String, i64>);
+
+impl Vars {
+    pub fn get(&self, name: &str) -> i64 {
+        self.0.get(name).copied().unwrap_or(0)
+    }
+
+    // the variables that aren't 0, for the hud
+    pub fn snapshot(&self) -> Vec<(String, i64)> {
+        self.0
+            .iter()
+            .filter(|(_, &value)| value != 0)
+            .map(|(name, &value)| (name.clone(), value))
+            .collect()
+    }
+}
+
+// what a `var` binding does to its variable, e.g.
+// `var = { name = "build", toggle = true }` or
+// `var = { name = "weapon", add = 1, wrap = 3 }`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VarChange {
+    pub name: String,
+    // exactly one of set, add and toggle
+    pub set: Option<i64>,
+    pub add: Option<i64>,
+    // 0 becomes 1 and anything else 0
+    #[serde(default)]
+    pub toggle: bool,
+    // counts modulo this after adding, so it cycles from 0 to wrap - 1
+    pub wrap: Option<i64>,
+}
+
+impl VarChange {
+    pub fn validate(&self) -> Result<(), String> {
+        let ops = [self.set.is_some(), self.add.is_some(), self.toggle];
+        if ops.iter().filter(|&&op| op).count() != 1 {
+            return Err(format!(
+                "var '{}' needs exactly one of set, add or toggle",
+                self.name
+            ));
+        }
+        if self.wrap.is_some_and(|wrap| wrap <= 0) {
+            return Err(format!("var '{}' wraps at a positive number", self.name));
+        }
+        Ok(())
+    }
+
+    // returns the new value
+    pub fn apply(&self, vars: &mut Vars) -> i64 {
+        let value = vars.0.entry(self.name.clone()).or_insert(0);
+        *value = match (self.set, self.add) {
+            (Some(set), _) => set,
+            (None, Some(add)) => value.saturating_add(add),
+            (None, None) => (*value == 0) as i64,
+        };
+        if let Some(wrap) = self.wrap {
+            *value = value.rem_euclid(wrap);
+        }
+        *value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(text: &str) -> VarChange {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn counters_wrap() {
+        let mut vars = Vars::default();
+        let next = change("name = \"weapon\"\nadd = 1\nwrap = 3");
+        let values: Vec<_> = (0..4).map(|_| next.apply(&mut vars)).collect();
+        assert_eq!(values, [1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn each_mapper_has_its_own() {
+        let (mut one, two) = (Vars::default(), Vars::default());
+        change("name = \"build\"\ntoggle = true").apply(&mut one);
+        assert_eq!((one.get("build"), two.get("build")), (1, 0));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::action::{parse_mouse_button, Action};
+use crate::keymap::parse_key;
+use crate::plugin::Plugin;
+use gilrs::{Axis, Button};
+use std::path::Path;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+// how much work a single hook call may do before it's cut off, so a
+// misbehaving module can't hang the event loop
+const FUEL_PER_CALL: u64 = 1_000_000;
+
+// and how far its memory may grow, so one can't eat the machine's memory
+// either. tables are held to 10,000 elements each
+const MEMORY_BYTES: usize = 64 << 20;
+
+// wasm plugins implement the same hooks as native ones, as optional exports:
+//
+//   on_button(button: i32, pressed: i32)
+//   on_axis(axis: i32, value: f32)
+//   on_tick()
+//
+// they can't touch the host directly. instead they import functions from the
+// `c2k` module to emit actions (strings are utf-8 ptr/len into their memory):
+//
+//   key_down(ptr, len)  key_up(ptr, len)  tap(ptr, len)
+//   mouse_down(ptr, len)  mouse_up(ptr, len)  click(ptr, len)
+//   move(dx: i32, dy: i32)  scroll(amount: i32)
+struct Host {
+    actions: Vec<Action>,
+    limits: StoreLimits,
+}
+
+pub struct WasmPlugin {
+    name: String,
+    store: Store<Host>,
+    on_button: Option<TypedFunc<(i32, i32), ()>>,
+    on_axis: Option<TypedFunc<(i32, f32), ()>>,
+    on_tick: Option<TypedFunc<(), ()>>,
+    // a module that traps is disabled rather than retried every tick
+    failed: bool,
+}
+
+fn read_str(caller: &mut Caller<'_, Host>, ptr: i32, len: i32) -> Option<String> {
+    let memory = match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => memory,
+        _ => return None,
+    };
+    let start = usize::try_from(ptr).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    let bytes = memory.data(&caller).get(start..end)?;
+    std::str::from_utf8(bytes).ok().map(str::to_string)
+}
+
+fn link_key(
+    linker: &mut Linker<Host>,
+    name: &str,
+    make: fn(enigo::Key) -> Action,
+) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "c2k",
+        name,
+        move |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            if let Some(key) = read_str(&mut caller, ptr, len).and_then(|s| parse_key(&s)) {
+                caller.data_mut().actions.push(make(key));
+            }
+        },
+    )?;
+    Ok(())
+}
+
+fn link_mouse_button(
+    linker: &mut Linker<Host>,
+    name: &str,
+    make: fn(enigo::MouseButton) -> Action,
+) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "c2k",
+        name,
+        move |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            if let Some(button) =
+                read_str(&mut caller, ptr, len).and_then(|s| parse_mouse_button(&s))
+            {
+                caller.data_mut().actions.push(make(button));
+            }
+        },
+    )?;
+    Ok(())
+}
+
+impl WasmPlugin {
+    pub fn load(path: &Path) -> wasmtime::Result<WasmPlugin> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, path)?;
+
+        let mut linker = Linker::new(&engine);
+        link_key(&mut linker, "key_down", Action::KeyDown)?;
+        link_key(&mut linker, "key_up", Action::KeyUp)?;
+        link_key(&mut linker, "tap", Action::KeyClick)?;
+        link_mouse_button(&mut linker, "mouse_down", Action::MouseDown)?;
+        link_mouse_button(&mut linker, "mouse_up", Action::MouseUp)?;
+        link_mouse_button(&mut linker, "click", Action::MouseClick)?;
+        linker.func_wrap(
+            "c2k",
+            "move",
+            |mut caller: Caller<'_, Host>, x: i32, y: i32| {
+                caller.data_mut().actions.push(Action::MouseMove(x, y));
+            },
+        )?;
+        linker.func_wrap(
+            "c2k",
+            "scroll",
+            |mut caller: Caller<'_, Host>, amount: i32| {
+                caller.data_mut().actions.push(Action::Scroll(amount));
+            },
+        )?;
+
+        let mut store = Store::new(
+            &engine,
+            Host {
+                actions: Vec::new(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MEMORY_BYTES)
+                    .table_elements(10_000)
+                    .instances(1)
+                    .build(),
+            },
+        );
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = linker.instantiate(&mut store, &module)?;
+
+        let on_button = instance.get_typed_func(&mut store, "on_button").ok();
+        let on_axis = instance.get_typed_func(&mut store, "on_axis").ok();
+        let on_tick = instance.get_typed_func(&mut store, "on_tick").ok();
+
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(WasmPlugin {
+            name,
+            store,
+            on_button,
+            on_axis,
+            on_tick,
+            failed: false,
+        })
+    }
+
+    fn check(&mut self, result: wasmtime::Result<()>) {
+        if let Err(e) = result {
+            println!("wasm plugin '{}' failed, disabling it: {}", self.name, e);
+            self.failed = true;
+        }
+    }
+}
+
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_button(&mut self, button: Button, pressed: bool) {
+        if let (Some(f), false) = (self.on_button.clone(), self.failed) {
+            let result = self
+                .store
+                .set_fuel(FUEL_PER_CALL)
+                .and_then(|_| f.call(&mut self.store, (button as i32, pressed as i32)));
+            self.check(result);
+        }
+    }
+
+    fn on_axis(&mut self, axis: Axis, value: f32) {
+        if let (Some(f), false) = (self.on_axis.clone(), self.failed) {
+            let result = self
+                .store
+                .set_fuel(FUEL_PER_CALL)
+                .and_then(|_| f.call(&mut self.store, (axis as i32, value)));
+            self.check(result);
+        }
+    }
+
+    fn on_tick(&mut self) {
+        if let (Some(f), false) = (self.on_tick.clone(), self.failed) {
+            let result = self
+                .store
+                .set_fuel(FUEL_PER_CALL)
+                .and_then(|_| f.call(&mut self.store, ()));
+            self.check(result);
+        }
+    }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.store.data_mut().actions)
+    }
+}
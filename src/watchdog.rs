@@ -0,0 +1,55 @@
+use crate::action::Output;
+use crate::input::InputState;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+// catches keys left down by a missed release: when the controller has been
+// at rest for a while but we still hold something, nothing on the pad can
+// be holding it, so it's let go, e.g. `watchdog = { after_secs = 5 }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogConfig {
+    pub after_secs: f32,
+    // sticks and triggers closer to rest than this count as untouched
+    pub rest: f32,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            after_secs: 5.0,
+            rest: 0.3,
+        }
+    }
+}
+
+pub struct Watchdog {
+    after: Duration,
+    rest: f32,
+    // last time anything on the controller was held or deflected
+    active: Instant,
+}
+
+impl Watchdog {
+    pub fn new(config: &WatchdogConfig) -> Watchdog {
+        Watchdog {
+            after: Duration::from_secs_f32(config.after_secs.max(0.5)),
+            rest: config.rest,
+            active: Instant::now(),
+        }
+    }
+
+    pub fn check(&mut self, input: &InputState, output: &mut Output) {
+        if !input.at_rest(self.rest) {
+            self.active = Instant::now();
+            return;
+        }
+        if output.holding() && self.active.elapsed() >= self.after {
+            println!(
+                "watchdog: controller at rest but {} still held -> released",
+                output.held().join(", ")
+            );
+            output.release_all();
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::config::Config;
+use crate::edit::{self, Assignment, BUTTONS};
+use gilrs::{EventType, Gilrs};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const PAGE: &str = include_str!("web/index.html");
+
+// open /api/events streams, each fed button presses as json
+type Listeners = Arc<Mutex<Vec<Sender<String>>>>;
+
+// `controller2keys web`: serves the profile editor on localhost. the page
+// reads and writes bindings through a small json api and shows the live
+// controller through a server-sent event stream:
+//
+//   GET /api/profiles                  profile names and the default one
+//   GET /api/profiles/<name>           what each button does in a profile
+//   PUT /api/profiles/<name>/<button>  {"kind": "key", "value": "ctrl+c"}
+//   GET /api/events                    `data: {"button": .., "pressed": ..}`
+pub fn run(port: u16) -> Result<(), String> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|e| e.to_string())?;
+    let listeners = Listeners::default();
+    let feed = listeners.clone();
+    std::thread::spawn(move || watch(feed));
+    println!("configurator at http://localhost:{}", port);
+
+    for request in server.incoming_requests() {
+        if !local(&request) {
+            let _ = request.respond(Response::empty(403));
+            continue;
+        }
+        if request.url() == "/api/events" {
+            stream(request, &listeners);
+            continue;
+        }
+        let _ = handle(request);
+    }
+    Ok(())
+}
+
+// a page on some other site can send requests to localhost too. requiring
+// our own host name stops dns rebinding, and requiring a json body on
+// writes forces a cors preflight that we never answer
+fn local(request: &Request) -> bool {
+    let header = |name: &str| {
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+            .map(|h| h.value.as_str().to_string())
+    };
+    let host = header("Host").unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    if host != "localhost" && host != "127.0.0.1" {
+        return false;
+    }
+    *request.method() != Method::Put
+        || header("Content-Type").is_some_and(|t| t.starts_with("application/json"))
+}
+
+fn handle(mut request: Request) -> std::io::Result<()> {
+    let path: Vec<String> = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode)
+        .collect();
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    let response = match (request.method(), path.as_slice()) {
+        (Method::Get, []) => {
+            let html = Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap();
+            return request.respond(Response::from_string(PAGE).with_header(html));
+        }
+        (Method::Get, ["api", "profiles"]) => Ok(profiles()),
+        (Method::Get, ["api", "profiles", name]) => Ok(profile(name)),
+        (Method::Put, ["api", "profiles", name, button]) => {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body)?;
+            save(name, button, &body)
+        }
+        _ => return request.respond(Response::empty(404)),
+    };
+    let (code, body) = match response {
+        Ok(body) => (200, body),
+        Err(e) => (400, json!({ "error": e })),
+    };
+    let json = Header::from_bytes("Content-Type", "application/json").unwrap();
+    request.respond(
+        Response::from_string(body.to_string())
+            .with_status_code(code)
+            .with_header(json),
+    )
+}
+
+fn profiles() -> Value {
+    let config = Config::load_or_default();
+    let mut names: Vec<&String> = config.profiles.keys().collect();
+    if !names.contains(&&config.profile) {
+        names.insert(0, &config.profile);
+    }
+    json!({ "default": config.profile, "profiles": names })
+}
+
+fn profile(name: &str) -> Value {
+    let profile = Config::load_or_default().get_profile(name);
+    let buttons: serde_json::Map<String, Value> = BUTTONS
+        .iter()
+        .map(|&button| {
+            let (kind, value) = match Assignment::current(&profile, button) {
+                Assignment::Default => ("default", String::new()),
+                Assignment::Key(key) => ("key", key),
+                Assignment::Mouse(mouse) => ("mouse", mouse),
+                Assignment::Macro(name) => ("macro", name),
+                Assignment::Other => ("other", String::new()),
+            };
+            (
+                format!("{:?}", button),
+                json!({ "kind": kind, "value": value }),
+            )
+        })
+        .collect();
+    json!({ "buttons": buttons })
+}
+
+fn save(profile: &str, button: &str, body: &str) -> Result<Value, String> {
+    let button = BUTTONS
+        .iter()
+        .copied()
+        .find(|b| format!("{:?}", b) == button)
+        .ok_or_else(|| format!("unknown button '{}'", button))?;
+    let body: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let value = body["value"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let assignment = match body["kind"].as_str() {
+        Some("default") => Assignment::Default,
+        Some("key") => Assignment::Key(value),
+        Some("mouse") => Assignment::Mouse(value),
+        Some("macro") => Assignment::Macro(value),
+        _ => return Err("kind must be default, key, mouse or macro".to_string()),
+    };
+    edit::save(profile, button, &assignment)?;
+    Ok(json!({ "saved": assignment.describe() }))
+}
+
+// tiny_http buffers chunked bodies, so events are written to the
+// connection directly until the browser goes away
+fn stream(request: Request, listeners: &Listeners) {
+    let (tx, rx) = channel();
+    listeners.lock().unwrap().push(tx);
+    std::thread::spawn(move || {
+        let mut writer = request.into_writer();
+        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                    Cache-Control: no-cache\r\nConnection: close\r\n\r\n";
+        if writer.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+        for event in rx {
+            let sent = write!(writer, "data: {}\n\n", event).and_then(|_| writer.flush());
+            if sent.is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn watch(listeners: Listeners) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(e) => {
+            println!("no live input: {}", e);
+            return;
+        }
+    };
+    loop {
+        // blocks until the next controller event
+        let Some(event) = gilrs.next_event_blocking(None) else {
+            continue;
+        };
+        let (button, pressed) = match event.event {
+            EventType::ButtonPressed(button, _) => (button, true),
+            EventType::ButtonReleased(button, _) => (button, false),
+            _ => continue,
+        };
+        let event = json!({ "button": format!("{:?}", button), "pressed": pressed }).to_string();
+        // closed streams drop their receiver, so sending fails and they're
+        // forgotten
+        listeners
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+// percent-decoding for profile names with spaces and the like
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,120 @@
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+// the hi-res wheel is part of the uinput keyboard and mouse
+#[cfg(target_os = "linux")]
+use crate::uinput::Device;
+#[cfg(target_os = "macos")]
+use macos::Device;
+#[cfg(target_os = "windows")]
+use windows::Device;
+
+use enigo::{Enigo, MouseControllable};
+use serde::{Deserialize, Serialize};
+
+// 120ths of a wheel notch, the unit windows and linux's hi-res wheel use
+pub const NOTCH: i32 = 120;
+
+// a tilt of the wheel, the horizontal notch some mice have. apps that bind
+// it (tab switching, back and forward) want exactly one notch per tilt, so
+// it never mixes with smooth scrolling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tilt {
+    Left,
+    Right,
+}
+
+impl Tilt {
+    pub fn sign(self) -> i32 {
+        match self {
+            Tilt::Left => -1,
+            Tilt::Right => 1,
+        }
+    }
+}
+
+// scrolling finer than whole wheel notches, so documents glide instead of
+// jumping a few lines at a time. where the platform can't, partial notches
+// add up and go out whole through enigo
+pub struct Wheel {
+    device: Option<Device>,
+    // scrolled but not yet a whole notch, for the fallback
+    remainder: (i32, i32),
+}
+
+impl Wheel {
+    pub fn open() -> Wheel {
+        let device = match Device::open() {
+            Ok(device) => Some(device),
+            Err(e) => {
+                println!(
+                    "smooth scrolling unavailable, scrolling whole notches: {}",
+                    e
+                );
+                None
+            }
+        };
+        Wheel {
+            device,
+            remainder: (0, 0),
+        }
+    }
+
+    // in 120ths of a notch, positive is right and down like enigo's
+    pub fn scroll(&mut self, dx: i32, dy: i32, enigo: &mut Enigo) {
+        if let Some(device) = self.device.as_mut() {
+            match device.smooth_scroll(dx, dy) {
+                Ok(()) => return,
+                Err(e) => {
+                    println!("smooth scrolling failed, scrolling whole notches: {}", e);
+                    self.device = None;
+                }
+            }
+        }
+        self.remainder.0 += dx;
+        self.remainder.1 += dy;
+        let notches = (self.remainder.0 / NOTCH, self.remainder.1 / NOTCH);
+        self.remainder.0 -= notches.0 * NOTCH;
+        self.remainder.1 -= notches.1 * NOTCH;
+        if notches.0 != 0 {
+            enigo.mouse_scroll_x(notches.0);
+        }
+        if notches.1 != 0 {
+            enigo.mouse_scroll_y(notches.1);
+        }
+    }
+
+    pub fn tilt(&mut self, tilt: Tilt, enigo: &mut Enigo) {
+        if let Some(device) = self.device.as_mut() {
+            match device.tilt(tilt.sign()) {
+                Ok(()) => return,
+                Err(e) => {
+                    println!("smooth scrolling failed, scrolling whole notches: {}", e);
+                    self.device = None;
+                }
+            }
+        }
+        enigo.mouse_scroll_x(tilt.sign());
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+enum Device {}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+impl Device {
+    fn open() -> Result<Device, String> {
+        Err("not supported on this platform".to_string())
+    }
+
+    fn smooth_scroll(&mut self, _dx: i32, _dy: i32) -> Result<(), String> {
+        match *self {}
+    }
+
+    fn tilt(&mut self, _direction: i32) -> Result<(), String> {
+        match *self {}
+    }
+}
@@ -0,0 +1,69 @@
+use super::NOTCH;
+use std::ffi::c_void;
+
+// kCGScrollEventUnitPixel, kCGScrollEventUnitLine and kCGHIDEventTap
+const UNIT_PIXEL: u32 = 0;
+const UNIT_LINE: u32 = 1;
+const HID_EVENT_TAP: u32 = 0;
+// about what a real wheel notch scrolls
+const PIXELS_PER_NOTCH: i32 = 40;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn CGEventCreateScrollWheelEvent(
+        source: *const c_void,
+        units: u32,
+        wheel_count: u32,
+        wheel1: i32,
+        ...
+    ) -> *mut c_void;
+    fn CGEventPost(tap: u32, event: *mut c_void);
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: *const c_void);
+}
+
+// pixel scroll events, the kind trackpads send
+pub struct Device {
+    // scrolled but not yet a whole pixel, in 120ths of a notch
+    partial: (i32, i32),
+}
+
+impl Device {
+    pub fn open() -> Result<Device, String> {
+        Ok(Device { partial: (0, 0) })
+    }
+
+    // one line sideways, like a tilting wheel on a mac
+    pub fn tilt(&mut self, direction: i32) -> Result<(), String> {
+        post(UNIT_LINE, 0, -direction)
+    }
+
+    pub fn smooth_scroll(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        self.partial.0 += dx * PIXELS_PER_NOTCH;
+        self.partial.1 += dy * PIXELS_PER_NOTCH;
+        let pixels = (self.partial.0 / NOTCH, self.partial.1 / NOTCH);
+        self.partial.0 -= pixels.0 * NOTCH;
+        self.partial.1 -= pixels.1 * NOTCH;
+        if pixels == (0, 0) {
+            return Ok(());
+        }
+        // wheel 1 is vertical and wheel 2 horizontal, both positive toward
+        // the top left
+        post(UNIT_PIXEL, -pixels.1, -pixels.0)
+    }
+}
+
+fn post(units: u32, vertical: i32, horizontal: i32) -> Result<(), String> {
+    unsafe {
+        let event = CGEventCreateScrollWheelEvent(std::ptr::null(), units, 2, vertical, horizontal);
+        if event.is_null() {
+            return Err("couldn't create a scroll event".to_string());
+        }
+        CGEventPost(HID_EVENT_TAP, event);
+        CFRelease(event);
+    }
+    Ok(())
+}
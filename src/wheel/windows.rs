@@ -0,0 +1,52 @@
+use super::NOTCH;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_WHEEL, MOUSEINPUT,
+    MOUSE_EVENT_FLAGS,
+};
+
+// windows takes wheel deltas that aren't whole notches as they are
+pub struct Device;
+
+fn wheel(flags: MOUSE_EVENT_FLAGS, delta: i32) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                mouseData: delta,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    }
+}
+
+impl Device {
+    pub fn open() -> Result<Device, String> {
+        Ok(Device)
+    }
+
+    // a whole horizontal notch is what a tilting wheel sends
+    pub fn tilt(&mut self, direction: i32) -> Result<(), String> {
+        send(&[wheel(MOUSEEVENTF_HWHEEL, direction * NOTCH)])
+    }
+
+    pub fn smooth_scroll(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        let mut inputs = Vec::new();
+        if dx != 0 {
+            inputs.push(wheel(MOUSEEVENTF_HWHEEL, dx));
+        }
+        // positive is away from the user, which is up
+        if dy != 0 {
+            inputs.push(wheel(MOUSEEVENTF_WHEEL, -dy));
+        }
+        send(&inputs)
+    }
+}
+
+fn send(inputs: &[INPUT]) -> Result<(), String> {
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(windows::core::Error::from_win32().to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use enigo::Key;
+use serde::Deserialize;
+
+// everyday window handling from a binding, e.g. `window = "switch_next"`.
+// each is the desktop's own shortcut, so it does whatever the window
+// manager does with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowOp {
+    // the app switcher, alt+tab (cmd+tab on macos). the modifier stays down
+    // while the button is held, so the switcher stays open; with `repeat`
+    // it keeps stepping through
+    SwitchNext,
+    SwitchPrevious,
+    Minimize,
+    Maximize,
+    Close,
+    // the focused window onto the next monitor
+    NextMonitor,
+    // virtual desktops (workspaces, spaces on macos) to either side, and
+    // the overview showing all of them
+    DesktopNext,
+    DesktopPrevious,
+    Overview,
+}
+
+impl WindowOp {
+    pub fn switches(self) -> bool {
+        matches!(self, WindowOp::SwitchNext | WindowOp::SwitchPrevious)
+    }
+
+    // tapped while the switcher's modifier is held
+    pub fn switch_keys(self) -> Vec<Key> {
+        match self {
+            WindowOp::SwitchPrevious => vec![Key::Shift, Key::Tab],
+            _ => vec![Key::Tab],
+        }
+    }
+}
+
+// held while the app switcher is open
+#[cfg(target_os = "macos")]
+pub const SWITCHER: Key = Key::Meta;
+#[cfg(not(target_os = "macos"))]
+pub const SWITCHER: Key = Key::Alt;
+
+#[cfg(target_os = "windows")]
+pub fn shortcut(op: WindowOp) -> Option<Vec<Key>> {
+    Some(match op {
+        WindowOp::Minimize => vec![Key::Meta, Key::DownArrow],
+        WindowOp::Maximize => vec![Key::Meta, Key::UpArrow],
+        WindowOp::Close => vec![Key::Alt, Key::F4],
+        WindowOp::NextMonitor => vec![Key::Meta, Key::Shift, Key::RightArrow],
+        WindowOp::DesktopNext => vec![Key::Meta, Key::Control, Key::RightArrow],
+        WindowOp::DesktopPrevious => vec![Key::Meta, Key::Control, Key::LeftArrow],
+        WindowOp::Overview => vec![Key::Meta, Key::Tab],
+        _ => return None,
+    })
+}
+
+// maximizing is full screen, and there's no shortcut for moving windows
+// between displays
+#[cfg(target_os = "macos")]
+pub fn shortcut(op: WindowOp) -> Option<Vec<Key>> {
+    Some(match op {
+        WindowOp::Minimize => vec![Key::Meta, Key::Layout('m')],
+        WindowOp::Maximize => vec![Key::Control, Key::Meta, Key::Layout('f')],
+        WindowOp::Close => vec![Key::Meta, Key::Layout('w')],
+        WindowOp::DesktopNext => vec![Key::Control, Key::RightArrow],
+        WindowOp::DesktopPrevious => vec![Key::Control, Key::LeftArrow],
+        // mission control
+        WindowOp::Overview => vec![Key::Control, Key::UpArrow],
+        _ => return None,
+    })
+}
+
+// the linux desktops whose workspace shortcuts differ
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Desktop {
+    Gnome,
+    Kde,
+    // xfce, cinnamon, mate and most others
+    Other,
+}
+
+// from XDG_CURRENT_DESKTOP, e.g. "ubuntu:GNOME" or "KDE"
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn desktop() -> Desktop {
+    let current = std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .to_uppercase();
+    if current.contains("GNOME") {
+        Desktop::Gnome
+    } else if current.contains("KDE") {
+        Desktop::Kde
+    } else {
+        Desktop::Other
+    }
+}
+
+// gnome's defaults, which kde and most tiling setups share apart from
+// minimizing. workspaces go by the desktop in use
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn shortcut(op: WindowOp) -> Option<Vec<Key>> {
+    Some(match (op, desktop()) {
+        (WindowOp::Minimize, _) => vec![Key::Meta, Key::Layout('h')],
+        (WindowOp::Maximize, _) => vec![Key::Meta, Key::UpArrow],
+        (WindowOp::Close, _) => vec![Key::Alt, Key::F4],
+        (WindowOp::NextMonitor, _) => vec![Key::Meta, Key::Shift, Key::RightArrow],
+        (WindowOp::DesktopNext, Desktop::Gnome) => vec![Key::Meta, Key::PageDown],
+        (WindowOp::DesktopPrevious, Desktop::Gnome) => vec![Key::Meta, Key::PageUp],
+        (WindowOp::DesktopNext, Desktop::Kde) => vec![Key::Meta, Key::Control, Key::RightArrow],
+        (WindowOp::DesktopPrevious, Desktop::Kde) => {
+            vec![Key::Meta, Key::Control, Key::LeftArrow]
+        }
+        (WindowOp::DesktopNext, Desktop::Other) => vec![Key::Control, Key::Alt, Key::RightArrow],
+        (WindowOp::DesktopPrevious, Desktop::Other) => {
+            vec![Key::Control, Key::Alt, Key::LeftArrow]
+        }
+        (WindowOp::Overview, Desktop::Gnome) => vec![Key::Meta],
+        (WindowOp::Overview, Desktop::Kde) => vec![Key::Meta, Key::Layout('w')],
+        (WindowOp::Overview, Desktop::Other) => vec![Key::Control, Key::Alt, Key::UpArrow],
+        _ => return None,
+    })
+}
@@ -0,0 +1,133 @@
+use crate::action::Action;
+use crate::input::InputState;
+use crate::keymap::parse_key;
+use enigo::Key;
+use gilrs::Button;
+use serde::Deserialize;
+use std::time::Instant;
+
+// the two triggers as a zoom control for maps and image viewers: the right
+// one zooms in and the left one out through ctrl+scroll, faster the further
+// they're pulled, e.g. `trigger_zoom = { rate = 8 }`. the triggers do
+// nothing else while it's set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TriggerZoomConfig {
+    // wheel clicks per second at full pull
+    pub rate: f32,
+    // pull ignored, so a resting finger doesn't creep
+    pub deadzone: f32,
+    // held around the scroll
+    pub modifier: String,
+}
+
+impl Default for TriggerZoomConfig {
+    fn default() -> Self {
+        TriggerZoomConfig {
+            rate: 10.0,
+            deadzone: 0.1,
+            modifier: "ctrl".to_string(),
+        }
+    }
+}
+
+pub struct TriggerZoom {
+    rate: f32,
+    deadzone: f32,
+    modifier: Key,
+    // fractional clicks carried over between ticks, positive is in. wheel
+    // clicks are the other way round: ctrl+scroll up zooms in
+    owed: f32,
+    last: Instant,
+}
+
+impl TriggerZoom {
+    pub fn new(config: &TriggerZoomConfig) -> TriggerZoom {
+        let modifier = parse_key(&config.modifier).unwrap_or_else(|| {
+            println!(
+                "unknown trigger_zoom modifier '{}', using ctrl",
+                config.modifier
+            );
+            Key::Control
+        });
+        TriggerZoom {
+            rate: config.rate,
+            deadzone: config.deadzone,
+            modifier,
+            owed: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    pub fn handles(&self, button: Button) -> bool {
+        matches!(button, Button::LeftTrigger2 | Button::RightTrigger2)
+    }
+
+    pub fn tick(&mut self, input: &InputState) -> Vec<Action> {
+        let elapsed = self.last.elapsed().as_secs_f32();
+        self.last = Instant::now();
+        let pull =
+            input.button_value(Button::RightTrigger2) - input.button_value(Button::LeftTrigger2);
+        if pull.abs() < self.deadzone {
+            self.owed = 0.0;
+            return vec![];
+        }
+        self.owed += pull * self.rate * elapsed;
+        let clicks = self.owed.trunc() as i32;
+        if clicks == 0 {
+            return vec![];
+        }
+        self.owed -= clicks as f32;
+        vec![
+            Action::KeyDown(self.modifier),
+            Action::Scroll(-clicks),
+            Action::KeyUp(self.modifier),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // one tick a second after the last, with the triggers pulled this far
+    fn tick(zoom: &mut TriggerZoom, left: f32, right: f32) -> Vec<Action> {
+        let mut input = InputState::default();
+        input.set_value(Button::LeftTrigger2, left);
+        input.set_value(Button::RightTrigger2, right);
+        zoom.last = Instant::now() - Duration::from_secs(1);
+        zoom.tick(&input)
+    }
+
+    fn zoom() -> TriggerZoom {
+        TriggerZoom::new(&TriggerZoomConfig {
+            rate: 4.0,
+            ..TriggerZoomConfig::default()
+        })
+    }
+
+    #[test]
+    fn right_trigger_scrolls_up_to_zoom_in() {
+        let actions = tick(&mut zoom(), 0.0, 1.0);
+        assert_eq!(
+            actions,
+            vec![
+                Action::KeyDown(Key::Control),
+                Action::Scroll(-4),
+                Action::KeyUp(Key::Control),
+            ]
+        );
+    }
+
+    #[test]
+    fn left_trigger_scrolls_down_to_zoom_out() {
+        let actions = tick(&mut zoom(), 0.5, 0.0);
+        assert_eq!(actions[1], Action::Scroll(2));
+    }
+
+    #[test]
+    fn pull_inside_the_deadzone_does_nothing() {
+        assert!(tick(&mut zoom(), 0.0, 0.05).is_empty());
+    }
+}
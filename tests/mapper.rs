@@ -0,0 +1,157 @@
+// random configs and button streams through the mapper, checking the
+// invariants stuck keys come from breaking: whatever goes down comes back up
+// once every button is let go, and nothing is pressed that no binding names
+use controller2keys::action::{parse_mouse_button, Action};
+use controller2keys::bindings::{self, Mapper};
+use controller2keys::config::Config;
+use controller2keys::input::InputState;
+use controller2keys::keymap::parse_chord;
+use enigo::{Key, MouseButton};
+use gilrs::Button;
+use proptest::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const BUTTONS: &[&str] = &[
+    "South",
+    "East",
+    "West",
+    "North",
+    "LeftTrigger",
+    "RightTrigger",
+    "DPadUp",
+    "DPadDown",
+];
+const KEYS: &[&str] = &["a", "space", "shift", "f5", "ctrl+c", "ctrl+shift+tab"];
+const MICE: &[&str] = &["left", "right"];
+
+fn button(name: &str) -> Button {
+    toml::Value::String(name.to_string()).try_into().unwrap()
+}
+
+// one binding's target, and optionally the condition it's under
+fn target() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(KEYS).prop_map(|k| format!("key = \"{}\"", k)),
+        prop::sample::select(MICE).prop_map(|m| format!("mouse = \"{}\"", m)),
+        prop::sample::select(KEYS).prop_map(|k| format!("key = \"{}\"\nrepeat = {{}}", k)),
+        Just("layer = \"fn\"".to_string()),
+        Just("toggle = \"alt\"".to_string()),
+        Just("scroll = 1".to_string()),
+        Just(String::new()),
+    ]
+}
+
+fn condition() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        Just("when = { layer = \"fn\" }".to_string()),
+        Just("when = { not = { layer = \"fn\" } }".to_string()),
+        Just("when = { toggle = \"alt\" }".to_string()),
+    ]
+}
+
+fn config() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        (prop::sample::select(BUTTONS), target(), condition()),
+        0..12,
+    )
+    .prop_map(|bindings| {
+        let mut text = "profile = \"p\"\n\n[profiles.p]\n".to_string();
+        for (button, target, condition) in bindings {
+            text.push_str(&format!(
+                "\n[[profiles.p.bindings]]\nbutton = \"{}\"\n{}\n{}\n",
+                button, target, condition
+            ));
+        }
+        text
+    })
+}
+
+// presses and releases by index into BUTTONS. ones that don't make sense
+// (pressing what's held, releasing what isn't) are skipped when run
+fn events() -> impl Strategy<Value = Vec<(usize, bool)>> {
+    prop::collection::vec((0..BUTTONS.len(), any::<bool>()), 0..64)
+}
+
+fn run(text: &str, events: &[(usize, bool)]) -> Vec<Action> {
+    let config: Config = toml::from_str(text).unwrap();
+    let profile = config.get_profile("p");
+    let mut mapper = Mapper::new(&profile.bindings, false);
+    let input = InputState::default();
+    let mut held = Vec::new();
+    let mut actions = Vec::new();
+    for &(i, press) in events {
+        let button = button(BUTTONS[i]);
+        if !mapper.handles(button) {
+            continue;
+        }
+        if press && !held.contains(&button) {
+            held.push(button);
+            actions.extend(mapper.press(button, &input));
+        } else if !press && held.contains(&button) {
+            held.retain(|b| *b != button);
+            actions.extend(mapper.release(button));
+        }
+        actions.extend(mapper.tick());
+    }
+    for button in held {
+        actions.extend(mapper.release(button));
+    }
+    actions.extend(mapper.tick());
+    actions
+}
+
+proptest! {
+    #[test]
+    fn generated_configs_compile(text in config()) {
+        let config: Config = toml::from_str(&text).unwrap();
+        let profile = config.get_profile("p");
+        prop_assert!(bindings::errors(&profile.bindings, false).is_empty());
+    }
+
+    #[test]
+    fn everything_pressed_is_released(text in config(), events in events()) {
+        let mut keys: HashMap<Key, i32> = HashMap::new();
+        let mut mice: HashMap<MouseButton, i32> = HashMap::new();
+        for action in run(&text, &events) {
+            match action {
+                Action::KeyDown(key) => *keys.entry(key).or_default() += 1,
+                Action::KeyUp(key) => *keys.entry(key).or_default() -= 1,
+                Action::MouseDown(button) => *mice.entry(button).or_default() += 1,
+                Action::MouseUp(button) => *mice.entry(button).or_default() -= 1,
+                _ => (),
+            }
+        }
+        prop_assert!(keys.values().all(|&n| n == 0), "keys left over: {:?}", keys);
+        prop_assert!(mice.values().all(|&n| n == 0), "buttons left over: {:?}", mice);
+    }
+
+    #[test]
+    fn only_bound_keys_are_sent(text in config(), events in events()) {
+        let keys: HashSet<Key> = KEYS.iter().flat_map(|k| parse_chord(k).unwrap()).collect();
+        let mice: HashSet<MouseButton> =
+            MICE.iter().map(|m| parse_mouse_button(m).unwrap()).collect();
+        for action in run(&text, &events) {
+            match action {
+                Action::KeyDown(key) | Action::KeyUp(key) | Action::KeyClick(key) => {
+                    prop_assert!(keys.contains(&key), "{:?} isn't bound", key)
+                }
+                Action::MouseDown(button) | Action::MouseUp(button) => {
+                    prop_assert!(mice.contains(&button), "{:?} isn't bound", button)
+                }
+                other => prop_assert!(matches!(other, Action::Scroll(_)), "unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn unbound_buttons_do_nothing(text in config()) {
+        let config: Config = toml::from_str(&text).unwrap();
+        let profile = config.get_profile("p");
+        let mapper = Mapper::new(&profile.bindings, false);
+        for name in BUTTONS {
+            let bound = profile.bindings.iter().any(|b| b.button == button(name));
+            prop_assert_eq!(mapper.handles(button(name)), bound);
+        }
+    }
+}